@@ -0,0 +1,164 @@
+use crate::data::extraction::upsert_variable;
+use crate::data::vault::{self, Vault};
+use crate::persistence::database::{self, Environment};
+use crate::services::environment_service;
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+
+/// Unlocks encrypted environment secrets and auth fields when set.
+pub const VAULT_PASSPHRASE_VAR: &str = "ASTRANOVA_VAULT_PASSPHRASE";
+
+/// Variable sources shared by every subcommand that sends requests.
+#[derive(Debug, clap::Args)]
+pub struct EnvArgs {
+    /// Environment name in the app database.
+    #[arg(long, short)]
+    pub env: Option<String>,
+    /// `.env` file whose variables override the environment.
+    #[arg(long, value_name = "FILE")]
+    pub env_file: Option<PathBuf>,
+    /// Extra variable; overrides the environment and env file.
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_var)]
+    pub vars: Vec<(String, String)>,
+}
+
+fn parse_var(value: &str) -> Result<(String, String), String> {
+    value
+        .split_once('=')
+        .filter(|(key, _)| !key.trim().is_empty())
+        .map(|(key, value)| (key.trim().to_string(), value.to_string()))
+        .ok_or_else(|| format!("Expected KEY=VALUE, got '{}'", value))
+}
+
+pub fn open_database(path: Option<&Path>) -> Result<Connection, String> {
+    match path {
+        Some(path) => database::open(path),
+        None => database::init(),
+    }
+    .map_err(|e| e.to_string())
+}
+
+/// Opens the vault with the passphrase from [`VAULT_PASSPHRASE_VAR`], if any.
+pub fn vault_from_env(conn: &Connection) -> Result<Option<Vault>, String> {
+    open_vault(conn, std::env::var(VAULT_PASSPHRASE_VAR).ok().as_deref())
+}
+
+fn open_vault(conn: &Connection, passphrase: Option<&str>) -> Result<Option<Vault>, String> {
+    match passphrase {
+        Some(passphrase) if environment_service::has_vault(conn) => {
+            environment_service::unlock_vault(conn, passphrase).map(Some)
+        }
+        Some(_) => Err("This database has no vault to unlock".to_string()),
+        None => Ok(None),
+    }
+}
+
+/// The named environment, overlaid with `--env-file` and `--var` values.
+pub fn build_environment(
+    conn: &Connection,
+    args: &EnvArgs,
+    vault: Option<&Vault>,
+) -> Result<Option<Environment>, String> {
+    let mut environment = match &args.env {
+        Some(name) => {
+            let env = environment_service::get_all(conn)
+                .into_iter()
+                .find(|e| &e.name == name)
+                .ok_or_else(|| format!("Environment '{}' not found", name))?;
+            Some(match vault {
+                Some(v) => vault::open_environment(&env, v),
+                None => env,
+            })
+        }
+        None => None,
+    };
+
+    let mut overrides = match &args.env_file {
+        Some(path) => environment_service::parse_env_file(
+            &std::fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?,
+        ),
+        None => Vec::new(),
+    };
+    overrides.extend(args.vars.iter().cloned());
+    if overrides.is_empty() {
+        return Ok(environment);
+    }
+
+    let env = environment.get_or_insert_with(|| Environment {
+        id: 0,
+        name: "cli".to_string(),
+        variables: Vec::new(),
+        default_endpoint: None,
+        secret_keys: Vec::new(),
+    });
+    for (key, value) in &overrides {
+        upsert_variable(&mut env.variables, key, value);
+    }
+    Ok(environment)
+}
+
+pub fn warn_locked_secrets(environment: Option<&Environment>) {
+    let locked = environment
+        .map(|env| {
+            env.variables
+                .iter()
+                .filter(|(_, v)| vault::is_encrypted(v))
+                .count()
+        })
+        .unwrap_or(0);
+    if locked > 0 {
+        eprintln!(
+            "warning: {} secret variable(s) stay unresolved; set {} to unlock them",
+            locked, VAULT_PASSPHRASE_VAR
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(vars: &[(&str, &str)]) -> EnvArgs {
+        EnvArgs {
+            env: None,
+            env_file: None,
+            vars: vars
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn parses_key_value_pairs() {
+        assert_eq!(
+            parse_var("TOKEN=a=b").unwrap(),
+            ("TOKEN".to_string(), "a=b".to_string())
+        );
+        assert!(parse_var("oops").is_err());
+        assert!(parse_var("=value").is_err());
+    }
+
+    #[test]
+    fn vars_create_an_environment_when_none_is_named() {
+        let conn = Connection::open_in_memory().unwrap();
+        let env = build_environment(&conn, &args(&[("HOST", "http://localhost")]), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            env.variables,
+            vec![("HOST".to_string(), "http://localhost".to_string())]
+        );
+        assert!(build_environment(&conn, &args(&[]), None)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn vault_passphrase_requires_a_vault() {
+        let conn = crate::persistence::database::open(std::path::Path::new(":memory:")).unwrap();
+        assert!(open_vault(&conn, None).unwrap().is_none());
+        assert!(open_vault(&conn, Some("master")).is_err());
+    }
+}
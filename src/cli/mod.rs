@@ -0,0 +1,29 @@
+mod environment;
+mod run;
+mod send;
+
+use clap::{Parser, Subcommand};
+
+/// Without a subcommand the desktop app starts.
+#[derive(Debug, Parser)]
+#[command(name = "astranova-client", version, about = "AstraNova HTTP client")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run a collection headlessly and report assertion results.
+    Run(run::RunArgs),
+    /// Send a single request and print the response.
+    Send(send::SendArgs),
+}
+
+/// Runs the requested subcommand, returning its exit code, or `None` to start the GUI.
+pub fn dispatch() -> Option<i32> {
+    match Cli::parse().command? {
+        Command::Run(args) => Some(run::execute(args)),
+        Command::Send(args) => Some(send::execute(args)),
+    }
+}
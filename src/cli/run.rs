@@ -0,0 +1,370 @@
+use super::environment::{self, EnvArgs};
+use crate::data::token_cache::TokenCache;
+use crate::export::astranova;
+use crate::import::postman::{parse_postman_collection, ImportedRequest};
+use crate::persistence::database::CollectionRequest;
+use crate::runner::collection::{run_requests, RunContext, RunOptions, StepResult};
+use crate::runner::{data, report};
+use crate::services::{collection_service, environment_service, run_service};
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+
+#[derive(Debug, clap::Args)]
+pub struct RunArgs {
+    /// Collection name in the app database, or path to an exported AstraNova or Postman collection.
+    pub collection: String,
+    /// Only run this folder (and its subfolders).
+    #[arg(long)]
+    pub folder: Option<String>,
+    #[command(flatten)]
+    pub environment: EnvArgs,
+    /// CSV or JSON file with one iteration per row.
+    #[arg(long, value_name = "FILE")]
+    pub data: Option<PathBuf>,
+    /// Pause between requests, in milliseconds.
+    #[arg(long, value_name = "MS", default_value_t = 0)]
+    pub delay: u64,
+    /// Stop at the first failing request.
+    #[arg(long)]
+    pub bail: bool,
+    #[arg(long, value_name = "FILE")]
+    pub junit: Option<PathBuf>,
+    #[arg(long, value_name = "FILE")]
+    pub json: Option<PathBuf>,
+    /// Database to use instead of the app's own.
+    #[arg(long, value_name = "FILE")]
+    pub db: Option<PathBuf>,
+}
+
+/// 0 when every request passed, 1 on failures, 2 when the run could not start.
+pub fn execute(args: RunArgs) -> i32 {
+    match run(&args) {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            2
+        }
+    }
+}
+
+/// Requests to run and where they came from.
+struct Target {
+    name: String,
+    /// Set when the collection comes from the database, so the run can be saved.
+    collection_id: Option<i32>,
+    folder_id: Option<i32>,
+    variables: Vec<(String, String)>,
+    requests: Vec<CollectionRequest>,
+}
+
+fn run(args: &RunArgs) -> Result<bool, String> {
+    let conn = environment::open_database(args.db.as_deref())?;
+    let vault = environment::vault_from_env(&conn)?;
+    let target = load_target(&conn, &args.collection, args.folder.as_deref())?;
+    let environment = environment::build_environment(&conn, &args.environment, vault.as_ref())?;
+    environment::warn_locked_secrets(environment.as_ref());
+    let options = RunOptions {
+        delay_ms: args.delay,
+        stop_on_failure: args.bail,
+        data: match &args.data {
+            Some(path) => data::load_data_file(path)?,
+            None => Vec::new(),
+        },
+    };
+    let context = RunContext {
+        globals: environment_service::get_globals(&conn),
+        collection_variables: target.variables.clone(),
+        environment,
+        runtime_variables: Vec::new(),
+        data: Vec::new(),
+        vault,
+    };
+
+    println!(
+        "Running {} ({} request(s), {} iteration(s))",
+        target.name,
+        target.requests.len(),
+        options.data.len().max(1)
+    );
+    let runtime =
+        tokio::runtime::Runtime::new().map_err(|e| format!("Failed to start runtime: {}", e))?;
+    let mut iteration = None;
+    let (report, _) = runtime.block_on(run_requests(
+        &reqwest::Client::new(),
+        &TokenCache::new(),
+        &target.name,
+        &target.requests,
+        context,
+        &options,
+        &AtomicBool::new(false),
+        |step| {
+            if options.data.len() > 1 && iteration != Some(step.iteration) {
+                iteration = Some(step.iteration);
+                println!("Iteration {}", step.iteration + 1);
+            }
+            print_step(step);
+        },
+    ));
+    println!(
+        "{} passed, {} failed in {} ms{}",
+        report.passed_count(),
+        report.failed_count(),
+        report.duration_ms,
+        if report.stopped_early {
+            " (stopped early)"
+        } else {
+            ""
+        }
+    );
+
+    if let Some(collection_id) = target.collection_id {
+        if let Err(e) = run_service::save_run(&conn, collection_id, target.folder_id, &report) {
+            eprintln!("warning: {}", e);
+        }
+    }
+    if let Some(path) = &args.junit {
+        write_file(path, &report::to_junit_xml(&report))?;
+    }
+    if let Some(path) = &args.json {
+        write_file(path, &report::to_json(&report)?)?;
+    }
+    Ok(report.passed())
+}
+
+fn print_step(step: &StepResult) {
+    let mark = if step.passed() { "✓" } else { "✗" };
+    let status = step
+        .status
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| "---".to_string());
+    println!(
+        "  {} {:<7} {} [{}, {} ms]",
+        mark, step.method, step.name, status, step.duration_ms
+    );
+    for failure in step.failures() {
+        println!("      {}", failure);
+    }
+    for error in &step.extraction_errors {
+        println!("      extraction failed: {}", error);
+    }
+}
+
+fn write_file(path: &Path, content: &str) -> Result<(), String> {
+    std::fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn load_target(
+    conn: &Connection,
+    collection: &str,
+    folder: Option<&str>,
+) -> Result<Target, String> {
+    let path = Path::new(collection);
+    if path.is_file() {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if astranova::is_astranova_collection(&content) {
+            return target_from_astranova(&content, folder);
+        }
+        return target_from_postman(&content, folder);
+    }
+
+    let col = collection_service::get_all(conn)
+        .into_iter()
+        .find(|c| c.name == collection)
+        .ok_or_else(|| format!("Collection '{}' not found", collection))?;
+    let folder_id = match folder {
+        Some(name) => Some(
+            collection_service::get_folders(conn, col.id)
+                .into_iter()
+                .find(|f| f.name == name)
+                .map(|f| f.id)
+                .ok_or_else(|| format!("Folder '{}' not found in '{}'", name, col.name))?,
+        ),
+        None => None,
+    };
+    Ok(Target {
+        name: match folder {
+            Some(name) => format!("{} / {}", col.name, name),
+            None => col.name.clone(),
+        },
+        collection_id: Some(col.id),
+        folder_id,
+        variables: col.variables,
+        requests: run_service::requests_for_run(conn, col.id, folder_id),
+    })
+}
+
+fn target_from_astranova(content: &str, folder: Option<&str>) -> Result<Target, String> {
+    let collection = astranova::parse_collection(content)?;
+    let (name, requests) = match folder {
+        Some(name) => {
+            let found = collection
+                .folders
+                .iter()
+                .find(|f| f.name == name)
+                .ok_or_else(|| format!("Folder '{}' not found in '{}'", name, collection.name))?;
+            (
+                format!("{} / {}", collection.name, name),
+                found.requests.iter().collect::<Vec<_>>(),
+            )
+        }
+        None => (
+            collection.name.clone(),
+            collection
+                .requests
+                .iter()
+                .chain(collection.folders.iter().flat_map(|f| &f.requests))
+                .collect(),
+        ),
+    };
+    Ok(Target {
+        name,
+        collection_id: None,
+        folder_id: None,
+        variables: collection.variables.clone(),
+        requests: requests
+            .into_iter()
+            .enumerate()
+            .map(|(i, req)| CollectionRequest {
+                id: i as i32,
+                sort_order: i as i32,
+                ..req.clone()
+            })
+            .collect(),
+    })
+}
+
+fn target_from_postman(content: &str, folder: Option<&str>) -> Result<Target, String> {
+    let imported = parse_postman_collection(content)?;
+    let (name, requests) = match folder {
+        Some(name) => {
+            let found = imported
+                .folders
+                .iter()
+                .find(|f| f.name == name)
+                .ok_or_else(|| format!("Folder '{}' not found in '{}'", name, imported.name))?;
+            (
+                format!("{} / {}", imported.name, name),
+                found.requests.iter().collect::<Vec<_>>(),
+            )
+        }
+        None => (
+            imported.name.clone(),
+            imported
+                .requests
+                .iter()
+                .chain(imported.folders.iter().flat_map(|f| &f.requests))
+                .collect(),
+        ),
+    };
+    Ok(Target {
+        name,
+        collection_id: None,
+        folder_id: None,
+        variables: Vec::new(),
+        requests: requests
+            .into_iter()
+            .enumerate()
+            .map(|(i, req)| imported_to_request(i as i32, req))
+            .collect(),
+    })
+}
+
+fn imported_to_request(index: i32, req: &ImportedRequest) -> CollectionRequest {
+    CollectionRequest {
+        id: index,
+        name: req.name.clone(),
+        method: req.method.clone(),
+        url: req.url.clone(),
+        headers: req.headers.clone(),
+        body: req.body.clone(),
+        body_type: "text".to_string(),
+        auth_type: "none".to_string(),
+        params: req.params.clone(),
+        sort_order: index,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{Cli, Command};
+    use clap::Parser;
+
+    const POSTMAN: &str = r#"{
+        "info": { "name": "Shop" },
+        "item": [
+            { "name": "Health", "request": { "method": "GET", "url": { "raw": "{{HOST}}/health" } } },
+            { "name": "Orders", "item": [
+                { "name": "List", "request": { "method": "GET", "url": { "raw": "{{HOST}}/orders" } } }
+            ] }
+        ]
+    }"#;
+
+    fn parse(args: &[&str]) -> RunArgs {
+        let mut argv = vec!["astranova-client", "run"];
+        argv.extend_from_slice(args);
+        match Cli::try_parse_from(argv).unwrap().command {
+            Some(Command::Run(args)) => args,
+            _ => panic!("expected run subcommand"),
+        }
+    }
+
+    #[test]
+    fn parses_run_flags() {
+        let args = parse(&[
+            "Shop",
+            "--env",
+            "staging",
+            "--var",
+            "TOKEN=a=b",
+            "--bail",
+            "--junit",
+            "out.xml",
+        ]);
+        assert_eq!(args.collection, "Shop");
+        assert_eq!(args.environment.env.as_deref(), Some("staging"));
+        assert_eq!(
+            args.environment.vars,
+            vec![("TOKEN".to_string(), "a=b".to_string())]
+        );
+        assert!(args.bail);
+        assert_eq!(args.junit, Some(PathBuf::from("out.xml")));
+        assert!(Cli::try_parse_from(["astranova-client", "run", "Shop", "--var", "oops"]).is_err());
+    }
+
+    #[test]
+    fn postman_target_lists_root_then_folder_requests() {
+        let target = target_from_postman(POSTMAN, None).unwrap();
+        let names: Vec<_> = target.requests.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["Health", "List"]);
+        assert_eq!(target.collection_id, None);
+
+        let folder = target_from_postman(POSTMAN, Some("Orders")).unwrap();
+        assert_eq!(folder.name, "Shop / Orders");
+        assert_eq!(folder.requests.len(), 1);
+        assert!(target_from_postman(POSTMAN, Some("Missing")).is_err());
+    }
+
+    #[test]
+    fn astranova_target_keeps_assertions_and_variables() {
+        let content = r#"{
+            "format": "astranova-collection", "version": 1, "name": "Shop",
+            "variables": [["HOST", "http://localhost"]],
+            "folders": [{ "name": "Orders", "requests": [{
+                "id": 0, "collection_id": 0, "folder_id": null, "name": "List",
+                "method": "GET", "url": "{{HOST}}/orders", "headers": [], "body": null,
+                "body_type": "text", "auth_type": "none", "auth_data": null, "params": [],
+                "config_json": null, "sort_order": 0,
+                "assertions": [{"kind": "StatusEquals", "target": "", "expected": "200"}]
+            }] }]
+        }"#;
+        let target = target_from_astranova(content, Some("Orders")).unwrap();
+        assert_eq!(target.name, "Shop / Orders");
+        assert_eq!(target.variables[0].0, "HOST");
+        assert_eq!(target.requests[0].assertions.len(), 1);
+    }
+}
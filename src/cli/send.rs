@@ -0,0 +1,591 @@
+use super::environment::{self, EnvArgs};
+use crate::data::assertions::{run_assertions, Assertion};
+use crate::data::token_cache::{send_with_token_cache, TokenCache};
+use crate::data::variables::VariableResolver;
+use crate::data::vault::{self, Vault};
+use crate::http_client::client;
+use crate::http_client::config::RequestConfig;
+use crate::http_client::request::HttpRequest;
+use crate::http_client::response::HttpResponse;
+use crate::http_client::template::RequestTemplate;
+use crate::persistence::database::{
+    CollectionFolder, CollectionRequest, Environment, DEFAULT_HISTORY_LIMIT,
+};
+use crate::services::{collection_service, environment_service, history_service, run_service};
+use rusqlite::Connection;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Debug, clap::Args)]
+pub struct SendArgs {
+    /// URL (with a scheme, as `host:port/...`, or starting with `{{var}}`), saved request name,
+    /// or `Collection/Folder/Request` path.
+    pub target: String,
+    /// HTTP method; defaults to GET, or to the saved request's method.
+    #[arg(long, short = 'X')]
+    pub method: Option<String>,
+    /// Header as `Name: value`; replaces a saved header of the same name.
+    #[arg(long = "header", short = 'H', value_name = "NAME: VALUE", value_parser = parse_header)]
+    pub headers: Vec<(String, String)>,
+    /// Request body, or `@FILE` to read it from a file.
+    #[arg(long, short = 'd', value_name = "BODY")]
+    pub data: Option<String>,
+    #[command(flatten)]
+    pub environment: EnvArgs,
+    /// Request timeout, in seconds.
+    #[arg(long, value_name = "SECS")]
+    pub timeout: Option<u64>,
+    /// Accept invalid TLS certificates.
+    #[arg(long, short = 'k')]
+    pub insecure: bool,
+    #[arg(long, value_name = "URL")]
+    pub proxy: Option<String>,
+    /// Print only the response body.
+    #[arg(long)]
+    pub body_only: bool,
+    /// Print the body as received instead of pretty-printing JSON.
+    #[arg(long)]
+    pub raw: bool,
+    /// Database to use instead of the app's own.
+    #[arg(long, value_name = "FILE")]
+    pub db: Option<PathBuf>,
+}
+
+fn parse_header(value: &str) -> Result<(String, String), String> {
+    value
+        .split_once(':')
+        .filter(|(name, _)| !name.trim().is_empty())
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .ok_or_else(|| format!("Expected 'Name: value', got '{}'", value))
+}
+
+/// 0 for a 1xx-3xx response with passing assertions, 1 for an error status or
+/// failed assertion, 2 when nothing was received.
+pub fn execute(args: SendArgs) -> i32 {
+    match send(&args) {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            2
+        }
+    }
+}
+
+/// The request to send, and a copy with `{{variables}}` kept and auth redacted for history.
+struct Prepared {
+    request: HttpRequest,
+    history: HttpRequest,
+    assertions: Vec<Assertion>,
+    unresolved: Vec<String>,
+}
+
+fn send(args: &SendArgs) -> Result<bool, String> {
+    let conn = environment::open_database(args.db.as_deref())?;
+    let vault = environment::vault_from_env(&conn)?;
+    let environment = environment::build_environment(&conn, &args.environment, vault.as_ref())?;
+    environment::warn_locked_secrets(environment.as_ref());
+    let prepared = prepare(
+        &conn,
+        args,
+        &environment_service::get_globals(&conn),
+        environment.as_ref(),
+        vault.as_ref(),
+    )?;
+    if !prepared.unresolved.is_empty() {
+        eprintln!(
+            "warning: unresolved variables: {}",
+            prepared.unresolved.join(", ")
+        );
+    }
+
+    let runtime =
+        tokio::runtime::Runtime::new().map_err(|e| format!("Failed to start runtime: {}", e))?;
+    let result = runtime.block_on(deliver(prepared.request.clone()));
+    record_history(&conn, &prepared, &result);
+    let response = result?;
+
+    print_response(&response, args.body_only, args.raw);
+    let mut passed = response.status < 400;
+    for result in run_assertions(&prepared.assertions, &response) {
+        let mark = if result.passed { "✓" } else { "✗" };
+        eprintln!("{} {}: {}", mark, result.description, result.message);
+        passed &= result.passed;
+    }
+    Ok(passed)
+}
+
+fn prepare(
+    conn: &Connection,
+    args: &SendArgs,
+    globals: &[(String, String)],
+    environment: Option<&Environment>,
+    vault: Option<&Vault>,
+) -> Result<Prepared, String> {
+    let (mut prepared, resolver) = if is_url(&args.target) {
+        let resolver = VariableResolver::scoped(globals, &[], environment, &[]);
+        let history = HttpRequest {
+            method: "GET".to_string(),
+            url: inline_url(&args.target),
+            headers: Vec::new(),
+            body: None,
+            config: RequestConfig::default(),
+            multipart_fields: Vec::new(),
+            auth: None,
+        };
+        let request = HttpRequest {
+            url: resolver.resolve(&history.url),
+            ..history.clone()
+        };
+        let prepared = Prepared {
+            request,
+            history,
+            assertions: Vec::new(),
+            unresolved: Vec::new(),
+        };
+        (prepared, resolver)
+    } else {
+        let (saved, collection_variables) = find_saved_request(conn, &args.target)?;
+        let mut template = RequestTemplate::from(&saved);
+        if let Some(vault) = vault {
+            template.auth = vault::open_auth(&template.auth, vault);
+        }
+        if vault::has_locked_secrets(&template.auth) {
+            return Err(format!(
+                "Credentials are encrypted; set {} to unlock them",
+                environment::VAULT_PASSPHRASE_VAR
+            ));
+        }
+        let resolver = VariableResolver::scoped(
+            globals,
+            &collection_variables,
+            environment,
+            &saved.variables,
+        );
+        let history = RequestTemplate {
+            auth: vault::redact_auth(&template.auth),
+            ..template.clone()
+        };
+        let prepared = Prepared {
+            request: template.build_resolved(&resolver),
+            history: history.build(),
+            assertions: saved.assertions,
+            unresolved: template.unresolved_variables(&resolver),
+        };
+        (prepared, resolver)
+    };
+
+    let body = match args.data.as_deref() {
+        Some(data) => Some(match data.strip_prefix('@') {
+            Some(path) => std::fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read {}: {}", path, e))?,
+            None => data.to_string(),
+        }),
+        None => None,
+    };
+    apply_overrides(&mut prepared.history, args, body.as_deref(), |text| {
+        text.to_string()
+    });
+    apply_overrides(&mut prepared.request, args, body.as_deref(), |text| {
+        resolver.resolve(text)
+    });
+    let auth = prepared.history.auth.clone().unwrap_or_default();
+    vault::redact_headers(&mut prepared.history.headers, &auth);
+    for text in std::iter::once(&prepared.history.url)
+        .chain(args.headers.iter().map(|(_, value)| value))
+        .chain(body.as_ref())
+    {
+        for name in resolver.unresolved(text) {
+            if !prepared.unresolved.contains(&name) {
+                prepared.unresolved.push(name);
+            }
+        }
+    }
+    Ok(prepared)
+}
+
+fn is_url(target: &str) -> bool {
+    target.contains("://") || target.starts_with("{{") || host_port(target).is_some()
+}
+
+/// The `host:port` that `target` starts with, as in `localhost:8080/orders`.
+fn host_port(target: &str) -> Option<&str> {
+    let authority = target.split('/').next()?;
+    let (host, port) = authority.rsplit_once(':')?;
+    let is_port = !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit());
+    (!host.is_empty() && is_port).then_some(authority)
+}
+
+/// Scheme-less `host:port` targets are sent over plain HTTP.
+fn inline_url(target: &str) -> String {
+    if !target.contains("://") && host_port(target).is_some() {
+        format!("http://{}", target)
+    } else {
+        target.to_string()
+    }
+}
+
+/// Command-line method, headers, body and connection flags win over the saved request.
+fn apply_overrides(
+    request: &mut HttpRequest,
+    args: &SendArgs,
+    body: Option<&str>,
+    resolve: impl Fn(&str) -> String,
+) {
+    if let Some(method) = &args.method {
+        request.method = method.to_uppercase();
+    }
+    for (name, value) in &args.headers {
+        let value = resolve(value);
+        match request
+            .headers
+            .iter_mut()
+            .find(|(existing, _)| existing.eq_ignore_ascii_case(name))
+        {
+            Some(header) => header.1 = value,
+            None => request.headers.push((name.clone(), value)),
+        }
+    }
+    if let Some(body) = body {
+        request.body = Some(resolve(body));
+        request.multipart_fields.clear();
+    }
+    if let Some(timeout) = args.timeout {
+        request.config.timeout = Duration::from_secs(timeout);
+    }
+    if args.insecure {
+        request.config.verify_ssl = false;
+    }
+    if let Some(proxy) = &args.proxy {
+        request.config.proxy_url = Some(proxy.clone());
+    }
+}
+
+/// Finds a request by its `Collection/Folder/Request` path, or by name when only one has it.
+fn find_saved_request(
+    conn: &Connection,
+    target: &str,
+) -> Result<(CollectionRequest, Vec<(String, String)>), String> {
+    let mut candidates = Vec::new();
+    for collection in collection_service::get_all(conn) {
+        let folders = collection_service::get_folders(conn, collection.id);
+        for request in run_service::requests_for_run(conn, collection.id, None) {
+            let path = request_path(&collection.name, &folders, &request);
+            candidates.push((path, request, collection.variables.clone()));
+        }
+    }
+
+    if let Some(index) = candidates.iter().position(|(path, ..)| path == target) {
+        let (_, request, variables) = candidates.swap_remove(index);
+        return Ok((request, variables));
+    }
+    let mut named: Vec<_> = candidates
+        .into_iter()
+        .filter(|(_, request, _)| request.name == target)
+        .collect();
+    match named.len() {
+        0 => Err(format!("No saved request named '{}'", target)),
+        1 => {
+            let (_, request, variables) = named.remove(0);
+            Ok((request, variables))
+        }
+        _ => Err(format!(
+            "'{}' matches several saved requests; use one of: {}",
+            target,
+            named
+                .iter()
+                .map(|(path, ..)| path.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+fn request_path(
+    collection: &str,
+    folders: &[CollectionFolder],
+    request: &CollectionRequest,
+) -> String {
+    let mut parts = vec![request.name.as_str()];
+    let mut folder_id = request.folder_id;
+    while let Some(folder) = folder_id.and_then(|id| folders.iter().find(|f| f.id == id)) {
+        if parts.len() > folders.len() {
+            break;
+        }
+        parts.push(&folder.name);
+        folder_id = folder.parent_folder_id;
+    }
+    parts.push(collection);
+    parts.reverse();
+    parts.join("/")
+}
+
+async fn deliver(request: HttpRequest) -> Result<HttpResponse, String> {
+    let http_client = if request.config.proxy_url.is_some() || !request.config.verify_ssl {
+        client::build_client(&request.config)
+            .map_err(|e| format!("Failed to build client: {}", e))?
+    } else {
+        reqwest::Client::new()
+    };
+    send_with_token_cache(&http_client, request, &TokenCache::new()).await
+}
+
+/// Failed sends are kept too, without a status or response.
+fn record_history(conn: &Connection, prepared: &Prepared, result: &Result<HttpResponse, String>) {
+    let request_data = serde_json::to_string(&prepared.history).ok();
+    let saved = match result {
+        Ok(response) => history_service::save_raw(
+            conn,
+            &response.method,
+            &response.url,
+            Some(response.status),
+            Some(response.duration.as_millis() as u64),
+            request_data.as_deref(),
+            serde_json::to_string(response).ok().as_deref(),
+        ),
+        Err(_) => history_service::save_raw(
+            conn,
+            &prepared.request.method,
+            &prepared.request.url,
+            None,
+            None,
+            request_data.as_deref(),
+            None,
+        ),
+    };
+    if let Err(e) = saved {
+        eprintln!("warning: Failed to save history: {}", e);
+    }
+    history_service::trim(conn, DEFAULT_HISTORY_LIMIT);
+}
+
+fn print_response(response: &HttpResponse, body_only: bool, raw: bool) {
+    if !body_only {
+        let reason = reqwest::StatusCode::from_u16(response.status)
+            .ok()
+            .and_then(|s| s.canonical_reason())
+            .unwrap_or("");
+        println!(
+            "HTTP {} {} ({} ms, {} bytes)",
+            response.status,
+            reason,
+            response.duration.as_millis(),
+            response.size
+        );
+        for (name, value) in &response.headers {
+            println!("{}: {}", name, value);
+        }
+        println!();
+    }
+    println!("{}", format_body(&response.body, raw));
+}
+
+fn format_body(body: &str, raw: bool) -> String {
+    if raw {
+        return body.to_string();
+    }
+    serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|json| serde_json::to_string_pretty(&json).ok())
+        .unwrap_or_else(|| body.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{Cli, Command};
+    use crate::persistence::database;
+    use clap::Parser;
+    use std::path::Path;
+
+    fn parse(args: &[&str]) -> SendArgs {
+        let mut argv = vec!["astranova-client", "send"];
+        argv.extend_from_slice(args);
+        match Cli::try_parse_from(argv).unwrap().command {
+            Some(Command::Send(args)) => args,
+            _ => panic!("expected send subcommand"),
+        }
+    }
+
+    fn setup_db() -> Connection {
+        let conn = database::open(Path::new(":memory:")).unwrap();
+        let shop = collection_service::create(&conn, "Shop").unwrap();
+        let orders = collection_service::create_folder(&conn, shop.id, "Orders").unwrap();
+        collection_service::save_request(
+            &conn,
+            shop.id,
+            Some(orders.id),
+            "List",
+            "GET",
+            "{{HOST}}/orders",
+            &[("Accept".to_string(), "text/plain".to_string())],
+            None,
+            "text",
+            "none",
+            None,
+            &[],
+            None,
+        )
+        .unwrap();
+        let admin = collection_service::create(&conn, "Admin").unwrap();
+        for id in [shop.id, admin.id] {
+            collection_service::save_request(
+                &conn,
+                id,
+                None,
+                "Health",
+                "GET",
+                "{{HOST}}/health",
+                &[],
+                None,
+                "text",
+                "none",
+                None,
+                &[],
+                None,
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    fn env(vars: &[(&str, &str)]) -> Environment {
+        Environment {
+            id: 0,
+            name: "cli".to_string(),
+            variables: vars
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            default_endpoint: None,
+            secret_keys: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn parses_curl_like_flags() {
+        let args = parse(&[
+            "{{HOST}}/orders",
+            "-X",
+            "post",
+            "-H",
+            "Content-Type: application/json",
+            "-d",
+            r#"{"id":1}"#,
+            "-k",
+            "-e",
+            "staging",
+        ]);
+        assert_eq!(args.method.as_deref(), Some("post"));
+        assert_eq!(
+            args.headers,
+            vec![("Content-Type".to_string(), "application/json".to_string())]
+        );
+        assert!(args.insecure);
+        assert_eq!(args.environment.env.as_deref(), Some("staging"));
+        assert!(Cli::try_parse_from(["astranova-client", "send", "x", "-H", "oops"]).is_err());
+    }
+
+    #[test]
+    fn inline_request_resolves_variables_but_history_keeps_them() {
+        let conn = setup_db();
+        let args = parse(&[
+            "{{HOST}}/orders",
+            "-X",
+            "post",
+            "-H",
+            "X-Tenant: {{TENANT}}",
+            "-d",
+            "{{BODY}}",
+            "--timeout",
+            "5",
+        ]);
+        let env = env(&[("HOST", "http://localhost:1"), ("TENANT", "acme")]);
+        let prepared = prepare(&conn, &args, &[], Some(&env), None).unwrap();
+        assert_eq!(prepared.request.method, "POST");
+        assert_eq!(prepared.request.url, "http://localhost:1/orders");
+        assert_eq!(
+            prepared.request.headers,
+            vec![("X-Tenant".to_string(), "acme".to_string())]
+        );
+        assert_eq!(prepared.request.config.timeout, Duration::from_secs(5));
+        assert_eq!(prepared.history.url, "{{HOST}}/orders");
+        assert_eq!(prepared.unresolved, vec!["BODY".to_string()]);
+    }
+
+    #[test]
+    fn saved_request_found_by_path_or_unique_name() {
+        let conn = setup_db();
+        let env = env(&[("HOST", "http://api")]);
+        let args = parse(&["Shop/Orders/List", "-H", "accept: application/json"]);
+        let prepared = prepare(&conn, &args, &[], Some(&env), None).unwrap();
+        assert_eq!(prepared.request.url, "http://api/orders");
+        assert_eq!(
+            prepared.request.headers,
+            vec![("Accept".to_string(), "application/json".to_string())]
+        );
+
+        assert!(find_saved_request(&conn, "List").is_ok());
+        assert!(find_saved_request(&conn, "Admin/Health").is_ok());
+        let err = find_saved_request(&conn, "Health").unwrap_err();
+        assert!(
+            err.contains("Shop/Health") && err.contains("Admin/Health"),
+            "{}",
+            err
+        );
+        assert!(find_saved_request(&conn, "Missing").is_err());
+    }
+
+    #[test]
+    fn history_records_failed_sends() {
+        let conn = setup_db();
+        let args = parse(&["http://localhost:1/orders"]);
+        let prepared = prepare(&conn, &args, &[], None, None).unwrap();
+        record_history(&conn, &prepared, &Err("connection refused".to_string()));
+        let entries = history_service::get_all(&conn, 10);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].url, "http://localhost:1/orders");
+        assert_eq!(entries[0].status, None);
+    }
+
+    #[test]
+    fn history_drops_credential_headers() {
+        let conn = setup_db();
+        let args = parse(&[
+            "http://api/orders",
+            "-H",
+            "Authorization: Bearer secret",
+            "-H",
+            "Proxy-Authorization: Basic c2VjcmV0",
+            "-H",
+            "X-Token: {{TOKEN}}",
+        ]);
+        let prepared = prepare(&conn, &args, &[], None, None).unwrap();
+        assert_eq!(prepared.request.headers[0].1, "Bearer secret");
+        assert_eq!(
+            prepared.history.headers,
+            vec![
+                ("Authorization".to_string(), String::new()),
+                ("Proxy-Authorization".to_string(), String::new()),
+                ("X-Token".to_string(), "{{TOKEN}}".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn host_and_port_targets_are_urls() {
+        assert!(is_url("localhost:8080/orders"));
+        assert!(is_url("127.0.0.1:3000"));
+        assert!(!is_url("Shop/Orders/List"));
+        assert!(!is_url("Health: check"));
+        assert_eq!(inline_url("localhost:8080/x"), "http://localhost:8080/x");
+        assert_eq!(inline_url("https://api:443/x"), "https://api:443/x");
+    }
+
+    #[test]
+    fn json_bodies_are_pretty_printed_unless_raw() {
+        assert_eq!(format_body(r#"{"a":1}"#, false), "{\n  \"a\": 1\n}");
+        assert_eq!(format_body(r#"{"a":1}"#, true), r#"{"a":1}"#);
+        assert_eq!(format_body("plain", false), "plain");
+    }
+}
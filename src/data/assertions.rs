@@ -0,0 +1,376 @@
+use crate::data::extraction::{json_to_plain, query_json_path};
+use crate::http_client::response::HttpResponse;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AssertionKind {
+    #[default]
+    StatusEquals,
+    StatusInRange,
+    JsonPathEquals,
+    JsonPathContains,
+    JsonPathMatches,
+    HeaderExists,
+    ResponseTimeBelow,
+    MatchesJsonSchema,
+}
+
+impl AssertionKind {
+    pub const ALL: [AssertionKind; 8] = [
+        AssertionKind::StatusEquals,
+        AssertionKind::StatusInRange,
+        AssertionKind::JsonPathEquals,
+        AssertionKind::JsonPathContains,
+        AssertionKind::JsonPathMatches,
+        AssertionKind::HeaderExists,
+        AssertionKind::ResponseTimeBelow,
+        AssertionKind::MatchesJsonSchema,
+    ];
+
+    /// Whether the assertion reads a JSONPath or header name from `target`.
+    pub fn has_target(&self) -> bool {
+        matches!(
+            self,
+            AssertionKind::JsonPathEquals
+                | AssertionKind::JsonPathContains
+                | AssertionKind::JsonPathMatches
+                | AssertionKind::HeaderExists
+        )
+    }
+
+    pub fn target_placeholder(&self) -> &'static str {
+        match self {
+            AssertionKind::HeaderExists => "Header name",
+            _ => "$.data.id",
+        }
+    }
+
+    pub fn expected_placeholder(&self) -> &'static str {
+        match self {
+            AssertionKind::StatusEquals => "200",
+            AssertionKind::StatusInRange => "200-299",
+            AssertionKind::JsonPathEquals | AssertionKind::JsonPathContains => "Expected value",
+            AssertionKind::JsonPathMatches => "Regex",
+            AssertionKind::HeaderExists => "Optional value",
+            AssertionKind::ResponseTimeBelow => "Milliseconds",
+            AssertionKind::MatchesJsonSchema => r#"{"type": "object"}"#,
+        }
+    }
+}
+
+impl std::fmt::Display for AssertionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssertionKind::StatusEquals => write!(f, "Status equals"),
+            AssertionKind::StatusInRange => write!(f, "Status in range"),
+            AssertionKind::JsonPathEquals => write!(f, "JSONPath equals"),
+            AssertionKind::JsonPathContains => write!(f, "JSONPath contains"),
+            AssertionKind::JsonPathMatches => write!(f, "JSONPath matches"),
+            AssertionKind::HeaderExists => write!(f, "Header exists"),
+            AssertionKind::ResponseTimeBelow => write!(f, "Response time below"),
+            AssertionKind::MatchesJsonSchema => write!(f, "Body matches JSON Schema"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Assertion {
+    pub kind: AssertionKind,
+    /// JSONPath expression or header name, depending on `kind`.
+    #[serde(default)]
+    pub target: String,
+    pub expected: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssertionResult {
+    pub description: String,
+    pub passed: bool,
+    pub message: String,
+}
+
+impl Assertion {
+    pub fn description(&self) -> String {
+        if self.kind.has_target() {
+            format!("{} {} {}", self.target, self.kind, self.expected)
+                .trim_end()
+                .to_string()
+        } else {
+            format!("{} {}", self.kind, self.expected)
+        }
+    }
+
+    pub fn evaluate(&self, response: &HttpResponse) -> AssertionResult {
+        let outcome = self.check(response);
+        AssertionResult {
+            description: self.description(),
+            passed: outcome.is_ok(),
+            message: match outcome {
+                Ok(message) | Err(message) => message,
+            },
+        }
+    }
+
+    /// `Ok` with what was observed, or `Err` explaining the failure.
+    fn check(&self, response: &HttpResponse) -> Result<String, String> {
+        let expected = self.expected.trim();
+        match self.kind {
+            AssertionKind::StatusEquals => {
+                let want: u16 = expected
+                    .parse()
+                    .map_err(|_| format!("Invalid status code '{}'", expected))?;
+                expect(
+                    response.status == want,
+                    format!("status {}", response.status),
+                )
+            }
+            AssertionKind::StatusInRange => {
+                let (low, high) = parse_range(expected)?;
+                expect(
+                    (low..=high).contains(&response.status),
+                    format!("status {}", response.status),
+                )
+            }
+            AssertionKind::JsonPathEquals => {
+                let actual = json_to_plain(&query_json_path(&response.body, &self.target)?);
+                expect(actual == expected, format!("got '{}'", actual))
+            }
+            AssertionKind::JsonPathContains => {
+                let node = query_json_path(&response.body, &self.target)?;
+                let contains = match &node {
+                    serde_json::Value::Array(items) => {
+                        items.iter().any(|item| json_to_plain(item) == expected)
+                    }
+                    other => json_to_plain(other).contains(expected),
+                };
+                expect(contains, format!("got '{}'", json_to_plain(&node)))
+            }
+            AssertionKind::JsonPathMatches => {
+                let re = regex::Regex::new(expected)
+                    .map_err(|e| format!("Invalid regex '{}': {}", expected, e))?;
+                let actual = json_to_plain(&query_json_path(&response.body, &self.target)?);
+                expect(re.is_match(&actual), format!("got '{}'", actual))
+            }
+            AssertionKind::HeaderExists => {
+                let value = response
+                    .headers
+                    .iter()
+                    .find(|(k, _)| k.eq_ignore_ascii_case(self.target.trim()))
+                    .map(|(_, v)| v.as_str())
+                    .ok_or_else(|| format!("Header '{}' missing", self.target))?;
+                expect(
+                    expected.is_empty() || value == expected,
+                    format!("got '{}'", value),
+                )
+            }
+            AssertionKind::ResponseTimeBelow => {
+                let limit: u128 = expected
+                    .parse()
+                    .map_err(|_| format!("Invalid duration '{}'", expected))?;
+                let took = response.duration.as_millis();
+                expect(took < limit, format!("took {}ms", took))
+            }
+            AssertionKind::MatchesJsonSchema => {
+                let schema: serde_json::Value = serde_json::from_str(expected)
+                    .map_err(|e| format!("Invalid schema JSON: {}", e))?;
+                let body: serde_json::Value = serde_json::from_str(&response.body)
+                    .map_err(|e| format!("Response is not JSON: {}", e))?;
+                let errors = schema_errors(&schema, &body)?;
+                if errors.is_empty() {
+                    Ok("body matches schema".to_string())
+                } else {
+                    Err(errors.join("; "))
+                }
+            }
+        }
+    }
+}
+
+fn expect(passed: bool, observed: String) -> Result<String, String> {
+    if passed {
+        Ok(observed)
+    } else {
+        Err(observed)
+    }
+}
+
+fn parse_range(range: &str) -> Result<(u16, u16), String> {
+    let invalid = || format!("Invalid status range '{}', expected e.g. 200-299", range);
+    let (low, high) = range.split_once('-').ok_or_else(invalid)?;
+    let low = low.trim().parse().map_err(|_| invalid())?;
+    let high = high.trim().parse().map_err(|_| invalid())?;
+    Ok((low, high))
+}
+
+/// Validation errors of `instance` against a JSON Schema, as `path: message` lines.
+pub fn schema_errors(
+    schema: &serde_json::Value,
+    instance: &serde_json::Value,
+) -> Result<Vec<String>, String> {
+    let validator =
+        jsonschema::validator_for(schema).map_err(|e| format!("Invalid JSON Schema: {}", e))?;
+    Ok(validator
+        .iter_errors(instance)
+        .map(|e| {
+            let path = e.instance_path.to_string();
+            if path.is_empty() {
+                e.to_string()
+            } else {
+                format!("{}: {}", path, e)
+            }
+        })
+        .collect())
+}
+
+/// Evaluates assertions in order; incomplete ones are skipped.
+pub fn run_assertions(assertions: &[Assertion], response: &HttpResponse) -> Vec<AssertionResult> {
+    assertions
+        .iter()
+        .filter(|a| !a.expected.trim().is_empty() || a.kind == AssertionKind::HeaderExists)
+        .filter(|a| !a.kind.has_target() || !a.target.trim().is_empty())
+        .map(|a| a.evaluate(response))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn response(status: u16, body: &str) -> HttpResponse {
+        HttpResponse {
+            url: "https://example.com/users".to_string(),
+            method: "GET".to_string(),
+            status,
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: body.to_string(),
+            duration: Duration::from_millis(120),
+            size: body.len() as u64,
+            redirect_chain: vec![],
+        }
+    }
+
+    fn assertion(kind: AssertionKind, target: &str, expected: &str) -> Assertion {
+        Assertion {
+            kind,
+            target: target.to_string(),
+            expected: expected.to_string(),
+        }
+    }
+
+    #[test]
+    fn status_equals_and_range() {
+        let resp = response(201, "{}");
+        assert!(
+            assertion(AssertionKind::StatusEquals, "", "201")
+                .evaluate(&resp)
+                .passed
+        );
+        assert!(
+            !assertion(AssertionKind::StatusEquals, "", "200")
+                .evaluate(&resp)
+                .passed
+        );
+        assert!(
+            assertion(AssertionKind::StatusInRange, "", "200-299")
+                .evaluate(&resp)
+                .passed
+        );
+        assert!(
+            !assertion(AssertionKind::StatusInRange, "", "oops")
+                .evaluate(&resp)
+                .passed
+        );
+    }
+
+    #[test]
+    fn json_path_checks() {
+        let resp = response(200, r#"{"name":"Ada","tags":["admin","dev"],"id":42}"#);
+        assert!(
+            assertion(AssertionKind::JsonPathEquals, "$.id", "42")
+                .evaluate(&resp)
+                .passed
+        );
+        assert!(
+            assertion(AssertionKind::JsonPathContains, "$.tags", "admin")
+                .evaluate(&resp)
+                .passed
+        );
+        assert!(
+            !assertion(AssertionKind::JsonPathContains, "$.tags", "ops")
+                .evaluate(&resp)
+                .passed
+        );
+        assert!(
+            assertion(AssertionKind::JsonPathMatches, "$.name", "^A")
+                .evaluate(&resp)
+                .passed
+        );
+        let missing = assertion(AssertionKind::JsonPathEquals, "$.missing", "1").evaluate(&resp);
+        assert!(!missing.passed);
+        assert!(missing.message.contains("No match"));
+    }
+
+    #[test]
+    fn header_exists_optionally_checks_value() {
+        let resp = response(200, "{}");
+        assert!(
+            assertion(AssertionKind::HeaderExists, "content-type", "")
+                .evaluate(&resp)
+                .passed
+        );
+        assert!(
+            !assertion(AssertionKind::HeaderExists, "content-type", "text/html")
+                .evaluate(&resp)
+                .passed
+        );
+        assert!(
+            !assertion(AssertionKind::HeaderExists, "ETag", "")
+                .evaluate(&resp)
+                .passed
+        );
+    }
+
+    #[test]
+    fn response_time_below() {
+        let resp = response(200, "{}");
+        assert!(
+            assertion(AssertionKind::ResponseTimeBelow, "", "500")
+                .evaluate(&resp)
+                .passed
+        );
+        assert!(
+            !assertion(AssertionKind::ResponseTimeBelow, "", "100")
+                .evaluate(&resp)
+                .passed
+        );
+    }
+
+    #[test]
+    fn json_schema_reports_violations() {
+        let schema =
+            r#"{"type":"object","required":["id"],"properties":{"id":{"type":"integer"}}}"#;
+        let ok = assertion(AssertionKind::MatchesJsonSchema, "", schema)
+            .evaluate(&response(200, r#"{"id":1}"#));
+        assert!(ok.passed);
+        let bad = assertion(AssertionKind::MatchesJsonSchema, "", schema)
+            .evaluate(&response(200, r#"{"id":"x"}"#));
+        assert!(!bad.passed);
+        assert!(bad.message.contains("/id"));
+    }
+
+    #[test]
+    fn run_assertions_skips_incomplete_entries() {
+        let resp = response(200, "{}");
+        let results = run_assertions(
+            &[
+                assertion(AssertionKind::StatusEquals, "", "200"),
+                assertion(AssertionKind::StatusEquals, "", ""),
+                assertion(AssertionKind::JsonPathEquals, "", "1"),
+            ],
+            &resp,
+        );
+        assert_eq!(results.len(), 1);
+        assert!(results[0].passed);
+    }
+}
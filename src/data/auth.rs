@@ -41,10 +41,15 @@ pub enum ApiKeyLocation {
     #[default]
     Header,
     Query,
+    Cookie,
 }
 
 impl ApiKeyLocation {
-    pub const ALL: [ApiKeyLocation; 2] = [ApiKeyLocation::Header, ApiKeyLocation::Query];
+    pub const ALL: [ApiKeyLocation; 3] = [
+        ApiKeyLocation::Header,
+        ApiKeyLocation::Query,
+        ApiKeyLocation::Cookie,
+    ];
 }
 
 impl std::fmt::Display for ApiKeyLocation {
@@ -52,6 +57,7 @@ impl std::fmt::Display for ApiKeyLocation {
         match self {
             ApiKeyLocation::Header => write!(f, "Header"),
             ApiKeyLocation::Query => write!(f, "Query Parameter"),
+            ApiKeyLocation::Cookie => write!(f, "Cookie"),
         }
     }
 }
@@ -98,6 +104,32 @@ pub struct OAuth2Config {
     pub device_code_expires_in: Option<u64>,
     pub device_code_interval: Option<u64>,
     pub status: OAuth2Status,
+    #[serde(default)]
+    pub issuer_url: String,
+    #[serde(default)]
+    pub jwks_uri: String,
+    #[serde(default)]
+    pub scopes_supported: Vec<String>,
+    #[serde(default)]
+    pub code_challenge_methods_supported: Vec<String>,
+    #[serde(default)]
+    pub id_token: String,
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+    #[serde(default)]
+    pub assertion: String,
+    #[serde(default)]
+    pub client_auth_method: ClientAuthMethod,
+    #[serde(default)]
+    pub private_key: String,
+    #[serde(default)]
+    pub introspection_url: String,
+    #[serde(default)]
+    pub revocation_url: String,
+    #[serde(skip)]
+    pub introspection: Option<crate::data::oauth2::IntrospectionResponse>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
@@ -118,14 +150,18 @@ pub enum OAuth2GrantType {
     ClientCredentials,
     Implicit,
     DeviceCode,
+    Password,
+    JwtBearer,
 }
 
 impl OAuth2GrantType {
-    pub const ALL: [OAuth2GrantType; 4] = [
+    pub const ALL: [OAuth2GrantType; 6] = [
         OAuth2GrantType::AuthorizationCode,
         OAuth2GrantType::ClientCredentials,
         OAuth2GrantType::Implicit,
         OAuth2GrantType::DeviceCode,
+        OAuth2GrantType::Password,
+        OAuth2GrantType::JwtBearer,
     ];
 }
 
@@ -136,6 +172,34 @@ impl std::fmt::Display for OAuth2GrantType {
             OAuth2GrantType::ClientCredentials => write!(f, "Client Credentials"),
             OAuth2GrantType::Implicit => write!(f, "Implicit"),
             OAuth2GrantType::DeviceCode => write!(f, "Device Code"),
+            OAuth2GrantType::Password => write!(f, "Password"),
+            OAuth2GrantType::JwtBearer => write!(f, "JWT Bearer"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum ClientAuthMethod {
+    #[default]
+    ClientSecretPost,
+    ClientSecretBasic,
+    PrivateKeyJwt,
+}
+
+impl ClientAuthMethod {
+    pub const ALL: [ClientAuthMethod; 3] = [
+        ClientAuthMethod::ClientSecretPost,
+        ClientAuthMethod::ClientSecretBasic,
+        ClientAuthMethod::PrivateKeyJwt,
+    ];
+}
+
+impl std::fmt::Display for ClientAuthMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientAuthMethod::ClientSecretPost => write!(f, "Client Secret (Body)"),
+            ClientAuthMethod::ClientSecretBasic => write!(f, "Client Secret (Basic)"),
+            ClientAuthMethod::PrivateKeyJwt => write!(f, "Private Key JWT"),
         }
     }
 }
@@ -163,6 +227,73 @@ impl Auth {
             Auth::OAuth2(_) => AuthType::OAuth2,
         }
     }
+
+    /// The `auth_type` column value a collection request is saved with.
+    pub fn stored_type(&self) -> &'static str {
+        match self {
+            Auth::None => "none",
+            Auth::BearerToken(_) => "bearer",
+            Auth::Basic { .. } => "basic",
+            Auth::ApiKey { .. } => "api_key",
+            Auth::Digest { .. } => "digest",
+            Auth::OAuth2(_) => "oauth2",
+        }
+    }
+
+    /// Reads `auth_data` as saved with a collection request: JSON, or the
+    /// older colon-separated strings keyed by `auth_type`.
+    pub fn from_stored(auth_type: &str, auth_data: Option<&str>) -> Auth {
+        let Some(data) = auth_data else {
+            return Auth::None;
+        };
+        if data.starts_with('{') {
+            if let Ok(auth) = serde_json::from_str(data) {
+                return auth;
+            }
+        }
+        match auth_type {
+            "bearer" => Auth::BearerToken(data.to_string()),
+            "basic" => match data.split_once(':') {
+                Some((user, pass)) => Auth::Basic {
+                    user: user.to_string(),
+                    pass: pass.to_string(),
+                },
+                None => Auth::None,
+            },
+            "api_key" => {
+                let parts: Vec<&str> = data.splitn(3, ':').collect();
+                match parts.as_slice() {
+                    [key, value, location] => Auth::ApiKey {
+                        key: key.to_string(),
+                        value: value.to_string(),
+                        location: match *location {
+                            "query" => ApiKeyLocation::Query,
+                            "cookie" => ApiKeyLocation::Cookie,
+                            _ => ApiKeyLocation::Header,
+                        },
+                    },
+                    [key, value] => Auth::ApiKey {
+                        key: key.to_string(),
+                        value: value.to_string(),
+                        location: ApiKeyLocation::Header,
+                    },
+                    _ => Auth::None,
+                }
+            }
+            "digest" => match data.split_once(':') {
+                Some((user, pass)) => Auth::Digest {
+                    user: user.to_string(),
+                    pass: pass.to_string(),
+                },
+                None => Auth::None,
+            },
+            "oauth2" => Auth::OAuth2(Box::new(OAuth2Config {
+                access_token: data.to_string(),
+                ..Default::default()
+            })),
+            _ => Auth::None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -320,4 +451,51 @@ mod tests {
         );
         assert_eq!(Auth::OAuth2(Box::default()).auth_type(), AuthType::OAuth2);
     }
+
+    #[test]
+    fn oauth2_config_deserializes_without_oidc_fields() {
+        let mut json = serde_json::to_value(OAuth2Config::default()).unwrap();
+        let obj = json.as_object_mut().unwrap();
+        for field in [
+            "issuer_url",
+            "jwks_uri",
+            "scopes_supported",
+            "code_challenge_methods_supported",
+            "id_token",
+        ] {
+            obj.remove(field);
+        }
+        let config: OAuth2Config = serde_json::from_value(json).unwrap();
+        assert!(config.issuer_url.is_empty());
+        assert!(config.scopes_supported.is_empty());
+    }
+
+    #[test]
+    fn oauth2_grant_type_all_has_6_variants() {
+        assert_eq!(OAuth2GrantType::ALL.len(), 6);
+        assert_eq!(OAuth2GrantType::Password.to_string(), "Password");
+        assert_eq!(OAuth2GrantType::JwtBearer.to_string(), "JWT Bearer");
+    }
+
+    #[test]
+    fn client_auth_method_defaults_to_post() {
+        assert_eq!(
+            ClientAuthMethod::default(),
+            ClientAuthMethod::ClientSecretPost
+        );
+        assert_eq!(
+            ClientAuthMethod::PrivateKeyJwt.to_string(),
+            "Private Key JWT"
+        );
+    }
+
+    #[test]
+    fn stored_legacy_token_may_be_a_variable() {
+        assert_eq!(
+            Auth::from_stored("bearer", Some("{{TOKEN}}")),
+            Auth::BearerToken("{{TOKEN}}".to_string())
+        );
+        assert_eq!(Auth::from_stored("basic", Some("no-colon")), Auth::None);
+        assert_eq!(Auth::from_stored("bearer", None), Auth::None);
+    }
 }
@@ -0,0 +1,277 @@
+use crate::http_client::response::HttpResponse;
+use serde::{Deserialize, Serialize};
+use serde_json_path::JsonPath;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ExtractionSource {
+    #[default]
+    JsonPath,
+    Header,
+    Regex,
+    Cookie,
+}
+
+impl ExtractionSource {
+    pub const ALL: [ExtractionSource; 4] = [
+        ExtractionSource::JsonPath,
+        ExtractionSource::Header,
+        ExtractionSource::Regex,
+        ExtractionSource::Cookie,
+    ];
+
+    pub fn placeholder(&self) -> &'static str {
+        match self {
+            ExtractionSource::JsonPath => "$.data.token",
+            ExtractionSource::Header => "Header name",
+            ExtractionSource::Regex => "Regex (first capture group)",
+            ExtractionSource::Cookie => "Cookie name",
+        }
+    }
+}
+
+impl std::fmt::Display for ExtractionSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExtractionSource::JsonPath => write!(f, "JSONPath"),
+            ExtractionSource::Header => write!(f, "Header"),
+            ExtractionSource::Regex => write!(f, "Regex"),
+            ExtractionSource::Cookie => write!(f, "Cookie"),
+        }
+    }
+}
+
+/// Where an extracted value is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ExtractionTarget {
+    #[default]
+    Environment,
+    Runtime,
+}
+
+impl ExtractionTarget {
+    pub const ALL: [ExtractionTarget; 2] =
+        [ExtractionTarget::Environment, ExtractionTarget::Runtime];
+}
+
+impl std::fmt::Display for ExtractionTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExtractionTarget::Environment => write!(f, "Environment"),
+            ExtractionTarget::Runtime => write!(f, "Runtime"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ExtractionRule {
+    pub variable: String,
+    pub source: ExtractionSource,
+    pub expression: String,
+    pub target: ExtractionTarget,
+}
+
+impl ExtractionRule {
+    pub fn is_empty(&self) -> bool {
+        self.variable.is_empty() || self.expression.is_empty()
+    }
+
+    pub fn extract(&self, response: &HttpResponse) -> Result<String, String> {
+        match self.source {
+            ExtractionSource::JsonPath => extract_json_path(&response.body, &self.expression),
+            ExtractionSource::Header => response
+                .headers
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(&self.expression))
+                .map(|(_, v)| v.clone())
+                .ok_or_else(|| format!("Header '{}' not found", self.expression)),
+            ExtractionSource::Regex => extract_regex(&response.body, &self.expression),
+            ExtractionSource::Cookie => extract_cookie(&response.headers, &self.expression),
+        }
+    }
+}
+
+/// Result of running one rule against a response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Extracted {
+    pub variable: String,
+    pub target: ExtractionTarget,
+    pub value: Result<String, String>,
+}
+
+/// Runs every complete rule against `response`, in order.
+pub fn run_extractions(rules: &[ExtractionRule], response: &HttpResponse) -> Vec<Extracted> {
+    rules
+        .iter()
+        .filter(|rule| !rule.is_empty())
+        .map(|rule| Extracted {
+            variable: rule.variable.clone(),
+            target: rule.target,
+            value: rule.extract(response),
+        })
+        .collect()
+}
+
+/// Sets `name` in a variable list, replacing an existing entry.
+pub fn upsert_variable(variables: &mut Vec<(String, String)>, name: &str, value: &str) {
+    match variables.iter_mut().find(|(k, _)| k == name) {
+        Some(entry) => entry.1 = value.to_string(),
+        None => variables.push((name.to_string(), value.to_string())),
+    }
+}
+
+/// First node matched by a JSONPath expression in a JSON body.
+pub fn query_json_path(body: &str, expression: &str) -> Result<serde_json::Value, String> {
+    let json: serde_json::Value =
+        serde_json::from_str(body).map_err(|e| format!("Response is not JSON: {}", e))?;
+    let path = JsonPath::parse(expression)
+        .map_err(|e| format!("Invalid JSONPath '{}': {}", expression, e))?;
+    path.query(&json)
+        .first()
+        .cloned()
+        .ok_or_else(|| format!("No match for '{}'", expression))
+}
+
+/// Strings without their quotes, everything else as JSON text.
+pub fn json_to_plain(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn extract_json_path(body: &str, expression: &str) -> Result<String, String> {
+    query_json_path(body, expression).map(|node| json_to_plain(&node))
+}
+
+fn extract_regex(body: &str, expression: &str) -> Result<String, String> {
+    let re = regex::Regex::new(expression)
+        .map_err(|e| format!("Invalid regex '{}': {}", expression, e))?;
+    let captures = re
+        .captures(body)
+        .ok_or_else(|| format!("No match for '{}'", expression))?;
+    let matched = captures.get(1).or_else(|| captures.get(0));
+    Ok(matched.map(|m| m.as_str().to_string()).unwrap_or_default())
+}
+
+fn extract_cookie(headers: &[(String, String)], name: &str) -> Result<String, String> {
+    headers
+        .iter()
+        .filter(|(k, _)| k.eq_ignore_ascii_case("set-cookie"))
+        .filter_map(|(_, v)| v.split(';').next()?.split_once('='))
+        .find(|(cookie, _)| cookie.trim() == name)
+        .map(|(_, value)| value.trim().to_string())
+        .ok_or_else(|| format!("Cookie '{}' not set", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn response(body: &str, headers: &[(&str, &str)]) -> HttpResponse {
+        HttpResponse {
+            url: "https://example.com/login".to_string(),
+            method: "POST".to_string(),
+            status: 200,
+            headers: headers
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            body: body.to_string(),
+            duration: Duration::from_millis(10),
+            size: body.len() as u64,
+            redirect_chain: vec![],
+        }
+    }
+
+    fn rule(source: ExtractionSource, expression: &str) -> ExtractionRule {
+        ExtractionRule {
+            variable: "out".to_string(),
+            source,
+            expression: expression.to_string(),
+            target: ExtractionTarget::Runtime,
+        }
+    }
+
+    #[test]
+    fn json_path_extracts_strings_without_quotes() {
+        let resp = response(r#"{"data":{"token":"abc","id":7}}"#, &[]);
+        assert_eq!(
+            rule(ExtractionSource::JsonPath, "$.data.token").extract(&resp),
+            Ok("abc".to_string())
+        );
+        assert_eq!(
+            rule(ExtractionSource::JsonPath, "$.data.id").extract(&resp),
+            Ok("7".to_string())
+        );
+        assert!(rule(ExtractionSource::JsonPath, "$.missing")
+            .extract(&resp)
+            .is_err());
+    }
+
+    #[test]
+    fn header_lookup_is_case_insensitive() {
+        let resp = response("", &[("Location", "/users/42")]);
+        assert_eq!(
+            rule(ExtractionSource::Header, "location").extract(&resp),
+            Ok("/users/42".to_string())
+        );
+    }
+
+    #[test]
+    fn regex_prefers_first_capture_group() {
+        let resp = response("<input name=\"csrf\" value=\"xyz\">", &[]);
+        assert_eq!(
+            rule(ExtractionSource::Regex, r#"value="(\w+)""#).extract(&resp),
+            Ok("xyz".to_string())
+        );
+        assert_eq!(
+            rule(ExtractionSource::Regex, r"csrf").extract(&resp),
+            Ok("csrf".to_string())
+        );
+    }
+
+    #[test]
+    fn cookie_reads_set_cookie_headers() {
+        let resp = response(
+            "",
+            &[
+                ("Set-Cookie", "theme=dark; Path=/"),
+                ("set-cookie", "session=s1; HttpOnly"),
+            ],
+        );
+        assert_eq!(
+            rule(ExtractionSource::Cookie, "session").extract(&resp),
+            Ok("s1".to_string())
+        );
+        assert!(rule(ExtractionSource::Cookie, "missing")
+            .extract(&resp)
+            .is_err());
+    }
+
+    #[test]
+    fn run_extractions_skips_incomplete_rules() {
+        let resp = response(r#"{"token":"t"}"#, &[]);
+        let rules = vec![
+            rule(ExtractionSource::JsonPath, "$.token"),
+            ExtractionRule::default(),
+        ];
+        let results = run_extractions(&rules, &resp);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].value, Ok("t".to_string()));
+    }
+
+    #[test]
+    fn upsert_variable_replaces_existing() {
+        let mut vars = vec![("token".to_string(), "old".to_string())];
+        upsert_variable(&mut vars, "token", "new");
+        upsert_variable(&mut vars, "id", "1");
+        assert_eq!(
+            vars,
+            vec![
+                ("token".to_string(), "new".to_string()),
+                ("id".to_string(), "1".to_string()),
+            ]
+        );
+    }
+}
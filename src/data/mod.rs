@@ -1,2 +1,8 @@
+pub mod assertions;
 pub mod auth;
+pub mod extraction;
 pub mod oauth2;
+pub mod oidc;
+pub mod token_cache;
+pub mod variables;
+pub mod vault;
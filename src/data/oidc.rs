@@ -0,0 +1,312 @@
+use base64::{engine::general_purpose, Engine as _};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct OidcDiscovery {
+    pub issuer: String,
+    #[serde(default)]
+    pub authorization_endpoint: Option<String>,
+    #[serde(default)]
+    pub token_endpoint: Option<String>,
+    #[serde(default)]
+    pub device_authorization_endpoint: Option<String>,
+    #[serde(default)]
+    pub jwks_uri: Option<String>,
+    #[serde(default)]
+    pub userinfo_endpoint: Option<String>,
+    #[serde(default)]
+    pub introspection_endpoint: Option<String>,
+    #[serde(default)]
+    pub revocation_endpoint: Option<String>,
+    #[serde(default)]
+    pub scopes_supported: Vec<String>,
+    #[serde(default)]
+    pub code_challenge_methods_supported: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedIdToken {
+    pub header: serde_json::Value,
+    pub claims: serde_json::Value,
+}
+
+pub fn discovery_url(issuer_url: &str) -> String {
+    let issuer = issuer_url.trim().trim_end_matches('/');
+    if issuer.ends_with("/.well-known/openid-configuration") {
+        issuer.to_string()
+    } else {
+        format!("{}/.well-known/openid-configuration", issuer)
+    }
+}
+
+pub async fn discover(issuer_url: &str) -> Result<OidcDiscovery, String> {
+    let client = Client::new();
+    let response = client
+        .get(discovery_url(issuer_url))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch OpenID configuration: {}", e))?;
+
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read response body: {}", e))?;
+
+    if !status.is_success() {
+        return Err(format!("OpenID discovery failed: HTTP {}", status.as_u16()));
+    }
+    serde_json::from_str(&body).map_err(|e| format!("Failed to parse OpenID configuration: {}", e))
+}
+
+fn decode_segment(segment: &str) -> Result<serde_json::Value, String> {
+    let bytes = general_purpose::URL_SAFE_NO_PAD
+        .decode(segment.trim_end_matches('='))
+        .map_err(|e| format!("Invalid base64 in id_token: {}", e))?;
+    serde_json::from_slice(&bytes).map_err(|e| format!("Invalid JSON in id_token: {}", e))
+}
+
+pub fn decode_id_token(id_token: &str) -> Result<DecodedIdToken, String> {
+    let parts: Vec<&str> = id_token.trim().split('.').collect();
+    if parts.len() != 3 {
+        return Err("id_token is not a JWS compact token".to_string());
+    }
+    Ok(DecodedIdToken {
+        header: decode_segment(parts[0])?,
+        claims: decode_segment(parts[1])?,
+    })
+}
+
+/// Signing algorithms a JWK can verify: its `alg` when given, otherwise those of its key type.
+fn jwk_algorithms(jwk: &jsonwebtoken::jwk::Jwk) -> Result<Vec<jsonwebtoken::Algorithm>, String> {
+    use jsonwebtoken::jwk::{AlgorithmParameters, EllipticCurve};
+    use jsonwebtoken::Algorithm;
+
+    if let Some(alg) = &jwk.common.key_algorithm {
+        let alg = format!("{:?}", alg)
+            .parse::<Algorithm>()
+            .map_err(|_| format!("JWKS key algorithm {:?} can't verify signatures", alg))?;
+        return Ok(vec![alg]);
+    }
+    Ok(match &jwk.algorithm {
+        AlgorithmParameters::RSA(_) => vec![
+            Algorithm::RS256,
+            Algorithm::RS384,
+            Algorithm::RS512,
+            Algorithm::PS256,
+            Algorithm::PS384,
+            Algorithm::PS512,
+        ],
+        AlgorithmParameters::EllipticCurve(params) => match params.curve {
+            EllipticCurve::P256 => vec![Algorithm::ES256],
+            EllipticCurve::P384 => vec![Algorithm::ES384],
+            _ => return Err("Unsupported JWKS key curve".to_string()),
+        },
+        AlgorithmParameters::OctetKeyPair(_) => vec![Algorithm::EdDSA],
+        AlgorithmParameters::OctetKey(_) => {
+            vec![Algorithm::HS256, Algorithm::HS384, Algorithm::HS512]
+        }
+    })
+}
+
+pub fn verify_with_jwks(id_token: &str, jwks_json: &str) -> Result<String, String> {
+    use jsonwebtoken::jwk::JwkSet;
+
+    let header = jsonwebtoken::decode_header(id_token)
+        .map_err(|e| format!("Invalid id_token header: {}", e))?;
+    let jwks: JwkSet =
+        serde_json::from_str(jwks_json).map_err(|e| format!("Failed to parse JWKS: {}", e))?;
+
+    let jwk = match &header.kid {
+        Some(kid) => jwks
+            .find(kid)
+            .ok_or_else(|| format!("No JWKS key matches kid '{}'", kid))?,
+        None if jwks.keys.len() == 1 => &jwks.keys[0],
+        None => {
+            return Err(format!(
+                "id_token has no kid to pick one of the {} JWKS keys",
+                jwks.keys.len()
+            ))
+        }
+    };
+
+    // The key decides which algorithms are acceptable, never the token itself.
+    let allowed = jwk_algorithms(jwk)?;
+    if !allowed.contains(&header.alg) {
+        return Err(format!(
+            "id_token alg {:?} doesn't match the JWKS key (expected {:?})",
+            header.alg, allowed
+        ));
+    }
+
+    let key = jsonwebtoken::DecodingKey::from_jwk(jwk)
+        .map_err(|e| format!("Unsupported JWKS key: {}", e))?;
+
+    let mut validation = jsonwebtoken::Validation::new(header.alg);
+    validation.algorithms = allowed;
+    validation.validate_exp = false;
+    validation.validate_aud = false;
+    validation.required_spec_claims.clear();
+
+    jsonwebtoken::decode::<serde_json::Value>(id_token, &key, &validation)
+        .map_err(|e| format!("id_token signature verification failed: {}", e))?;
+
+    Ok(format!("id_token signature valid ({:?})", header.alg))
+}
+
+pub async fn verify_id_token(id_token: &str, jwks_uri: &str) -> Result<String, String> {
+    let client = Client::new();
+    let response = client
+        .get(jwks_uri)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch JWKS: {}", e))?;
+
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read response body: {}", e))?;
+
+    if !status.is_success() {
+        return Err(format!("JWKS request failed: HTTP {}", status.as_u16()));
+    }
+    verify_with_jwks(id_token, &body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(value: &serde_json::Value) -> String {
+        general_purpose::URL_SAFE_NO_PAD.encode(serde_json::to_vec(value).unwrap())
+    }
+
+    fn hs256_token(secret: &[u8], kid: &str) -> String {
+        let mut header = jsonwebtoken::Header::new(jsonwebtoken::Algorithm::HS256);
+        header.kid = Some(kid.to_string());
+        jsonwebtoken::encode(
+            &header,
+            &serde_json::json!({"sub": "user-1", "iss": "https://issuer.example.com"}),
+            &jsonwebtoken::EncodingKey::from_secret(secret),
+        )
+        .unwrap()
+    }
+
+    fn hs256_jwks(secret: &[u8], kid: &str) -> String {
+        serde_json::json!({
+            "keys": [{
+                "kty": "oct",
+                "kid": kid,
+                "alg": "HS256",
+                "k": general_purpose::URL_SAFE_NO_PAD.encode(secret)
+            }]
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn discovery_url_appends_well_known_path() {
+        assert_eq!(
+            discovery_url("https://issuer.example.com/"),
+            "https://issuer.example.com/.well-known/openid-configuration"
+        );
+        assert_eq!(
+            discovery_url("https://issuer.example.com/.well-known/openid-configuration"),
+            "https://issuer.example.com/.well-known/openid-configuration"
+        );
+    }
+
+    #[test]
+    fn discovery_document_deserialization() {
+        let json = r#"{
+            "issuer": "https://issuer.example.com",
+            "authorization_endpoint": "https://issuer.example.com/authorize",
+            "token_endpoint": "https://issuer.example.com/token",
+            "jwks_uri": "https://issuer.example.com/jwks",
+            "scopes_supported": ["openid", "email"],
+            "code_challenge_methods_supported": ["S256"]
+        }"#;
+        let discovery: OidcDiscovery = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            discovery.token_endpoint.as_deref(),
+            Some("https://issuer.example.com/token")
+        );
+        assert!(discovery.device_authorization_endpoint.is_none());
+        assert_eq!(discovery.scopes_supported, vec!["openid", "email"]);
+        assert_eq!(discovery.code_challenge_methods_supported, vec!["S256"]);
+    }
+
+    #[test]
+    fn decode_id_token_reads_header_and_claims() {
+        let token = format!(
+            "{}.{}.sig",
+            encode(&serde_json::json!({"alg": "RS256", "kid": "k1"})),
+            encode(&serde_json::json!({"sub": "user-1", "email": "a@example.com"}))
+        );
+        let decoded = decode_id_token(&token).unwrap();
+        assert_eq!(decoded.header["kid"], "k1");
+        assert_eq!(decoded.claims["sub"], "user-1");
+    }
+
+    #[test]
+    fn decode_id_token_rejects_malformed_token() {
+        assert!(decode_id_token("not-a-jwt").is_err());
+        assert!(decode_id_token("a.b.c").is_err());
+    }
+
+    #[test]
+    fn verify_with_jwks_accepts_matching_key() {
+        let token = hs256_token(b"secret-key", "k1");
+        let result = verify_with_jwks(&token, &hs256_jwks(b"secret-key", "k1"));
+        assert!(result.unwrap().contains("HS256"));
+    }
+
+    #[test]
+    fn verify_with_jwks_rejects_wrong_key() {
+        let token = hs256_token(b"secret-key", "k1");
+        let result = verify_with_jwks(&token, &hs256_jwks(b"other-key", "k1"));
+        assert!(result.unwrap_err().contains("verification failed"));
+    }
+
+    #[test]
+    fn verify_with_jwks_rejects_alg_the_key_does_not_declare() {
+        let mut header = jsonwebtoken::Header::new(jsonwebtoken::Algorithm::HS512);
+        header.kid = Some("k1".to_string());
+        let token = jsonwebtoken::encode(
+            &header,
+            &serde_json::json!({"sub": "user-1"}),
+            &jsonwebtoken::EncodingKey::from_secret(b"secret-key"),
+        )
+        .unwrap();
+        let result = verify_with_jwks(&token, &hs256_jwks(b"secret-key", "k1"));
+        assert!(result.unwrap_err().contains("doesn't match the JWKS key"));
+    }
+
+    #[test]
+    fn verify_with_jwks_requires_kid_when_several_keys() {
+        let token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::new(jsonwebtoken::Algorithm::HS256),
+            &serde_json::json!({"sub": "user-1"}),
+            &jsonwebtoken::EncodingKey::from_secret(b"secret-key"),
+        )
+        .unwrap();
+        let mut jwks: serde_json::Value =
+            serde_json::from_str(&hs256_jwks(b"secret-key", "k1")).unwrap();
+        let key = jwks["keys"][0].clone();
+        jwks["keys"].as_array_mut().unwrap().push(key);
+        let result = verify_with_jwks(&token, &jwks.to_string());
+        assert!(result.unwrap_err().contains("no kid"));
+
+        let single = hs256_jwks(b"secret-key", "k1");
+        assert!(verify_with_jwks(&token, &single).is_ok());
+    }
+
+    #[test]
+    fn verify_with_jwks_reports_missing_kid() {
+        let token = hs256_token(b"secret-key", "k1");
+        let result = verify_with_jwks(&token, &hs256_jwks(b"secret-key", "k2"));
+        assert!(result.unwrap_err().contains("No JWKS key"));
+    }
+}
@@ -0,0 +1,568 @@
+use crate::data::auth::{Auth, OAuth2Config};
+use crate::data::oauth2::OAuth2TokenResponse;
+use crate::data::variables::VariableResolver;
+use crate::http_client::request::HttpRequest;
+use crate::http_client::response::HttpResponse;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+// Tokens are renewed this long before they actually expire.
+const REFRESH_MARGIN_SECS: i64 = 60;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TokenKey {
+    pub token_url: String,
+    pub client_id: String,
+    pub scopes: String,
+}
+
+impl TokenKey {
+    pub fn from_config(config: &OAuth2Config) -> Self {
+        let mut scopes: Vec<&str> = config.scopes.split_whitespace().collect();
+        scopes.sort_unstable();
+        scopes.dedup();
+        Self {
+            token_url: config.token_url.trim().to_string(),
+            client_id: config.client_id.trim().to_string(),
+            scopes: scopes.join(" "),
+        }
+    }
+
+    /// Key of `config` once its `{{variables}}` are resolved, so tabs and sends agree on it.
+    pub fn resolved(config: &OAuth2Config, resolver: &VariableResolver) -> Self {
+        Self::from_config(&OAuth2Config {
+            token_url: resolver.resolve(&config.token_url),
+            client_id: resolver.resolve(&config.client_id),
+            scopes: resolver.resolve(&config.scopes),
+            ..Default::default()
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedToken {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl CachedToken {
+    pub fn from_config(config: &OAuth2Config) -> Option<Self> {
+        if config.access_token.is_empty() {
+            return None;
+        }
+        Some(Self {
+            access_token: config.access_token.clone(),
+            refresh_token: config.refresh_token.clone(),
+            expires_at: config
+                .token_expiry
+                .as_deref()
+                .and_then(|e| DateTime::parse_from_rfc3339(e).ok())
+                .map(|e| e.with_timezone(&Utc)),
+        })
+    }
+
+    pub fn from_response(response: &OAuth2TokenResponse, previous_refresh: &str) -> Self {
+        Self {
+            access_token: response.access_token.clone(),
+            refresh_token: response
+                .refresh_token
+                .clone()
+                .unwrap_or_else(|| previous_refresh.to_string()),
+            expires_at: response
+                .expires_in
+                .map(|secs| Utc::now() + chrono::Duration::seconds(secs as i64)),
+        }
+    }
+
+    pub fn needs_refresh(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at
+            .map(|expires_at| expires_at - chrono::Duration::seconds(REFRESH_MARGIN_SECS) <= now)
+            .unwrap_or(false)
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    pub fn expiry_string(&self) -> Option<String> {
+        self.expires_at.map(|e| e.to_rfc3339())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TokenCache {
+    tokens: Arc<Mutex<HashMap<TokenKey, CachedToken>>>,
+    /// One lock per key, so concurrent requests renew a token only once.
+    renewals: Arc<Mutex<HashMap<TokenKey, Arc<tokio::sync::Mutex<()>>>>>,
+}
+
+impl TokenCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, key: &TokenKey) -> Option<CachedToken> {
+        self.tokens.lock().ok()?.get(key).cloned()
+    }
+
+    pub fn store(&self, key: TokenKey, token: CachedToken) {
+        if let Ok(mut tokens) = self.tokens.lock() {
+            tokens.insert(key, token);
+        }
+    }
+
+    pub fn remove(&self, key: &TokenKey) {
+        if let Ok(mut tokens) = self.tokens.lock() {
+            tokens.remove(key);
+        }
+    }
+
+    pub fn store_response(
+        &self,
+        key: TokenKey,
+        config: &OAuth2Config,
+        response: &OAuth2TokenResponse,
+    ) {
+        let previous_refresh = self
+            .get(&key)
+            .map(|t| t.refresh_token)
+            .unwrap_or_else(|| config.refresh_token.clone());
+        self.store(key, CachedToken::from_response(response, &previous_refresh));
+    }
+
+    /// Copies the cached token under `key` into the config. Returns true when the config changed.
+    pub fn sync_config(&self, key: &TokenKey, config: &mut OAuth2Config) -> bool {
+        let Some(token) = self.get(key) else {
+            return false;
+        };
+        let expiry = token.expiry_string();
+        if config.access_token == token.access_token
+            && config.refresh_token == token.refresh_token
+            && config.token_expiry == expiry
+        {
+            return false;
+        }
+        config.access_token = token.access_token;
+        config.refresh_token = token.refresh_token;
+        config.token_expiry = expiry;
+        true
+    }
+
+    fn renewal_lock(&self, key: &TokenKey) -> Arc<tokio::sync::Mutex<()>> {
+        match self.renewals.lock() {
+            Ok(mut renewals) => renewals.entry(key.clone()).or_default().clone(),
+            Err(_) => Arc::default(),
+        }
+    }
+
+    /// Returns a usable access token, renewing it when it is about to expire or when
+    /// `force_renew` is set (e.g. after the server rejected it with a 401).
+    pub async fn token_for(
+        &self,
+        config: &OAuth2Config,
+        force_renew: bool,
+    ) -> Result<Option<String>, String> {
+        let key = TokenKey::from_config(config);
+        let cached = match self.get(&key) {
+            Some(token) => Some(token),
+            None => {
+                let seeded = CachedToken::from_config(config);
+                if let Some(token) = &seeded {
+                    self.store(key.clone(), token.clone());
+                }
+                seeded
+            }
+        };
+
+        let stale = match &cached {
+            Some(token) => force_renew || token.needs_refresh(Utc::now()),
+            None => true,
+        };
+        if !stale {
+            return Ok(cached.map(|t| t.access_token));
+        }
+
+        let lock = self.renewal_lock(&key);
+        let _renewing = lock.lock().await;
+        // Another request may have renewed the token while this one waited.
+        let current = self.get(&key);
+        if current != cached {
+            if let Some(token) = current.filter(|t| !t.needs_refresh(Utc::now())) {
+                return Ok(Some(token.access_token));
+            }
+        }
+
+        let renewed = match renew(config, cached.as_ref()).await {
+            Ok(renewed) => renewed,
+            // A proactive refresh may fail while the cached token is still usable.
+            Err(e) => match cached.filter(|t| !force_renew && !t.is_expired(Utc::now())) {
+                Some(token) => {
+                    log::warn!("OAuth2 token refresh failed, using cached token: {}", e);
+                    return Ok(Some(token.access_token));
+                }
+                None => return Err(e),
+            },
+        };
+        match renewed {
+            Some(response) => {
+                let previous_refresh = cached
+                    .as_ref()
+                    .map(|t| t.refresh_token.clone())
+                    .unwrap_or_default();
+                let token = CachedToken::from_response(&response, &previous_refresh);
+                let access_token = token.access_token.clone();
+                self.store(key, token);
+                Ok(Some(access_token))
+            }
+            None if force_renew => Ok(None),
+            None => Ok(cached.map(|t| t.access_token)),
+        }
+    }
+}
+
+async fn renew(
+    config: &OAuth2Config,
+    cached: Option<&CachedToken>,
+) -> Result<Option<OAuth2TokenResponse>, String> {
+    if config.token_url.is_empty() {
+        return Ok(None);
+    }
+
+    let refresh = cached
+        .map(|t| t.refresh_token.clone())
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| config.refresh_token.clone());
+
+    if !refresh.is_empty() {
+        log::info!("Refreshing OAuth2 token for {}", config.token_url);
+        return crate::data::oauth2::refresh_token(
+            &config.token_url,
+            &refresh,
+            &crate::data::oauth2::ClientAuth::from_config(config),
+        )
+        .await
+        .map(Some);
+    }
+
+    log::info!(
+        "Requesting {} token for {}",
+        config.grant_type,
+        config.token_url
+    );
+    crate::data::oauth2::request_direct_token(config).await
+}
+
+fn with_bearer(mut request: HttpRequest, token: &str) -> HttpRequest {
+    request
+        .headers
+        .retain(|(k, _)| !k.eq_ignore_ascii_case("authorization"));
+    request
+        .headers
+        .push(("Authorization".to_string(), format!("Bearer {}", token)));
+    request
+}
+
+/// Sends a request, attaching a cached OAuth2 token when the request uses OAuth2 auth.
+/// A 401 response triggers one renewal and retry.
+pub async fn send_with_token_cache(
+    client: &reqwest::Client,
+    request: HttpRequest,
+    cache: &TokenCache,
+) -> Result<HttpResponse, String> {
+    let config = match &request.auth {
+        Some(Auth::OAuth2(config)) => config.as_ref().clone(),
+        _ => return crate::http_client::client::send_request(client, request).await,
+    };
+
+    let token = cache.token_for(&config, false).await?;
+    let request = match &token {
+        Some(token) => with_bearer(request, token),
+        None => request,
+    };
+
+    let retry_request = request.clone();
+    let response = crate::http_client::client::send_request(client, request).await?;
+    if response.status != 401 {
+        return Ok(response);
+    }
+
+    match cache.token_for(&config, true).await {
+        Ok(Some(new_token)) if Some(&new_token) != token.as_ref() => {
+            log::info!("Retrying request with renewed OAuth2 token");
+            crate::http_client::client::send_request(client, with_bearer(retry_request, &new_token))
+                .await
+        }
+        Ok(_) => Ok(response),
+        Err(e) => {
+            log::warn!("OAuth2 token renewal after 401 failed: {}", e);
+            Ok(response)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::auth::OAuth2GrantType;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn config(token_url: &str) -> OAuth2Config {
+        OAuth2Config {
+            grant_type: OAuth2GrantType::ClientCredentials,
+            token_url: token_url.to_string(),
+            client_id: "client".to_string(),
+            client_secret: "secret".to_string(),
+            scopes: "write read".to_string(),
+            ..Default::default()
+        }
+    }
+
+    async fn serve_json_once(body: &'static str) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let _ = stream.read(&mut buffer).await;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+        format!("http://127.0.0.1:{}/token", port)
+    }
+
+    #[test]
+    fn token_key_normalizes_scope_order() {
+        let mut a = config("https://auth.example.com/token");
+        let mut b = a.clone();
+        a.scopes = "read write".to_string();
+        b.scopes = " write  read read".to_string();
+        assert_eq!(TokenKey::from_config(&a), TokenKey::from_config(&b));
+    }
+
+    #[test]
+    fn resolved_token_key_matches_the_sent_config() {
+        let mut template = config("{{authHost}}/token");
+        template.client_id = "{{clientId}}".to_string();
+        let resolver = VariableResolver::default().with_scope(
+            crate::data::variables::VariableScope::Environment,
+            vec![
+                (
+                    "authHost".to_string(),
+                    "https://auth.example.com".to_string(),
+                ),
+                ("clientId".to_string(), "client".to_string()),
+            ],
+        );
+        assert_eq!(
+            TokenKey::resolved(&template, &resolver),
+            TokenKey::from_config(&config("https://auth.example.com/token"))
+        );
+    }
+
+    #[test]
+    fn token_key_differs_by_client() {
+        let a = config("https://auth.example.com/token");
+        let mut b = a.clone();
+        b.client_id = "other".to_string();
+        assert_ne!(TokenKey::from_config(&a), TokenKey::from_config(&b));
+    }
+
+    #[test]
+    fn cached_token_needs_refresh_near_expiry() {
+        let now = Utc::now();
+        let token = CachedToken {
+            access_token: "a".to_string(),
+            refresh_token: String::new(),
+            expires_at: Some(now + chrono::Duration::seconds(30)),
+        };
+        assert!(token.needs_refresh(now));
+
+        let token = CachedToken {
+            expires_at: Some(now + chrono::Duration::seconds(3600)),
+            ..token
+        };
+        assert!(!token.needs_refresh(now));
+    }
+
+    #[test]
+    fn cached_token_without_expiry_never_refreshes() {
+        let token = CachedToken {
+            access_token: "a".to_string(),
+            refresh_token: String::new(),
+            expires_at: None,
+        };
+        assert!(!token.needs_refresh(Utc::now()));
+    }
+
+    #[test]
+    fn cached_token_from_response_keeps_previous_refresh() {
+        let response = OAuth2TokenResponse {
+            access_token: "new".to_string(),
+            token_type: "Bearer".to_string(),
+            expires_in: Some(60),
+            refresh_token: None,
+            scope: None,
+            id_token: None,
+        };
+        let token = CachedToken::from_response(&response, "old_refresh");
+        assert_eq!(token.refresh_token, "old_refresh");
+        assert!(token.expires_at.is_some());
+    }
+
+    #[test]
+    fn sync_config_copies_cached_token() {
+        let cache = TokenCache::new();
+        let mut cfg = config("https://auth.example.com/token");
+        cache.store(
+            TokenKey::from_config(&cfg),
+            CachedToken {
+                access_token: "shared".to_string(),
+                refresh_token: "r".to_string(),
+                expires_at: None,
+            },
+        );
+        let key = TokenKey::from_config(&cfg);
+        assert!(cache.sync_config(&key, &mut cfg));
+        assert_eq!(cfg.access_token, "shared");
+        assert_eq!(cfg.refresh_token, "r");
+        assert!(!cache.sync_config(&key, &mut cfg));
+    }
+
+    #[test]
+    fn remove_drops_cached_token() {
+        let cache = TokenCache::new();
+        let cfg = config("https://auth.example.com/token");
+        let key = TokenKey::from_config(&cfg);
+        cache.store(
+            key.clone(),
+            CachedToken {
+                access_token: "a".to_string(),
+                refresh_token: String::new(),
+                expires_at: None,
+            },
+        );
+        cache.remove(&key);
+        assert!(cache.get(&key).is_none());
+    }
+
+    #[tokio::test]
+    async fn token_for_returns_valid_cached_token_without_network() {
+        let cache = TokenCache::new();
+        let mut cfg = config("http://127.0.0.1:9/token");
+        cfg.access_token = "still_valid".to_string();
+        cfg.token_expiry = Some((Utc::now() + chrono::Duration::seconds(3600)).to_rfc3339());
+        let token = cache.token_for(&cfg, false).await.unwrap();
+        assert_eq!(token, Some("still_valid".to_string()));
+    }
+
+    #[tokio::test]
+    async fn token_for_fetches_client_credentials_when_missing() {
+        let token_url =
+            serve_json_once(r#"{"access_token":"fresh","token_type":"Bearer","expires_in":3600}"#)
+                .await;
+        let cache = TokenCache::new();
+        let cfg = config(&token_url);
+        let token = cache.token_for(&cfg, false).await.unwrap();
+        assert_eq!(token, Some("fresh".to_string()));
+        assert_eq!(
+            cache
+                .get(&TokenKey::from_config(&cfg))
+                .unwrap()
+                .access_token,
+            "fresh"
+        );
+    }
+
+    #[tokio::test]
+    async fn token_for_refreshes_expiring_token() {
+        let token_url = serve_json_once(
+            r#"{"access_token":"renewed","token_type":"Bearer","expires_in":3600,"refresh_token":"r2"}"#,
+        )
+        .await;
+        let cache = TokenCache::new();
+        let mut cfg = config(&token_url);
+        cfg.grant_type = OAuth2GrantType::AuthorizationCode;
+        cfg.access_token = "expiring".to_string();
+        cfg.refresh_token = "r1".to_string();
+        cfg.token_expiry = Some((Utc::now() + chrono::Duration::seconds(10)).to_rfc3339());
+        let token = cache.token_for(&cfg, false).await.unwrap();
+        assert_eq!(token, Some("renewed".to_string()));
+        assert_eq!(
+            cache
+                .get(&TokenKey::from_config(&cfg))
+                .unwrap()
+                .refresh_token,
+            "r2"
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_refresh_keeps_still_valid_token() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let _ = stream.read(&mut buffer).await;
+            let _ = stream
+                .write_all(b"HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                .await;
+        });
+        let cache = TokenCache::new();
+        let mut cfg = config(&format!("http://127.0.0.1:{}/token", port));
+        cfg.grant_type = OAuth2GrantType::AuthorizationCode;
+        cfg.access_token = "expiring".to_string();
+        cfg.refresh_token = "r1".to_string();
+        cfg.token_expiry = Some((Utc::now() + chrono::Duration::seconds(10)).to_rfc3339());
+        let token = cache.token_for(&cfg, false).await.unwrap();
+        assert_eq!(token, Some("expiring".to_string()));
+    }
+
+    #[tokio::test]
+    async fn failed_refresh_of_expired_token_errors() {
+        let cache = TokenCache::new();
+        let mut cfg = config("http://127.0.0.1:9/token");
+        cfg.grant_type = OAuth2GrantType::AuthorizationCode;
+        cfg.access_token = "expired".to_string();
+        cfg.refresh_token = "r1".to_string();
+        cfg.token_expiry = Some((Utc::now() - chrono::Duration::seconds(10)).to_rfc3339());
+        assert!(cache.token_for(&cfg, false).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn concurrent_renewals_fetch_the_token_once() {
+        // The server answers a single request; a second fetch would fail to connect.
+        let token_url =
+            serve_json_once(r#"{"access_token":"once","token_type":"Bearer","expires_in":3600}"#)
+                .await;
+        let cache = TokenCache::new();
+        let cfg = config(&token_url);
+        let (a, b) = tokio::join!(cache.token_for(&cfg, false), cache.token_for(&cfg, false));
+        assert_eq!(a.unwrap(), Some("once".to_string()));
+        assert_eq!(b.unwrap(), Some("once".to_string()));
+    }
+
+    #[test]
+    fn with_bearer_replaces_existing_authorization() {
+        let request = HttpRequest {
+            method: "GET".to_string(),
+            url: "https://api.example.com".to_string(),
+            headers: vec![("authorization".to_string(), "Bearer stale".to_string())],
+            body: None,
+            config: Default::default(),
+            multipart_fields: vec![],
+            auth: None,
+        };
+        let request = with_bearer(request, "fresh");
+        assert_eq!(
+            request.headers,
+            vec![("Authorization".to_string(), "Bearer fresh".to_string())]
+        );
+    }
+}
@@ -0,0 +1,498 @@
+use crate::data::auth::Auth;
+use crate::persistence::database::Environment;
+use base64::{engine::general_purpose, Engine as _};
+
+/// Where a variable value comes from, in order of precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum VariableScope {
+    Request,
+    /// The current row of a data-driven collection run.
+    Data,
+    /// Values captured from responses during this session.
+    Runtime,
+    Environment,
+    Collection,
+    Global,
+}
+
+impl std::fmt::Display for VariableScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            Self::Request => "Request",
+            Self::Runtime => "Runtime",
+            Self::Data => "Data",
+            Self::Environment => "Environment",
+            Self::Collection => "Collection",
+            Self::Global => "Global",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// Resolves `{{name}}` templates at send time, leaving the stored templates untouched.
+#[derive(Debug, Clone, Default)]
+pub struct VariableResolver {
+    layers: Vec<(VariableScope, Vec<(String, String)>)>,
+}
+
+impl VariableResolver {
+    pub fn with_scope(mut self, scope: VariableScope, variables: Vec<(String, String)>) -> Self {
+        let variables: Vec<(String, String)> = variables
+            .into_iter()
+            .filter(|(k, _)| !k.is_empty())
+            .collect();
+        self.layers.retain(|(s, _)| *s != scope);
+        let position = self
+            .layers
+            .iter()
+            .position(|(s, _)| *s > scope)
+            .unwrap_or(self.layers.len());
+        self.layers.insert(position, (scope, variables));
+        self
+    }
+
+    pub fn from_environment(env: Option<&Environment>) -> Self {
+        Self::default().with_scope(
+            VariableScope::Environment,
+            env.map(|e| e.variables.clone()).unwrap_or_default(),
+        )
+    }
+
+    /// Request > environment > collection > global.
+    pub fn scoped(
+        global: &[(String, String)],
+        collection: &[(String, String)],
+        environment: Option<&Environment>,
+        request: &[(String, String)],
+    ) -> Self {
+        Self::from_environment(environment)
+            .with_scope(VariableScope::Global, global.to_vec())
+            .with_scope(VariableScope::Collection, collection.to_vec())
+            .with_scope(VariableScope::Request, request.to_vec())
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<(&str, VariableScope)> {
+        self.layers.iter().find_map(|(scope, vars)| {
+            vars.iter()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| (v.as_str(), *scope))
+        })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.lookup(name).map(|(value, _)| value)
+    }
+
+    /// Every defined name with the value that wins and the scope it came from.
+    pub fn effective_variables(&self) -> Vec<(String, String, VariableScope)> {
+        let mut result: Vec<(String, String, VariableScope)> = Vec::new();
+        for (scope, vars) in &self.layers {
+            for (key, value) in vars {
+                if !result.iter().any(|(k, _, _)| k == key) {
+                    result.push((key.clone(), value.clone(), *scope));
+                }
+            }
+        }
+        result
+    }
+
+    pub fn resolve(&self, template: &str) -> String {
+        self.render(template, &mut Vec::new())
+    }
+
+    /// Names of variables in `template` that have no value, in order of appearance.
+    pub fn unresolved(&self, template: &str) -> Vec<String> {
+        let mut missing = Vec::new();
+        self.render(template, &mut missing);
+        missing
+    }
+
+    fn render(&self, template: &str, missing: &mut Vec<String>) -> String {
+        self.render_nested(template, missing, &mut Vec::new())
+    }
+
+    /// `expanding` holds the variables whose values are being rendered, so a
+    /// value that refers back to one of them is left as a placeholder.
+    fn render_nested(
+        &self,
+        template: &str,
+        missing: &mut Vec<String>,
+        expanding: &mut Vec<String>,
+    ) -> String {
+        let mut output = String::with_capacity(template.len());
+        let mut rest = template;
+
+        while let Some(start) = rest.find("{{") {
+            output.push_str(&rest[..start]);
+            let after_open = &rest[start + 2..];
+            let Some(end) = find_closing(after_open) else {
+                output.push_str(&rest[start..]);
+                return output;
+            };
+
+            let name = after_open[..end].trim();
+            match self.evaluate(name, missing, expanding) {
+                Some(value) => output.push_str(&value),
+                None => {
+                    if !missing.iter().any(|m| m == name) {
+                        missing.push(name.to_string());
+                    }
+                    output.push_str(&rest[start..start + 2 + end + 2]);
+                }
+            }
+            rest = &after_open[end + 2..];
+        }
+
+        output.push_str(rest);
+        output
+    }
+
+    fn evaluate(
+        &self,
+        name: &str,
+        missing: &mut Vec<String>,
+        expanding: &mut Vec<String>,
+    ) -> Option<String> {
+        if let Some(arg) = name.strip_prefix("$base64:") {
+            let resolved = self.render_nested(arg, missing, expanding);
+            return Some(general_purpose::STANDARD.encode(resolved));
+        }
+        match name {
+            "$uuid" => Some(uuid_v4()),
+            "$timestamp" => Some(crate::utils::timestamp_seconds()),
+            "$isoTimestamp" => Some(chrono::Utc::now().to_rfc3339()),
+            "$randomInt" => {
+                use rand::Rng;
+                Some(rand::thread_rng().gen_range(0..1000).to_string())
+            }
+            _ => {
+                // A sealed secret means the vault is locked; treat it as missing.
+                let value = self
+                    .get(name)
+                    .filter(|value| !crate::data::vault::is_encrypted(value))?;
+                if expanding.iter().any(|n| n == name) {
+                    return None;
+                }
+                // Values may hold templates of their own, e.g. `{{host}}/v1`.
+                expanding.push(name.to_string());
+                let rendered = self.render_nested(value, missing, expanding);
+                expanding.pop();
+                Some(rendered)
+            }
+        }
+    }
+
+    pub fn resolve_auth(&self, auth: &Auth) -> Auth {
+        map_auth_strings(auth, |s| self.resolve(s))
+    }
+
+    pub fn unresolved_in_auth(&self, auth: &Auth) -> Vec<String> {
+        let mut missing = Vec::new();
+        map_auth_strings(auth, |s| self.render(s, &mut missing));
+        missing
+    }
+}
+
+/// Length of the template name up to its matching `}}`, allowing nested `{{...}}`.
+fn find_closing(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i + 1 < bytes.len() {
+        if bytes[i] == b'{' && bytes[i + 1] == b'{' {
+            depth += 1;
+            i += 2;
+        } else if bytes[i] == b'}' && bytes[i + 1] == b'}' {
+            if depth == 0 {
+                return Some(i);
+            }
+            depth -= 1;
+            i += 2;
+        } else {
+            i += 1;
+        }
+    }
+    None
+}
+
+fn uuid_v4() -> String {
+    use rand::Rng;
+    let mut bytes: [u8; 16] = rand::thread_rng().gen();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+fn map_auth_strings(auth: &Auth, mut f: impl FnMut(&str) -> String) -> Auth {
+    match auth {
+        Auth::None => Auth::None,
+        Auth::BearerToken(token) => Auth::BearerToken(f(token)),
+        Auth::Basic { user, pass } => Auth::Basic {
+            user: f(user),
+            pass: f(pass),
+        },
+        Auth::ApiKey {
+            key,
+            value,
+            location,
+        } => Auth::ApiKey {
+            key: f(key),
+            value: f(value),
+            location: *location,
+        },
+        Auth::Digest { user, pass } => Auth::Digest {
+            user: f(user),
+            pass: f(pass),
+        },
+        Auth::OAuth2(config) => {
+            let mut config = config.clone();
+            config.auth_url = f(&config.auth_url);
+            config.token_url = f(&config.token_url);
+            config.device_auth_url = f(&config.device_auth_url);
+            config.client_id = f(&config.client_id);
+            config.client_secret = f(&config.client_secret);
+            config.scopes = f(&config.scopes);
+            config.redirect_uri = f(&config.redirect_uri);
+            config.issuer_url = f(&config.issuer_url);
+            config.username = f(&config.username);
+            config.password = f(&config.password);
+            config.assertion = f(&config.assertion);
+            config.private_key = f(&config.private_key);
+            config.introspection_url = f(&config.introspection_url);
+            config.revocation_url = f(&config.revocation_url);
+            config.access_token = f(&config.access_token);
+            config.refresh_token = f(&config.refresh_token);
+            Auth::OAuth2(config)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn resolver(vars: &[(&str, &str)]) -> VariableResolver {
+        VariableResolver::default().with_scope(VariableScope::Environment, pairs(vars))
+    }
+
+    #[test]
+    fn resolve_replaces_known_variables() {
+        let r = resolver(&[("HOST", "example.com"), ("PORT", "8080")]);
+        assert_eq!(
+            r.resolve("https://{{HOST}}:{{PORT}}/api"),
+            "https://example.com:8080/api"
+        );
+    }
+
+    #[test]
+    fn resolve_trims_whitespace_in_names() {
+        let r = resolver(&[("HOST", "example.com")]);
+        assert_eq!(r.resolve("{{ HOST }}"), "example.com");
+    }
+
+    #[test]
+    fn resolve_keeps_unknown_placeholders() {
+        let r = resolver(&[]);
+        assert_eq!(r.resolve("{{MISSING}}/x"), "{{MISSING}}/x");
+    }
+
+    #[test]
+    fn resolve_keeps_unterminated_placeholder() {
+        let r = resolver(&[("A", "1")]);
+        assert_eq!(r.resolve("{{A}} {{B"), "1 {{B");
+    }
+
+    #[test]
+    fn unresolved_lists_missing_names_once() {
+        let r = resolver(&[("A", "1")]);
+        assert_eq!(
+            r.unresolved("{{A}} {{B}} {{C}} {{B}}"),
+            vec!["B".to_string(), "C".to_string()]
+        );
+    }
+
+    #[test]
+    fn resolve_renders_variables_inside_values() {
+        let resolver = VariableResolver::default().with_scope(
+            VariableScope::Environment,
+            pairs(&[
+                ("baseUrl", "https://{{region}}.example.com"),
+                ("region", "eu"),
+                ("loop", "{{loop}}-{{tenant}}"),
+            ]),
+        );
+        assert_eq!(
+            resolver.resolve("{{baseUrl}}/x"),
+            "https://eu.example.com/x"
+        );
+        assert_eq!(resolver.resolve("{{loop}}"), "{{loop}}-{{tenant}}");
+        assert_eq!(resolver.unresolved("{{loop}}"), vec!["loop", "tenant"]);
+    }
+
+    #[test]
+    fn builtin_uuid_is_v4_format() {
+        let value = resolver(&[]).resolve("{{$uuid}}");
+        assert_eq!(value.len(), 36);
+        assert_eq!(value.chars().nth(14), Some('4'));
+        assert_ne!(value, resolver(&[]).resolve("{{$uuid}}"));
+    }
+
+    #[test]
+    fn builtin_timestamps() {
+        let r = resolver(&[]);
+        assert!(r.resolve("{{$timestamp}}").parse::<u64>().is_ok());
+        assert!(chrono::DateTime::parse_from_rfc3339(&r.resolve("{{$isoTimestamp}}")).is_ok());
+    }
+
+    #[test]
+    fn builtin_random_int_in_range() {
+        let value: u32 = resolver(&[]).resolve("{{$randomInt}}").parse().unwrap();
+        assert!(value < 1000);
+    }
+
+    #[test]
+    fn builtin_base64_resolves_nested_variables() {
+        let r = resolver(&[("USER", "admin"), ("PASS", "secret")]);
+        assert_eq!(
+            r.resolve("Basic {{$base64:{{USER}}:{{PASS}}}}"),
+            format!("Basic {}", general_purpose::STANDARD.encode("admin:secret"))
+        );
+    }
+
+    #[test]
+    fn unknown_builtin_is_unresolved() {
+        assert_eq!(resolver(&[]).unresolved("{{$nope}}"), vec!["$nope"]);
+    }
+
+    #[test]
+    fn resolve_auth_leaves_original_untouched() {
+        let r = resolver(&[("TOKEN", "abc")]);
+        let auth = Auth::BearerToken("{{TOKEN}}".to_string());
+        assert_eq!(r.resolve_auth(&auth), Auth::BearerToken("abc".to_string()));
+        assert_eq!(auth, Auth::BearerToken("{{TOKEN}}".to_string()));
+    }
+
+    #[test]
+    fn unresolved_in_auth_checks_oauth2_fields() {
+        let r = resolver(&[]);
+        let auth = Auth::OAuth2(Box::new(crate::data::auth::OAuth2Config {
+            token_url: "{{AUTH_HOST}}/token".to_string(),
+            client_secret: "{{SECRET}}".to_string(),
+            ..Default::default()
+        }));
+        assert_eq!(r.unresolved_in_auth(&auth), vec!["AUTH_HOST", "SECRET"]);
+    }
+
+    #[test]
+    fn from_environment_uses_env_variables() {
+        let env = Environment {
+            id: 1,
+            name: "dev".to_string(),
+            variables: vec![("A".to_string(), "1".to_string())],
+            default_endpoint: None,
+            secret_keys: vec![],
+        };
+        assert_eq!(
+            VariableResolver::from_environment(Some(&env)).get("A"),
+            Some("1")
+        );
+        assert!(VariableResolver::from_environment(None).get("A").is_none());
+    }
+
+    #[test]
+    fn scoped_resolution_follows_precedence() {
+        let env = Environment {
+            id: 1,
+            name: "dev".to_string(),
+            variables: pairs(&[("HOST", "env.example.com"), ("TOKEN", "env-token")]),
+            default_endpoint: None,
+            secret_keys: vec![],
+        };
+        let r = VariableResolver::scoped(
+            &pairs(&[
+                ("HOST", "global.example.com"),
+                ("VERSION", "v1"),
+                ("TEAM", "core"),
+            ]),
+            &pairs(&[("HOST", "col.example.com"), ("VERSION", "v2")]),
+            Some(&env),
+            &pairs(&[("TOKEN", "request-token")]),
+        );
+        assert_eq!(
+            r.lookup("TOKEN"),
+            Some(("request-token", VariableScope::Request))
+        );
+        assert_eq!(
+            r.lookup("HOST"),
+            Some(("env.example.com", VariableScope::Environment))
+        );
+        assert_eq!(r.lookup("VERSION"), Some(("v2", VariableScope::Collection)));
+        assert_eq!(r.lookup("TEAM"), Some(("core", VariableScope::Global)));
+        assert_eq!(
+            r.resolve("https://{{HOST}}/{{VERSION}}"),
+            "https://env.example.com/v2"
+        );
+    }
+
+    #[test]
+    fn with_scope_ignores_insertion_order() {
+        let r = VariableResolver::default()
+            .with_scope(VariableScope::Global, pairs(&[("A", "global")]))
+            .with_scope(VariableScope::Request, pairs(&[("A", "request")]));
+        assert_eq!(r.get("A"), Some("request"));
+    }
+
+    #[test]
+    fn data_rows_override_environment_and_runtime() {
+        let r = VariableResolver::default()
+            .with_scope(
+                VariableScope::Environment,
+                pairs(&[("A", "env"), ("B", "env")]),
+            )
+            .with_scope(VariableScope::Data, pairs(&[("A", "row"), ("B", "row")]))
+            .with_scope(VariableScope::Runtime, pairs(&[("B", "runtime")]));
+        assert_eq!(r.lookup("A"), Some(("row", VariableScope::Data)));
+        assert_eq!(r.lookup("B"), Some(("row", VariableScope::Data)));
+    }
+
+    #[test]
+    fn effective_variables_lists_winning_scope_once() {
+        let r = VariableResolver::default()
+            .with_scope(VariableScope::Global, pairs(&[("A", "1"), ("B", "2")]))
+            .with_scope(
+                VariableScope::Collection,
+                pairs(&[("A", "3"), ("", "blank")]),
+            );
+        assert_eq!(
+            r.effective_variables(),
+            vec![
+                ("A".to_string(), "3".to_string(), VariableScope::Collection),
+                ("B".to_string(), "2".to_string(), VariableScope::Global),
+            ]
+        );
+    }
+
+    #[test]
+    fn encrypted_values_are_unresolved() {
+        let r = VariableResolver::default().with_scope(
+            VariableScope::Environment,
+            pairs(&[("TOKEN", "enc:v1:AAAA")]),
+        );
+        assert_eq!(r.resolve("Bearer {{TOKEN}}"), "Bearer {{TOKEN}}");
+        assert_eq!(r.unresolved("{{TOKEN}}"), vec!["TOKEN"]);
+    }
+}
@@ -0,0 +1,409 @@
+use crate::data::auth::Auth;
+use crate::persistence::database::Environment;
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::{engine::general_purpose, Engine as _};
+use rand::RngCore;
+use std::convert::Infallible;
+
+pub const ENCRYPTED_PREFIX: &str = "enc:v1:";
+pub const MASK: &str = "••••••••";
+const VERIFIER_PLAINTEXT: &str = "astranova-vault";
+
+/// Salt and an encrypted known value, used to check the master passphrase.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultRecord {
+    pub salt: String,
+    pub verifier: String,
+}
+
+/// AES-256-GCM key derived from the master passphrase with Argon2id.
+#[derive(Clone)]
+pub struct Vault {
+    cipher: Aes256Gcm,
+}
+
+impl std::fmt::Debug for Vault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Vault { .. }")
+    }
+}
+
+impl Vault {
+    fn derive(passphrase: &str, salt: &[u8]) -> Result<Self, String> {
+        let mut key = [0u8; 32];
+        argon2::Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| format!("Failed to derive vault key: {}", e))?;
+        let cipher = Aes256Gcm::new_from_slice(&key)
+            .map_err(|e| format!("Failed to derive vault key: {}", e))?;
+        Ok(Self { cipher })
+    }
+
+    pub fn create(passphrase: &str) -> Result<(Self, VaultRecord), String> {
+        if passphrase.is_empty() {
+            return Err("Master passphrase cannot be empty".to_string());
+        }
+        let mut salt = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut salt);
+        let vault = Self::derive(passphrase, &salt)?;
+        let record = VaultRecord {
+            salt: general_purpose::STANDARD.encode(salt),
+            verifier: vault.encrypt(VERIFIER_PLAINTEXT)?,
+        };
+        Ok((vault, record))
+    }
+
+    pub fn open(passphrase: &str, record: &VaultRecord) -> Result<Self, String> {
+        let salt = general_purpose::STANDARD
+            .decode(&record.salt)
+            .map_err(|e| format!("Invalid vault salt: {}", e))?;
+        let vault = Self::derive(passphrase, &salt)?;
+        match vault.decrypt(&record.verifier) {
+            Ok(value) if value == VERIFIER_PLAINTEXT => Ok(vault),
+            _ => Err("Incorrect master passphrase".to_string()),
+        }
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, String> {
+        let mut nonce = [0u8; 12];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_bytes())
+            .map_err(|e| format!("Failed to encrypt secret: {}", e))?;
+        let mut payload = nonce.to_vec();
+        payload.extend(ciphertext);
+        Ok(format!(
+            "{}{}",
+            ENCRYPTED_PREFIX,
+            general_purpose::STANDARD.encode(payload)
+        ))
+    }
+
+    /// Decrypts a sealed value; plain values are returned unchanged.
+    pub fn decrypt(&self, value: &str) -> Result<String, String> {
+        let Some(encoded) = value.strip_prefix(ENCRYPTED_PREFIX) else {
+            return Ok(value.to_string());
+        };
+        let payload = general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|e| format!("Invalid encrypted value: {}", e))?;
+        if payload.len() < 12 {
+            return Err("Invalid encrypted value: too short".to_string());
+        }
+        let (nonce, ciphertext) = payload.split_at(12);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| "Failed to decrypt secret: wrong key or corrupted data".to_string())?;
+        String::from_utf8(plaintext).map_err(|e| format!("Invalid decrypted value: {}", e))
+    }
+}
+
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_PREFIX)
+}
+
+/// Encrypts the values of secret variables; fails if a plaintext secret has no vault.
+pub fn seal_environment(env: &Environment, vault: Option<&Vault>) -> Result<Environment, String> {
+    let mut sealed = env.clone();
+    for (key, value) in &mut sealed.variables {
+        if !env.secret_keys.contains(key) || value.is_empty() || is_encrypted(value) {
+            continue;
+        }
+        let vault = vault.ok_or_else(|| {
+            format!(
+                "Unlock the vault to save secret variable '{}'",
+                key.as_str()
+            )
+        })?;
+        *value = vault.encrypt(value)?;
+    }
+    Ok(sealed)
+}
+
+pub fn open_environment(env: &Environment, vault: &Vault) -> Environment {
+    let mut opened = env.clone();
+    for (_, value) in &mut opened.variables {
+        if let Ok(plain) = vault.decrypt(value) {
+            *value = plain;
+        }
+    }
+    opened
+}
+
+/// Encrypts literal credential fields when a vault is unlocked; `{{variables}}` stay
+/// readable. Without a vault, only auth whose credentials are empty or templates can be stored.
+pub fn seal_auth(auth: &Auth, vault: Option<&Vault>) -> Result<Auth, String> {
+    match vault {
+        Some(vault) => map_secret_fields(auth, |value| {
+            if value.is_empty() || is_encrypted(value) || value.contains("{{") {
+                Ok(value.to_string())
+            } else {
+                vault.encrypt(value)
+            }
+        }),
+        None if has_plaintext_secrets(auth) => {
+            Err("Create or unlock the vault in Environments to save credentials".to_string())
+        }
+        None => Ok(auth.clone()),
+    }
+}
+
+pub fn open_auth(auth: &Auth, vault: &Vault) -> Auth {
+    map_secrets(auth, |value| {
+        vault.decrypt(value).unwrap_or_else(|_| value.to_string())
+    })
+}
+
+/// Blanks credential fields, e.g. before writing a request to history.
+pub fn redact_auth(auth: &Auth) -> Auth {
+    map_secrets(auth, |_| String::new())
+}
+
+/// Blanks only literal credentials, keeping sealed values and `{{variables}}`.
+pub fn redact_plaintext(auth: &Auth) -> Auth {
+    map_secrets(auth, |value| {
+        if is_encrypted(value) || value.contains("{{") {
+            value.to_string()
+        } else {
+            String::new()
+        }
+    })
+}
+
+pub fn has_locked_secrets(auth: &Auth) -> bool {
+    any_secret(auth, is_encrypted)
+}
+
+/// Variable references such as `{{token}}` don't count, their values live elsewhere.
+pub fn has_plaintext_secrets(auth: &Auth) -> bool {
+    any_secret(auth, |value| {
+        !value.is_empty() && !is_encrypted(value) && !value.contains("{{")
+    })
+}
+
+/// Headers that carry credentials: `Authorization`, `Proxy-Authorization` and the
+/// header an API key auth sends.
+pub fn is_credential_header(name: &str, auth: &Auth) -> bool {
+    name.eq_ignore_ascii_case("authorization")
+        || name.eq_ignore_ascii_case("proxy-authorization")
+        || matches!(auth, Auth::ApiKey { key, .. } if key.eq_ignore_ascii_case(name))
+}
+
+/// Blanks credentials typed straight into headers, which `redact_auth` doesn't see.
+pub fn redact_headers(headers: &mut [(String, String)], auth: &Auth) {
+    for (name, value) in headers {
+        if is_credential_header(name, auth) && !value.contains("{{") {
+            value.clear();
+        }
+    }
+}
+
+fn map_secrets(auth: &Auth, mut f: impl FnMut(&str) -> String) -> Auth {
+    let Ok(mapped) = map_secret_fields(auth, |value| Ok::<_, Infallible>(f(value)));
+    mapped
+}
+
+fn any_secret(auth: &Auth, predicate: impl Fn(&str) -> bool) -> bool {
+    let mut found = false;
+    map_secrets(auth, |value| {
+        found |= predicate(value);
+        value.to_string()
+    });
+    found
+}
+
+fn map_secret_fields<E>(
+    auth: &Auth,
+    mut f: impl FnMut(&str) -> Result<String, E>,
+) -> Result<Auth, E> {
+    Ok(match auth {
+        Auth::None => Auth::None,
+        Auth::BearerToken(token) => Auth::BearerToken(f(token)?),
+        Auth::Basic { user, pass } => Auth::Basic {
+            user: user.clone(),
+            pass: f(pass)?,
+        },
+        Auth::ApiKey {
+            key,
+            value,
+            location,
+        } => Auth::ApiKey {
+            key: key.clone(),
+            value: f(value)?,
+            location: *location,
+        },
+        Auth::Digest { user, pass } => Auth::Digest {
+            user: user.clone(),
+            pass: f(pass)?,
+        },
+        Auth::OAuth2(config) => {
+            let mut config = config.clone();
+            config.client_secret = f(&config.client_secret)?;
+            config.password = f(&config.password)?;
+            config.assertion = f(&config.assertion)?;
+            config.private_key = f(&config.private_key)?;
+            config.access_token = f(&config.access_token)?;
+            config.refresh_token = f(&config.refresh_token)?;
+            config.id_token = f(&config.id_token)?;
+            Auth::OAuth2(config)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_vault() -> Vault {
+        Vault::derive("correct horse", b"0123456789abcdef").unwrap()
+    }
+
+    #[test]
+    fn encrypt_decrypt_round_trip() {
+        let vault = test_vault();
+        let sealed = vault.encrypt("s3cret").unwrap();
+        assert!(is_encrypted(&sealed));
+        assert!(!sealed.contains("s3cret"));
+        assert_eq!(vault.decrypt(&sealed).unwrap(), "s3cret");
+    }
+
+    #[test]
+    fn encrypt_uses_fresh_nonce() {
+        let vault = test_vault();
+        assert_ne!(
+            vault.encrypt("same").unwrap(),
+            vault.encrypt("same").unwrap()
+        );
+    }
+
+    #[test]
+    fn decrypt_passes_plain_values_through() {
+        assert_eq!(test_vault().decrypt("plain").unwrap(), "plain");
+    }
+
+    #[test]
+    fn decrypt_with_wrong_key_fails() {
+        let sealed = test_vault().encrypt("s3cret").unwrap();
+        let other = Vault::derive("wrong", b"0123456789abcdef").unwrap();
+        assert!(other.decrypt(&sealed).is_err());
+    }
+
+    #[test]
+    fn create_and_open_vault() {
+        let (vault, record) = Vault::create("master").unwrap();
+        let sealed = vault.encrypt("token").unwrap();
+        let reopened = Vault::open("master", &record).unwrap();
+        assert_eq!(reopened.decrypt(&sealed).unwrap(), "token");
+        assert_eq!(
+            Vault::open("not-master", &record).unwrap_err(),
+            "Incorrect master passphrase"
+        );
+        assert!(Vault::create("").is_err());
+    }
+
+    #[test]
+    fn seal_environment_encrypts_only_secret_keys() {
+        let vault = test_vault();
+        let env = Environment {
+            id: 1,
+            name: "prod".to_string(),
+            variables: vec![
+                ("HOST".to_string(), "api.example.com".to_string()),
+                ("API_KEY".to_string(), "k-123".to_string()),
+            ],
+            default_endpoint: None,
+            secret_keys: vec!["API_KEY".to_string()],
+        };
+        let sealed = seal_environment(&env, Some(&vault)).unwrap();
+        assert_eq!(sealed.variables[0].1, "api.example.com");
+        assert!(is_encrypted(&sealed.variables[1].1));
+        assert_eq!(open_environment(&sealed, &vault), env);
+        assert!(seal_environment(&env, None)
+            .unwrap_err()
+            .contains("API_KEY"));
+    }
+
+    #[test]
+    fn seal_and_open_auth() {
+        let vault = test_vault();
+        let auth = Auth::Basic {
+            user: "admin".to_string(),
+            pass: "hunter2".to_string(),
+        };
+        let sealed = seal_auth(&auth, Some(&vault)).unwrap();
+        match &sealed {
+            Auth::Basic { user, pass } => {
+                assert_eq!(user, "admin");
+                assert!(is_encrypted(pass));
+            }
+            _ => panic!("Expected Basic"),
+        }
+        assert!(has_locked_secrets(&sealed));
+        assert!(!has_plaintext_secrets(&sealed));
+        assert_eq!(open_auth(&sealed, &vault), auth);
+    }
+
+    #[test]
+    fn seal_auth_keeps_variable_templates() {
+        let vault = test_vault();
+        let auth = Auth::Basic {
+            user: "admin".to_string(),
+            pass: "{{password}}".to_string(),
+        };
+        assert_eq!(seal_auth(&auth, Some(&vault)).unwrap(), auth);
+    }
+
+    #[test]
+    fn seal_auth_without_vault_refuses_plaintext_credentials() {
+        let auth = Auth::BearerToken("s3cret".to_string());
+        assert!(seal_auth(&auth, None).unwrap_err().contains("vault"));
+
+        let templated = Auth::BearerToken("{{token}}".to_string());
+        assert_eq!(seal_auth(&templated, None).unwrap(), templated);
+    }
+
+    #[test]
+    fn redact_headers_blanks_literal_credentials() {
+        let auth = Auth::ApiKey {
+            key: "X-Api-Key".to_string(),
+            value: String::new(),
+            location: crate::data::auth::ApiKeyLocation::Header,
+        };
+        let mut headers = vec![
+            ("authorization".to_string(), "Bearer s3cret".to_string()),
+            ("x-api-key".to_string(), "k".to_string()),
+            (
+                "Proxy-Authorization".to_string(),
+                "{{proxyAuth}}".to_string(),
+            ),
+            ("Accept".to_string(), "*/*".to_string()),
+        ];
+        redact_headers(&mut headers, &auth);
+        let values: Vec<&str> = headers.iter().map(|(_, v)| v.as_str()).collect();
+        assert_eq!(values, vec!["", "", "{{proxyAuth}}", "*/*"]);
+    }
+
+    #[test]
+    fn redact_auth_blanks_oauth2_tokens() {
+        let auth = Auth::OAuth2(Box::new(crate::data::auth::OAuth2Config {
+            client_id: "app".to_string(),
+            client_secret: "shh".to_string(),
+            access_token: "at".to_string(),
+            refresh_token: "rt".to_string(),
+            ..Default::default()
+        }));
+        match redact_auth(&auth) {
+            Auth::OAuth2(config) => {
+                assert_eq!(config.client_id, "app");
+                assert!(config.client_secret.is_empty());
+                assert!(config.access_token.is_empty());
+                assert!(config.refresh_token.is_empty());
+            }
+            _ => panic!("Expected OAuth2"),
+        }
+        assert!(has_plaintext_secrets(&auth));
+    }
+}
@@ -0,0 +1,164 @@
+use crate::data::auth::Auth;
+use crate::persistence::database::{Collection, CollectionFolder, CollectionRequest};
+use serde::{Deserialize, Serialize};
+
+/// Value of the `format` field that marks an AstraNova collection file.
+pub const FORMAT: &str = "astranova-collection";
+
+/// A collection with everything the app stores for its requests, including auth,
+/// extraction rules and assertions that a Postman export can't carry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AstraNovaCollection {
+    pub format: String,
+    pub version: u32,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub variables: Vec<(String, String)>,
+    #[serde(default)]
+    pub folders: Vec<AstraNovaFolder>,
+    #[serde(default)]
+    pub requests: Vec<CollectionRequest>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AstraNovaFolder {
+    pub name: String,
+    #[serde(default)]
+    pub requests: Vec<CollectionRequest>,
+}
+
+pub fn export_collection(
+    collection: &Collection,
+    folders: &[CollectionFolder],
+    requests: &[CollectionRequest],
+) -> Result<String, String> {
+    let in_folder = |folder_id: Option<i32>| -> Vec<CollectionRequest> {
+        requests
+            .iter()
+            .filter(|r| r.folder_id == folder_id)
+            .map(portable_request)
+            .collect()
+    };
+    let exported = AstraNovaCollection {
+        format: FORMAT.to_string(),
+        version: 1,
+        name: collection.name.clone(),
+        description: collection.description.clone(),
+        variables: collection.variables.clone(),
+        folders: folders
+            .iter()
+            .map(|folder| AstraNovaFolder {
+                name: folder.name.clone(),
+                requests: in_folder(Some(folder.id)),
+            })
+            .collect(),
+        requests: in_folder(None),
+    };
+    serde_json::to_string_pretty(&exported).map_err(|e| format!("Serialization error: {}", e))
+}
+
+/// Drops database ids and literal credentials; sealed secrets and `{{variables}}` stay.
+fn portable_request(req: &CollectionRequest) -> CollectionRequest {
+    let auth_data = req
+        .auth_data
+        .as_deref()
+        .map(|data| match serde_json::from_str::<Auth>(data) {
+            Ok(auth) => serde_json::to_string(&crate::data::vault::redact_plaintext(&auth))
+                .unwrap_or_default(),
+            Err(_) => String::new(),
+        });
+    CollectionRequest {
+        id: 0,
+        collection_id: 0,
+        folder_id: None,
+        auth_data,
+        operation: None,
+        ..req.clone()
+    }
+}
+
+pub fn is_astranova_collection(content: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(content)
+        .map(|value| value["format"] == FORMAT)
+        .unwrap_or(false)
+}
+
+pub fn parse_collection(content: &str) -> Result<AstraNovaCollection, String> {
+    let collection: AstraNovaCollection = serde_json::from_str(content)
+        .map_err(|e| format!("Invalid AstraNova collection: {}", e))?;
+    if collection.format != FORMAT {
+        return Err(format!(
+            "Invalid AstraNova collection: format is '{}'",
+            collection.format
+        ));
+    }
+    Ok(collection)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::assertions::{Assertion, AssertionKind};
+    use crate::data::extraction::ExtractionRule;
+
+    #[test]
+    fn round_trip_keeps_assertions_extractions_and_auth() {
+        let collection = Collection {
+            id: 7,
+            name: "Shop".to_string(),
+            description: None,
+            variables: vec![("HOST".to_string(), "http://localhost".to_string())],
+        };
+        let folders = vec![CollectionFolder {
+            id: 3,
+            collection_id: 7,
+            name: "Orders".to_string(),
+            parent_folder_id: None,
+        }];
+        let request = CollectionRequest {
+            id: 11,
+            collection_id: 7,
+            folder_id: Some(3),
+            name: "List".to_string(),
+            method: "GET".to_string(),
+            url: "{{HOST}}/orders".to_string(),
+            auth_type: "bearer".to_string(),
+            auth_data: serde_json::to_string(&Auth::BearerToken("{{token}}".to_string())).ok(),
+            extractions: vec![ExtractionRule {
+                variable: "first".to_string(),
+                expression: "$[0].id".to_string(),
+                ..Default::default()
+            }],
+            assertions: vec![Assertion {
+                kind: AssertionKind::StatusEquals,
+                target: String::new(),
+                expected: "200".to_string(),
+            }],
+            ..Default::default()
+        };
+        let mut secret = request.clone();
+        secret.folder_id = None;
+        secret.auth_data = serde_json::to_string(&Auth::BearerToken("s3cret".to_string())).ok();
+
+        let json = export_collection(&collection, &folders, &[request, secret]).unwrap();
+        assert!(is_astranova_collection(&json));
+        assert!(!json.contains("s3cret"));
+
+        let parsed = parse_collection(&json).unwrap();
+        assert_eq!(parsed.variables, collection.variables);
+        let listed = &parsed.folders[0].requests[0];
+        assert_eq!(listed.assertions.len(), 1);
+        assert_eq!(listed.extractions[0].variable, "first");
+        assert!(listed.auth_data.as_deref().unwrap().contains("{{token}}"));
+        assert_eq!(parsed.requests.len(), 1);
+    }
+
+    #[test]
+    fn postman_files_are_not_astranova_collections() {
+        let postman = r#"{"info": {"name": "Shop"}, "item": []}"#;
+        assert!(!is_astranova_collection(postman));
+        assert!(parse_collection(postman).is_err());
+    }
+}
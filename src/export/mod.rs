@@ -1 +1,2 @@
+pub mod astranova;
 pub mod postman;
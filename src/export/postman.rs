@@ -1,3 +1,4 @@
+use crate::data::auth::Auth;
 use crate::persistence::database::{Collection, CollectionFolder, CollectionRequest};
 use serde::{Deserialize, Serialize};
 
@@ -93,10 +94,24 @@ pub fn export_collection(
     serde_json::to_string_pretty(&collection).map_err(|e| format!("Serialization error: {}", e))
 }
 
+/// Literal credentials (not `{{variable}}` references) must not leave the app.
+fn is_credential_header(req: &CollectionRequest, key: &str, value: &str) -> bool {
+    if value.contains("{{") {
+        return false;
+    }
+    let auth = req
+        .auth_data
+        .as_deref()
+        .and_then(|data| serde_json::from_str::<Auth>(data).ok())
+        .unwrap_or(Auth::None);
+    crate::data::vault::is_credential_header(key, &auth)
+}
+
 fn request_to_postman_item(req: &CollectionRequest) -> PostmanItem {
     let headers: Vec<PostmanHeader> = req
         .headers
         .iter()
+        .filter(|(k, v)| !is_credential_header(req, k, v))
         .map(|(k, v)| PostmanHeader {
             key: k.clone(),
             value: v.clone(),
@@ -131,6 +146,7 @@ mod tests {
             id: 1,
             name: name.to_string(),
             description: None,
+            variables: vec![],
         }
     }
 
@@ -149,18 +165,12 @@ mod tests {
         let requests = vec![CollectionRequest {
             id: 1,
             collection_id: 1,
-            folder_id: None,
             name: "Get Users".to_string(),
             method: "GET".to_string(),
             url: "https://api.example.com/users".to_string(),
-            headers: vec![],
-            body: None,
             body_type: "text".to_string(),
             auth_type: "none".to_string(),
-            auth_data: None,
-            params: vec![],
-            config_json: None,
-            sort_order: 0,
+            ..Default::default()
         }];
 
         let json = export_collection(&col, &[], &requests).unwrap();
@@ -189,10 +199,7 @@ mod tests {
             body: Some(r#"{"user":"admin"}"#.to_string()),
             body_type: "text".to_string(),
             auth_type: "none".to_string(),
-            auth_data: None,
-            params: vec![],
-            config_json: None,
-            sort_order: 0,
+            ..Default::default()
         }];
 
         let json = export_collection(&col, &folders, &requests).unwrap();
@@ -207,26 +214,52 @@ mod tests {
         let requests = vec![CollectionRequest {
             id: 1,
             collection_id: 1,
-            folder_id: None,
             name: "Auth Request".to_string(),
             method: "GET".to_string(),
             url: "https://api.example.com/data".to_string(),
             headers: vec![
-                ("Authorization".to_string(), "Bearer token".to_string()),
+                ("Authorization".to_string(), "Bearer {{TOKEN}}".to_string()),
                 ("Accept".to_string(), "application/json".to_string()),
             ],
-            body: None,
             body_type: "text".to_string(),
             auth_type: "none".to_string(),
-            auth_data: None,
-            params: vec![],
-            config_json: None,
-            sort_order: 0,
+            ..Default::default()
         }];
 
         let json = export_collection(&col, &[], &requests).unwrap();
         assert!(json.contains("Authorization"));
-        assert!(json.contains("Bearer token"));
+        assert!(json.contains("Bearer {{TOKEN}}"));
+    }
+
+    #[test]
+    fn export_omits_literal_credentials() {
+        let col = make_collection("API");
+        let requests = vec![CollectionRequest {
+            id: 1,
+            collection_id: 1,
+            name: "Secret Request".to_string(),
+            method: "GET".to_string(),
+            url: "https://api.example.com/data".to_string(),
+            headers: vec![
+                ("Authorization".to_string(), "Bearer s3cret".to_string()),
+                ("X-Api-Key".to_string(), "k-123".to_string()),
+                ("Accept".to_string(), "application/json".to_string()),
+            ],
+            body_type: "text".to_string(),
+            auth_type: "api_key".to_string(),
+            auth_data: serde_json::to_string(&Auth::ApiKey {
+                key: "X-Api-Key".to_string(),
+                value: "k-123".to_string(),
+                location: crate::data::auth::ApiKeyLocation::Header,
+            })
+            .ok(),
+            ..Default::default()
+        }];
+
+        let json = export_collection(&col, &[], &requests).unwrap();
+        assert!(!json.contains("s3cret"));
+        assert!(!json.contains("k-123"));
+        assert!(json.contains("application/json"));
     }
 
     #[test]
@@ -235,18 +268,13 @@ mod tests {
         let requests = vec![CollectionRequest {
             id: 1,
             collection_id: 1,
-            folder_id: None,
             name: "Create User".to_string(),
             method: "POST".to_string(),
             url: "https://api.example.com/users".to_string(),
-            headers: vec![],
             body: Some(r#"{"name":"John"}"#.to_string()),
             body_type: "text".to_string(),
             auth_type: "none".to_string(),
-            auth_data: None,
-            params: vec![],
-            config_json: None,
-            sort_order: 0,
+            ..Default::default()
         }];
 
         let json = export_collection(&col, &[], &requests).unwrap();
@@ -262,18 +290,12 @@ mod tests {
         let requests = vec![CollectionRequest {
             id: 1,
             collection_id: 1,
-            folder_id: None,
             name: "Test".to_string(),
             method: "GET".to_string(),
             url: "https://example.com".to_string(),
-            headers: vec![],
-            body: None,
             body_type: "text".to_string(),
             auth_type: "none".to_string(),
-            auth_data: None,
-            params: vec![],
-            config_json: None,
-            sort_order: 0,
+            ..Default::default()
         }];
 
         let json = export_collection(&col, &[], &requests).unwrap();
@@ -3,3 +3,4 @@ pub mod config;
 pub mod request;
 pub mod response;
 pub mod snippets;
+pub mod template;
@@ -0,0 +1,283 @@
+use super::config::RequestConfig;
+use super::request::{HttpRequest, MultipartField, MultipartValue};
+use crate::data::auth::{ApiKeyLocation, Auth};
+use crate::data::variables::VariableResolver;
+use crate::persistence::database::CollectionRequest;
+use base64::{engine::general_purpose, Engine as _};
+
+/// A request as typed or saved, with `{{variables}}` still in place.
+#[derive(Debug, Clone, Default)]
+pub struct RequestTemplate {
+    pub method: String,
+    pub url: String,
+    /// Appended to the URL as an encoded query string.
+    pub params: Vec<(String, String)>,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+    /// Sent as `Content-Type` with a body, unless a header already sets it.
+    pub content_type: Option<String>,
+    pub multipart_fields: Vec<MultipartField>,
+    pub auth: Auth,
+    pub config: RequestConfig,
+}
+
+impl From<&CollectionRequest> for RequestTemplate {
+    fn from(req: &CollectionRequest) -> Self {
+        let multipart = req.body_type == "multipart";
+        Self {
+            method: req.method.clone(),
+            url: req.url.clone(),
+            params: req.params.clone(),
+            headers: req.headers.clone(),
+            body: req.body.clone().filter(|b| !b.is_empty()),
+            content_type: (!multipart).then(|| "application/json".to_string()),
+            multipart_fields: Vec::new(),
+            auth: Auth::from_stored(&req.auth_type, req.auth_data.as_deref()),
+            config: RequestConfig::default(),
+        }
+    }
+}
+
+impl RequestTemplate {
+    /// Builds the request with `{{variables}}` left as typed.
+    pub fn build(&self) -> HttpRequest {
+        self.build_with(None)
+    }
+
+    pub fn build_resolved(&self, resolver: &VariableResolver) -> HttpRequest {
+        self.build_with(Some(resolver))
+    }
+
+    /// Names of `{{variables}}` used by this request that `resolver` cannot fill.
+    pub fn unresolved_variables(&self, resolver: &VariableResolver) -> Vec<String> {
+        let mut texts: Vec<&str> = vec![&self.url];
+        texts.extend(self.body.as_deref());
+        for (key, value) in self.headers.iter().chain(self.params.iter()) {
+            texts.push(key);
+            texts.push(value);
+        }
+        for field in &self.multipart_fields {
+            texts.push(&field.name);
+            texts.push(match &field.value {
+                MultipartValue::Text(text) => text,
+                MultipartValue::File { path, .. } => path,
+            });
+        }
+
+        let mut missing: Vec<String> = Vec::new();
+        let found = texts
+            .iter()
+            .flat_map(|text| resolver.unresolved(text))
+            .chain(resolver.unresolved_in_auth(&self.auth));
+        for name in found {
+            if !missing.contains(&name) {
+                missing.push(name);
+            }
+        }
+        missing
+    }
+
+    fn build_with(&self, resolver: Option<&VariableResolver>) -> HttpRequest {
+        let resolve = |text: &str| match resolver {
+            Some(resolver) => resolver.resolve(text),
+            None => text.to_string(),
+        };
+        let auth = match resolver {
+            Some(resolver) => resolver.resolve_auth(&self.auth),
+            None => self.auth.clone(),
+        };
+        let url = resolve(&self.url);
+
+        let query_string = self
+            .params
+            .iter()
+            .filter(|(k, _)| !k.is_empty())
+            .map(|(k, v)| {
+                format!(
+                    "{}={}",
+                    urlencoding::encode(&resolve(k)),
+                    urlencoding::encode(&resolve(v))
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("&");
+
+        let mut final_url = if query_string.is_empty() {
+            url
+        } else if url.contains('?') {
+            format!("{}&{}", url, query_string)
+        } else {
+            format!("{}?{}", url, query_string)
+        };
+
+        let mut headers: Vec<(String, String)> = self
+            .headers
+            .iter()
+            .filter(|(k, _)| !k.is_empty())
+            .map(|(k, v)| (resolve(k), resolve(v)))
+            .collect();
+
+        match &auth {
+            Auth::BearerToken(token) if !token.is_empty() => {
+                headers.push(("Authorization".to_string(), format!("Bearer {}", token)));
+            }
+            Auth::Basic { user, pass } if !user.is_empty() || !pass.is_empty() => {
+                let encoded = general_purpose::STANDARD.encode(format!("{}:{}", user, pass));
+                headers.push(("Authorization".to_string(), format!("Basic {}", encoded)));
+            }
+            Auth::ApiKey {
+                key,
+                value,
+                location,
+            } if !key.is_empty() => match location {
+                ApiKeyLocation::Header => {
+                    headers.push((key.clone(), value.clone()));
+                }
+                ApiKeyLocation::Query => {
+                    let separator = if final_url.contains('?') { "&" } else { "?" };
+                    final_url = format!(
+                        "{}{}{}={}",
+                        final_url,
+                        separator,
+                        urlencoding::encode(key),
+                        urlencoding::encode(value)
+                    );
+                }
+                ApiKeyLocation::Cookie => {
+                    let pair = format!("{}={}", key, value);
+                    match headers
+                        .iter_mut()
+                        .find(|(k, _)| k.eq_ignore_ascii_case("cookie"))
+                    {
+                        Some((_, cookies)) => *cookies = format!("{}; {}", cookies, pair),
+                        None => headers.push(("Cookie".to_string(), pair)),
+                    }
+                }
+            },
+            Auth::OAuth2(config) if !config.access_token.is_empty() => {
+                headers.push((
+                    "Authorization".to_string(),
+                    format!("Bearer {}", config.access_token),
+                ));
+            }
+            _ => {}
+        }
+
+        let body = self.body.as_deref().map(resolve);
+        if let (Some(_), Some(content_type)) = (&body, &self.content_type) {
+            if !headers
+                .iter()
+                .any(|(k, _)| k.eq_ignore_ascii_case("content-type"))
+            {
+                headers.push(("Content-Type".to_string(), content_type.clone()));
+            }
+        }
+
+        let multipart_fields = self
+            .multipart_fields
+            .iter()
+            .filter(|f| !f.name.is_empty())
+            .map(|f| MultipartField {
+                name: resolve(&f.name),
+                value: match &f.value {
+                    MultipartValue::Text(text) => MultipartValue::Text(resolve(text)),
+                    MultipartValue::File { path, filename } => MultipartValue::File {
+                        path: resolve(path),
+                        filename: filename.clone(),
+                    },
+                },
+            })
+            .collect();
+
+        HttpRequest {
+            method: self.method.clone(),
+            url: final_url,
+            headers,
+            body,
+            config: self.config.clone(),
+            multipart_fields,
+            auth: Some(auth),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::variables::VariableScope;
+
+    fn saved(auth_type: &str, auth_data: Option<&str>) -> CollectionRequest {
+        CollectionRequest {
+            id: 1,
+            collection_id: 1,
+            name: "Create".to_string(),
+            method: "POST".to_string(),
+            url: "{{HOST}}/users".to_string(),
+            headers: vec![("X-Tenant".to_string(), "{{TENANT}}".to_string())],
+            body: Some(r#"{"name":"{{NAME}}"}"#.to_string()),
+            body_type: "text".to_string(),
+            auth_type: auth_type.to_string(),
+            auth_data: auth_data.map(str::to_string),
+            params: vec![("page".to_string(), "1".to_string())],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn saved_request_builds_like_the_editor() {
+        let template = RequestTemplate::from(&saved("bearer", Some("{{TOKEN}}")));
+        let resolver = VariableResolver::default().with_scope(
+            VariableScope::Environment,
+            vec![
+                ("HOST".to_string(), "https://api.example.com".to_string()),
+                ("TENANT".to_string(), "acme".to_string()),
+                ("TOKEN".to_string(), "abc".to_string()),
+            ],
+        );
+        let request = template.build_resolved(&resolver);
+        assert_eq!(request.url, "https://api.example.com/users?page=1");
+        assert_eq!(
+            request.headers,
+            vec![
+                ("X-Tenant".to_string(), "acme".to_string()),
+                ("Authorization".to_string(), "Bearer abc".to_string()),
+                ("Content-Type".to_string(), "application/json".to_string()),
+            ]
+        );
+        assert_eq!(template.unresolved_variables(&resolver), vec!["NAME"]);
+        assert_eq!(template.build().url, "{{HOST}}/users?page=1");
+    }
+
+    #[test]
+    fn existing_content_type_header_is_kept() {
+        let mut template = RequestTemplate::from(&saved("none", None));
+        template
+            .headers
+            .push(("content-type".to_string(), "text/plain".to_string()));
+        let request = template.build();
+        let content_types: Vec<_> = request
+            .headers
+            .iter()
+            .filter(|(k, _)| k.eq_ignore_ascii_case("content-type"))
+            .collect();
+        assert_eq!(content_types.len(), 1);
+        assert_eq!(content_types[0].1, "text/plain");
+    }
+
+    #[test]
+    fn api_key_in_query_is_appended_to_url() {
+        let template = RequestTemplate::from(&saved("api_key", Some("key:secret:query")));
+        assert_eq!(template.build().url, "{{HOST}}/users?page=1&key=secret");
+    }
+
+    #[test]
+    fn api_key_in_cookie_joins_existing_cookie_header() {
+        let mut template = RequestTemplate::from(&saved("api_key", Some("session:abc:cookie")));
+        template
+            .headers
+            .push(("Cookie".to_string(), "theme=dark".to_string()));
+        let request = template.build();
+        let cookie = request.headers.iter().find(|(k, _)| k == "Cookie").unwrap();
+        assert_eq!(cookie.1, "theme=dark; session=abc");
+    }
+}
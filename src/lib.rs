@@ -0,0 +1,65 @@
+//! Core of the AstraNova HTTP client: building and sending requests, resolving
+//! variables, running collections, parsing OpenAPI specs and reading saved
+//! collections. The desktop app and the command line are built on this crate;
+//! everything else stays internal.
+//!
+//! ```no_run
+//! use astranova_client::{get_collections, get_requests, open_database, send_request, RequestTemplate};
+//!
+//! # async fn example() -> Result<(), String> {
+//! let conn = open_database().map_err(|e| e.to_string())?;
+//! for collection in get_collections(&conn) {
+//!     for saved in get_requests(&conn, collection.id, None) {
+//!         let request = RequestTemplate::from(&saved).build();
+//!         let response = send_request(&reqwest::Client::new(), request).await?;
+//!         println!("{} {}", response.status, saved.name);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+mod cli;
+pub(crate) mod data;
+pub(crate) mod error;
+pub(crate) mod export;
+pub(crate) mod http_client;
+pub(crate) mod import;
+pub(crate) mod mock;
+pub(crate) mod openapi;
+pub(crate) mod persistence;
+pub(crate) mod protocols;
+pub(crate) mod runner;
+pub(crate) mod services;
+mod ui;
+pub(crate) mod utils;
+
+pub use data::token_cache::TokenCache;
+pub use data::variables::{VariableResolver, VariableScope};
+pub use error::AppError;
+pub use http_client::client::{build_client, send_request};
+pub use http_client::config::RequestConfig;
+pub use http_client::request::HttpRequest;
+pub use http_client::response::HttpResponse;
+pub use http_client::template::RequestTemplate;
+pub use openapi::models::ParsedSpec;
+pub use openapi::{parse_spec, parse_spec_from_yaml};
+pub use persistence::database::{
+    init as open_database, Collection, CollectionRequest, Environment,
+};
+pub use runner::collection::{
+    run_requests, RunContext, RunEvent, RunOptions, RunReport, StepResult,
+};
+pub use runner::report;
+pub use services::collection_service::{get_all as get_collections, get_requests};
+
+/// Runs the subcommand given on the command line and returns its exit code, or
+/// `None` when there is none and the desktop app should start.
+pub fn run_cli() -> Option<i32> {
+    cli::dispatch()
+}
+
+/// Starts the desktop app.
+pub fn run_app() -> iced::Result {
+    ui::app::main()
+}
@@ -1,16 +1,7 @@
-mod data;
-mod error;
-mod export;
-mod http_client;
-mod import;
-mod openapi;
-mod persistence;
-mod protocols;
-mod services;
-mod ui;
-mod utils;
-
 fn main() -> iced::Result {
     env_logger::init();
-    ui::app::main()
+    if let Some(code) = astranova_client::run_cli() {
+        std::process::exit(code);
+    }
+    astranova_client::run_app()
 }
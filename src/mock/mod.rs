@@ -0,0 +1,5 @@
+pub mod route;
+pub mod server;
+
+pub use route::{routes_from_collection, routes_from_spec, MockRoute};
+pub use server::{MockEvent, MockLogEntry, MockState};
@@ -0,0 +1,360 @@
+use crate::http_client::response::HttpResponse;
+use crate::openapi::models::{ParsedEndpoint, ParsedSpec};
+use crate::persistence::database::{CollectionRequest, RequestHistoryEntry};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockResponse {
+    pub status: u16,
+    pub content_type: Option<String>,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockRoute {
+    pub name: String,
+    pub method: String,
+    /// Path pattern; `{id}`, `:id` and `{{id}}` segments match any value.
+    pub path: String,
+    /// Ordered by status code.
+    pub responses: Vec<MockResponse>,
+    /// Index into `responses` of the one served by default.
+    pub selected: usize,
+}
+
+impl MockRoute {
+    pub fn from_endpoint(endpoint: &ParsedEndpoint) -> Self {
+        let mut responses: Vec<MockResponse> = endpoint
+            .responses
+            .iter()
+            .filter_map(|r| {
+                Some(MockResponse {
+                    status: r.status.parse().ok()?,
+                    content_type: r.content_type.clone(),
+                    body: r.example.clone().unwrap_or_default(),
+                })
+            })
+            .collect();
+        if responses.is_empty() {
+            responses.push(MockResponse {
+                status: 200,
+                content_type: endpoint
+                    .response_example
+                    .as_ref()
+                    .map(|_| "application/json".to_string()),
+                body: endpoint.response_example.clone().unwrap_or_default(),
+            });
+        }
+
+        let name = endpoint
+            .summary
+            .clone()
+            .or_else(|| endpoint.operation_id.clone())
+            .unwrap_or_else(|| format!("{} {}", endpoint.method, endpoint.path));
+        Self::new(name, &endpoint.method, endpoint.path.clone(), responses)
+    }
+
+    /// Serves the most recent response recorded in `history` for each status,
+    /// or an empty 200 when the request was never sent.
+    pub fn from_request(request: &CollectionRequest, history: &[RequestHistoryEntry]) -> Self {
+        let path = url_path(&request.url);
+        let pattern = segments(&path);
+        let mut responses: Vec<MockResponse> = Vec::new();
+        for entry in history {
+            if !entry.method.eq_ignore_ascii_case(&request.method)
+                || match_segments(&pattern, &url_path(&entry.url)).is_none()
+            {
+                continue;
+            }
+            let Some(response) = entry
+                .response_data
+                .as_deref()
+                .and_then(|data| serde_json::from_str::<HttpResponse>(data).ok())
+            else {
+                continue;
+            };
+            if responses.iter().any(|r| r.status == response.status) {
+                continue;
+            }
+            responses.push(MockResponse {
+                status: response.status,
+                content_type: response
+                    .headers
+                    .iter()
+                    .find(|(k, _)| k.eq_ignore_ascii_case("content-type"))
+                    .map(|(_, v)| v.clone()),
+                body: response.body,
+            });
+        }
+        if responses.is_empty() {
+            responses.push(MockResponse {
+                status: 200,
+                content_type: None,
+                body: String::new(),
+            });
+        }
+        Self::new(request.name.clone(), &request.method, path, responses)
+    }
+
+    fn new(name: String, method: &str, path: String, mut responses: Vec<MockResponse>) -> Self {
+        responses.sort_by_key(|r| r.status);
+        let selected = responses
+            .iter()
+            .position(|r| (200..300).contains(&r.status))
+            .unwrap_or(0);
+        Self {
+            name,
+            method: method.to_uppercase(),
+            path,
+            responses,
+            selected,
+        }
+    }
+
+    /// The response for `status`, or the selected one when `status` is `None`.
+    pub fn response(&self, status: Option<u16>) -> Option<&MockResponse> {
+        match status {
+            Some(status) => self.responses.iter().find(|r| r.status == status),
+            None => self.responses.get(self.selected),
+        }
+    }
+
+    pub fn statuses(&self) -> Vec<u16> {
+        self.responses.iter().map(|r| r.status).collect()
+    }
+
+    /// Number of literal segments matched, so the most specific route can win.
+    pub fn matches(&self, method: &str, path: &str) -> Option<usize> {
+        if !self.method.eq_ignore_ascii_case(method) {
+            return None;
+        }
+        match_segments(&segments(&self.path), path)
+    }
+}
+
+pub fn routes_from_spec(spec: &ParsedSpec) -> Vec<MockRoute> {
+    spec.endpoints
+        .iter()
+        .map(MockRoute::from_endpoint)
+        .collect()
+}
+
+pub fn routes_from_collection(
+    requests: &[CollectionRequest],
+    history: &[RequestHistoryEntry],
+) -> Vec<MockRoute> {
+    requests
+        .iter()
+        .map(|request| MockRoute::from_request(request, history))
+        .collect()
+}
+
+/// The route that answers `method` and `path`, preferring literal segments over parameters.
+pub fn find_route<'a>(routes: &'a [MockRoute], method: &str, path: &str) -> Option<&'a MockRoute> {
+    routes
+        .iter()
+        .filter_map(|route| Some((route.matches(method, path)?, route)))
+        .max_by_key(|(score, _)| *score)
+        .map(|(_, route)| route)
+}
+
+/// Path of a saved or typed URL, without host, query or a leading `{{variable}}`.
+pub fn url_path(url: &str) -> String {
+    let url = url.split(['?', '#']).next().unwrap_or_default();
+    let rest = if url.starts_with("{{") {
+        url.split_once("}}")
+            .map(|(_, rest)| rest)
+            .unwrap_or_default()
+    } else if let Some((_, after_scheme)) = url.split_once("://") {
+        after_scheme
+            .find('/')
+            .map(|i| &after_scheme[i..])
+            .unwrap_or_default()
+    } else {
+        url
+    };
+    format!("/{}", rest.trim_start_matches('/'))
+}
+
+fn segments(path: &str) -> Vec<&str> {
+    path.split('/').filter(|s| !s.is_empty()).collect()
+}
+
+fn is_parameter(segment: &str) -> bool {
+    (segment.starts_with('{') && segment.ends_with('}')) || segment.starts_with(':')
+}
+
+fn match_segments(pattern: &[&str], path: &str) -> Option<usize> {
+    let path = segments(path);
+    if pattern.len() != path.len() {
+        return None;
+    }
+    let mut literal = 0;
+    for (expected, actual) in pattern.iter().zip(path) {
+        if is_parameter(expected) {
+            continue;
+        }
+        if *expected != actual {
+            return None;
+        }
+        literal += 1;
+    }
+    Some(literal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::openapi::models::ParsedResponse;
+    use std::time::Duration;
+
+    fn route(method: &str, path: &str) -> MockRoute {
+        MockRoute::new(
+            format!("{} {}", method, path),
+            method,
+            path.to_string(),
+            vec![],
+        )
+    }
+
+    fn saved(method: &str, url: &str) -> CollectionRequest {
+        CollectionRequest {
+            id: 1,
+            collection_id: 1,
+            name: "Get user".to_string(),
+            method: method.to_string(),
+            url: url.to_string(),
+            body_type: "text".to_string(),
+            auth_type: "none".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn history(id: i32, method: &str, url: &str, status: u16, body: &str) -> RequestHistoryEntry {
+        let response = HttpResponse {
+            url: url.to_string(),
+            method: method.to_string(),
+            status,
+            headers: vec![("content-type".to_string(), "application/json".to_string())],
+            body: body.to_string(),
+            duration: Duration::from_millis(5),
+            size: body.len() as u64,
+            redirect_chain: vec![],
+        };
+        RequestHistoryEntry {
+            id,
+            method: method.to_string(),
+            url: url.to_string(),
+            status: Some(status),
+            duration_ms: Some(5),
+            timestamp: "0".to_string(),
+            request_data: None,
+            response_data: serde_json::to_string(&response).ok(),
+        }
+    }
+
+    #[test]
+    fn url_path_strips_host_variables_and_query() {
+        assert_eq!(url_path("{{baseUrl}}/users/{{id}}?page=1"), "/users/{{id}}");
+        assert_eq!(url_path("https://api.example.com/v1/users"), "/v1/users");
+        assert_eq!(url_path("https://api.example.com"), "/");
+        assert_eq!(url_path("users"), "/users");
+    }
+
+    #[test]
+    fn parameters_match_any_segment_and_literals_win() {
+        let routes = vec![
+            route("GET", "/users/{id}"),
+            route("GET", "/users/me"),
+            route("DELETE", "/users/:id"),
+        ];
+        assert_eq!(
+            find_route(&routes, "GET", "/users/42").map(|r| r.path.as_str()),
+            Some("/users/{id}")
+        );
+        assert_eq!(
+            find_route(&routes, "GET", "/users/me/").map(|r| r.path.as_str()),
+            Some("/users/me")
+        );
+        assert_eq!(
+            find_route(&routes, "delete", "/users/7").map(|r| r.path.as_str()),
+            Some("/users/:id")
+        );
+        assert!(find_route(&routes, "GET", "/users/42/posts").is_none());
+        assert!(find_route(&routes, "POST", "/users/42").is_none());
+    }
+
+    #[test]
+    fn endpoint_routes_select_the_first_success_response() {
+        let endpoint = ParsedEndpoint {
+            path: "/users/{id}".to_string(),
+            method: "GET".to_string(),
+            operation_id: Some("getUser".to_string()),
+            summary: None,
+            description: None,
+            tags: vec![],
+            parameters: vec![],
+            request_body_example: None,
+            response_example: None,
+            responses: vec![
+                ParsedResponse {
+                    status: "200".to_string(),
+                    description: None,
+                    content_type: Some("application/json".to_string()),
+                    example: Some("{\"id\": 1}".to_string()),
+                },
+                ParsedResponse {
+                    status: "default".to_string(),
+                    description: None,
+                    content_type: None,
+                    example: None,
+                },
+                ParsedResponse {
+                    status: "404".to_string(),
+                    description: None,
+                    content_type: None,
+                    example: None,
+                },
+            ],
+            deprecated: false,
+            security: vec![],
+        };
+        let route = MockRoute::from_endpoint(&endpoint);
+        assert_eq!(route.name, "getUser");
+        assert_eq!(route.statuses(), vec![200, 404]);
+        assert_eq!(route.response(None).unwrap().body, "{\"id\": 1}");
+        assert_eq!(route.response(Some(404)).unwrap().body, "");
+        assert!(route.response(Some(500)).is_none());
+    }
+
+    #[test]
+    fn request_routes_replay_the_latest_response_per_status() {
+        let entries = vec![
+            history(
+                4,
+                "GET",
+                "https://api.example.com/users/2",
+                200,
+                "{\"id\":2}",
+            ),
+            history(3, "GET", "https://api.example.com/users/1", 404, "{}"),
+            history(
+                2,
+                "GET",
+                "https://api.example.com/users/1",
+                200,
+                "{\"id\":1}",
+            ),
+            history(1, "DELETE", "https://api.example.com/users/1", 204, ""),
+        ];
+        let route = MockRoute::from_request(&saved("GET", "{{HOST}}/users/{{id}}"), &entries);
+        assert_eq!(route.path, "/users/{{id}}");
+        assert_eq!(route.statuses(), vec![200, 404]);
+        let response = route.response(None).unwrap();
+        assert_eq!(response.body, "{\"id\":2}");
+        assert_eq!(response.content_type.as_deref(), Some("application/json"));
+
+        let unsent = MockRoute::from_request(&saved("POST", "{{HOST}}/orders"), &entries);
+        assert_eq!(unsent.statuses(), vec![200]);
+        assert_eq!(unsent.response(None).unwrap().body, "");
+    }
+}
@@ -0,0 +1,345 @@
+use super::route::{find_route, MockRoute};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+const MAX_HEADER_BYTES: usize = 64 * 1024;
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// Routes and latency of a running mock server; changes apply to the next request.
+#[derive(Debug, Clone, Default)]
+pub struct MockState {
+    pub routes: Vec<MockRoute>,
+    pub latency: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockLogEntry {
+    pub time: String,
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    /// Name of the route that answered, if any.
+    pub route: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum MockEvent {
+    Listening(u16),
+    Request(MockLogEntry),
+    /// The server stopped, with the error that stopped it, if any.
+    Stopped(Option<String>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockReply {
+    pub status: u16,
+    pub content_type: Option<String>,
+    pub body: String,
+    pub route: Option<String>,
+}
+
+/// Answers a request from `routes`. `prefer` picks a status other than the selected one,
+/// as sent in a `Prefer: code=404` header. `HEAD` falls back to the `GET` route.
+pub fn respond(routes: &[MockRoute], method: &str, path: &str, prefer: Option<u16>) -> MockReply {
+    let route = find_route(routes, method, path).or_else(|| {
+        method
+            .eq_ignore_ascii_case("HEAD")
+            .then(|| find_route(routes, "GET", path))
+            .flatten()
+    });
+    match route {
+        Some(route) => {
+            let response = route.response(prefer);
+            MockReply {
+                status: response.map(|r| r.status).or(prefer).unwrap_or(200),
+                content_type: response.and_then(|r| r.content_type.clone()),
+                body: response.map(|r| r.body.clone()).unwrap_or_default(),
+                route: Some(route.name.clone()),
+            }
+        }
+        // Browsers send a preflight before cross-origin calls.
+        None if method.eq_ignore_ascii_case("OPTIONS") => MockReply {
+            status: 204,
+            content_type: None,
+            body: String::new(),
+            route: None,
+        },
+        None => MockReply {
+            status: 404,
+            content_type: Some("application/json".to_string()),
+            body: serde_json::json!({
+                "error": format!("No mock route for {} {}", method.to_uppercase(), path)
+            })
+            .to_string(),
+            route: None,
+        },
+    }
+}
+
+pub async fn bind(port: u16) -> Result<TcpListener, String> {
+    TcpListener::bind(("127.0.0.1", port))
+        .await
+        .map_err(|e| format!("Failed to listen on port {}: {}", port, e))
+}
+
+/// Serves requests until `cancel` is set, reporting each one to `on_request`.
+pub async fn serve(
+    listener: TcpListener,
+    state: Arc<RwLock<MockState>>,
+    cancel: &AtomicBool,
+    mut on_request: impl FnMut(MockLogEntry),
+) -> Result<(), String> {
+    let (log_tx, mut log_rx) = tokio::sync::mpsc::unbounded_channel();
+    while !cancel.load(Ordering::Relaxed) {
+        tokio::select! {
+            accepted = listener.accept() => {
+                // Errors such as running out of file descriptors pass; retry after a pause.
+                let stream = match accepted {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        log::warn!("Failed to accept mock connection: {}", e);
+                        tokio::time::sleep(ACCEPT_RETRY_DELAY).await;
+                        continue;
+                    }
+                };
+                let state = state.clone();
+                let log_tx = log_tx.clone();
+                tokio::spawn(async move {
+                    if let Some(entry) = handle_connection(stream, &state).await {
+                        let _ = log_tx.send(entry);
+                    }
+                });
+            }
+            Some(entry) = log_rx.recv() => on_request(entry),
+            _ = tokio::time::sleep(Duration::from_millis(100)) => {}
+        }
+    }
+    Ok(())
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    state: &RwLock<MockState>,
+) -> Option<MockLogEntry> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        if let Some(end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end + 4;
+        }
+        if buffer.len() > MAX_HEADER_BYTES {
+            return None;
+        }
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let target = request_line.next()?.to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(k, v)| (k.trim().to_lowercase(), v.trim().to_string()))
+        .collect();
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    };
+
+    // Drain the body so the client sees a clean response rather than a reset.
+    let content_length: usize = header("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    let mut remaining = content_length.saturating_sub(buffer.len() - header_end);
+    while remaining > 0 {
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            break;
+        }
+        remaining = remaining.saturating_sub(read);
+    }
+
+    let prefer = header("prefer").and_then(preferred_status);
+    let path = target.split('?').next().unwrap_or("/").to_string();
+    let (reply, latency) = {
+        let state = state.read().ok()?;
+        (
+            respond(&state.routes, &method, &path, prefer),
+            state.latency,
+        )
+    };
+    if !latency.is_zero() {
+        tokio::time::sleep(latency).await;
+    }
+
+    let reason = http::StatusCode::from_u16(reply.status)
+        .ok()
+        .and_then(|s| s.canonical_reason())
+        .unwrap_or("");
+    // HEAD gets the headers of the full response, without its body.
+    let body = if method.eq_ignore_ascii_case("HEAD") {
+        ""
+    } else {
+        reply.body.as_str()
+    };
+    let mut response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Length: {}\r\nConnection: close\r\nAccess-Control-Allow-Origin: *\r\nAccess-Control-Allow-Methods: *\r\nAccess-Control-Allow-Headers: *\r\n",
+        reply.status,
+        reason,
+        reply.body.len()
+    );
+    if let Some(content_type) = &reply.content_type {
+        response.push_str(&format!("Content-Type: {}\r\n", content_type));
+    }
+    response.push_str("\r\n");
+    response.push_str(body);
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.flush().await;
+
+    Some(MockLogEntry {
+        time: chrono::Local::now().format("%H:%M:%S").to_string(),
+        method,
+        path: target,
+        status: reply.status,
+        route: reply.route,
+    })
+}
+
+/// Reads the status from a `Prefer: code=404` header.
+fn preferred_status(value: &str) -> Option<u16> {
+    value
+        .split([',', ';'])
+        .filter_map(|part| part.trim().strip_prefix("code="))
+        .find_map(|code| code.trim().parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::route::MockResponse;
+
+    fn users_route() -> MockRoute {
+        MockRoute {
+            name: "Get user".to_string(),
+            method: "GET".to_string(),
+            path: "/users/{id}".to_string(),
+            responses: vec![
+                MockResponse {
+                    status: 200,
+                    content_type: Some("application/json".to_string()),
+                    body: "{\"id\":1}".to_string(),
+                },
+                MockResponse {
+                    status: 404,
+                    content_type: None,
+                    body: "missing".to_string(),
+                },
+            ],
+            selected: 0,
+        }
+    }
+
+    #[test]
+    fn prefer_header_picks_the_status() {
+        assert_eq!(preferred_status("code=404"), Some(404));
+        assert_eq!(preferred_status("respond-async, code=500"), Some(500));
+        assert_eq!(preferred_status("return=minimal"), None);
+
+        let routes = vec![users_route()];
+        assert_eq!(respond(&routes, "GET", "/users/9", None).status, 200);
+        let missing = respond(&routes, "GET", "/users/9", Some(404));
+        assert_eq!((missing.status, missing.body.as_str()), (404, "missing"));
+        let undocumented = respond(&routes, "GET", "/users/9", Some(503));
+        assert_eq!((undocumented.status, undocumented.body.as_str()), (503, ""));
+    }
+
+    #[test]
+    fn unknown_routes_return_404_and_preflights_succeed() {
+        let routes = vec![users_route()];
+        let reply = respond(&routes, "POST", "/users", None);
+        assert_eq!(reply.status, 404);
+        assert!(reply.body.contains("No mock route for POST /users"));
+        assert_eq!(respond(&routes, "OPTIONS", "/users/1", None).status, 204);
+    }
+
+    #[test]
+    fn head_is_answered_by_the_get_route() {
+        let routes = vec![users_route()];
+        let reply = respond(&routes, "HEAD", "/users/1", None);
+        assert_eq!(reply.status, 200);
+        assert_eq!(reply.route.as_deref(), Some("Get user"));
+        assert_eq!(respond(&routes, "HEAD", "/orders", None).status, 404);
+    }
+
+    #[tokio::test]
+    async fn serves_routes_with_latency_and_logs_requests() {
+        let listener = bind(0).await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let state = Arc::new(RwLock::new(MockState {
+            routes: vec![users_route()],
+            latency: Duration::from_millis(50),
+        }));
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (log_tx, mut log_rx) = tokio::sync::mpsc::unbounded_channel();
+        let server = {
+            let cancel = cancel.clone();
+            let state = state.clone();
+            tokio::spawn(async move {
+                serve(listener, state, &cancel, |entry| {
+                    let _ = log_tx.send(entry);
+                })
+                .await
+            })
+        };
+
+        let client = reqwest::Client::new();
+        let started = std::time::Instant::now();
+        let response = client
+            .get(format!("http://127.0.0.1:{}/users/5?full=1", port))
+            .send()
+            .await
+            .unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        assert_eq!(response.status().as_u16(), 200);
+        assert_eq!(
+            response.headers()["content-type"].to_str().unwrap(),
+            "application/json"
+        );
+        assert_eq!(response.text().await.unwrap(), "{\"id\":1}");
+
+        state.write().unwrap().routes[0].selected = 1;
+        let response = client
+            .post(format!("http://127.0.0.1:{}/users/5", port))
+            .body("{\"name\":\"x\"}")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 404);
+        let response = client
+            .get(format!("http://127.0.0.1:{}/users/5", port))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.text().await.unwrap(), "missing");
+
+        let first = log_rx.recv().await.unwrap();
+        assert_eq!(first.path, "/users/5?full=1");
+        assert_eq!(first.route.as_deref(), Some("Get user"));
+        let second = log_rx.recv().await.unwrap();
+        assert_eq!((second.method.as_str(), second.status), ("POST", 404));
+        assert_eq!(second.route, None);
+
+        cancel.store(true, Ordering::Relaxed);
+        assert!(server.await.unwrap().is_ok());
+    }
+}
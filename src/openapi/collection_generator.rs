@@ -1,5 +1,8 @@
-use super::models::{ParsedEndpoint, ParsedSpec};
+use super::models::{ParsedEndpoint, ParsedSpec, SecurityScheme};
+use super::security::auth_for;
+use crate::data::auth::Auth;
 use crate::persistence::database::{Collection, CollectionFolder, CollectionRequest};
+use std::collections::HashMap;
 
 #[derive(Debug)]
 pub struct GeneratedCollection {
@@ -8,42 +11,107 @@ pub struct GeneratedCollection {
     pub requests: Vec<GeneratedRequest>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct GeneratedRequest {
     pub name: String,
     pub method: String,
+    /// Path of the operation in the spec, such as `/users/{id}`.
+    pub path: String,
     pub url: String,
     pub headers: Vec<(String, String)>,
     pub body: Option<String>,
     pub params: Vec<(String, String)>,
     pub folder_name: Option<String>,
+    pub auth: Auth,
+}
+
+/// Variable that generated request URLs start with.
+pub const BASE_URL_VARIABLE: &str = "baseUrl";
+
+/// Variable holding the receiver that sample webhook requests are sent to.
+pub const WEBHOOK_URL_VARIABLE: &str = "webhookUrl";
+
+/// Folder of the requests generated from webhooks.
+pub const WEBHOOKS_FOLDER: &str = "Webhooks";
+
+/// How generated requests are filed into folders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FolderGrouping {
+    #[default]
+    Tag,
+    PathSegment,
 }
 
+impl FolderGrouping {
+    pub const ALL: [FolderGrouping; 2] = [FolderGrouping::Tag, FolderGrouping::PathSegment];
+
+    /// The name stored with an imported spec.
+    pub fn stored_name(self) -> &'static str {
+        match self {
+            FolderGrouping::Tag => "tag",
+            FolderGrouping::PathSegment => "path",
+        }
+    }
+
+    pub fn from_stored(name: &str) -> Self {
+        match name {
+            "path" => FolderGrouping::PathSegment,
+            _ => FolderGrouping::Tag,
+        }
+    }
+}
+
+impl std::fmt::Display for FolderGrouping {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FolderGrouping::Tag => write!(f, "By tag"),
+            FolderGrouping::PathSegment => write!(f, "By path segment"),
+        }
+    }
+}
+
+#[allow(dead_code)]
 pub fn generate_collection(spec: &ParsedSpec, collection_id: i32) -> GeneratedCollection {
-    let collection = Collection {
+    generate_grouped_collection(spec, collection_id, FolderGrouping::Tag)
+}
+
+pub fn generate_grouped_collection(
+    spec: &ParsedSpec,
+    collection_id: i32,
+    grouping: FolderGrouping,
+) -> GeneratedCollection {
+    let base_url = spec.base_url.as_deref().unwrap_or("http://localhost");
+    let mut collection = Collection {
         id: collection_id,
         name: format!("{} (OpenAPI)", spec.title),
         description: spec.description.clone(),
+        variables: vec![(
+            BASE_URL_VARIABLE.to_string(),
+            base_url.trim_end_matches('/').to_string(),
+        )],
     };
 
-    let by_tag = spec.endpoints_by_tag();
+    let groups = match grouping {
+        FolderGrouping::Tag => spec.endpoints_by_tag(),
+        FolderGrouping::PathSegment => spec.endpoints_by_path_segment(),
+    };
     let mut folders = Vec::new();
     let mut requests = Vec::new();
     let mut folder_id_counter = 1;
 
-    let mut sorted_tags: Vec<&String> = by_tag.keys().collect();
-    sorted_tags.sort();
+    let mut group_names: Vec<&String> = groups.keys().collect();
+    group_names.sort();
 
-    for tag_name in sorted_tags {
-        let endpoints = &by_tag[tag_name];
-        let needs_folder = by_tag.len() > 1 || endpoints.len() > 3;
+    for group_name in group_names {
+        let endpoints = &groups[group_name];
+        let needs_folder = groups.len() > 1 || endpoints.len() > 3;
 
         let folder_id = if needs_folder {
             let folder = CollectionFolder {
                 id: folder_id_counter,
                 collection_id,
-                name: tag_name.clone(),
+                name: group_name.clone(),
                 parent_folder_id: None,
             };
             folders.push(folder);
@@ -55,11 +123,29 @@ pub fn generate_collection(spec: &ParsedSpec, collection_id: i32) -> GeneratedCo
         };
 
         for endpoint in endpoints {
-            let generated = endpoint_to_request(endpoint, collection_id, folder_id, &spec.base_url);
+            let mut generated =
+                endpoint_to_request(endpoint, collection_id, folder_id, &spec.security_schemes);
+            generated.folder_name = folder_id.map(|_| group_name.clone());
             requests.push(generated);
         }
     }
 
+    if !spec.webhooks.is_empty() {
+        collection.variables.push((
+            WEBHOOK_URL_VARIABLE.to_string(),
+            "http://localhost:8080/webhooks".to_string(),
+        ));
+        folders.push(CollectionFolder {
+            id: folder_id_counter,
+            collection_id,
+            name: WEBHOOKS_FOLDER.to_string(),
+            parent_folder_id: None,
+        });
+        for webhook in &spec.webhooks {
+            requests.push(webhook_to_request(webhook));
+        }
+    }
+
     GeneratedCollection {
         collection,
         folders,
@@ -71,10 +157,10 @@ fn endpoint_to_request(
     endpoint: &ParsedEndpoint,
     _collection_id: i32,
     _folder_id: Option<i32>,
-    base_url: &Option<String>,
+    security_schemes: &HashMap<String, SecurityScheme>,
 ) -> GeneratedRequest {
     let name = generate_request_name(endpoint);
-    let url = build_url(endpoint, base_url);
+    let url = build_url(endpoint);
     let headers = generate_headers(endpoint);
     let body = endpoint.request_body_example.clone();
     let params = generate_params(endpoint);
@@ -82,11 +168,28 @@ fn endpoint_to_request(
     GeneratedRequest {
         name,
         method: endpoint.method.clone(),
+        path: endpoint.path.clone(),
         url,
         headers,
         body,
         params,
         folder_name: None,
+        auth: auth_for(&endpoint.security, security_schemes),
+    }
+}
+
+/// A sample of the request the API sends when the webhook fires.
+fn webhook_to_request(webhook: &ParsedEndpoint) -> GeneratedRequest {
+    GeneratedRequest {
+        name: generate_request_name(webhook),
+        method: webhook.method.clone(),
+        path: webhook.path.clone(),
+        url: format!("{{{{{}}}}}", WEBHOOK_URL_VARIABLE),
+        headers: generate_headers(webhook),
+        body: webhook.request_body_example.clone(),
+        params: vec![],
+        folder_name: Some(WEBHOOKS_FOLDER.to_string()),
+        auth: Auth::None,
     }
 }
 
@@ -126,9 +229,8 @@ fn generate_request_name(endpoint: &ParsedEndpoint) -> String {
     format!("{}{}", method_display, path_display)
 }
 
-fn build_url(endpoint: &ParsedEndpoint, base_url: &Option<String>) -> String {
-    let base = base_url.as_deref().unwrap_or("http://localhost");
-    let base = base.trim_end_matches('/');
+fn build_url(endpoint: &ParsedEndpoint) -> String {
+    let base = format!("{{{{{}}}}}", BASE_URL_VARIABLE);
 
     let path = endpoint
         .path
@@ -193,11 +295,18 @@ pub fn to_collection_requests(
                 headers: req.headers.clone(),
                 body: req.body.clone(),
                 body_type: "text".to_string(),
-                auth_type: "none".to_string(),
-                auth_data: None,
+                auth_type: req.auth.stored_type().to_string(),
+                auth_data: match req.auth {
+                    Auth::None => None,
+                    ref auth => serde_json::to_string(auth).ok(),
+                },
                 params: req.params.clone(),
                 config_json: None,
                 sort_order: i as i32,
+                variables: vec![],
+                extractions: vec![],
+                assertions: vec![],
+                operation: None,
             };
             (collection_req, folder_id)
         })
@@ -208,15 +317,19 @@ pub fn to_collection_requests(
 mod tests {
     use super::*;
 
-    #[allow(dead_code)]
     fn make_spec(title: &str) -> ParsedSpec {
         ParsedSpec {
             title: title.to_string(),
             description: None,
             version: Some("1.0".to_string()),
             base_url: Some("https://api.example.com".to_string()),
+            spec_version: Some("3.0.0".to_string()),
+            servers: vec![],
             endpoints: vec![],
+            webhooks: vec![],
             tags: vec![],
+            security_schemes: HashMap::new(),
+            unresolved_refs: vec![],
         }
     }
 
@@ -232,7 +345,9 @@ mod tests {
             parameters: vec![],
             request_body_example: None,
             response_example: None,
+            responses: vec![],
             deprecated: false,
+            security: vec![],
         };
         assert_eq!(generate_request_name(&endpoint), "listUsers");
     }
@@ -249,7 +364,9 @@ mod tests {
             parameters: vec![],
             request_body_example: None,
             response_example: None,
+            responses: vec![],
             deprecated: false,
+            security: vec![],
         };
         assert_eq!(generate_request_name(&endpoint), "List all users");
     }
@@ -266,13 +383,15 @@ mod tests {
             parameters: vec![],
             request_body_example: None,
             response_example: None,
+            responses: vec![],
             deprecated: false,
+            security: vec![],
         };
         assert_eq!(generate_request_name(&endpoint), "GetUsers");
     }
 
     #[test]
-    fn build_url_with_base() {
+    fn build_url_starts_with_base_url_variable() {
         let endpoint = ParsedEndpoint {
             path: "/users/{id}".to_string(),
             method: "GET".to_string(),
@@ -283,28 +402,99 @@ mod tests {
             parameters: vec![],
             request_body_example: None,
             response_example: None,
+            responses: vec![],
             deprecated: false,
+            security: vec![],
         };
-        let url = build_url(&endpoint, &Some("https://api.example.com/v1".to_string()));
-        assert_eq!(url, "https://api.example.com/v1/users/{id}");
+        let url = build_url(&endpoint);
+        assert_eq!(url, "{{baseUrl}}/users/{id}");
     }
 
     #[test]
-    fn build_url_without_base() {
-        let endpoint = ParsedEndpoint {
-            path: "/users".to_string(),
-            method: "GET".to_string(),
+    fn base_url_becomes_a_collection_variable() {
+        let mut spec = make_spec("API");
+        spec.base_url = Some("https://api.example.com/v1/".to_string());
+        let generated = generate_collection(&spec, 1);
+        assert_eq!(
+            generated.collection.variables,
+            vec![(
+                "baseUrl".to_string(),
+                "https://api.example.com/v1".to_string()
+            )]
+        );
+
+        spec.base_url = None;
+        let generated = generate_collection(&spec, 1);
+        assert_eq!(generated.collection.variables[0].1, "http://localhost");
+    }
+
+    #[test]
+    fn path_segment_grouping_skips_the_shared_prefix() {
+        let endpoint = |method: &str, path: &str| ParsedEndpoint {
+            path: path.to_string(),
+            method: method.to_string(),
             operation_id: None,
             summary: None,
             description: None,
-            tags: vec![],
+            tags: vec!["gateway".to_string()],
             parameters: vec![],
             request_body_example: None,
             response_example: None,
+            responses: vec![],
             deprecated: false,
+            security: vec![],
         };
-        let url = build_url(&endpoint, &None);
-        assert_eq!(url, "http://localhost/users");
+        let mut spec = make_spec("Gateway");
+        spec.endpoints = vec![
+            endpoint("GET", "/api/v1/users"),
+            endpoint("GET", "/api/v1/users/{id}"),
+            endpoint("POST", "/api/v1/orders"),
+        ];
+
+        let generated = generate_grouped_collection(&spec, 1, FolderGrouping::PathSegment);
+        let folders: Vec<&str> = generated.folders.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(folders, vec!["orders", "users"]);
+        assert_eq!(generated.requests[0].folder_name.as_deref(), Some("orders"));
+
+        let by_tag = generate_collection(&spec, 1);
+        assert!(by_tag.folders.is_empty());
+    }
+
+    #[test]
+    fn webhooks_get_their_own_folder_and_url() {
+        let spec = crate::openapi::parse_spec(
+            r#"{
+                "openapi": "3.1.0",
+                "info": { "title": "Hooks", "version": "1" },
+                "paths": { "/ping": { "get": { "responses": {} } } },
+                "webhooks": {
+                    "orderPaid": {
+                        "post": {
+                            "operationId": "orderPaid",
+                            "requestBody": {
+                                "content": { "application/json": { "example": { "id": 7 } } }
+                            },
+                            "responses": {}
+                        }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        let generated = generate_collection(&spec, 1);
+        assert_eq!(generated.collection.variables[1].0, WEBHOOK_URL_VARIABLE);
+        let folders: Vec<&str> = generated.folders.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(folders, vec![WEBHOOKS_FOLDER]);
+
+        let webhook = generated
+            .requests
+            .iter()
+            .find(|r| r.name == "orderPaid")
+            .unwrap();
+        assert_eq!(webhook.url, "{{webhookUrl}}");
+        assert_eq!(webhook.path, "orderPaid");
+        assert_eq!(webhook.folder_name.as_deref(), Some(WEBHOOKS_FOLDER));
+        assert!(webhook.body.as_deref().unwrap().contains("\"id\": 7"));
     }
 
     #[test]
@@ -319,7 +509,9 @@ mod tests {
             parameters: vec![],
             request_body_example: Some("{}".to_string()),
             response_example: None,
+            responses: vec![],
             deprecated: false,
+            security: vec![],
         };
         let headers = generate_headers(&endpoint);
         assert!(headers
@@ -354,7 +546,9 @@ mod tests {
             ],
             request_body_example: None,
             response_example: None,
+            responses: vec![],
             deprecated: false,
+            security: vec![],
         };
         let params = generate_params(&endpoint);
         assert_eq!(params.len(), 1);
@@ -1,6 +1,9 @@
 pub mod collection_generator;
 pub mod models;
 pub mod parser;
+pub mod refs;
+pub mod schema_tree;
+pub mod security;
+pub mod validation;
 
-pub use collection_generator::generate_collection;
 pub use parser::{parse_spec, parse_spec_from_yaml};
@@ -1,4 +1,4 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -8,13 +8,38 @@ pub struct OpenApiSpec {
     pub info: Info,
     #[serde(default)]
     pub servers: Vec<Server>,
+    /// Optional since OpenAPI 3.1.
+    #[serde(default)]
     pub paths: HashMap<String, PathItem>,
+    /// OpenAPI 3.1 requests the API sends to its consumers, by name.
+    #[serde(default)]
+    pub webhooks: HashMap<String, PathItem>,
     #[serde(default)]
     pub components: Option<Components>,
     #[serde(default)]
     pub definitions: Option<HashMap<String, Schema>>,
     #[serde(default)]
     pub tags: Vec<Tag>,
+    /// Requirements for every operation that doesn't declare its own.
+    #[serde(default)]
+    pub security: Option<Vec<SecurityRequirement>>,
+    /// Swagger 2 security schemes.
+    #[serde(default, rename = "securityDefinitions")]
+    pub security_definitions: Option<HashMap<String, SecurityScheme>>,
+}
+
+/// Scheme names mapped to the scopes an operation needs from them.
+pub type SecurityRequirement = HashMap<String, Vec<String>>;
+
+impl OpenApiSpec {
+    /// `components.schemas`, or Swagger 2 `definitions`.
+    pub fn component_schemas(&self) -> HashMap<String, Schema> {
+        self.components
+            .as_ref()
+            .and_then(|c| c.schemas.clone())
+            .or_else(|| self.definitions.clone())
+            .unwrap_or_default()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,6 +56,58 @@ pub struct Server {
     pub url: String,
     #[serde(default)]
     pub description: Option<String>,
+    /// Values for the `{name}` placeholders in `url`.
+    #[serde(default)]
+    pub variables: HashMap<String, ServerVariable>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ServerVariable {
+    #[serde(default, deserialize_with = "scalar_string")]
+    pub default: String,
+    #[serde(default, rename = "enum", deserialize_with = "scalar_strings")]
+    pub enum_values: Vec<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl ServerVariable {
+    /// The default, or the first allowed value when the default is missing.
+    pub fn value(&self) -> &str {
+        match self.enum_values.first() {
+            Some(first) if self.default.is_empty() => first,
+            _ => &self.default,
+        }
+    }
+}
+
+impl Server {
+    /// `url` with every variable replaced by its default value.
+    pub fn resolved_url(&self) -> String {
+        self.variables
+            .iter()
+            .fold(self.url.clone(), |url, (name, variable)| {
+                url.replace(&format!("{{{}}}", name), variable.value())
+            })
+    }
+
+    /// `url` with each `{variable}` turned into a `{{variable}}` placeholder.
+    pub fn templated_url(&self) -> String {
+        self.variables.keys().fold(self.url.clone(), |url, name| {
+            url.replace(&format!("{{{}}}", name), &format!("{{{{{}}}}}", name))
+        })
+    }
+
+    /// Variables in name order, with their default values.
+    pub fn variable_defaults(&self) -> Vec<(String, String)> {
+        let mut defaults: Vec<(String, String)> = self
+            .variables
+            .iter()
+            .map(|(name, variable)| (name.clone(), variable.value().to_string()))
+            .collect();
+        defaults.sort();
+        defaults
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -78,8 +155,9 @@ pub struct Operation {
     pub responses: HashMap<String, Response>,
     #[serde(default)]
     pub deprecated: bool,
+    /// `None` inherits the spec's requirements; an empty list means no auth.
     #[serde(default)]
-    pub security: Vec<HashMap<String, Vec<String>>>,
+    pub security: Option<Vec<SecurityRequirement>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -123,27 +201,87 @@ pub struct Response {
     pub description: Option<String>,
     #[serde(default)]
     pub content: HashMap<String, MediaType>,
+    /// Swagger 2 puts the schema on the response itself.
+    #[serde(default)]
+    pub schema: Option<Schema>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Components {
     #[serde(default)]
     pub schemas: Option<HashMap<String, Schema>>,
+    #[serde(default, rename = "securitySchemes")]
+    pub security_schemes: Option<HashMap<String, SecurityScheme>>,
+}
+
+/// An OpenAPI 3 security scheme, or a Swagger 2 security definition.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecurityScheme {
+    #[serde(rename = "type")]
+    pub scheme_type: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// `bearer` or `basic` for `http` schemes.
+    #[serde(default)]
+    pub scheme: Option<String>,
+    #[serde(default, rename = "bearerFormat")]
+    pub bearer_format: Option<String>,
+    /// Header, query or cookie name of an `apiKey` scheme.
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default, rename = "in")]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub flows: Option<OAuthFlows>,
+    /// Swagger 2 keeps a single OAuth2 flow on the scheme itself.
+    #[serde(default)]
+    pub flow: Option<String>,
+    #[serde(default, rename = "authorizationUrl")]
+    pub authorization_url: Option<String>,
+    #[serde(default, rename = "tokenUrl")]
+    pub token_url: Option<String>,
+    #[serde(default)]
+    pub scopes: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OAuthFlows {
+    #[serde(default, rename = "authorizationCode")]
+    pub authorization_code: Option<OAuthFlow>,
+    #[serde(default, rename = "clientCredentials")]
+    pub client_credentials: Option<OAuthFlow>,
+    #[serde(default)]
+    pub password: Option<OAuthFlow>,
+    #[serde(default)]
+    pub implicit: Option<OAuthFlow>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OAuthFlow {
+    #[serde(default, rename = "authorizationUrl")]
+    pub authorization_url: Option<String>,
+    #[serde(default, rename = "tokenUrl")]
+    pub token_url: Option<String>,
+    #[serde(default, rename = "refreshUrl")]
+    pub refresh_url: Option<String>,
+    #[serde(default)]
+    pub scopes: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Schema {
-    #[serde(default, rename = "type")]
-    pub schema_type: Option<String>,
+    /// One type, or an OpenAPI 3.1 list such as `["string", "null"]`.
+    #[serde(default, rename = "type", deserialize_with = "one_or_many")]
+    pub types: Vec<String>,
     #[serde(default)]
     pub format: Option<String>,
     #[serde(default)]
     pub title: Option<String>,
     #[serde(default)]
     pub description: Option<String>,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "bool_or_schema_map")]
     pub properties: Option<HashMap<String, Schema>>,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "bool_or_schema")]
     pub items: Option<Box<Schema>>,
     #[serde(default)]
     pub required: Vec<String>,
@@ -159,6 +297,132 @@ pub struct Schema {
     pub any_of: Option<Vec<Schema>>,
     #[serde(default, rename = "enum")]
     pub enum_values: Option<Vec<serde_json::Value>>,
+    #[serde(default)]
+    pub nullable: bool,
+    #[serde(default, rename = "const")]
+    pub const_value: Option<serde_json::Value>,
+    /// JSON Schema `examples`, which OpenAPI 3.1 uses instead of `example`.
+    #[serde(default, deserialize_with = "list_or_empty")]
+    pub examples: Vec<serde_json::Value>,
+    /// Schemas of the leading array items, in order.
+    #[serde(default, rename = "prefixItems")]
+    pub prefix_items: Option<Vec<Schema>>,
+    /// Written as `false`, so no value matches it.
+    #[serde(skip)]
+    pub never: bool,
+}
+
+impl Schema {
+    /// The first type that isn't `null`.
+    pub fn schema_type(&self) -> Option<&str> {
+        self.types
+            .iter()
+            .map(String::as_str)
+            .find(|kind| *kind != "null")
+    }
+
+    /// Types other than `null`.
+    pub fn non_null_types(&self) -> Vec<&str> {
+        self.types
+            .iter()
+            .map(String::as_str)
+            .filter(|kind| *kind != "null")
+            .collect()
+    }
+
+    /// `nullable: true`, or `null` among the OpenAPI 3.1 types.
+    pub fn is_nullable(&self) -> bool {
+        self.nullable || self.types.iter().any(|kind| kind == "null")
+    }
+
+    /// `example`, or else the first of `examples`.
+    pub fn first_example(&self) -> Option<&serde_json::Value> {
+        self.example.as_ref().or(self.examples.first())
+    }
+}
+
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match Option::<OneOrMany>::deserialize(deserializer)? {
+        Some(OneOrMany::One(kind)) => vec![kind],
+        Some(OneOrMany::Many(kinds)) => kinds,
+        None => Vec::new(),
+    })
+}
+
+/// JSON Schema allows `true` for any value and `false` for none in place of a schema.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BoolOrSchema {
+    Bool(bool),
+    Schema(Box<Schema>),
+}
+
+impl From<BoolOrSchema> for Schema {
+    fn from(value: BoolOrSchema) -> Self {
+        match value {
+            BoolOrSchema::Bool(allowed) => Schema {
+                never: !allowed,
+                ..Default::default()
+            },
+            BoolOrSchema::Schema(schema) => *schema,
+        }
+    }
+}
+
+fn bool_or_schema<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Box<Schema>>, D::Error> {
+    Ok(Option::<BoolOrSchema>::deserialize(deserializer)?.map(|schema| Box::new(schema.into())))
+}
+
+fn bool_or_schema_map<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<HashMap<String, Schema>>, D::Error> {
+    Ok(
+        Option::<HashMap<String, BoolOrSchema>>::deserialize(deserializer)?.map(|properties| {
+            properties
+                .into_iter()
+                .map(|(name, schema)| (name, schema.into()))
+                .collect()
+        }),
+    )
+}
+
+/// Anything other than a list, such as a misplaced examples map, is ignored.
+fn list_or_empty<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<serde_json::Value>, D::Error> {
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Array(items) => Ok(items),
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// Server variables are meant to be strings, but specs often write ports as numbers.
+fn scalar_text(value: serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text,
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn scalar_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    serde_json::Value::deserialize(deserializer).map(scalar_text)
+}
+
+fn scalar_strings<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    Ok(Option::<Vec<serde_json::Value>>::deserialize(deserializer)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(scalar_text)
+        .collect())
 }
 
 #[derive(Debug, Clone)]
@@ -173,7 +437,20 @@ pub struct ParsedEndpoint {
     pub parameters: Vec<ParsedParameter>,
     pub request_body_example: Option<String>,
     pub response_example: Option<String>,
+    /// Every documented response, ordered by status code.
+    pub responses: Vec<ParsedResponse>,
     pub deprecated: bool,
+    /// Requirements that apply, after falling back to the spec-wide ones.
+    pub security: Vec<SecurityRequirement>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParsedResponse {
+    /// A status code such as `"404"`, or `"default"`.
+    pub status: String,
+    pub description: Option<String>,
+    pub content_type: Option<String>,
+    pub example: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -193,12 +470,37 @@ pub struct ParsedSpec {
     pub description: Option<String>,
     pub version: Option<String>,
     pub base_url: Option<String>,
+    /// The `openapi` or `swagger` version, such as `3.1.0`.
+    pub spec_version: Option<String>,
+    /// Every server the spec lists; `base_url` is the first one.
+    pub servers: Vec<Server>,
     pub endpoints: Vec<ParsedEndpoint>,
+    /// OpenAPI 3.1 webhooks, with the webhook name as the path.
+    pub webhooks: Vec<ParsedEndpoint>,
     pub tags: Vec<Tag>,
+    pub security_schemes: HashMap<String, SecurityScheme>,
+    /// `(method, path, $ref)` of the references an operation has whose target
+    /// is missing from the spec.
+    pub unresolved_refs: Vec<(String, String, String)>,
 }
 
 #[allow(dead_code)]
 impl ParsedSpec {
+    /// Fails on the first missing `$ref` of an operation that `included` accepts.
+    pub fn check_refs(&self, included: impl Fn(&str, &str) -> bool) -> Result<(), String> {
+        match self
+            .unresolved_refs
+            .iter()
+            .find(|(method, path, _)| included(method, path))
+        {
+            Some((method, path, reference)) => Err(format!(
+                "Unresolved $ref {} in {} {}",
+                reference, method, path
+            )),
+            None => Ok(()),
+        }
+    }
+
     pub fn endpoints_by_tag(&self) -> HashMap<String, Vec<&ParsedEndpoint>> {
         let mut map: HashMap<String, Vec<&ParsedEndpoint>> = HashMap::new();
         for endpoint in &self.endpoints {
@@ -212,22 +514,63 @@ impl ParsedSpec {
         map
     }
 
+    /// Groups by the first path segment after the prefix every path shares, so
+    /// `/api/v1/users` and `/api/v1/orders` land in `users` and `orders`.
+    pub fn endpoints_by_path_segment(&self) -> HashMap<String, Vec<&ParsedEndpoint>> {
+        let literal_segments = |path: &str| -> Vec<String> {
+            path.split('/')
+                .filter(|s| !s.is_empty())
+                .take_while(|s| !s.starts_with('{') && !s.starts_with(':'))
+                .map(str::to_string)
+                .collect()
+        };
+        let segments: Vec<Vec<String>> = self
+            .endpoints
+            .iter()
+            .map(|e| literal_segments(&e.path))
+            .collect();
+        let mut shared = segments.first().map(Vec::len).unwrap_or(0);
+        for path in &segments {
+            shared = shared.min(
+                path.iter()
+                    .zip(&segments[0])
+                    .take_while(|(a, b)| a == b)
+                    .count(),
+            );
+        }
+        // Keep one segment to group by when every path is the same.
+        if segments.iter().all(|path| path.len() <= shared) {
+            shared = shared.saturating_sub(1);
+        }
+
+        let mut map: HashMap<String, Vec<&ParsedEndpoint>> = HashMap::new();
+        for (endpoint, path) in self.endpoints.iter().zip(&segments) {
+            let group = path.get(shared).cloned().unwrap_or_else(|| "/".to_string());
+            map.entry(group).or_default().push(endpoint);
+        }
+        map
+    }
+
     pub fn search_endpoints(&self, query: &str) -> Vec<&ParsedEndpoint> {
+        self.endpoints.iter().filter(|e| e.matches(query)).collect()
+    }
+}
+
+impl ParsedEndpoint {
+    /// Whether the path, summary, operation ID or method contains `query`.
+    pub fn matches(&self, query: &str) -> bool {
         let query_lower = query.to_lowercase();
-        self.endpoints
-            .iter()
-            .filter(|e| {
-                e.path.to_lowercase().contains(&query_lower)
-                    || e.summary
-                        .as_ref()
-                        .map(|s| s.to_lowercase().contains(&query_lower))
-                        .unwrap_or(false)
-                    || e.operation_id
-                        .as_ref()
-                        .map(|o| o.to_lowercase().contains(&query_lower))
-                        .unwrap_or(false)
-                    || e.method.to_lowercase().contains(&query_lower)
-            })
-            .collect()
+        self.path.to_lowercase().contains(&query_lower)
+            || self
+                .summary
+                .as_ref()
+                .map(|s| s.to_lowercase().contains(&query_lower))
+                .unwrap_or(false)
+            || self
+                .operation_id
+                .as_ref()
+                .map(|o| o.to_lowercase().contains(&query_lower))
+                .unwrap_or(false)
+            || self.method.to_lowercase().contains(&query_lower)
     }
 }
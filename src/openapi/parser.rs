@@ -1,37 +1,49 @@
 use super::models::*;
+use super::refs::{detach_unresolved, read_document, resolve_refs};
 use std::collections::HashMap;
 
 pub fn parse_spec(content: &str) -> Result<ParsedSpec, String> {
     let spec_value: serde_json::Value =
         serde_json::from_str(content).map_err(|e| format!("Invalid JSON: {}", e))?;
-
-    if spec_value.get("openapi").is_some() {
-        parse_openapi3(&spec_value)
-    } else if spec_value.get("swagger").is_some() {
-        parse_swagger2(&spec_value)
-    } else {
-        Err("Not a valid OpenAPI or Swagger spec: missing 'openapi' or 'swagger' field".to_string())
-    }
+    parse_value(spec_value)
 }
 
 pub fn parse_spec_from_yaml(content: &str) -> Result<ParsedSpec, String> {
     let spec_value: serde_json::Value =
         serde_yaml::from_str(content).map_err(|e| format!("Invalid YAML: {}", e))?;
+    parse_value(spec_value)
+}
 
-    if spec_value.get("openapi").is_some() {
-        parse_openapi3(&spec_value)
+fn parse_value(spec_value: serde_json::Value) -> Result<ParsedSpec, String> {
+    let parse = if spec_value.get("openapi").is_some() {
+        parse_openapi3
     } else if spec_value.get("swagger").is_some() {
-        parse_swagger2(&spec_value)
+        parse_swagger2
     } else {
-        Err("Not a valid OpenAPI or Swagger spec: missing 'openapi' or 'swagger' field".to_string())
-    }
+        return Err(
+            "Not a valid OpenAPI or Swagger spec: missing 'openapi' or 'swagger' field".to_string(),
+        );
+    };
+    let mut document = resolve_refs(spec_value, None)?;
+    let unresolved_refs = detach_unresolved(&mut document);
+    Ok(ParsedSpec {
+        unresolved_refs,
+        ..parse(&document)?
+    })
+}
+
+/// Reads a JSON or YAML spec into the raw document model, without flattening operations.
+pub fn load_spec_model(content: &str) -> Result<OpenApiSpec, String> {
+    let mut value = resolve_refs(read_document(content)?, None)?;
+    detach_unresolved(&mut value);
+    serde_json::from_value(value).map_err(|e| format!("Failed to parse spec: {}", e))
 }
 
 fn parse_openapi3(value: &serde_json::Value) -> Result<ParsedSpec, String> {
     let spec: OpenApiSpec = serde_json::from_value(value.clone())
         .map_err(|e| format!("Failed to parse spec: {}", e))?;
 
-    let base_url = spec.servers.first().map(|s| s.url.clone()).or_else(|| {
+    let base_url = spec.servers.first().map(Server::resolved_url).or_else(|| {
         spec.info
             .description
             .as_ref()
@@ -39,23 +51,41 @@ fn parse_openapi3(value: &serde_json::Value) -> Result<ParsedSpec, String> {
     });
 
     let schemas = spec.components.as_ref().and_then(|c| c.schemas.clone());
+    let security = spec.security.clone().unwrap_or_default();
     let mut endpoints = Vec::new();
 
     for (path, path_item) in &spec.paths {
         let operations = extract_operations(path_item);
         for (method, op) in operations {
-            let endpoint = build_endpoint(path, &method, op, &schemas);
+            let endpoint = build_endpoint(path, &method, op, &schemas, &security);
             endpoints.push(endpoint);
         }
     }
 
+    // Webhooks are sent by the API, so the API's own security doesn't apply.
+    let mut webhooks = Vec::new();
+    for (name, path_item) in &spec.webhooks {
+        for (method, op) in extract_operations(path_item) {
+            webhooks.push(build_endpoint(name, &method, op, &schemas, &[]));
+        }
+    }
+    webhooks.sort_by(|a, b| (&a.path, &a.method).cmp(&(&b.path, &b.method)));
+
     Ok(ParsedSpec {
         title: spec.info.title.clone(),
         description: spec.info.description.clone(),
         version: spec.info.version.clone(),
         base_url,
+        spec_version: spec.openapi,
+        servers: spec.servers,
         endpoints,
+        webhooks,
         tags: spec.tags,
+        security_schemes: spec
+            .components
+            .and_then(|c| c.security_schemes)
+            .unwrap_or_default(),
+        unresolved_refs: Vec::new(),
     })
 }
 
@@ -63,26 +93,40 @@ fn parse_swagger2(value: &serde_json::Value) -> Result<ParsedSpec, String> {
     let spec: OpenApiSpec = serde_json::from_value(value.clone())
         .map_err(|e| format!("Failed to parse spec: {}", e))?;
 
-    let base_url = value.get("host").and_then(|h| h.as_str()).map(|host| {
-        let scheme = value
-            .get("schemes")
-            .and_then(|s| s.as_array())
-            .and_then(|arr| arr.first())
-            .and_then(|s| s.as_str())
-            .unwrap_or("https");
-        let base_path = value
-            .get("basePath")
-            .and_then(|bp| bp.as_str())
-            .unwrap_or("/");
-        format!("{}://{}{}", scheme, host, base_path)
-    });
+    let servers: Vec<Server> = match value.get("host").and_then(|h| h.as_str()) {
+        Some(host) => {
+            let base_path = value
+                .get("basePath")
+                .and_then(|bp| bp.as_str())
+                .unwrap_or("/");
+            let mut schemes: Vec<&str> = value
+                .get("schemes")
+                .and_then(|s| s.as_array())
+                .map(|arr| arr.iter().filter_map(|s| s.as_str()).collect())
+                .unwrap_or_default();
+            if schemes.is_empty() {
+                schemes.push("https");
+            }
+            schemes
+                .into_iter()
+                .map(|scheme| Server {
+                    url: format!("{}://{}{}", scheme, host, base_path),
+                    description: None,
+                    variables: HashMap::new(),
+                })
+                .collect()
+        }
+        None => Vec::new(),
+    };
+    let base_url = servers.first().map(|s| s.url.clone());
 
+    let security = spec.security.clone().unwrap_or_default();
     let mut endpoints = Vec::new();
 
     for (path, path_item) in &spec.paths {
         let operations = extract_operations(path_item);
         for (method, op) in operations {
-            let endpoint = build_endpoint(path, &method, op, &spec.definitions);
+            let endpoint = build_endpoint(path, &method, op, &spec.definitions, &security);
             endpoints.push(endpoint);
         }
     }
@@ -92,12 +136,17 @@ fn parse_swagger2(value: &serde_json::Value) -> Result<ParsedSpec, String> {
         description: spec.info.description.clone(),
         version: spec.info.version.clone(),
         base_url,
+        spec_version: version_string(value.get("swagger")),
+        servers,
         endpoints,
+        webhooks: Vec::new(),
         tags: spec.tags,
+        security_schemes: spec.security_definitions.unwrap_or_default(),
+        unresolved_refs: Vec::new(),
     })
 }
 
-fn extract_operations(path_item: &PathItem) -> Vec<(String, &Operation)> {
+pub(crate) fn extract_operations(path_item: &PathItem) -> Vec<(String, &Operation)> {
     let mut ops = Vec::new();
     if let Some(ref op) = path_item.get {
         ops.push(("GET".to_string(), op));
@@ -128,6 +177,7 @@ fn build_endpoint(
     method: &str,
     operation: &Operation,
     schemas: &Option<HashMap<String, Schema>>,
+    default_security: &[SecurityRequirement],
 ) -> ParsedEndpoint {
     let parameters: Vec<ParsedParameter> = operation
         .parameters
@@ -177,6 +227,13 @@ fn build_endpoint(
             })
         });
 
+    let mut responses: Vec<ParsedResponse> = operation
+        .responses
+        .iter()
+        .map(|(status, resp)| build_response(status, resp, schemas))
+        .collect();
+    responses.sort_by(|a, b| a.status.cmp(&b.status));
+
     ParsedEndpoint {
         path: path.to_string(),
         method: method.to_uppercase(),
@@ -187,7 +244,55 @@ fn build_endpoint(
         parameters,
         request_body_example,
         response_example,
+        responses,
         deprecated: operation.deprecated,
+        security: operation
+            .security
+            .clone()
+            .unwrap_or_else(|| default_security.to_vec()),
+    }
+}
+
+fn build_response(
+    status: &str,
+    response: &Response,
+    schemas: &Option<HashMap<String, Schema>>,
+) -> ParsedResponse {
+    let media = response
+        .content
+        .get_key_value("application/json")
+        .or_else(|| response.content.iter().min_by_key(|(name, _)| *name));
+
+    let (content_type, example) = match media {
+        Some((name, mt)) => {
+            let example = mt
+                .example
+                .as_ref()
+                .and_then(|v| match v {
+                    serde_json::Value::String(text) if !name.contains("json") => Some(text.clone()),
+                    _ => serde_json::to_string_pretty(v).ok(),
+                })
+                .or_else(|| {
+                    mt.schema
+                        .as_ref()
+                        .and_then(|s| generate_example_from_schema(s, schemas))
+                });
+            (Some(name.clone()), example)
+        }
+        None => match &response.schema {
+            Some(schema) => (
+                Some("application/json".to_string()),
+                generate_example_from_schema(schema, schemas),
+            ),
+            None => (None, None),
+        },
+    };
+
+    ParsedResponse {
+        status: status.to_string(),
+        description: response.description.clone(),
+        content_type,
+        example,
     }
 }
 
@@ -195,20 +300,37 @@ fn generate_example_from_schema(
     schema: &Schema,
     schemas: &Option<HashMap<String, Schema>>,
 ) -> Option<String> {
-    if let Some(ref example) = schema.example {
-        return serde_json::to_string_pretty(example).ok();
+    let value = example_value(schema, schemas, &mut Vec::new())?;
+    serde_json::to_string_pretty(&value).ok()
+}
+
+/// `visiting` holds the referenced schemas being expanded, so recursive models
+/// stop instead of overflowing.
+fn example_value<'a>(
+    schema: &'a Schema,
+    schemas: &'a Option<HashMap<String, Schema>>,
+    visiting: &mut Vec<&'a str>,
+) -> Option<serde_json::Value> {
+    if schema.never {
+        return None;
+    }
+    if let Some(example) = schema.first_example().or(schema.const_value.as_ref()) {
+        return Some(example.clone());
     }
 
     if let Some(ref r#ref) = schema.r#ref {
         let ref_name = r#ref.split('/').next_back()?;
-        if let Some(schemas) = schemas {
-            if let Some(resolved) = schemas.get(ref_name) {
-                return generate_example_from_schema(resolved, &Some(schemas.clone()));
-            }
+        if visiting.contains(&ref_name) {
+            return None;
         }
+        let resolved = schemas.as_ref()?.get(ref_name)?;
+        visiting.push(ref_name);
+        let value = example_value(resolved, schemas, visiting);
+        visiting.pop();
+        return value;
     }
 
-    let value = match schema.schema_type.as_deref() {
+    let value = match schema.schema_type() {
         Some("string") => {
             let s = match schema.format.as_deref() {
                 Some("email") => "user@example.com",
@@ -222,25 +344,29 @@ fn generate_example_from_schema(
         }
         Some("integer") | Some("number") => serde_json::Value::Number(0.into()),
         Some("boolean") => serde_json::Value::Bool(false),
-        Some("array") => {
-            if let Some(ref items) = schema.items {
-                if let Some(item_example) = generate_example_from_schema(items, schemas) {
-                    let parsed: serde_json::Value = serde_json::from_str(&item_example).ok()?;
-                    serde_json::Value::Array(vec![parsed])
-                } else {
-                    serde_json::Value::Array(vec![])
-                }
-            } else {
-                serde_json::Value::Array(vec![])
-            }
-        }
+        Some("array") => match &schema.prefix_items {
+            Some(prefix) => serde_json::Value::Array(
+                prefix
+                    .iter()
+                    .map(|item| example_value(item, schemas, visiting))
+                    .map(|item| item.unwrap_or(serde_json::Value::Null))
+                    .collect(),
+            ),
+            None => serde_json::Value::Array(
+                schema
+                    .items
+                    .as_ref()
+                    .and_then(|items| example_value(items, schemas, visiting))
+                    .into_iter()
+                    .collect(),
+            ),
+        },
         Some("object") => {
             let mut map = serde_json::Map::new();
             if let Some(ref properties) = schema.properties {
                 for (key, prop_schema) in properties {
-                    if let Some(ex) = generate_example_from_schema(prop_schema, schemas) {
-                        let parsed: serde_json::Value = serde_json::from_str(&ex).ok()?;
-                        map.insert(key.clone(), parsed);
+                    if let Some(ex) = example_value(prop_schema, schemas, visiting) {
+                        map.insert(key.clone(), ex);
                     }
                 }
             }
@@ -249,41 +375,51 @@ fn generate_example_from_schema(
         _ => return None,
     };
 
-    serde_json::to_string_pretty(&value).ok()
+    Some(value)
 }
 
-#[allow(dead_code)]
-pub fn detect_format(content: &str) -> SpecFormat {
-    if content.trim_start().starts_with('{') {
-        if let Ok(value) = serde_json::from_str::<serde_json::Value>(content) {
-            if value.get("openapi").is_some() {
-                return SpecFormat::OpenApi3Json;
-            }
-            if value.get("swagger").is_some() {
-                return SpecFormat::Swagger2Json;
-            }
-        }
+/// The `openapi` or `swagger` field as text; YAML may read `2.0` as a number.
+fn version_string(value: Option<&serde_json::Value>) -> Option<String> {
+    match value? {
+        serde_json::Value::String(version) => Some(version.clone()),
+        serde_json::Value::Number(version) => Some(match version.as_f64() {
+            Some(n) if n.fract() == 0.0 => format!("{:.1}", n),
+            _ => version.to_string(),
+        }),
+        _ => None,
     }
+}
 
-    if content.trim_start().starts_with("openapi:") || content.trim_start().starts_with("swagger:")
-    {
-        return if content.contains("openapi: 3") {
-            SpecFormat::OpenApi3Yaml
+pub fn detect_format(content: &str) -> SpecFormat {
+    let json = content.trim_start().starts_with('{');
+    let Ok(value) = read_document(content) else {
+        return SpecFormat::Unknown;
+    };
+    if let Some(version) = version_string(value.get("openapi")) {
+        return if json {
+            SpecFormat::OpenApi3Json(version)
         } else {
-            SpecFormat::Swagger2Yaml
+            SpecFormat::OpenApi3Yaml(version)
+        };
+    }
+    if let Some(version) = version_string(value.get("swagger")) {
+        return if json {
+            SpecFormat::Swagger2Json(version)
+        } else {
+            SpecFormat::Swagger2Yaml(version)
         };
     }
-
     SpecFormat::Unknown
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-#[allow(dead_code)]
+/// The document format, with the exact `openapi` or `swagger` version.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub enum SpecFormat {
-    OpenApi3Json,
-    OpenApi3Yaml,
-    Swagger2Json,
-    Swagger2Yaml,
+    OpenApi3Json(String),
+    OpenApi3Yaml(String),
+    Swagger2Json(String),
+    Swagger2Yaml(String),
+    #[default]
     Unknown,
 }
 
@@ -293,13 +429,23 @@ impl SpecFormat {
         !matches!(self, SpecFormat::Unknown)
     }
 
-    pub fn label(&self) -> &str {
+    pub fn version(&self) -> Option<&str> {
         match self {
-            SpecFormat::OpenApi3Json => "OpenAPI 3.x (JSON)",
-            SpecFormat::OpenApi3Yaml => "OpenAPI 3.x (YAML)",
-            SpecFormat::Swagger2Json => "Swagger 2.0 (JSON)",
-            SpecFormat::Swagger2Yaml => "Swagger 2.0 (YAML)",
-            SpecFormat::Unknown => "Unknown",
+            SpecFormat::OpenApi3Json(version)
+            | SpecFormat::OpenApi3Yaml(version)
+            | SpecFormat::Swagger2Json(version)
+            | SpecFormat::Swagger2Yaml(version) => Some(version),
+            SpecFormat::Unknown => None,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            SpecFormat::OpenApi3Json(version) => format!("OpenAPI {} (JSON)", version),
+            SpecFormat::OpenApi3Yaml(version) => format!("OpenAPI {} (YAML)", version),
+            SpecFormat::Swagger2Json(version) => format!("Swagger {} (JSON)", version),
+            SpecFormat::Swagger2Yaml(version) => format!("Swagger {} (YAML)", version),
+            SpecFormat::Unknown => "Unknown".to_string(),
         }
     }
 }
@@ -366,6 +512,7 @@ mod tests {
             Some("https://petstore.example.com/api".to_string())
         );
         assert_eq!(spec.endpoints.len(), 2);
+        assert_eq!(spec.servers.len(), 1);
     }
 
     #[test]
@@ -385,6 +532,65 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_server_variables_with_defaults() {
+        let json = r#"{
+            "openapi": "3.0.0",
+            "info": { "title": "API", "version": "1.0" },
+            "servers": [
+                {
+                    "url": "https://{region}.example.com/{version}",
+                    "variables": {
+                        "region": { "enum": ["eu", "us"] },
+                        "version": { "default": "v2", "enum": ["v1", "v2"] }
+                    }
+                },
+                { "url": "http://localhost:8080" }
+            ],
+            "paths": {}
+        }"#;
+        let spec = parse_spec(json).unwrap();
+        assert_eq!(spec.base_url.as_deref(), Some("https://eu.example.com/v2"));
+        assert_eq!(spec.servers.len(), 2);
+        assert_eq!(
+            spec.servers[0].variables["version"].enum_values,
+            ["v1", "v2"]
+        );
+        assert_eq!(
+            spec.servers[0].variable_defaults(),
+            vec![
+                ("region".to_string(), "eu".to_string()),
+                ("version".to_string(), "v2".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_numeric_server_variables() {
+        let yaml = r#"
+openapi: 3.0.0
+info:
+  title: API
+  version: "1.0"
+servers:
+  - url: https://api.example.com:{port}
+    variables:
+      port:
+        default: 8443
+        enum: [8443, 443]
+paths: {}
+"#;
+        let spec = parse_spec_from_yaml(yaml).unwrap();
+        assert_eq!(
+            spec.base_url.as_deref(),
+            Some("https://api.example.com:8443")
+        );
+        assert_eq!(
+            spec.servers[0].variables["port"].enum_values,
+            ["8443", "443"]
+        );
+    }
+
     #[test]
     fn parse_openapi3_with_parameters() {
         let json = r#"{
@@ -445,6 +651,144 @@ mod tests {
         assert!(example.contains("email"));
     }
 
+    #[test]
+    fn parse_responses_per_status() {
+        let json = r#"{
+            "openapi": "3.0.0",
+            "info": { "title": "API", "version": "1.0" },
+            "paths": {
+                "/users/{id}": {
+                    "get": {
+                        "responses": {
+                            "404": {
+                                "description": "Missing",
+                                "content": { "text/plain": { "example": "not found" } }
+                            },
+                            "200": {
+                                "description": "Found",
+                                "content": { "application/json": { "example": { "id": 1 } } }
+                            },
+                            "204": { "description": "Empty" }
+                        }
+                    }
+                }
+            }
+        }"#;
+        let spec = parse_spec(json).unwrap();
+        let responses = &spec.endpoints[0].responses;
+        let statuses: Vec<&str> = responses.iter().map(|r| r.status.as_str()).collect();
+        assert_eq!(statuses, vec!["200", "204", "404"]);
+        assert_eq!(
+            responses[0].content_type.as_deref(),
+            Some("application/json")
+        );
+        assert!(responses[0].example.as_ref().unwrap().contains("\"id\": 1"));
+        assert_eq!(responses[1].content_type, None);
+        assert_eq!(responses[2].example.as_deref(), Some("not found"));
+    }
+
+    #[test]
+    fn parse_referenced_components_and_recursive_schemas() {
+        let json = r##"{
+            "openapi": "3.0.0",
+            "info": { "title": "API", "version": "1.0" },
+            "paths": {
+                "/nodes/{id}": {
+                    "put": {
+                        "parameters": [{ "$ref": "#/components/parameters/Id" }],
+                        "requestBody": { "$ref": "#/components/requestBodies/Node" },
+                        "responses": { "200": { "$ref": "#/components/responses/Node" } }
+                    }
+                }
+            },
+            "components": {
+                "parameters": {
+                    "Id": { "name": "id", "in": "path", "required": true }
+                },
+                "requestBodies": {
+                    "Node": {
+                        "content": {
+                            "application/json": { "schema": { "$ref": "#/components/schemas/Node" } }
+                        }
+                    }
+                },
+                "responses": {
+                    "Node": {
+                        "description": "The node",
+                        "content": {
+                            "application/json": { "schema": { "$ref": "#/components/schemas/Node" } }
+                        }
+                    }
+                },
+                "schemas": {
+                    "Node": {
+                        "type": "object",
+                        "properties": {
+                            "name": { "type": "string" },
+                            "parent": { "$ref": "#/components/schemas/Node" }
+                        }
+                    }
+                }
+            }
+        }"##;
+        let spec = parse_spec(json).unwrap();
+        let endpoint = &spec.endpoints[0];
+        assert_eq!(endpoint.parameters[0].name, "id");
+        assert_eq!(
+            endpoint.responses[0].description.as_deref(),
+            Some("The node")
+        );
+        let body = endpoint.request_body_example.as_deref().unwrap();
+        assert!(body.contains("\"name\""));
+        assert!(!body.contains("parent"));
+    }
+
+    #[test]
+    fn parse_openapi31_webhooks_and_schema_keywords() {
+        let yaml = r##"
+openapi: 3.1.0
+info: { title: Pets, version: "1" }
+webhooks:
+  petAdopted:
+    $ref: "#/components/pathItems/PetEvent"
+components:
+  pathItems:
+    PetEvent:
+      post:
+        summary: Pet adopted
+        requestBody:
+          content:
+            application/json:
+              schema: { $ref: "#/components/schemas/Event" }
+        responses:
+          "200": { description: Received }
+  schemas:
+    Event:
+      type: object
+      properties:
+        kind: { const: adopted }
+        name: { type: [string, "null"], examples: [Rex, Fido] }
+        location:
+          type: array
+          prefixItems: [{ type: number }, { type: number }]
+"##;
+        let spec = parse_spec_from_yaml(yaml).unwrap();
+        assert_eq!(spec.spec_version.as_deref(), Some("3.1.0"));
+        assert!(spec.endpoints.is_empty());
+        assert_eq!(spec.webhooks.len(), 1);
+        let webhook = &spec.webhooks[0];
+        assert_eq!(
+            (webhook.method.as_str(), webhook.path.as_str()),
+            ("POST", "petAdopted")
+        );
+        let body: serde_json::Value =
+            serde_json::from_str(webhook.request_body_example.as_deref().unwrap()).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({ "kind": "adopted", "name": "Rex", "location": [0, 0] })
+        );
+    }
+
     #[test]
     fn parse_openapi3_with_tags() {
         let json = r#"{
@@ -482,11 +826,11 @@ mod tests {
     fn detect_format_json() {
         assert_eq!(
             detect_format(r#"{"openapi": "3.0.0", "info": {"title": "test"}}"#),
-            SpecFormat::OpenApi3Json
+            SpecFormat::OpenApi3Json("3.0.0".to_string())
         );
         assert_eq!(
             detect_format(r#"{"swagger": "2.0", "info": {"title": "test"}}"#),
-            SpecFormat::Swagger2Json
+            SpecFormat::Swagger2Json("2.0".to_string())
         );
     }
 
@@ -494,11 +838,18 @@ mod tests {
     fn detect_format_yaml() {
         assert_eq!(
             detect_format("openapi: 3.0.0\ninfo:\n  title: test"),
-            SpecFormat::OpenApi3Yaml
+            SpecFormat::OpenApi3Yaml("3.0.0".to_string())
         );
         assert_eq!(
             detect_format("swagger: '2.0'\ninfo:\n  title: test"),
-            SpecFormat::Swagger2Yaml
+            SpecFormat::Swagger2Yaml("2.0".to_string())
+        );
+        let format = detect_format("openapi: 3.1.0\ninfo:\n  title: test");
+        assert_eq!(format.version(), Some("3.1.0"));
+        assert_eq!(format.label(), "OpenAPI 3.1.0 (YAML)");
+        assert_eq!(
+            detect_format("swagger: 2.0\ninfo:\n  title: test").version(),
+            Some("2.0")
         );
     }
 
@@ -553,7 +904,7 @@ mod tests {
         let schemas = None;
 
         let email_schema = Schema {
-            schema_type: Some("string".to_string()),
+            types: vec!["string".to_string()],
             format: Some("email".to_string()),
             ..Default::default()
         };
@@ -561,7 +912,7 @@ mod tests {
         assert!(example.contains("@"));
 
         let url_schema = Schema {
-            schema_type: Some("string".to_string()),
+            types: vec!["string".to_string()],
             format: Some("url".to_string()),
             ..Default::default()
         };
@@ -0,0 +1,494 @@
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A document (`None` for the root spec) and a JSON Pointer into it.
+type Target = (Option<PathBuf>, String);
+
+/// Inlines every `$ref` in a spec: parameters, request bodies, responses, path
+/// items and schemas, including ones in other files next to the spec.
+///
+/// References to a root component schema (`#/components/schemas/Pet` or
+/// `#/definitions/Pet`) are kept, since the importer resolves those by name.
+/// So are references to a missing target, with a warning; see
+/// [`detach_unresolved`].
+/// A reference that leads back to itself is moved into the component schemas
+/// and referenced by name, so recursive models stay finite. Other files are
+/// only read when `base_dir` is given; otherwise their references are kept.
+pub fn resolve_refs(document: Value, base_dir: Option<&Path>) -> Result<Value, String> {
+    let swagger = document.get("swagger").is_some();
+    let mut resolver = Resolver {
+        base_dir,
+        root: document,
+        files: HashMap::new(),
+        stack: Vec::new(),
+        hoisted: HashMap::new(),
+        extra_schemas: Map::new(),
+        schemas_prefix: if swagger {
+            "#/definitions/"
+        } else {
+            "#/components/schemas/"
+        },
+    };
+    let root = resolver.root.clone();
+    let mut resolved = resolver.resolve(&root, &None)?;
+    if !resolver.extra_schemas.is_empty() {
+        let container = if swagger {
+            &mut resolved
+        } else {
+            resolved
+                .as_object_mut()
+                .ok_or("The spec is not an object")?
+                .entry("components")
+                .or_insert_with(|| json!({}))
+        };
+        let key = if swagger { "definitions" } else { "schemas" };
+        let schemas = container
+            .as_object_mut()
+            .ok_or("Invalid components section")?
+            .entry(key)
+            .or_insert_with(|| json!({}))
+            .as_object_mut()
+            .ok_or("Invalid schemas section")?;
+        schemas.extend(resolver.extra_schemas);
+    }
+    Ok(resolved)
+}
+
+/// Whether the document refers to another file.
+pub fn has_external_refs(value: &Value) -> bool {
+    match value {
+        Value::Object(map) => {
+            let external = map
+                .get("$ref")
+                .and_then(Value::as_str)
+                .is_some_and(|reference| !reference.starts_with('#'));
+            external || map.values().any(has_external_refs)
+        }
+        Value::Array(items) => items.iter().any(has_external_refs),
+        _ => false,
+    }
+}
+
+/// Parses a JSON or YAML document.
+pub fn read_document(content: &str) -> Result<Value, String> {
+    if content.trim_start().starts_with('{') {
+        serde_json::from_str(content).map_err(|e| format!("Invalid JSON: {}", e))
+    } else {
+        serde_yaml::from_str(content).map_err(|e| format!("Invalid YAML: {}", e))
+    }
+}
+
+/// Pulls the files a spec refers to into a single JSON document, so it can be
+/// stored and parsed without the folder it came from. Specs without external
+/// references are returned unchanged.
+pub fn bundle(content: &str, base_dir: &Path) -> Result<String, String> {
+    let document = read_document(content)?;
+    if !has_external_refs(&document) {
+        return Ok(content.to_string());
+    }
+    let resolved = resolve_refs(document, Some(base_dir))?;
+    serde_json::to_string_pretty(&resolved).map_err(|e| format!("Failed to bundle spec: {}", e))
+}
+
+struct Resolver<'a> {
+    base_dir: Option<&'a Path>,
+    root: Value,
+    files: HashMap<PathBuf, Value>,
+    /// Targets being inlined, innermost last.
+    stack: Vec<Target>,
+    /// Recursive targets and the schema name they were moved to.
+    hoisted: HashMap<Target, String>,
+    extra_schemas: Map<String, Value>,
+    schemas_prefix: &'static str,
+}
+
+impl Resolver<'_> {
+    fn resolve(&mut self, value: &Value, document: &Option<PathBuf>) -> Result<Value, String> {
+        match value {
+            Value::Object(map) => match map.get("$ref").and_then(Value::as_str) {
+                Some(reference) => self.resolve_ref(reference, map, document),
+                None => map
+                    .iter()
+                    .map(|(key, item)| Ok((key.clone(), self.resolve(item, document)?)))
+                    .collect::<Result<Map<_, _>, String>>()
+                    .map(Value::Object),
+            },
+            Value::Array(items) => items
+                .iter()
+                .map(|item| self.resolve(item, document))
+                .collect::<Result<Vec<_>, _>>()
+                .map(Value::Array),
+            other => Ok(other.clone()),
+        }
+    }
+
+    fn resolve_ref(
+        &mut self,
+        reference: &str,
+        map: &Map<String, Value>,
+        document: &Option<PathBuf>,
+    ) -> Result<Value, String> {
+        let (file, fragment) = reference.split_once('#').unwrap_or((reference, ""));
+        let pointer = urlencoding::decode(fragment)
+            .map_err(|e| format!("Invalid $ref {}: {}", reference, e))?
+            .into_owned();
+        if !pointer.is_empty() && !pointer.starts_with('/') {
+            return Err(format!("Unsupported $ref {}", reference));
+        }
+
+        let target_document = if file.is_empty() {
+            document.clone()
+        } else if file.contains("://") {
+            log::warn!("Skipping remote $ref {}", reference);
+            return Ok(Value::Object(map.clone()));
+        } else {
+            let dir = match document {
+                Some(path) => path.parent(),
+                None => self.base_dir,
+            };
+            let Some(dir) = dir else {
+                return Ok(Value::Object(map.clone()));
+            };
+            let file = urlencoding::decode(file)
+                .map_err(|e| format!("Invalid $ref {}: {}", reference, e))?;
+            Some(dir.join(file.as_ref()))
+        };
+
+        if target_document.is_none() && is_component_schema(&pointer) {
+            return Ok(Value::Object(map.clone()));
+        }
+
+        let target = (target_document, pointer);
+        if let Some(name) = self.hoisted.get(&target) {
+            return Ok(self.named_ref(name, map));
+        }
+        if self.stack.contains(&target) {
+            let name = self.schema_name(&target);
+            self.hoisted.insert(target, name.clone());
+            return Ok(self.named_ref(&name, map));
+        }
+
+        let Some(value) = self.lookup(&target)? else {
+            log::warn!("Keeping unresolved $ref {}", reference);
+            return Ok(Value::Object(map.clone()));
+        };
+        self.stack.push(target.clone());
+        let resolved = self.resolve(&value, &target.0);
+        self.stack.pop();
+        let mut resolved = resolved?;
+
+        if let Some(name) = self.hoisted.get(&target).cloned() {
+            self.extra_schemas.insert(name.clone(), resolved);
+            return Ok(self.named_ref(&name, map));
+        }
+        // Keys next to `$ref`, such as a description, override the target's.
+        if let Value::Object(object) = &mut resolved {
+            for (key, sibling) in map.iter().filter(|(key, _)| *key != "$ref") {
+                object.insert(key.clone(), self.resolve(sibling, document)?);
+            }
+        }
+        Ok(resolved)
+    }
+
+    fn lookup(&mut self, (document, pointer): &Target) -> Result<Option<Value>, String> {
+        let source = match document {
+            None => &self.root,
+            Some(path) => {
+                if !self.files.contains_key(path) {
+                    let content = std::fs::read_to_string(path)
+                        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+                    let value = read_document(&content)
+                        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+                    self.files.insert(path.clone(), value);
+                }
+                &self.files[path]
+            }
+        };
+        Ok(source.pointer(pointer).cloned())
+    }
+
+    fn named_ref(&self, name: &str, map: &Map<String, Value>) -> Value {
+        let mut object = map.clone();
+        object.insert(
+            "$ref".to_string(),
+            Value::String(format!("{}{}", self.schemas_prefix, name)),
+        );
+        Value::Object(object)
+    }
+
+    /// The pointer's last segment, or the file name, made unique among schemas.
+    fn schema_name(&self, (document, pointer): &Target) -> String {
+        let base = pointer
+            .rsplit('/')
+            .find(|segment| !segment.is_empty())
+            .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+            .or_else(|| {
+                document
+                    .as_ref()
+                    .and_then(|path| path.file_stem())
+                    .map(|stem| stem.to_string_lossy().into_owned())
+            })
+            .unwrap_or_else(|| "Schema".to_string());
+        let existing = self
+            .root
+            .pointer(&self.schemas_prefix[1..self.schemas_prefix.len() - 1]);
+        let taken = |name: &str| {
+            existing.is_some_and(|schemas| schemas.get(name).is_some())
+                || self.extra_schemas.contains_key(name)
+                || self.hoisted.values().any(|hoisted| hoisted == name)
+        };
+        let mut name = base.clone();
+        let mut counter = 2;
+        while taken(&name) {
+            name = format!("{}{}", base, counter);
+            counter += 1;
+        }
+        name
+    }
+}
+
+/// Operation methods as they appear in a path item.
+const METHODS: [&str; 7] = ["get", "post", "put", "patch", "delete", "head", "options"];
+
+/// Removes the local `$ref`s that lead nowhere from the operations of a resolved
+/// document, so the rest of it still parses. Returns `(method, path, $ref)` for
+/// each one, so importing an operation that needs it can fail.
+pub fn detach_unresolved(document: &mut Value) -> Vec<(String, String, String)> {
+    let lookup = document.clone();
+    let mut unresolved = Vec::new();
+    for section in ["paths", "webhooks"] {
+        let Some(items) = document.get_mut(section).and_then(Value::as_object_mut) else {
+            continue;
+        };
+        for (path, item) in items.iter_mut() {
+            let mut shared = Vec::new();
+            if let Some(parameters) = item.get_mut("parameters") {
+                strip_dangling(parameters, &lookup, &mut shared);
+            }
+            for method in METHODS {
+                let Some(operation) = item.get_mut(method) else {
+                    continue;
+                };
+                let mut found = shared.clone();
+                strip_dangling(operation, &lookup, &mut found);
+                unresolved.extend(
+                    found
+                        .into_iter()
+                        .map(|reference| (method.to_uppercase(), path.clone(), reference)),
+                );
+            }
+        }
+    }
+    unresolved
+}
+
+fn strip_dangling(value: &mut Value, document: &Value, found: &mut Vec<String>) {
+    let mut keep = |child: &Value| match dangling_ref(child, document) {
+        Some(reference) => {
+            found.push(reference);
+            false
+        }
+        None => true,
+    };
+    match value {
+        Value::Object(map) => {
+            map.retain(|_, child| keep(child));
+            for child in map.values_mut() {
+                strip_dangling(child, document, found);
+            }
+        }
+        Value::Array(items) => {
+            items.retain(|child| keep(child));
+            for child in items.iter_mut() {
+                strip_dangling(child, document, found);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The reference in `value`, when it points inside `document` at nothing.
+fn dangling_ref(value: &Value, document: &Value) -> Option<String> {
+    let reference = value.get("$ref")?.as_str()?;
+    let pointer = urlencoding::decode(reference.strip_prefix('#')?).ok()?;
+    (!is_component_schema(&pointer) && document.pointer(&pointer).is_none())
+        .then(|| reference.to_string())
+}
+
+/// `/components/schemas/Name` or `/definitions/Name`.
+fn is_component_schema(pointer: &str) -> bool {
+    let name = pointer
+        .strip_prefix("/components/schemas/")
+        .or_else(|| pointer.strip_prefix("/definitions/"));
+    name.is_some_and(|name| !name.is_empty() && !name.contains('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn non_schema_components_are_inlined() {
+        let document = json!({
+            "openapi": "3.0.0",
+            "paths": {
+                "/pets/{id}": { "$ref": "#/components/pathItems/Pet" }
+            },
+            "components": {
+                "parameters": {
+                    "Id": { "name": "id", "in": "path", "required": true }
+                },
+                "responses": {
+                    "Pet": {
+                        "description": "A pet",
+                        "content": {
+                            "application/json": {
+                                "schema": { "$ref": "#/components/schemas/Pet" }
+                            }
+                        }
+                    }
+                },
+                "pathItems": {
+                    "Pet": {
+                        "get": {
+                            "parameters": [{ "$ref": "#/components/parameters/Id" }],
+                            "responses": {
+                                "200": { "$ref": "#/components/responses/Pet", "description": "Found" }
+                            }
+                        }
+                    }
+                },
+                "schemas": { "Pet": { "type": "object" } }
+            }
+        });
+        let resolved = resolve_refs(document, None).unwrap();
+        let get = &resolved["paths"]["/pets/{id}"]["get"];
+        assert_eq!(get["parameters"][0]["name"], "id");
+        assert_eq!(get["responses"]["200"]["description"], "Found");
+        assert_eq!(
+            get["responses"]["200"]["content"]["application/json"]["schema"],
+            json!({ "$ref": "#/components/schemas/Pet" })
+        );
+    }
+
+    #[test]
+    fn escaped_pointers_and_missing_targets() {
+        let document = json!({
+            "swagger": "2.0",
+            "paths": {
+                "/a/{id}": { "get": { "responses": {} } },
+                "/b": { "$ref": "#/paths/~1a~1%7Bid%7D" }
+            }
+        });
+        let resolved = resolve_refs(document, None).unwrap();
+        assert!(resolved["paths"]["/b"]["get"].is_object());
+
+        let missing = json!({ "openapi": "3.0.0", "paths": { "/a": { "$ref": "#/nope" } } });
+        assert_eq!(
+            resolve_refs(missing, None).unwrap()["paths"]["/a"],
+            json!({ "$ref": "#/nope" })
+        );
+    }
+
+    #[test]
+    fn unresolved_refs_are_detached_per_operation() {
+        let document = json!({
+            "openapi": "3.0.0",
+            "paths": {
+                "/a": {
+                    "parameters": [{ "$ref": "#/components/parameters/Gone" }],
+                    "get": { "responses": { "200": { "$ref": "#/components/responses/Ok" } } },
+                    "post": {
+                        "requestBody": { "$ref": "#/components/requestBodies/Missing" },
+                        "responses": {}
+                    }
+                },
+                "/b": { "get": { "parameters": [{ "$ref": "#/components/parameters/Id" }] } }
+            },
+            "components": {
+                "parameters": { "Id": { "name": "id", "in": "query" } },
+                "responses": { "Ok": { "description": "ok" } }
+            }
+        });
+        let mut resolved = resolve_refs(document, None).unwrap();
+        let unresolved = detach_unresolved(&mut resolved);
+        assert_eq!(
+            unresolved,
+            vec![
+                (
+                    "GET".to_string(),
+                    "/a".to_string(),
+                    "#/components/parameters/Gone".to_string()
+                ),
+                (
+                    "POST".to_string(),
+                    "/a".to_string(),
+                    "#/components/parameters/Gone".to_string()
+                ),
+                (
+                    "POST".to_string(),
+                    "/a".to_string(),
+                    "#/components/requestBodies/Missing".to_string()
+                ),
+            ]
+        );
+        assert_eq!(resolved["paths"]["/a"]["parameters"], json!([]));
+        assert!(resolved["paths"]["/a"]["post"].get("requestBody").is_none());
+        assert_eq!(
+            resolved["paths"]["/b"]["get"]["parameters"][0]["name"],
+            "id"
+        );
+    }
+
+    #[test]
+    fn external_files_are_bundled_and_cycles_become_schemas() {
+        let dir = std::env::temp_dir().join(format!("astranova-refs-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("models")).unwrap();
+        std::fs::write(
+            dir.join("models/node.yaml"),
+            "type: object\nproperties:\n  name: { type: string }\n  children:\n    type: array\n    items: { $ref: '#' }\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("common.yaml"),
+            "parameters:\n  Limit: { name: limit, in: query, schema: { type: integer } }\n",
+        )
+        .unwrap();
+        let spec = r#"
+openapi: 3.0.0
+info: { title: Tree, version: "1" }
+paths:
+  /nodes:
+    get:
+      parameters:
+        - $ref: "common.yaml#/parameters/Limit"
+      responses:
+        "200":
+          description: ok
+          content:
+            application/json:
+              schema: { $ref: "models/node.yaml" }
+"#;
+        let bundled = bundle(spec, &dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let value: Value = serde_json::from_str(&bundled).unwrap();
+        let get = &value["paths"]["/nodes"]["get"];
+        assert_eq!(get["parameters"][0]["name"], "limit");
+        assert_eq!(
+            get["responses"]["200"]["content"]["application/json"]["schema"],
+            json!({ "$ref": "#/components/schemas/node" })
+        );
+        assert_eq!(
+            value["components"]["schemas"]["node"]["properties"]["children"]["items"],
+            json!({ "$ref": "#/components/schemas/node" })
+        );
+        assert!(crate::openapi::parse_spec(&bundled).is_ok());
+
+        assert_eq!(
+            bundle("openapi: 3.0.0\n", &dir).unwrap(),
+            "openapi: 3.0.0\n"
+        );
+    }
+}
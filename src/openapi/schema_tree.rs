@@ -0,0 +1,389 @@
+use super::models::{OpenApiSpec, PathItem, Schema};
+use super::parser::extract_operations;
+use std::collections::{BTreeSet, HashMap};
+
+/// One schema or property, with `$ref`s and `allOf` already resolved.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SchemaNode {
+    /// Property name, `items`, or `Option 1` for a `oneOf`/`anyOf` alternative.
+    pub name: String,
+    /// Such as `string (email)`, `array of Pet` or `Pet`.
+    pub type_label: String,
+    pub required: bool,
+    pub nullable: bool,
+    pub description: Option<String>,
+    pub enum_values: Vec<String>,
+    /// Component the node was resolved from.
+    pub reference: Option<String>,
+    /// `oneOf` or `anyOf` when the children are alternatives.
+    pub choice: Option<&'static str>,
+    /// The reference is already expanded further up, so it isn't repeated.
+    pub recursive: bool,
+    pub children: Vec<SchemaNode>,
+}
+
+/// An operation whose parameters, body or responses refer to a schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaUsage {
+    pub method: String,
+    pub path: String,
+    /// `request body`, `response 200` or `parameter id`.
+    pub location: String,
+}
+
+/// The component schemas of a spec and the operations using each one.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaBrowser {
+    schemas: HashMap<String, Schema>,
+    usages: HashMap<String, Vec<SchemaUsage>>,
+}
+
+fn ref_name(reference: &str) -> &str {
+    reference.rsplit('/').next().unwrap_or(reference)
+}
+
+impl SchemaBrowser {
+    pub fn new(spec: &OpenApiSpec) -> Self {
+        let mut browser = Self {
+            schemas: spec.component_schemas(),
+            usages: HashMap::new(),
+        };
+        // Webhooks are listed under their name, like the requests made from them.
+        let mut paths: Vec<(&String, &PathItem)> =
+            spec.paths.iter().chain(&spec.webhooks).collect();
+        paths.sort_by_key(|(path, _)| *path);
+        for (path, item) in paths {
+            for (method, operation) in extract_operations(item) {
+                let mut record = |schema: &Schema, location: String| {
+                    browser.record(schema, &method, path, location)
+                };
+                for parameter in item.parameters.iter().chain(&operation.parameters) {
+                    if let Some(schema) = &parameter.schema {
+                        record(schema, format!("parameter {}", parameter.name));
+                    }
+                }
+                if let Some(body) = &operation.request_body {
+                    for media in body.content.values() {
+                        if let Some(schema) = &media.schema {
+                            record(schema, "request body".to_string());
+                        }
+                    }
+                }
+                let mut statuses: Vec<&String> = operation.responses.keys().collect();
+                statuses.sort();
+                for status in statuses {
+                    let response = &operation.responses[status];
+                    let schemas = response
+                        .content
+                        .values()
+                        .filter_map(|media| media.schema.as_ref())
+                        .chain(response.schema.as_ref());
+                    for schema in schemas {
+                        record(schema, format!("response {}", status));
+                    }
+                }
+            }
+        }
+        browser
+    }
+
+    /// Schema names in alphabetical order.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.schemas.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+
+    pub fn tree(&self, name: &str) -> Option<SchemaNode> {
+        let schema = self.schemas.get(name)?;
+        let mut stack = vec![name.to_string()];
+        let mut node = self.node(name.to_string(), schema, false, &mut stack);
+        node.reference = Some(name.to_string());
+        Some(node)
+    }
+
+    /// Operations that use `name`, directly or through another schema.
+    pub fn usages(&self, name: &str) -> &[SchemaUsage] {
+        self.usages.get(name).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    fn record(&mut self, schema: &Schema, method: &str, path: &str, location: String) {
+        let mut names = BTreeSet::new();
+        self.referenced(schema, &mut names);
+        for name in names {
+            let usage = SchemaUsage {
+                method: method.to_string(),
+                path: path.to_string(),
+                location: location.clone(),
+            };
+            let usages = self.usages.entry(name).or_default();
+            if !usages.contains(&usage) {
+                usages.push(usage);
+            }
+        }
+    }
+
+    fn referenced(&self, schema: &Schema, names: &mut BTreeSet<String>) {
+        if let Some(reference) = &schema.r#ref {
+            let name = ref_name(reference);
+            if names.insert(name.to_string()) {
+                if let Some(target) = self.schemas.get(name) {
+                    self.referenced(target, names);
+                }
+            }
+        }
+        let nested = schema
+            .properties
+            .iter()
+            .flat_map(|properties| properties.values())
+            .chain(schema.items.as_deref())
+            .chain(schema.prefix_items.iter().flatten())
+            .chain(schema.all_of.iter().flatten())
+            .chain(schema.one_of.iter().flatten())
+            .chain(schema.any_of.iter().flatten());
+        for child in nested {
+            self.referenced(child, names);
+        }
+    }
+
+    fn node(
+        &self,
+        name: String,
+        schema: &Schema,
+        required: bool,
+        stack: &mut Vec<String>,
+    ) -> SchemaNode {
+        if let Some(reference) = &schema.r#ref {
+            let target = ref_name(reference).to_string();
+            let mut node = SchemaNode {
+                name,
+                type_label: target.clone(),
+                required,
+                description: schema.description.clone(),
+                reference: Some(target.clone()),
+                ..Default::default()
+            };
+            match self.schemas.get(&target) {
+                Some(_) if stack.contains(&target) => node.recursive = true,
+                Some(resolved) => {
+                    stack.push(target.clone());
+                    let inner = self.node(node.name.clone(), resolved, required, stack);
+                    stack.pop();
+                    node.nullable = inner.nullable;
+                    node.description = node.description.or(inner.description);
+                    node.enum_values = inner.enum_values;
+                    node.choice = inner.choice;
+                    node.children = inner.children;
+                }
+                None => node.type_label = format!("{} (missing)", target),
+            }
+            return node;
+        }
+
+        let mut node = SchemaNode {
+            name,
+            type_label: type_label(schema),
+            required,
+            nullable: schema.is_nullable(),
+            description: schema.description.clone(),
+            enum_values: schema
+                .enum_values
+                .iter()
+                .flatten()
+                .chain(schema.const_value.as_ref())
+                .map(|value| match value {
+                    serde_json::Value::String(text) => text.clone(),
+                    other => other.to_string(),
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        // allOf parts are merged into one set of properties.
+        let mut required_names: Vec<&String> = schema.required.iter().collect();
+        for part in schema.all_of.iter().flatten() {
+            let merged = self.node(String::new(), part, false, stack);
+            node.children.extend(merged.children);
+            if node.description.is_none() {
+                node.description = merged.description;
+            }
+            if let Some(resolved) = match &part.r#ref {
+                Some(reference) => self.schemas.get(ref_name(reference)),
+                None => Some(part),
+            } {
+                required_names.extend(&resolved.required);
+            }
+        }
+
+        if let Some(properties) = &schema.properties {
+            let mut names: Vec<&String> = properties.keys().collect();
+            names.sort();
+            for property in names {
+                let child = self.node(property.clone(), &properties[property], false, stack);
+                node.children.push(child);
+            }
+        }
+        for child in &mut node.children {
+            child.required = child.required || required_names.contains(&&child.name);
+        }
+
+        for (index, item) in schema.prefix_items.iter().flatten().enumerate() {
+            let child = self.node(format!("[{}]", index), item, false, stack);
+            node.children.push(child);
+        }
+        if let Some(items) = &schema.items {
+            let child = self.node("items".to_string(), items, false, stack);
+            if schema.prefix_items.is_none() {
+                node.type_label = format!("array of {}", child.type_label);
+            }
+            node.children.push(child);
+        }
+
+        for (choice, alternatives) in [("oneOf", &schema.one_of), ("anyOf", &schema.any_of)] {
+            let Some(alternatives) = alternatives else {
+                continue;
+            };
+            node.choice = Some(choice);
+            node.type_label = choice.to_string();
+            for (index, alternative) in alternatives.iter().enumerate() {
+                let child = self.node(format!("Option {}", index + 1), alternative, false, stack);
+                node.children.push(child);
+            }
+        }
+        node
+    }
+}
+
+fn type_label(schema: &Schema) -> String {
+    let types = schema.non_null_types();
+    let base = match types.as_slice() {
+        [] if schema.never => "never".to_string(),
+        [] if schema.properties.is_some() || schema.all_of.is_some() => "object".to_string(),
+        [] => "any".to_string(),
+        types => types.join(" | "),
+    };
+    match &schema.format {
+        Some(format) => format!("{} ({})", base, format),
+        None => base,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::openapi::parser::load_spec_model;
+
+    const SPEC: &str = r##"
+openapi: 3.0.0
+info: { title: Pets, version: "1" }
+paths:
+  /pets:
+    get:
+      responses:
+        "200":
+          description: ok
+          content:
+            application/json:
+              schema: { type: array, items: { $ref: "#/components/schemas/Pet" } }
+    post:
+      requestBody:
+        content:
+          application/json:
+            schema: { $ref: "#/components/schemas/NewPet" }
+      responses:
+        "201": { description: created }
+  /owners/{id}:
+    get:
+      parameters:
+        - { name: id, in: path, schema: { type: string, format: uuid } }
+      responses:
+        default:
+          description: error
+          content:
+            application/json:
+              schema: { $ref: "#/components/schemas/Error" }
+components:
+  schemas:
+    NewPet:
+      type: object
+      required: [name]
+      properties:
+        name: { type: string, description: Display name }
+        status: { type: string, enum: [available, sold] }
+        tag: { $ref: "#/components/schemas/Tag" }
+    Pet:
+      allOf:
+        - $ref: "#/components/schemas/NewPet"
+        - required: [id]
+          properties:
+            id: { type: integer, format: int64 }
+            parent: { $ref: "#/components/schemas/Pet" }
+    Tag:
+      oneOf:
+        - { type: string }
+        - { type: object, properties: { label: { type: string } } }
+    Error:
+      type: object
+      properties: { message: { type: string, nullable: true } }
+"##;
+
+    fn browser() -> SchemaBrowser {
+        SchemaBrowser::new(&load_spec_model(SPEC).unwrap())
+    }
+
+    fn child<'a>(node: &'a SchemaNode, name: &str) -> &'a SchemaNode {
+        node.children.iter().find(|c| c.name == name).unwrap()
+    }
+
+    #[test]
+    fn all_of_and_refs_are_merged_into_one_tree() {
+        let browser = browser();
+        assert_eq!(browser.names(), vec!["Error", "NewPet", "Pet", "Tag"]);
+
+        let pet = browser.tree("Pet").unwrap();
+        assert_eq!(pet.type_label, "object");
+        let names: Vec<&str> = pet.children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["name", "status", "tag", "id", "parent"]);
+        assert!(child(&pet, "name").required);
+        assert!(child(&pet, "id").required);
+        assert!(!child(&pet, "status").required);
+        assert_eq!(child(&pet, "id").type_label, "integer (int64)");
+        assert_eq!(child(&pet, "status").enum_values, vec!["available", "sold"]);
+        assert_eq!(
+            child(&pet, "name").description.as_deref(),
+            Some("Display name")
+        );
+
+        let parent = child(&pet, "parent");
+        assert!(parent.recursive);
+        assert!(parent.children.is_empty());
+
+        let tag = child(&pet, "tag");
+        assert_eq!(tag.type_label, "Tag");
+        assert_eq!(tag.choice, Some("oneOf"));
+        assert_eq!(tag.children.len(), 2);
+        assert_eq!(child(&tag.children[1], "label").type_label, "string");
+    }
+
+    #[test]
+    fn usages_include_schemas_reached_through_other_schemas() {
+        let browser = browser();
+        let locations = |name: &str| -> Vec<String> {
+            browser
+                .usages(name)
+                .iter()
+                .map(|u| format!("{} {} {}", u.method, u.path, u.location))
+                .collect()
+        };
+        assert_eq!(locations("Pet"), vec!["GET /pets response 200"]);
+        assert_eq!(
+            locations("Tag"),
+            vec!["GET /pets response 200", "POST /pets request body"]
+        );
+        assert_eq!(
+            locations("Error"),
+            vec!["GET /owners/{id} response default"]
+        );
+        assert!(browser.usages("Missing").is_empty());
+    }
+}
@@ -0,0 +1,234 @@
+use super::models::{OAuthFlow, SecurityRequirement, SecurityScheme};
+use crate::data::auth::{ApiKeyLocation, Auth, OAuth2Config, OAuth2GrantType};
+use std::collections::HashMap;
+
+/// Auth for the first requirement naming a scheme this client can send.
+/// Credentials are `{{variable}}` placeholders named after the scheme.
+pub fn auth_for(
+    requirements: &[SecurityRequirement],
+    schemes: &HashMap<String, SecurityScheme>,
+) -> Auth {
+    for requirement in requirements {
+        let mut names: Vec<&String> = requirement.keys().collect();
+        names.sort();
+        for name in names {
+            let auth = schemes
+                .get(name)
+                .and_then(|scheme| scheme_to_auth(name, scheme, &requirement[name]));
+            if let Some(auth) = auth {
+                return auth;
+            }
+        }
+    }
+    Auth::None
+}
+
+fn scheme_to_auth(name: &str, scheme: &SecurityScheme, scopes: &[String]) -> Option<Auth> {
+    let basic = || Auth::Basic {
+        user: placeholder(name, Some("username")),
+        pass: placeholder(name, Some("password")),
+    };
+    match scheme.scheme_type.as_str() {
+        "http" => match scheme.scheme.as_deref()?.to_ascii_lowercase().as_str() {
+            "bearer" => Some(Auth::BearerToken(placeholder(name, None))),
+            "basic" => Some(basic()),
+            _ => None,
+        },
+        "basic" => Some(basic()),
+        "apiKey" => Some(Auth::ApiKey {
+            key: scheme.name.clone()?,
+            value: placeholder(name, None),
+            location: match scheme.location.as_deref() {
+                Some("query") => ApiKeyLocation::Query,
+                Some("cookie") => ApiKeyLocation::Cookie,
+                _ => ApiKeyLocation::Header,
+            },
+        }),
+        "oauth2" => oauth2_auth(name, scheme, scopes),
+        _ => None,
+    }
+}
+
+fn oauth2_auth(name: &str, scheme: &SecurityScheme, scopes: &[String]) -> Option<Auth> {
+    let (grant_type, flow) = match &scheme.flows {
+        Some(flows) => [
+            (
+                OAuth2GrantType::AuthorizationCode,
+                &flows.authorization_code,
+            ),
+            (
+                OAuth2GrantType::ClientCredentials,
+                &flows.client_credentials,
+            ),
+            (OAuth2GrantType::Password, &flows.password),
+            (OAuth2GrantType::Implicit, &flows.implicit),
+        ]
+        .into_iter()
+        .find_map(|(grant, flow)| flow.clone().map(|flow| (grant, flow)))?,
+        None => {
+            let grant_type = match scheme.flow.as_deref()? {
+                "accessCode" => OAuth2GrantType::AuthorizationCode,
+                "application" => OAuth2GrantType::ClientCredentials,
+                "password" => OAuth2GrantType::Password,
+                "implicit" => OAuth2GrantType::Implicit,
+                _ => return None,
+            };
+            let flow = OAuthFlow {
+                authorization_url: scheme.authorization_url.clone(),
+                token_url: scheme.token_url.clone(),
+                refresh_url: None,
+                scopes: scheme.scopes.clone(),
+            };
+            (grant_type, flow)
+        }
+    };
+
+    // The operation's own scopes, or every scope the flow offers.
+    let scopes = if scopes.is_empty() {
+        let mut all: Vec<&String> = flow.scopes.keys().collect();
+        all.sort();
+        all.into_iter().cloned().collect::<Vec<_>>().join(" ")
+    } else {
+        scopes.join(" ")
+    };
+    let is_password = grant_type == OAuth2GrantType::Password;
+    let client_secret = if grant_type == OAuth2GrantType::Implicit {
+        String::new()
+    } else {
+        placeholder(name, Some("client_secret"))
+    };
+
+    Some(Auth::OAuth2(Box::new(OAuth2Config {
+        grant_type,
+        auth_url: flow.authorization_url.unwrap_or_default(),
+        token_url: flow.token_url.unwrap_or_default(),
+        client_id: placeholder(name, Some("client_id")),
+        client_secret,
+        scopes,
+        username: if is_password {
+            placeholder(name, Some("username"))
+        } else {
+            String::new()
+        },
+        password: if is_password {
+            placeholder(name, Some("password"))
+        } else {
+            String::new()
+        },
+        ..Default::default()
+    })))
+}
+
+fn placeholder(scheme: &str, field: Option<&str>) -> String {
+    match field {
+        Some(field) => format!("{{{{{}_{}}}}}", scheme, field),
+        None => format!("{{{{{}}}}}", scheme),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schemes(yaml: &str) -> HashMap<String, SecurityScheme> {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    fn requirement(name: &str, scopes: &[&str]) -> SecurityRequirement {
+        HashMap::from([(
+            name.to_string(),
+            scopes.iter().map(|s| s.to_string()).collect(),
+        )])
+    }
+
+    #[test]
+    fn http_and_api_key_schemes_use_placeholders() {
+        let schemes = schemes(
+            r#"
+bearerAuth: { type: http, scheme: bearer, bearerFormat: JWT }
+basicAuth: { type: http, scheme: Basic }
+session: { type: apiKey, in: cookie, name: SESSIONID }
+"#,
+        );
+        assert_eq!(
+            auth_for(&[requirement("bearerAuth", &[])], &schemes),
+            Auth::BearerToken("{{bearerAuth}}".to_string())
+        );
+        assert_eq!(
+            auth_for(&[requirement("basicAuth", &[])], &schemes),
+            Auth::Basic {
+                user: "{{basicAuth_username}}".to_string(),
+                pass: "{{basicAuth_password}}".to_string(),
+            }
+        );
+        assert_eq!(
+            auth_for(&[requirement("session", &[])], &schemes),
+            Auth::ApiKey {
+                key: "SESSIONID".to_string(),
+                value: "{{session}}".to_string(),
+                location: ApiKeyLocation::Cookie,
+            }
+        );
+    }
+
+    #[test]
+    fn oauth2_flows_carry_urls_and_scopes() {
+        let schemes = schemes(
+            r#"
+oauth:
+  type: oauth2
+  flows:
+    clientCredentials:
+      tokenUrl: https://auth.example.com/token
+      scopes: { write: Write, read: Read }
+legacy:
+  type: oauth2
+  flow: accessCode
+  authorizationUrl: https://auth.example.com/authorize
+  tokenUrl: https://auth.example.com/token
+  scopes: { pets: Pets }
+"#,
+        );
+        let Auth::OAuth2(config) = auth_for(&[requirement("oauth", &[])], &schemes) else {
+            panic!("expected OAuth2");
+        };
+        assert_eq!(config.grant_type, OAuth2GrantType::ClientCredentials);
+        assert_eq!(config.token_url, "https://auth.example.com/token");
+        assert_eq!(config.scopes, "read write");
+        assert_eq!(config.client_id, "{{oauth_client_id}}");
+        assert_eq!(config.client_secret, "{{oauth_client_secret}}");
+
+        let Auth::OAuth2(config) = auth_for(&[requirement("legacy", &["pets"])], &schemes) else {
+            panic!("expected OAuth2");
+        };
+        assert_eq!(config.grant_type, OAuth2GrantType::AuthorizationCode);
+        assert_eq!(config.auth_url, "https://auth.example.com/authorize");
+        assert_eq!(config.scopes, "pets");
+    }
+
+    #[test]
+    fn unsupported_schemes_fall_through_to_the_next_requirement() {
+        let schemes = schemes(
+            r#"
+mtls: { type: mutualTLS }
+key: { type: apiKey, in: query, name: api_key }
+"#,
+        );
+        let auth = auth_for(
+            &[requirement("mtls", &[]), requirement("key", &[])],
+            &schemes,
+        );
+        assert!(matches!(
+            auth,
+            Auth::ApiKey {
+                location: ApiKeyLocation::Query,
+                ..
+            }
+        ));
+        assert_eq!(auth_for(&[HashMap::new()], &schemes), Auth::None);
+        assert_eq!(
+            auth_for(&[requirement("missing", &[])], &schemes),
+            Auth::None
+        );
+    }
+}
@@ -0,0 +1,550 @@
+use super::models::{OpenApiSpec, Operation, PathItem, Response, Schema};
+use std::collections::HashMap;
+
+/// Deep enough for real specs while stopping `$ref` cycles.
+const MAX_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// `status`, `content-type`, or a JSON path into the body such as `$.items[0].id`.
+    pub location: String,
+    pub message: String,
+}
+
+/// The documented responses of one operation, with the schemas they refer to.
+#[derive(Debug, Clone)]
+pub struct ResponseContract {
+    /// `GET /users/{id}`.
+    pub operation: String,
+    responses: HashMap<String, Response>,
+    schemas: HashMap<String, Schema>,
+}
+
+impl ResponseContract {
+    pub fn for_operation(spec: &OpenApiSpec, path: &str, method: &str) -> Option<Self> {
+        let operation = find_operation(spec.paths.get(path)?, method)?;
+        Some(Self {
+            operation: format!("{} {}", method.to_uppercase(), path),
+            responses: operation.responses.clone(),
+            schemas: spec.component_schemas(),
+        })
+    }
+
+    pub fn validate(&self, status: u16, content_type: Option<&str>, body: &str) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        let Some(response) = self.response_for(status) else {
+            let mut documented: Vec<&str> = self.responses.keys().map(String::as_str).collect();
+            documented.sort();
+            violations.push(Violation {
+                location: "status".to_string(),
+                message: format!(
+                    "Status {} is not documented (expected {})",
+                    status,
+                    documented.join(", ")
+                ),
+            });
+            return violations;
+        };
+
+        let media_type = content_type
+            .and_then(|ct| ct.split(';').next())
+            .map(|ct| ct.trim().to_lowercase())
+            .unwrap_or_default();
+        let schema = if response.content.is_empty() {
+            // Swagger 2 documents the schema on the response itself.
+            response.schema.as_ref()
+        } else {
+            match response
+                .content
+                .iter()
+                .find(|(name, _)| media_type_matches(name, &media_type))
+            {
+                Some((_, media)) => media.schema.as_ref(),
+                None => {
+                    let mut expected: Vec<&str> =
+                        response.content.keys().map(String::as_str).collect();
+                    expected.sort();
+                    violations.push(Violation {
+                        location: "content-type".to_string(),
+                        message: format!(
+                            "Content type '{}' is not documented (expected {})",
+                            content_type.unwrap_or("none"),
+                            expected.join(", ")
+                        ),
+                    });
+                    return violations;
+                }
+            }
+        };
+
+        let Some(schema) = schema else {
+            return violations;
+        };
+        if !media_type.is_empty() && !media_type.contains("json") {
+            return violations;
+        }
+        match serde_json::from_str::<serde_json::Value>(body) {
+            Ok(value) => self.check(schema, &value, "$", 0, &mut violations),
+            Err(e) => violations.push(Violation {
+                location: "$".to_string(),
+                message: format!("Body is not valid JSON: {}", e),
+            }),
+        }
+        violations
+    }
+
+    /// The exact status, then its range such as `4XX`, then `default`.
+    fn response_for(&self, status: u16) -> Option<&Response> {
+        self.responses
+            .get(&status.to_string())
+            .or_else(|| self.responses.get(&format!("{}XX", status / 100)))
+            .or_else(|| self.responses.get(&format!("{}xx", status / 100)))
+            .or_else(|| self.responses.get("default"))
+    }
+
+    fn resolve<'a>(&'a self, schema: &'a Schema) -> Option<&'a Schema> {
+        match &schema.r#ref {
+            Some(reference) => self.schemas.get(reference.split('/').next_back()?),
+            None => Some(schema),
+        }
+    }
+
+    fn check(
+        &self,
+        schema: &Schema,
+        value: &serde_json::Value,
+        path: &str,
+        depth: usize,
+        violations: &mut Vec<Violation>,
+    ) {
+        if depth > MAX_DEPTH {
+            return;
+        }
+        let Some(schema) = self.resolve(schema) else {
+            violations.push(Violation {
+                location: path.to_string(),
+                message: format!(
+                    "Cannot resolve {}",
+                    schema.r#ref.as_deref().unwrap_or_default()
+                ),
+            });
+            return;
+        };
+        if schema.r#ref.is_some() {
+            return self.check(schema, value, path, depth + 1, violations);
+        }
+        if schema.never {
+            violations.push(Violation {
+                location: path.to_string(),
+                message: "No value is allowed here".to_string(),
+            });
+            return;
+        }
+        if value.is_null() && schema.is_nullable() {
+            return;
+        }
+
+        for part in schema.all_of.iter().flatten() {
+            self.check(part, value, path, depth + 1, violations);
+        }
+        if let Some(options) = &schema.one_of {
+            let matching = options
+                .iter()
+                .filter(|option| self.matches(option, value, depth + 1))
+                .count();
+            if matching != 1 {
+                violations.push(Violation {
+                    location: path.to_string(),
+                    message: format!(
+                        "Matches {} of the {} oneOf schemas, expected exactly 1",
+                        matching,
+                        options.len()
+                    ),
+                });
+            }
+        }
+        if let Some(options) = &schema.any_of {
+            if !options
+                .iter()
+                .any(|option| self.matches(option, value, depth + 1))
+            {
+                violations.push(Violation {
+                    location: path.to_string(),
+                    message: "Matches none of the anyOf schemas".to_string(),
+                });
+            }
+        }
+        if let Some(allowed) = &schema.enum_values {
+            if !allowed.contains(value) {
+                let allowed: Vec<String> = allowed.iter().map(|v| v.to_string()).collect();
+                violations.push(Violation {
+                    location: path.to_string(),
+                    message: format!("{} is not one of {}", value, allowed.join(", ")),
+                });
+            }
+        }
+
+        if let Some(expected) = &schema.const_value {
+            if expected != value {
+                violations.push(Violation {
+                    location: path.to_string(),
+                    message: format!("{} is not {}", value, expected),
+                });
+            }
+        }
+
+        let expected = schema.non_null_types();
+        if expected.is_empty() {
+            return;
+        }
+        if !expected.iter().any(|kind| type_matches(kind, value)) {
+            violations.push(Violation {
+                location: path.to_string(),
+                message: format!(
+                    "Expected {}, got {}",
+                    expected.join(" or "),
+                    type_name(value)
+                ),
+            });
+            return;
+        }
+        match value {
+            serde_json::Value::Object(map) => {
+                for name in &schema.required {
+                    if !map.contains_key(name) {
+                        violations.push(Violation {
+                            location: path.to_string(),
+                            message: format!("Missing required property '{}'", name),
+                        });
+                    }
+                }
+                for (name, property) in schema.properties.iter().flatten() {
+                    if let Some(child) = map.get(name) {
+                        let child_path = format!("{}.{}", path, name);
+                        self.check(property, child, &child_path, depth + 1, violations);
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => {
+                let prefix = schema.prefix_items.as_deref().unwrap_or_default();
+                for (i, item) in items.iter().enumerate() {
+                    let item_schema = prefix.get(i).or(schema.items.as_deref());
+                    if let Some(item_schema) = item_schema {
+                        let item_path = format!("{}[{}]", path, i);
+                        self.check(item_schema, item, &item_path, depth + 1, violations);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn matches(&self, schema: &Schema, value: &serde_json::Value, depth: usize) -> bool {
+        let mut violations = Vec::new();
+        self.check(schema, value, "$", depth, &mut violations);
+        violations.is_empty()
+    }
+}
+
+fn find_operation<'a>(item: &'a PathItem, method: &str) -> Option<&'a Operation> {
+    match method.to_uppercase().as_str() {
+        "GET" => item.get.as_ref(),
+        "POST" => item.post.as_ref(),
+        "PUT" => item.put.as_ref(),
+        "PATCH" => item.patch.as_ref(),
+        "DELETE" => item.delete.as_ref(),
+        "HEAD" => item.head.as_ref(),
+        "OPTIONS" => item.options.as_ref(),
+        _ => None,
+    }
+}
+
+/// Whether a documented media type such as `application/*` covers the received one.
+fn media_type_matches(documented: &str, received: &str) -> bool {
+    let documented = documented.to_lowercase();
+    match documented.split_once('/') {
+        _ if documented == received || documented == "*/*" => true,
+        Some((kind, "*")) => received
+            .split_once('/')
+            .is_some_and(|(received_kind, _)| received_kind == kind),
+        _ => false,
+    }
+}
+
+fn type_matches(expected: &str, value: &serde_json::Value) -> bool {
+    match expected {
+        "string" => value.is_string(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &serde_json::Value) -> &'static str {
+    match value {
+        serde_json::Value::Null => "null",
+        serde_json::Value::Bool(_) => "boolean",
+        serde_json::Value::Number(n) if n.is_f64() => "number",
+        serde_json::Value::Number(_) => "integer",
+        serde_json::Value::String(_) => "string",
+        serde_json::Value::Array(_) => "array",
+        serde_json::Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::openapi::parser::load_spec_model;
+
+    const SPEC: &str = r##"{
+        "openapi": "3.0.0",
+        "info": { "title": "Pets", "version": "1.0" },
+        "paths": {
+            "/pets/{id}": {
+                "get": {
+                    "responses": {
+                        "200": {
+                            "description": "A pet",
+                            "content": {
+                                "application/json": { "schema": { "$ref": "#/components/schemas/Pet" } }
+                            }
+                        },
+                        "4XX": {
+                            "description": "Error",
+                            "content": {
+                                "application/json": { "schema": { "$ref": "#/components/schemas/Error" } }
+                            }
+                        }
+                    }
+                }
+            }
+        },
+        "components": {
+            "schemas": {
+                "Pet": {
+                    "allOf": [
+                        { "$ref": "#/components/schemas/Named" },
+                        {
+                            "type": "object",
+                            "required": ["id", "kind"],
+                            "properties": {
+                                "id": { "type": "integer" },
+                                "kind": { "type": "string", "enum": ["cat", "dog"] },
+                                "tags": { "type": "array", "items": { "type": "string" } },
+                                "owner": { "type": "string", "nullable": true },
+                                "chip": {
+                                    "oneOf": [
+                                        { "type": "string" },
+                                        { "type": "integer" }
+                                    ]
+                                }
+                            }
+                        }
+                    ]
+                },
+                "Named": {
+                    "type": "object",
+                    "required": ["name"],
+                    "properties": { "name": { "type": "string" } }
+                },
+                "Error": {
+                    "type": "object",
+                    "required": ["message"],
+                    "properties": { "message": { "type": "string" } }
+                }
+            }
+        }
+    }"##;
+
+    fn contract() -> ResponseContract {
+        let spec = load_spec_model(SPEC).unwrap();
+        ResponseContract::for_operation(&spec, "/pets/{id}", "get").unwrap()
+    }
+
+    fn locations(violations: &[Violation]) -> Vec<&str> {
+        violations.iter().map(|v| v.location.as_str()).collect()
+    }
+
+    #[test]
+    fn valid_body_has_no_violations() {
+        let body =
+            r#"{"id": 1, "name": "Rex", "kind": "dog", "tags": ["a"], "owner": null, "chip": 42}"#;
+        assert_eq!(
+            contract().validate(200, Some("application/json; charset=utf-8"), body),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn body_violations_point_at_the_offending_value() {
+        let body = r#"{"id": "1", "kind": "fish", "tags": ["a", 2], "chip": true}"#;
+        let violations = contract().validate(200, Some("application/json"), body);
+        let mut found = locations(&violations);
+        found.sort();
+        assert_eq!(found, vec!["$", "$.chip", "$.id", "$.kind", "$.tags[1]"]);
+        assert!(violations
+            .iter()
+            .any(|v| v.message == "Missing required property 'name'"));
+        assert!(violations
+            .iter()
+            .any(|v| v.message == "Expected integer, got string"));
+    }
+
+    #[test]
+    fn status_ranges_and_content_types_are_checked() {
+        let contract = contract();
+        let violations = contract.validate(404, Some("application/json"), r#"{"message": 1}"#);
+        assert_eq!(locations(&violations), vec!["$.message"]);
+
+        let violations = contract.validate(500, Some("application/json"), "{}");
+        assert_eq!(
+            violations[0].message,
+            "Status 500 is not documented (expected 200, 4XX)"
+        );
+
+        let violations = contract.validate(200, Some("text/html"), "<html>");
+        assert_eq!(locations(&violations), vec!["content-type"]);
+
+        let violations = contract.validate(200, Some("application/json"), "not json");
+        assert!(violations[0].message.starts_with("Body is not valid JSON"));
+    }
+
+    #[test]
+    fn reference_cycles_stop() {
+        let spec = load_spec_model(
+            r##"{
+                "swagger": "2.0",
+                "info": { "title": "Loop", "version": "1" },
+                "paths": {
+                    "/loop": {
+                        "get": {
+                            "responses": {
+                                "200": { "description": "ok", "schema": { "$ref": "#/definitions/A" } }
+                            }
+                        }
+                    }
+                },
+                "definitions": {
+                    "A": { "$ref": "#/definitions/B" },
+                    "B": { "$ref": "#/definitions/A" }
+                }
+            }"##,
+        )
+        .unwrap();
+        let contract = ResponseContract::for_operation(&spec, "/loop", "GET").unwrap();
+        assert_eq!(contract.validate(200, None, "{}"), vec![]);
+        assert!(ResponseContract::for_operation(&spec, "/loop", "POST").is_none());
+    }
+
+    #[test]
+    fn openapi_31_keywords_are_checked() {
+        let spec = load_spec_model(
+            r#"{
+                "openapi": "3.1.0",
+                "info": { "title": "T", "version": "1" },
+                "paths": {
+                    "/point": {
+                        "get": {
+                            "responses": {
+                                "200": {
+                                    "description": "ok",
+                                    "content": {
+                                        "application/json": {
+                                            "schema": {
+                                                "type": "object",
+                                                "properties": {
+                                                    "kind": { "const": "point" },
+                                                    "label": { "type": ["string", "null"] },
+                                                    "id": { "type": ["integer", "string"] },
+                                                    "at": {
+                                                        "type": "array",
+                                                        "prefixItems": [{ "type": "number" }, { "type": "number" }],
+                                                        "items": { "type": "string" }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        let contract = ResponseContract::for_operation(&spec, "/point", "GET").unwrap();
+        let ok = r#"{"kind": "point", "label": null, "id": "a1", "at": [1.5, 2, "north"]}"#;
+        assert!(contract
+            .validate(200, Some("application/json"), ok)
+            .is_empty());
+
+        let bad = r#"{"kind": "line", "label": 3, "id": true, "at": ["x", 2, 3]}"#;
+        let violations = contract.validate(200, Some("application/json"), bad);
+        let mut found = locations(&violations);
+        found.sort();
+        assert_eq!(
+            found,
+            vec!["$.at[0]", "$.at[2]", "$.id", "$.kind", "$.label"]
+        );
+        assert!(violations
+            .iter()
+            .any(|v| v.message == "Expected integer or string, got boolean"));
+    }
+
+    #[test]
+    fn boolean_schemas_allow_everything_or_nothing() {
+        let spec = load_spec_model(
+            r#"{
+                "openapi": "3.1.0",
+                "info": { "title": "T", "version": "1" },
+                "paths": {
+                    "/pair": {
+                        "get": {
+                            "responses": {
+                                "200": {
+                                    "description": "ok",
+                                    "content": {
+                                        "application/json": {
+                                            "schema": {
+                                                "type": "object",
+                                                "properties": {
+                                                    "meta": true,
+                                                    "legacy": false,
+                                                    "pair": {
+                                                        "type": "array",
+                                                        "prefixItems": [{ "type": "string" }, { "type": "integer" }],
+                                                        "items": false
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        let contract = ResponseContract::for_operation(&spec, "/pair", "GET").unwrap();
+        let ok = r#"{"meta": {"any": [1]}, "pair": ["a", 1]}"#;
+        assert!(contract
+            .validate(200, Some("application/json"), ok)
+            .is_empty());
+
+        let bad = r#"{"legacy": 1, "pair": ["a", 1, "extra"]}"#;
+        let violations = contract.validate(200, Some("application/json"), bad);
+        let mut found = locations(&violations);
+        found.sort();
+        assert_eq!(found, vec!["$.legacy", "$.pair[2]"]);
+    }
+}
@@ -1,8 +1,11 @@
+use crate::data::assertions::Assertion;
+use crate::data::extraction::ExtractionRule;
+use crate::data::vault::VaultRecord;
 use crate::error::AppError;
 use directories::ProjectDirs;
 use rusqlite::{params, Connection, Result};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Environment {
@@ -10,6 +13,8 @@ pub struct Environment {
     pub name: String,
     pub variables: Vec<(String, String)>,
     pub default_endpoint: Option<String>,
+    #[serde(default)]
+    pub secret_keys: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,6 +34,8 @@ pub struct Collection {
     pub id: i32,
     pub name: String,
     pub description: Option<String>,
+    #[serde(default)]
+    pub variables: Vec<(String, String)>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -40,6 +47,20 @@ pub struct CollectionFolder {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CollectionRun {
+    pub id: i32,
+    pub collection_id: i32,
+    pub folder_id: Option<i32>,
+    pub started_at: String,
+    pub duration_ms: u64,
+    pub total: u32,
+    pub passed: u32,
+    pub failed: u32,
+    /// Serialized `RunReport`.
+    pub report: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct CollectionRequest {
     pub id: i32,
     pub collection_id: i32,
@@ -55,6 +76,53 @@ pub struct CollectionRequest {
     pub params: Vec<(String, String)>,
     pub config_json: Option<String>,
     pub sort_order: i32,
+    #[serde(default)]
+    pub variables: Vec<(String, String)>,
+    #[serde(default)]
+    pub extractions: Vec<ExtractionRule>,
+    #[serde(default)]
+    pub assertions: Vec<Assertion>,
+    /// Set on requests generated from an OpenAPI spec.
+    #[serde(default)]
+    pub operation: Option<OperationRef>,
+}
+
+/// Links a generated request to its operation in a stored OpenAPI spec.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OperationRef {
+    pub spec_id: i32,
+    pub path: String,
+    pub method: String,
+    /// Hashes of the request as generated, to spot the user's edits on re-sync.
+    #[serde(default)]
+    pub generated: Option<RequestFingerprint>,
+}
+
+/// A hash of each field that re-syncing a collection can update.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RequestFingerprint {
+    pub name: String,
+    pub url: String,
+    pub headers: String,
+    pub body: String,
+    pub params: String,
+    pub auth: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OpenApiSpecRecord {
+    pub id: i32,
+    pub collection_id: i32,
+    pub title: String,
+    /// The spec as imported, JSON or YAML.
+    pub content: String,
+    /// File path or URL the spec was imported from.
+    pub source: Option<String>,
+    /// `(method, path)` of the operations left out at import, which re-sync
+    /// doesn't offer again.
+    pub excluded_operations: Vec<(String, String)>,
+    /// How the imported requests were filed into folders, `tag` or `path`.
+    pub grouping: String,
 }
 
 impl std::fmt::Display for Collection {
@@ -85,8 +153,14 @@ fn get_db_path() -> std::result::Result<PathBuf, AppError> {
 }
 
 pub fn init() -> std::result::Result<Connection, AppError> {
-    let db_path = get_db_path()?;
-    let conn = Connection::open(db_path)?;
+    open(&get_db_path()?)
+}
+
+/// Opens the database at `path`, creating and migrating tables as needed.
+pub fn open(path: &Path) -> std::result::Result<Connection, AppError> {
+    let conn = Connection::open(path)?;
+    // SQLite leaves foreign keys off per connection, which would skip ON DELETE CASCADE.
+    conn.execute_batch("PRAGMA foreign_keys = ON")?;
     conn.execute(
         "CREATE TABLE IF NOT EXISTS environments (
             id INTEGER PRIMARY KEY,
@@ -100,6 +174,11 @@ pub fn init() -> std::result::Result<Connection, AppError> {
         [],
     )
     .ok();
+    conn.execute(
+        "ALTER TABLE environments ADD COLUMN secret_keys TEXT NOT NULL DEFAULT '[]'",
+        [],
+    )
+    .ok();
     conn.execute(
         "CREATE TABLE IF NOT EXISTS request_history (
             id INTEGER PRIMARY KEY,
@@ -163,6 +242,83 @@ pub fn init() -> std::result::Result<Connection, AppError> {
         )",
         [],
     )?;
+    conn.execute(
+        "ALTER TABLE collections ADD COLUMN variables TEXT NOT NULL DEFAULT '[]'",
+        [],
+    )
+    .ok();
+    conn.execute(
+        "ALTER TABLE collection_requests ADD COLUMN variables TEXT NOT NULL DEFAULT '[]'",
+        [],
+    )
+    .ok();
+    conn.execute(
+        "ALTER TABLE collection_requests ADD COLUMN extractions TEXT NOT NULL DEFAULT '[]'",
+        [],
+    )
+    .ok();
+    conn.execute(
+        "ALTER TABLE collection_requests ADD COLUMN assertions TEXT NOT NULL DEFAULT '[]'",
+        [],
+    )
+    .ok();
+    conn.execute(
+        "ALTER TABLE collection_requests ADD COLUMN operation TEXT",
+        [],
+    )
+    .ok();
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS global_variables (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            variables TEXT NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS vault (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            salt TEXT NOT NULL,
+            verifier TEXT NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS collection_runs (
+            id INTEGER PRIMARY KEY,
+            collection_id INTEGER NOT NULL,
+            folder_id INTEGER,
+            started_at TEXT NOT NULL,
+            duration_ms INTEGER NOT NULL,
+            total INTEGER NOT NULL,
+            passed INTEGER NOT NULL,
+            failed INTEGER NOT NULL,
+            report TEXT NOT NULL,
+            FOREIGN KEY (collection_id) REFERENCES collections(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS openapi_specs (
+            id INTEGER PRIMARY KEY,
+            collection_id INTEGER NOT NULL,
+            title TEXT NOT NULL,
+            content TEXT NOT NULL,
+            FOREIGN KEY (collection_id) REFERENCES collections(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+    conn.execute("ALTER TABLE openapi_specs ADD COLUMN source TEXT", [])
+        .ok();
+    conn.execute(
+        "ALTER TABLE openapi_specs ADD COLUMN excluded_operations TEXT NOT NULL DEFAULT '[]'",
+        [],
+    )
+    .ok();
+    conn.execute(
+        "ALTER TABLE openapi_specs ADD COLUMN grouping TEXT NOT NULL DEFAULT 'tag'",
+        [],
+    )
+    .ok();
     Ok(conn)
 }
 
@@ -180,21 +336,24 @@ pub fn create_environment(conn: &Connection, name: &str) -> Result<Environment>
         name: name.to_string(),
         variables,
         default_endpoint: None,
+        secret_keys: Vec::new(),
     })
 }
 
 pub fn get_environments(conn: &Connection) -> Result<Vec<Environment>> {
-    let mut stmt =
-        conn.prepare("SELECT id, name, variables, default_endpoint FROM environments")?;
+    let mut stmt = conn
+        .prepare("SELECT id, name, variables, default_endpoint, secret_keys FROM environments")?;
     let env_iter = stmt.query_map([], |row| {
         let variables_json: String = row.get(2)?;
         let variables: Vec<(String, String)> =
             serde_json::from_str(&variables_json).unwrap_or_default();
+        let secret_keys_json: String = row.get(4)?;
         Ok(Environment {
             id: row.get(0)?,
             name: row.get(1)?,
             variables,
             default_endpoint: row.get(3)?,
+            secret_keys: serde_json::from_str(&secret_keys_json).unwrap_or_default(),
         })
     })?;
 
@@ -208,12 +367,15 @@ pub fn get_environments(conn: &Connection) -> Result<Vec<Environment>> {
 pub fn update_environment(conn: &Connection, env: &Environment) -> Result<()> {
     let variables_json = serde_json::to_value(&env.variables)
         .map_err(|e| rusqlite::Error::InvalidParameterName(e.to_string()))?;
+    let secret_keys_json = serde_json::to_string(&env.secret_keys)
+        .map_err(|e| rusqlite::Error::InvalidParameterName(e.to_string()))?;
     conn.execute(
-        "UPDATE environments SET name = ?1, variables = ?2, default_endpoint = ?3 WHERE id = ?4",
+        "UPDATE environments SET name = ?1, variables = ?2, default_endpoint = ?3, secret_keys = ?4 WHERE id = ?5",
         params![
             &env.name,
             &variables_json.to_string(),
             &env.default_endpoint,
+            &secret_keys_json,
             &env.id.to_string(),
         ],
     )?;
@@ -225,6 +387,48 @@ pub fn delete_environment(conn: &Connection, id: i32) -> Result<()> {
     Ok(())
 }
 
+pub fn get_global_variables(conn: &Connection) -> Result<Vec<(String, String)>> {
+    let variables_json: Option<String> = conn
+        .query_row(
+            "SELECT variables FROM global_variables WHERE id = 1",
+            [],
+            |row| row.get(0),
+        )
+        .ok();
+    Ok(variables_json
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default())
+}
+
+pub fn save_global_variables(conn: &Connection, variables: &[(String, String)]) -> Result<()> {
+    let variables_json = serde_json::to_string(variables)
+        .map_err(|e| rusqlite::Error::InvalidParameterName(e.to_string()))?;
+    conn.execute(
+        "INSERT OR REPLACE INTO global_variables (id, variables) VALUES (1, ?1)",
+        [variables_json],
+    )?;
+    Ok(())
+}
+
+pub fn get_vault_record(conn: &Connection) -> Result<Option<VaultRecord>> {
+    let mut stmt = conn.prepare("SELECT salt, verifier FROM vault WHERE id = 1")?;
+    let mut rows = stmt.query_map([], |row| {
+        Ok(VaultRecord {
+            salt: row.get(0)?,
+            verifier: row.get(1)?,
+        })
+    })?;
+    rows.next().transpose()
+}
+
+pub fn save_vault_record(conn: &Connection, record: &VaultRecord) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO vault (id, salt, verifier) VALUES (1, ?1, ?2)",
+        params![record.salt, record.verifier],
+    )?;
+    Ok(())
+}
+
 pub fn save_request_history(
     conn: &Connection,
     method: &str,
@@ -325,25 +529,36 @@ pub fn create_collection(
         id: id as i32,
         name: name.to_string(),
         description: description.map(|s| s.to_string()),
+        variables: Vec::new(),
     })
 }
 
 pub fn get_collections(conn: &Connection) -> Result<Vec<Collection>> {
-    let mut stmt = conn.prepare("SELECT id, name, description FROM collections ORDER BY name")?;
+    let mut stmt =
+        conn.prepare("SELECT id, name, description, variables FROM collections ORDER BY name")?;
     let rows = stmt.query_map([], |row| {
+        let variables_json: String = row.get(3)?;
         Ok(Collection {
             id: row.get(0)?,
             name: row.get(1)?,
             description: row.get(2)?,
+            variables: serde_json::from_str(&variables_json).unwrap_or_default(),
         })
     })?;
     rows.collect()
 }
 
 pub fn update_collection(conn: &Connection, collection: &Collection) -> Result<()> {
+    let variables_json = serde_json::to_string(&collection.variables)
+        .map_err(|e| rusqlite::Error::InvalidParameterName(e.to_string()))?;
     conn.execute(
-        "UPDATE collections SET name = ?1, description = ?2 WHERE id = ?3",
-        params![collection.name, collection.description, collection.id],
+        "UPDATE collections SET name = ?1, description = ?2, variables = ?3 WHERE id = ?4",
+        params![
+            collection.name,
+            collection.description,
+            variables_json,
+            collection.id
+        ],
     )?;
     Ok(())
 }
@@ -462,6 +677,10 @@ pub fn save_collection_request(
         params: params.to_vec(),
         config_json: config_json.map(|s| s.to_string()),
         sort_order: max_order + 1,
+        variables: Vec::new(),
+        extractions: Vec::new(),
+        assertions: Vec::new(),
+        operation: None,
     })
 }
 
@@ -471,7 +690,7 @@ pub fn get_collection_requests(
     folder_id: Option<i32>,
 ) -> Result<Vec<CollectionRequest>> {
     let mut stmt = conn.prepare(
-        "SELECT id, collection_id, folder_id, name, method, url, headers, body, body_type, auth_type, auth_data, params, config_json, sort_order FROM collection_requests WHERE collection_id = ?1 AND folder_id IS ?2 ORDER BY sort_order",
+        "SELECT id, collection_id, folder_id, name, method, url, headers, body, body_type, auth_type, auth_data, params, config_json, sort_order, variables, extractions, assertions, operation FROM collection_requests WHERE collection_id = ?1 AND folder_id IS ?2 ORDER BY sort_order",
     )?;
     let rows = stmt.query_map(params![collection_id, folder_id], |row| {
         parse_collection_request(row)
@@ -482,6 +701,9 @@ pub fn get_collection_requests(
 fn parse_collection_request(row: &rusqlite::Row) -> rusqlite::Result<CollectionRequest> {
     let headers_json: String = row.get(6)?;
     let params_json: String = row.get(11)?;
+    let variables_json: String = row.get(14)?;
+    let extractions_json: String = row.get(15)?;
+    let assertions_json: String = row.get(16)?;
     Ok(CollectionRequest {
         id: row.get(0)?,
         collection_id: row.get(1)?,
@@ -497,6 +719,12 @@ fn parse_collection_request(row: &rusqlite::Row) -> rusqlite::Result<CollectionR
         params: serde_json::from_str(&params_json).unwrap_or_default(),
         config_json: row.get(12)?,
         sort_order: row.get(13)?,
+        variables: serde_json::from_str(&variables_json).unwrap_or_default(),
+        extractions: serde_json::from_str(&extractions_json).unwrap_or_default(),
+        assertions: serde_json::from_str(&assertions_json).unwrap_or_default(),
+        operation: row
+            .get::<_, Option<String>>(17)?
+            .and_then(|json| serde_json::from_str(&json).ok()),
     })
 }
 
@@ -520,71 +748,201 @@ pub fn move_collection_request(
     Ok(())
 }
 
+/// Saves the name, method, URL, headers, body, params and auth of a request.
+pub fn update_collection_request(conn: &Connection, request: &CollectionRequest) -> Result<()> {
+    let headers_json = serde_json::to_string(&request.headers)
+        .map_err(|e| rusqlite::Error::InvalidParameterName(e.to_string()))?;
+    let params_json = serde_json::to_string(&request.params)
+        .map_err(|e| rusqlite::Error::InvalidParameterName(e.to_string()))?;
+    conn.execute(
+        "UPDATE collection_requests SET name = ?1, method = ?2, url = ?3, headers = ?4, body = ?5, body_type = ?6, auth_type = ?7, auth_data = ?8, params = ?9 WHERE id = ?10",
+        params![
+            request.name,
+            request.method,
+            request.url,
+            headers_json,
+            request.body,
+            request.body_type,
+            request.auth_type,
+            request.auth_data,
+            params_json,
+            request.id,
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn update_collection_request_variables(
+    conn: &Connection,
+    id: i32,
+    variables: &[(String, String)],
+) -> Result<()> {
+    let variables_json = serde_json::to_string(variables)
+        .map_err(|e| rusqlite::Error::InvalidParameterName(e.to_string()))?;
+    conn.execute(
+        "UPDATE collection_requests SET variables = ?1 WHERE id = ?2",
+        params![variables_json, id],
+    )?;
+    Ok(())
+}
+
+pub fn update_collection_request_extractions(
+    conn: &Connection,
+    id: i32,
+    extractions: &[ExtractionRule],
+) -> Result<()> {
+    let extractions_json = serde_json::to_string(extractions)
+        .map_err(|e| rusqlite::Error::InvalidParameterName(e.to_string()))?;
+    conn.execute(
+        "UPDATE collection_requests SET extractions = ?1 WHERE id = ?2",
+        params![extractions_json, id],
+    )?;
+    Ok(())
+}
+
+pub fn update_collection_request_assertions(
+    conn: &Connection,
+    id: i32,
+    assertions: &[Assertion],
+) -> Result<()> {
+    let assertions_json = serde_json::to_string(assertions)
+        .map_err(|e| rusqlite::Error::InvalidParameterName(e.to_string()))?;
+    conn.execute(
+        "UPDATE collection_requests SET assertions = ?1 WHERE id = ?2",
+        params![assertions_json, id],
+    )?;
+    Ok(())
+}
+
+pub fn update_collection_request_operation(
+    conn: &Connection,
+    id: i32,
+    operation: Option<&OperationRef>,
+) -> Result<()> {
+    let operation_json = operation
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| rusqlite::Error::InvalidParameterName(e.to_string()))?;
+    conn.execute(
+        "UPDATE collection_requests SET operation = ?1 WHERE id = ?2",
+        params![operation_json, id],
+    )?;
+    Ok(())
+}
+
 pub fn delete_collection_request(conn: &Connection, id: i32) -> Result<()> {
     conn.execute("DELETE FROM collection_requests WHERE id = ?1", [id])?;
     Ok(())
 }
 
+pub fn save_openapi_spec(
+    conn: &Connection,
+    collection_id: i32,
+    title: &str,
+    content: &str,
+    source: Option<&str>,
+    excluded_operations: &[(String, String)],
+    grouping: &str,
+) -> Result<i32> {
+    let excluded_json = serde_json::to_string(excluded_operations)
+        .map_err(|e| rusqlite::Error::InvalidParameterName(e.to_string()))?;
+    conn.execute(
+        "INSERT INTO openapi_specs (collection_id, title, content, source, excluded_operations, grouping) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![collection_id, title, content, source, excluded_json, grouping],
+    )?;
+    Ok(conn.last_insert_rowid() as i32)
+}
+
+/// Replaces a stored spec after a re-sync, keeping its id so links stay valid.
+pub fn update_openapi_spec(conn: &Connection, id: i32, title: &str, content: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE openapi_specs SET title = ?1, content = ?2 WHERE id = ?3",
+        params![title, content, id],
+    )?;
+    Ok(())
+}
+
+fn parse_openapi_spec(row: &rusqlite::Row) -> rusqlite::Result<OpenApiSpecRecord> {
+    let excluded_json: String = row.get(5)?;
+    Ok(OpenApiSpecRecord {
+        id: row.get(0)?,
+        collection_id: row.get(1)?,
+        title: row.get(2)?,
+        content: row.get(3)?,
+        source: row.get(4)?,
+        excluded_operations: serde_json::from_str(&excluded_json).unwrap_or_default(),
+        grouping: row.get(6)?,
+    })
+}
+
+pub fn get_openapi_spec(conn: &Connection, id: i32) -> Result<Option<OpenApiSpecRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, collection_id, title, content, source, excluded_operations, grouping FROM openapi_specs WHERE id = ?1",
+    )?;
+    let mut rows = stmt.query_map([id], parse_openapi_spec)?;
+    rows.next().transpose()
+}
+
+/// The spec a collection was imported from, if any.
+pub fn get_collection_openapi_spec(
+    conn: &Connection,
+    collection_id: i32,
+) -> Result<Option<OpenApiSpecRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, collection_id, title, content, source, excluded_operations, grouping FROM openapi_specs WHERE collection_id = ?1 ORDER BY id DESC",
+    )?;
+    let mut rows = stmt.query_map([collection_id], parse_openapi_spec)?;
+    rows.next().transpose()
+}
+
+pub fn save_collection_run(conn: &Connection, run: &CollectionRun) -> Result<i32> {
+    conn.execute(
+        "INSERT INTO collection_runs (collection_id, folder_id, started_at, duration_ms, total, passed, failed, report) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            run.collection_id,
+            run.folder_id,
+            run.started_at,
+            run.duration_ms as i64,
+            run.total,
+            run.passed,
+            run.failed,
+            run.report
+        ],
+    )?;
+    Ok(conn.last_insert_rowid() as i32)
+}
+
+/// Most recent runs of a collection first, including runs of its folders.
+pub fn get_collection_runs(
+    conn: &Connection,
+    collection_id: i32,
+    limit: usize,
+) -> Result<Vec<CollectionRun>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, collection_id, folder_id, started_at, duration_ms, total, passed, failed, report FROM collection_runs WHERE collection_id = ?1 ORDER BY id DESC LIMIT ?2",
+    )?;
+    let rows = stmt.query_map(params![collection_id, limit as i64], |row| {
+        Ok(CollectionRun {
+            id: row.get(0)?,
+            collection_id: row.get(1)?,
+            folder_id: row.get(2)?,
+            started_at: row.get(3)?,
+            duration_ms: row.get::<_, i64>(4)? as u64,
+            total: row.get(5)?,
+            passed: row.get(6)?,
+            failed: row.get(7)?,
+            report: row.get(8)?,
+        })
+    })?;
+    rows.collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     fn setup_test_db() -> Connection {
-        let conn = Connection::open_in_memory().unwrap();
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS environments (
-                id INTEGER PRIMARY KEY,
-                name TEXT NOT NULL UNIQUE,
-                variables TEXT NOT NULL
-            )",
-            [],
-        )
-        .unwrap();
-        conn.execute(
-            "ALTER TABLE environments ADD COLUMN default_endpoint TEXT",
-            [],
-        )
-        .ok();
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS collections (
-                id INTEGER PRIMARY KEY,
-                name TEXT NOT NULL,
-                description TEXT
-            )",
-            [],
-        )
-        .unwrap();
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS collection_folders (
-                id INTEGER PRIMARY KEY,
-                collection_id INTEGER NOT NULL,
-                name TEXT NOT NULL,
-                parent_folder_id INTEGER
-            )",
-            [],
-        )
-        .unwrap();
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS collection_requests (
-                id INTEGER PRIMARY KEY,
-                collection_id INTEGER NOT NULL,
-                folder_id INTEGER,
-                name TEXT NOT NULL,
-                method TEXT NOT NULL,
-                url TEXT NOT NULL,
-                headers TEXT NOT NULL DEFAULT '[]',
-                body TEXT,
-                body_type TEXT NOT NULL DEFAULT 'text',
-                auth_type TEXT NOT NULL DEFAULT 'none',
-                auth_data TEXT,
-                params TEXT NOT NULL DEFAULT '[]',
-                config_json TEXT,
-                sort_order INTEGER NOT NULL DEFAULT 0
-            )",
-            [],
-        )
-        .unwrap();
-        conn
+        open(Path::new(":memory:")).unwrap()
     }
 
     #[test]
@@ -652,6 +1010,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn update_environment_secret_keys() {
+        let conn = setup_test_db();
+        let mut env = create_environment(&conn, "secrets").unwrap();
+        env.variables = vec![("TOKEN".to_string(), "enc:v1:abc".to_string())];
+        env.secret_keys = vec!["TOKEN".to_string()];
+        update_environment(&conn, &env).unwrap();
+
+        let envs = get_environments(&conn).unwrap();
+        assert_eq!(envs[0].secret_keys, vec!["TOKEN"]);
+    }
+
+    #[test]
+    fn vault_record_round_trip() {
+        let conn = setup_test_db();
+        assert!(get_vault_record(&conn).unwrap().is_none());
+        let record = VaultRecord {
+            salt: "c2FsdA==".to_string(),
+            verifier: "enc:v1:xyz".to_string(),
+        };
+        save_vault_record(&conn, &record).unwrap();
+        assert_eq!(get_vault_record(&conn).unwrap(), Some(record));
+    }
+
     #[test]
     fn delete_existing_environment() {
         let conn = setup_test_db();
@@ -676,6 +1058,7 @@ mod tests {
             name: "my-env".to_string(),
             variables: vec![],
             default_endpoint: None,
+            secret_keys: vec![],
         };
         assert_eq!(env.to_string(), "my-env");
     }
@@ -687,6 +1070,7 @@ mod tests {
             name: "clone-test".to_string(),
             variables: vec![("k".to_string(), "v".to_string())],
             default_endpoint: None,
+            secret_keys: vec![],
         };
         let cloned = env.clone();
         assert_eq!(env, cloned);
@@ -849,10 +1233,14 @@ mod tests {
     fn delete_collection_test() {
         let conn = setup_test_db();
         let col = create_collection(&conn, "To Delete", None).unwrap();
+        save_openapi_spec(&conn, col.id, "API", "openapi: 3.0.0", None, &[], "tag").unwrap();
         delete_collection(&conn, col.id).unwrap();
 
         let cols = get_collections(&conn).unwrap();
         assert!(cols.is_empty());
+        assert!(get_collection_openapi_spec(&conn, col.id)
+            .unwrap()
+            .is_none());
     }
 
     #[test]
@@ -1012,6 +1400,155 @@ mod tests {
         assert_eq!(folder_reqs[0].name, "New Name");
     }
 
+    #[test]
+    fn update_collection_variables() {
+        let conn = setup_test_db();
+        let mut col = create_collection(&conn, "API", None).unwrap();
+        assert!(col.variables.is_empty());
+        col.variables = vec![("VERSION".to_string(), "v2".to_string())];
+        update_collection(&conn, &col).unwrap();
+
+        let cols = get_collections(&conn).unwrap();
+        assert_eq!(cols[0].variables, col.variables);
+    }
+
+    #[test]
+    fn update_collection_request_variables_persists() {
+        let conn = setup_test_db();
+        let col = create_collection(&conn, "API", None).unwrap();
+        let req = save_collection_request(
+            &conn,
+            col.id,
+            None,
+            "Get",
+            "GET",
+            "{{HOST}}/items",
+            &[],
+            None,
+            "text",
+            "none",
+            None,
+            &[],
+            None,
+        )
+        .unwrap();
+        let vars = vec![("HOST".to_string(), "https://local.test".to_string())];
+        update_collection_request_variables(&conn, req.id, &vars).unwrap();
+
+        let reqs = get_collection_requests(&conn, col.id, None).unwrap();
+        assert_eq!(reqs[0].variables, vars);
+    }
+
+    #[test]
+    fn update_collection_request_extractions_persists() {
+        let conn = setup_test_db();
+        let col = create_collection(&conn, "API", None).unwrap();
+        let req = save_collection_request(
+            &conn,
+            col.id,
+            None,
+            "Login",
+            "POST",
+            "{{HOST}}/login",
+            &[],
+            None,
+            "text",
+            "none",
+            None,
+            &[],
+            None,
+        )
+        .unwrap();
+        let rules = vec![ExtractionRule {
+            variable: "token".to_string(),
+            expression: "$.token".to_string(),
+            ..Default::default()
+        }];
+        update_collection_request_extractions(&conn, req.id, &rules).unwrap();
+
+        let reqs = get_collection_requests(&conn, col.id, None).unwrap();
+        assert_eq!(reqs[0].extractions, rules);
+    }
+
+    #[test]
+    fn update_collection_request_assertions_persists() {
+        let conn = setup_test_db();
+        let col = create_collection(&conn, "API", None).unwrap();
+        let req = save_collection_request(
+            &conn,
+            col.id,
+            None,
+            "Health",
+            "GET",
+            "/health",
+            &[],
+            None,
+            "text",
+            "none",
+            None,
+            &[],
+            None,
+        )
+        .unwrap();
+        let assertions = vec![Assertion {
+            expected: "200".to_string(),
+            ..Default::default()
+        }];
+        update_collection_request_assertions(&conn, req.id, &assertions).unwrap();
+
+        let reqs = get_collection_requests(&conn, col.id, None).unwrap();
+        assert_eq!(reqs[0].assertions, assertions);
+    }
+
+    #[test]
+    fn collection_runs_newest_first() {
+        let conn = setup_test_db();
+        let col = create_collection(&conn, "API", None).unwrap();
+        let mut run = CollectionRun {
+            id: 0,
+            collection_id: col.id,
+            folder_id: None,
+            started_at: "2024-01-01T00:00:00Z".to_string(),
+            duration_ms: 120,
+            total: 2,
+            passed: 2,
+            failed: 0,
+            report: "{}".to_string(),
+        };
+        save_collection_run(&conn, &run).unwrap();
+        run.passed = 1;
+        run.failed = 1;
+        run.folder_id = Some(3);
+        let second = save_collection_run(&conn, &run).unwrap();
+
+        let runs = get_collection_runs(&conn, col.id, 10).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].id, second);
+        assert_eq!(runs[0].failed, 1);
+        assert_eq!(runs[0].folder_id, Some(3));
+        assert_eq!(runs[1].duration_ms, 120);
+        assert!(get_collection_runs(&conn, col.id + 1, 10)
+            .unwrap()
+            .is_empty());
+
+        delete_collection(&conn, col.id).unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM collection_runs", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn global_variables_round_trip() {
+        let conn = setup_test_db();
+        assert!(get_global_variables(&conn).unwrap().is_empty());
+
+        let vars = vec![("TEAM".to_string(), "core".to_string())];
+        save_global_variables(&conn, &vars).unwrap();
+        save_global_variables(&conn, &vars).unwrap();
+        assert_eq!(get_global_variables(&conn).unwrap(), vars);
+    }
+
     #[test]
     fn delete_collection_request_test() {
         let conn = setup_test_db();
@@ -0,0 +1,524 @@
+use crate::data::assertions::{run_assertions, AssertionResult};
+use crate::data::extraction::{run_extractions, upsert_variable, ExtractionTarget};
+use crate::data::token_cache::{send_with_token_cache, TokenCache};
+use crate::data::variables::{VariableResolver, VariableScope};
+use crate::data::vault::{self, Vault};
+use crate::http_client::request::HttpRequest;
+use crate::http_client::template::RequestTemplate;
+use crate::persistence::database::{CollectionRequest, Environment};
+use crate::runner::data::IterationData;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunOptions {
+    /// Pause between consecutive requests.
+    pub delay_ms: u64,
+    pub stop_on_failure: bool,
+    /// One iteration per row; empty runs the requests once without data.
+    pub data: Vec<IterationData>,
+}
+
+/// Variables and credentials a run starts from; extraction rules update it as steps complete.
+#[derive(Debug, Clone, Default)]
+pub struct RunContext {
+    pub globals: Vec<(String, String)>,
+    pub collection_variables: Vec<(String, String)>,
+    pub environment: Option<Environment>,
+    pub runtime_variables: Vec<(String, String)>,
+    /// Variables of the current iteration's data row.
+    pub data: IterationData,
+    pub vault: Option<Vault>,
+}
+
+impl RunContext {
+    fn resolver(&self, request: &CollectionRequest) -> VariableResolver {
+        VariableResolver::scoped(
+            &self.globals,
+            &self.collection_variables,
+            self.environment.as_ref(),
+            &request.variables,
+        )
+        .with_scope(VariableScope::Runtime, self.runtime_variables.clone())
+        .with_scope(VariableScope::Data, self.data.clone())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepResult {
+    /// Zero-based data row this step ran with.
+    #[serde(default)]
+    pub iteration: usize,
+    pub request_id: i32,
+    pub name: String,
+    pub method: String,
+    pub url: String,
+    pub status: Option<u16>,
+    pub duration_ms: u64,
+    pub assertions: Vec<AssertionResult>,
+    /// Names of variables set by extraction rules.
+    #[serde(default)]
+    pub extracted: Vec<String>,
+    #[serde(default)]
+    pub extraction_errors: Vec<String>,
+    /// Why the request could not be sent or received.
+    pub error: Option<String>,
+}
+
+impl StepResult {
+    pub fn passed(&self) -> bool {
+        self.error.is_none() && self.assertions.iter().all(|a| a.passed)
+    }
+
+    /// Human-readable reasons this step failed.
+    pub fn failures(&self) -> Vec<String> {
+        self.error
+            .iter()
+            .cloned()
+            .chain(
+                self.assertions
+                    .iter()
+                    .filter(|a| !a.passed)
+                    .map(|a| format!("{}: {}", a.description, a.message)),
+            )
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunReport {
+    pub name: String,
+    pub started_at: String,
+    pub duration_ms: u64,
+    pub steps: Vec<StepResult>,
+    /// True when stop-on-failure or cancellation ended the run before the last request.
+    pub stopped_early: bool,
+    #[serde(default = "single_iteration")]
+    pub iterations: usize,
+}
+
+fn single_iteration() -> usize {
+    1
+}
+
+impl RunReport {
+    pub fn passed_count(&self) -> usize {
+        self.steps.iter().filter(|s| s.passed()).count()
+    }
+
+    pub fn failed_count(&self) -> usize {
+        self.steps.len() - self.passed_count()
+    }
+
+    pub fn passed(&self) -> bool {
+        !self.stopped_early && self.failed_count() == 0
+    }
+}
+
+/// Steps grouped by iteration, in order; iterations that never ran are omitted.
+pub fn group_by_iteration(steps: &[StepResult]) -> Vec<(usize, &[StepResult])> {
+    steps
+        .chunk_by(|a, b| a.iteration == b.iteration)
+        .map(|group| (group[0].iteration, group))
+        .collect()
+}
+
+/// Progress of a run streamed back to the UI.
+#[derive(Debug, Clone)]
+pub enum RunEvent {
+    Step(StepResult),
+    Finished(Box<(RunReport, RunContext)>),
+}
+
+/// Sends `requests` one after another, once per data row, applying extraction
+/// rules between steps. `on_step` is called as each request completes.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_requests(
+    client: &reqwest::Client,
+    token_cache: &TokenCache,
+    name: &str,
+    requests: &[CollectionRequest],
+    mut context: RunContext,
+    options: &RunOptions,
+    cancel: &AtomicBool,
+    mut on_step: impl FnMut(&StepResult),
+) -> (RunReport, RunContext) {
+    let started_at = chrono::Utc::now().to_rfc3339();
+    let start = Instant::now();
+    let mut steps = Vec::new();
+    let mut stopped_early = false;
+    let single = [IterationData::new()];
+    let rows = if options.data.is_empty() {
+        &single[..]
+    } else {
+        &options.data[..]
+    };
+
+    // Each row starts from the session's runtime variables, not the previous row's extractions.
+    let session_runtime = context.runtime_variables.clone();
+    'iterations: for (iteration, row) in rows.iter().enumerate() {
+        context.data = row.clone();
+        if iteration > 0 {
+            context.runtime_variables = session_runtime.clone();
+        }
+        for (i, request) in requests.iter().enumerate() {
+            if cancel.load(Ordering::Relaxed) {
+                stopped_early = true;
+                break 'iterations;
+            }
+            if (iteration > 0 || i > 0) && options.delay_ms > 0 {
+                tokio::time::sleep(Duration::from_millis(options.delay_ms)).await;
+            }
+
+            let mut step = run_step(client, token_cache, request, &mut context).await;
+            step.iteration = iteration;
+            on_step(&step);
+            let failed = !step.passed();
+            steps.push(step);
+            let is_last = iteration + 1 == rows.len() && i + 1 == requests.len();
+            if failed && options.stop_on_failure && !is_last {
+                stopped_early = true;
+                break 'iterations;
+            }
+        }
+    }
+    context.data.clear();
+
+    let report = RunReport {
+        name: name.to_string(),
+        started_at,
+        duration_ms: start.elapsed().as_millis() as u64,
+        steps,
+        stopped_early,
+        iterations: rows.len(),
+    };
+    (report, context)
+}
+
+async fn run_step(
+    client: &reqwest::Client,
+    token_cache: &TokenCache,
+    request: &CollectionRequest,
+    context: &mut RunContext,
+) -> StepResult {
+    let mut step = StepResult {
+        iteration: 0,
+        request_id: request.id,
+        name: request.name.clone(),
+        method: request.method.clone(),
+        url: request.url.clone(),
+        status: None,
+        duration_ms: 0,
+        assertions: Vec::new(),
+        extracted: Vec::new(),
+        extraction_errors: Vec::new(),
+        error: None,
+    };
+
+    let http_request = match prepare_request(request, context) {
+        Ok(http_request) => http_request,
+        Err(e) => {
+            step.error = Some(e);
+            return step;
+        }
+    };
+    step.url = http_request.url.clone();
+
+    let client = if http_request.config.proxy_url.is_some() || !http_request.config.verify_ssl {
+        match crate::http_client::client::build_client(&http_request.config) {
+            Ok(c) => c,
+            Err(e) => {
+                step.error = Some(format!("Failed to build client: {}", e));
+                return step;
+            }
+        }
+    } else {
+        client.clone()
+    };
+
+    let started = Instant::now();
+    let response = send_with_token_cache(&client, http_request, token_cache).await;
+    step.duration_ms = started.elapsed().as_millis() as u64;
+    let response = match response {
+        Ok(response) => response,
+        Err(e) => {
+            step.error = Some(e);
+            return step;
+        }
+    };
+
+    step.status = Some(response.status);
+    step.duration_ms = response.duration.as_millis() as u64;
+    step.assertions = run_assertions(&request.assertions, &response);
+    for extracted in run_extractions(&request.extractions, &response) {
+        match extracted.value {
+            Ok(value) => {
+                match (extracted.target, context.environment.as_mut()) {
+                    (ExtractionTarget::Environment, Some(env)) => {
+                        upsert_variable(&mut env.variables, &extracted.variable, &value)
+                    }
+                    (target, _) => {
+                        if target == ExtractionTarget::Environment {
+                            step.extraction_errors.push(format!(
+                                "{}: no environment selected, stored as a runtime variable",
+                                extracted.variable
+                            ));
+                        }
+                        upsert_variable(&mut context.runtime_variables, &extracted.variable, &value)
+                    }
+                }
+                step.extracted.push(extracted.variable);
+            }
+            Err(e) => step
+                .extraction_errors
+                .push(format!("{}: {}", extracted.variable, e)),
+        }
+    }
+    step
+}
+
+/// Resolves a saved request into something sendable.
+pub fn prepare_request(
+    request: &CollectionRequest,
+    context: &RunContext,
+) -> Result<HttpRequest, String> {
+    let mut template = RequestTemplate::from(request);
+    if let Some(vault) = &context.vault {
+        template.auth = vault::open_auth(&template.auth, vault);
+    }
+    if vault::has_locked_secrets(&template.auth) {
+        return Err("Credentials are encrypted; unlock the vault first".to_string());
+    }
+    Ok(template.build_resolved(&context.resolver(request)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::assertions::{Assertion, AssertionKind};
+    use crate::data::extraction::ExtractionRule;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Serves `/login` with a token and `/me` only when that token is sent back.
+    async fn serve_login_flow() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut stream, _)) = listener.accept().await else {
+                    break;
+                };
+                let mut buffer = vec![0u8; 4096];
+                let n = stream.read(&mut buffer).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buffer[..n]).to_lowercase();
+                let (status, body) = if request.starts_with("post /login") {
+                    ("200 OK", r#"{"token":"abc"}"#)
+                } else if request.contains("authorization: bearer abc") {
+                    ("200 OK", r#"{"name":"Ada"}"#)
+                } else {
+                    ("401 Unauthorized", r#"{"error":"unauthorized"}"#)
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://127.0.0.1:{}", port)
+    }
+
+    fn request(id: i32, method: &str, url: &str) -> CollectionRequest {
+        CollectionRequest {
+            id,
+            collection_id: 1,
+            name: format!("step {}", id),
+            method: method.to_string(),
+            url: url.to_string(),
+            body_type: "text".to_string(),
+            auth_type: "none".to_string(),
+            sort_order: id,
+            assertions: vec![Assertion {
+                kind: AssertionKind::StatusEquals,
+                target: String::new(),
+                expected: "200".to_string(),
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn login_flow() -> Vec<CollectionRequest> {
+        let mut login = request(1, "POST", "{{HOST}}/login");
+        login.extractions = vec![ExtractionRule {
+            variable: "token".to_string(),
+            expression: "$.token".to_string(),
+            target: ExtractionTarget::Runtime,
+            ..Default::default()
+        }];
+        let mut me = request(2, "GET", "{{HOST}}/me");
+        me.headers = vec![("Authorization".to_string(), "Bearer {{token}}".to_string())];
+        vec![login, me]
+    }
+
+    fn context(host: &str) -> RunContext {
+        RunContext {
+            globals: vec![("HOST".to_string(), host.to_string())],
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn run_chains_extracted_values_between_steps() {
+        let host = serve_login_flow().await;
+        let mut seen = 0;
+        let (report, context) = run_requests(
+            &reqwest::Client::new(),
+            &TokenCache::new(),
+            "Login flow",
+            &login_flow(),
+            context(&host),
+            &RunOptions::default(),
+            &AtomicBool::new(false),
+            |_| seen += 1,
+        )
+        .await;
+
+        assert_eq!(seen, 2);
+        assert!(report.passed(), "{:?}", report);
+        assert_eq!(report.steps[0].extracted, vec!["token"]);
+        assert_eq!(report.steps[1].url, format!("{}/me", host));
+        assert_eq!(
+            context.runtime_variables,
+            vec![("token".to_string(), "abc".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn environment_extraction_without_environment_is_reported() {
+        let host = serve_login_flow().await;
+        let mut requests = login_flow();
+        requests[0].extractions[0].target = ExtractionTarget::Environment;
+        let (report, context) = run_requests(
+            &reqwest::Client::new(),
+            &TokenCache::new(),
+            "Login flow",
+            &requests,
+            context(&host),
+            &RunOptions::default(),
+            &AtomicBool::new(false),
+            |_| {},
+        )
+        .await;
+
+        assert!(report.passed(), "{:?}", report);
+        assert!(report.steps[0].extraction_errors[0].contains("no environment selected"));
+        assert_eq!(context.runtime_variables.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn stop_on_failure_skips_remaining_requests() {
+        let host = serve_login_flow().await;
+        let mut requests = login_flow();
+        requests[0].extractions.clear();
+        requests.push(request(3, "POST", "{{HOST}}/login"));
+        let (report, _) = run_requests(
+            &reqwest::Client::new(),
+            &TokenCache::new(),
+            "Login flow",
+            &requests,
+            context(&host),
+            &RunOptions {
+                stop_on_failure: true,
+                ..Default::default()
+            },
+            &AtomicBool::new(false),
+            |_| {},
+        )
+        .await;
+
+        assert_eq!(report.steps.len(), 2);
+        assert!(report.stopped_early);
+        assert_eq!(report.steps[1].status, Some(401));
+        assert_eq!(report.failed_count(), 1);
+        assert!(report.steps[1].failures()[0].contains("status 401"));
+    }
+
+    #[tokio::test]
+    async fn data_rows_run_as_separate_iterations() {
+        let host = serve_login_flow().await;
+        let row = |tenant: &str| vec![("tenant".to_string(), tenant.to_string())];
+        let mut requests = vec![request(1, "POST", "{{HOST}}/login?tenant={{tenant}}")];
+        requests[0].extractions = vec![ExtractionRule {
+            variable: "token".to_string(),
+            expression: "$.token".to_string(),
+            target: ExtractionTarget::Runtime,
+            ..Default::default()
+        }];
+        let mut seen_tokens = Vec::new();
+        let (report, context) = run_requests(
+            &reqwest::Client::new(),
+            &TokenCache::new(),
+            "Tenants",
+            &requests,
+            RunContext {
+                // A stale session value must not shadow the data column.
+                runtime_variables: vec![("tenant".to_string(), "stale".to_string())],
+                ..context(&host)
+            },
+            &RunOptions {
+                data: vec![row("acme"), row("globex")],
+                ..Default::default()
+            },
+            &AtomicBool::new(false),
+            |step| seen_tokens.push(step.extracted.clone()),
+        )
+        .await;
+
+        assert_eq!(report.iterations, 2);
+        assert_eq!(seen_tokens, vec![vec!["token"], vec!["token"]]);
+        assert_eq!(
+            context.runtime_variables,
+            vec![
+                ("tenant".to_string(), "stale".to_string()),
+                ("token".to_string(), "abc".to_string())
+            ]
+        );
+        let groups = group_by_iteration(&report.steps);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[1].0, 1);
+        assert_eq!(groups[1].1[0].url, format!("{}/login?tenant=globex", host));
+        assert!(context.data.is_empty());
+    }
+
+    #[tokio::test]
+    async fn cancelled_run_sends_nothing() {
+        let (report, _) = run_requests(
+            &reqwest::Client::new(),
+            &TokenCache::new(),
+            "Cancelled",
+            &login_flow(),
+            RunContext::default(),
+            &RunOptions::default(),
+            &AtomicBool::new(true),
+            |_| {},
+        )
+        .await;
+        assert!(report.steps.is_empty());
+        assert!(report.stopped_early);
+    }
+
+    #[test]
+    fn locked_credentials_fail_the_step_before_sending() {
+        let mut req = request(1, "GET", "http://localhost/");
+        req.auth_type = "bearer".to_string();
+        req.auth_data = serde_json::to_string(&crate::data::auth::Auth::BearerToken(
+            "enc:v1:AAAA".to_string(),
+        ))
+        .ok();
+        let err = prepare_request(&req, &RunContext::default()).unwrap_err();
+        assert!(err.contains("vault"));
+    }
+}
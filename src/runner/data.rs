@@ -0,0 +1,115 @@
+use crate::data::extraction::json_to_plain;
+use std::path::Path;
+
+/// Variables for one iteration of a data-driven run.
+pub type IterationData = Vec<(String, String)>;
+
+/// Reads iteration rows from a `.csv` file or a `.json` array of objects.
+pub fn load_data_file(path: &Path) -> Result<Vec<IterationData>, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let is_csv = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+    parse_data(&content, is_csv)
+}
+
+pub fn parse_data(content: &str, is_csv: bool) -> Result<Vec<IterationData>, String> {
+    if is_csv {
+        parse_csv(content)
+    } else {
+        parse_json(content)
+    }
+}
+
+/// The header row names the variables; each following row is one iteration.
+pub fn parse_csv(content: &str) -> Result<Vec<IterationData>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::Headers)
+        .from_reader(content.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("Failed to parse CSV header: {}", e))?
+        .clone();
+    reader
+        .records()
+        .enumerate()
+        .map(|(i, record)| {
+            let record = record.map_err(|e| format!("Failed to parse CSV row {}: {}", i + 1, e))?;
+            Ok(headers
+                .iter()
+                .zip(record.iter())
+                .filter(|(name, _)| !name.is_empty())
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect())
+        })
+        .collect()
+}
+
+/// Expects an array of flat objects; nested values are kept as JSON text.
+pub fn parse_json(content: &str) -> Result<Vec<IterationData>, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| format!("Failed to parse JSON: {}", e))?;
+    let items = value
+        .as_array()
+        .ok_or("Data file must contain a JSON array of objects")?;
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let object = item
+                .as_object()
+                .ok_or_else(|| format!("Element {} is not a JSON object", i))?;
+            Ok(object
+                .iter()
+                .map(|(k, v)| (k.clone(), json_to_plain(v)))
+                .collect())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(pairs: &[(&str, &str)]) -> IterationData {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn csv_rows_become_iterations() {
+        let rows = parse_csv("tenant, account\nacme,1\n\"globex, inc\",2\n").unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                row(&[("tenant", "acme"), ("account", "1")]),
+                row(&[("tenant", "globex, inc"), ("account", "2")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_reports_ragged_rows() {
+        let err = parse_csv("a,b\n1,2\n3\n").unwrap_err();
+        assert!(err.contains("row 2"), "{}", err);
+    }
+
+    #[test]
+    fn json_array_elements_become_iterations() {
+        let rows =
+            parse_json(r#"[{"tenant":"acme","id":7,"tags":["a"]},{"tenant":"globex"}]"#).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].contains(&("id".to_string(), "7".to_string())));
+        assert!(rows[0].contains(&("tags".to_string(), r#"["a"]"#.to_string())));
+        assert_eq!(rows[1], row(&[("tenant", "globex")]));
+    }
+
+    #[test]
+    fn json_must_be_array_of_objects() {
+        assert!(parse_json(r#"{"tenant":"acme"}"#).is_err());
+        assert!(parse_json("[1, 2]").unwrap_err().contains("Element 0"));
+    }
+}
@@ -0,0 +1,598 @@
+use crate::data::token_cache::{send_with_token_cache, TokenCache};
+use crate::http_client::request::HttpRequest;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// How often collected samples are handed to the progress callback.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadOptions {
+    pub virtual_users: u32,
+    /// Stop starting new requests after this long.
+    pub duration: Option<Duration>,
+    /// Total passes over the requests, shared between all users.
+    pub iterations: Option<u32>,
+    /// Users start evenly spread over this period.
+    pub ramp_up: Duration,
+    /// Cap on requests started per second across all users.
+    pub requests_per_second: Option<u32>,
+}
+
+impl LoadOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.virtual_users == 0 {
+            return Err("At least one virtual user is required".to_string());
+        }
+        if self.duration.is_none() && self.iterations.is_none() {
+            return Err("Set a duration or an iteration count".to_string());
+        }
+        if self.duration == Some(Duration::ZERO) {
+            return Err("Duration must be above zero".to_string());
+        }
+        if self.requests_per_second == Some(0) {
+            return Err("Requests per second must be above zero".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoadSample {
+    /// When the request was sent, in milliseconds since the test started.
+    pub started_ms: u64,
+    pub latency_ms: u64,
+    pub status: Option<u16>,
+    pub error: Option<String>,
+}
+
+impl LoadSample {
+    pub fn is_error(&self) -> bool {
+        self.error.is_some() || self.status.is_none_or(|s| s >= 400)
+    }
+
+    /// The status code, or the kind of failure when no response came back.
+    pub fn outcome(&self) -> String {
+        match (self.status, &self.error) {
+            (Some(status), _) => status.to_string(),
+            (None, Some(error)) => error_kind(error).to_string(),
+            (None, None) => "No response".to_string(),
+        }
+    }
+}
+
+fn error_kind(error: &str) -> &'static str {
+    let error = error.to_lowercase();
+    if error.contains("timed out") || error.contains("timeout") {
+        "Timeout"
+    } else if error.contains("connect") || error.contains("dns") {
+        "Connection error"
+    } else if error.contains("certificate") || error.contains("tls") || error.contains("ssl") {
+        "TLS error"
+    } else {
+        "Request error"
+    }
+}
+
+/// Requests sent during one second of the test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimelinePoint {
+    pub second: u64,
+    pub requests: usize,
+    pub errors: usize,
+    pub mean_ms: u64,
+    pub p90_ms: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LoadReport {
+    pub total: usize,
+    pub errors: usize,
+    pub duration_ms: u64,
+    /// Completed requests per second over the whole test.
+    pub throughput: f64,
+    pub min_ms: u64,
+    pub mean_ms: u64,
+    pub p50_ms: u64,
+    pub p90_ms: u64,
+    pub p99_ms: u64,
+    pub max_ms: u64,
+    /// Requests per status code or failure kind, most frequent first.
+    pub outcomes: Vec<(String, usize)>,
+    pub timeline: Vec<TimelinePoint>,
+    pub stopped_early: bool,
+}
+
+fn count_outcome(outcomes: &mut Vec<(String, usize)>, outcome: String) {
+    match outcomes.iter_mut().find(|(o, _)| *o == outcome) {
+        Some((_, count)) => *count += 1,
+        None => outcomes.push((outcome, 1)),
+    }
+}
+
+fn sort_outcomes(outcomes: &mut [(String, usize)]) {
+    outcomes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+}
+
+fn throughput(total: usize, elapsed: Duration) -> f64 {
+    let seconds = elapsed.as_secs_f64();
+    if seconds > 0.0 {
+        total as f64 / seconds
+    } else {
+        0.0
+    }
+}
+
+/// Latency counts kept to two significant digits, so memory stays bounded
+/// however long a test runs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    counts: BTreeMap<u64, usize>,
+    total: usize,
+}
+
+impl LatencyHistogram {
+    pub fn record(&mut self, latency_ms: u64) {
+        let mut scale = 1;
+        while latency_ms / scale >= 100 {
+            scale *= 10;
+        }
+        *self.counts.entry(latency_ms / scale * scale).or_default() += 1;
+        self.total += 1;
+    }
+
+    /// Nearest-rank percentile, to the histogram's precision.
+    pub fn percentile(&self, p: f64) -> u64 {
+        let rank = (p / 100.0 * self.total as f64).ceil() as usize;
+        let mut seen = 0;
+        for (latency, count) in &self.counts {
+            seen += count;
+            if seen >= rank.max(1) {
+                return *latency;
+            }
+        }
+        0
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct SecondStats {
+    requests: usize,
+    errors: usize,
+    sum_ms: u64,
+    latencies: LatencyHistogram,
+}
+
+/// Running totals of a test, both while it runs and for its final report.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LiveStats {
+    total: usize,
+    errors: usize,
+    sum_ms: u64,
+    min_ms: Option<u64>,
+    max_ms: u64,
+    latencies: LatencyHistogram,
+    outcomes: Vec<(String, usize)>,
+    seconds: Vec<SecondStats>,
+}
+
+impl LiveStats {
+    pub fn add(&mut self, sample: &LoadSample) {
+        let is_error = sample.is_error();
+        self.total += 1;
+        self.errors += usize::from(is_error);
+        self.sum_ms += sample.latency_ms;
+        self.min_ms = Some(
+            self.min_ms
+                .map_or(sample.latency_ms, |m| m.min(sample.latency_ms)),
+        );
+        self.max_ms = self.max_ms.max(sample.latency_ms);
+        self.latencies.record(sample.latency_ms);
+        count_outcome(&mut self.outcomes, sample.outcome());
+
+        let second = (sample.started_ms / 1000) as usize;
+        if self.seconds.len() <= second {
+            self.seconds.resize_with(second + 1, SecondStats::default);
+        }
+        let bucket = &mut self.seconds[second];
+        bucket.requests += 1;
+        bucket.errors += usize::from(is_error);
+        bucket.sum_ms += sample.latency_ms;
+        bucket.latencies.record(sample.latency_ms);
+    }
+
+    pub fn report(&self, elapsed: Duration) -> LoadReport {
+        let mut outcomes = self.outcomes.clone();
+        sort_outcomes(&mut outcomes);
+        LoadReport {
+            total: self.total,
+            errors: self.errors,
+            duration_ms: elapsed.as_millis() as u64,
+            throughput: throughput(self.total, elapsed),
+            min_ms: self.min_ms.unwrap_or(0),
+            mean_ms: self.sum_ms.checked_div(self.total as u64).unwrap_or(0),
+            p50_ms: self.latencies.percentile(50.0),
+            p90_ms: self.latencies.percentile(90.0),
+            p99_ms: self.latencies.percentile(99.0),
+            max_ms: self.max_ms,
+            outcomes,
+            timeline: self
+                .seconds
+                .iter()
+                .enumerate()
+                .map(|(second, bucket)| TimelinePoint {
+                    second: second as u64,
+                    requests: bucket.requests,
+                    errors: bucket.errors,
+                    mean_ms: bucket
+                        .sum_ms
+                        .checked_div(bucket.requests as u64)
+                        .unwrap_or(0),
+                    p90_ms: bucket.latencies.percentile(90.0),
+                })
+                .collect(),
+            stopped_early: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum LoadEvent {
+    Samples(Vec<LoadSample>),
+    Finished(LoadReport),
+}
+
+/// Shared pacing for all virtual users.
+struct Schedule<'a> {
+    started: Instant,
+    deadline: Option<Instant>,
+    iterations: Option<u32>,
+    iterations_started: AtomicU32,
+    interval: Option<Duration>,
+    next_slot: tokio::sync::Mutex<Instant>,
+    cancel: &'a AtomicBool,
+}
+
+impl Schedule<'_> {
+    fn should_stop(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed) || self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+
+    /// Claims the next pass over the requests, if any are left.
+    fn claim_iteration(&self) -> bool {
+        if self.should_stop() {
+            return false;
+        }
+        match self.iterations {
+            Some(limit) => self.iterations_started.fetch_add(1, Ordering::Relaxed) < limit,
+            None => true,
+        }
+    }
+
+    /// Waits for this request's turn under the requests-per-second cap.
+    async fn throttle(&self) {
+        let Some(interval) = self.interval else {
+            return;
+        };
+        let slot = {
+            let mut next = self.next_slot.lock().await;
+            let slot = (*next).max(Instant::now());
+            *next = slot + interval;
+            slot
+        };
+        self.wait_until(slot).await;
+    }
+
+    /// Sleeps until `until`, waking early when the test is stopped.
+    async fn wait_until(&self, until: Instant) {
+        while Instant::now() < until && !self.should_stop() {
+            let step = until.saturating_duration_since(Instant::now());
+            tokio::time::sleep(step.min(Duration::from_millis(100))).await;
+        }
+    }
+}
+
+/// Sends `requests` in order from each virtual user, concurrently, through one
+/// shared client. Samples are batched to `on_samples` while the test runs.
+pub async fn run_load(
+    client: &reqwest::Client,
+    token_cache: &TokenCache,
+    requests: &[HttpRequest],
+    options: &LoadOptions,
+    cancel: &AtomicBool,
+    mut on_samples: impl FnMut(Vec<LoadSample>),
+) -> LoadReport {
+    let started = Instant::now();
+    let schedule = Schedule {
+        started,
+        deadline: options.duration.map(|d| started + d),
+        iterations: options.iterations,
+        iterations_started: AtomicU32::new(0),
+        interval: options
+            .requests_per_second
+            .filter(|rps| *rps > 0)
+            .map(|rps| Duration::from_secs_f64(1.0 / rps as f64)),
+        next_slot: tokio::sync::Mutex::new(started),
+        cancel,
+    };
+
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    let users = futures::future::join_all((0..options.virtual_users).map(|user| {
+        let delay = options
+            .ramp_up
+            .mul_f64(user as f64 / options.virtual_users as f64);
+        virtual_user(
+            client,
+            token_cache,
+            requests,
+            &schedule,
+            started + delay,
+            sender.clone(),
+        )
+    }));
+    drop(sender);
+
+    let collect = async {
+        let mut stats = LiveStats::default();
+        let mut batch = Vec::new();
+        let mut last_batch = Instant::now();
+        while let Some(sample) = receiver.recv().await {
+            stats.add(&sample);
+            batch.push(sample);
+            if last_batch.elapsed() >= PROGRESS_INTERVAL {
+                on_samples(std::mem::take(&mut batch));
+                last_batch = Instant::now();
+            }
+        }
+        if !batch.is_empty() {
+            on_samples(batch);
+        }
+        stats
+    };
+
+    let (_, stats) = futures::join!(users, collect);
+    LoadReport {
+        stopped_early: cancel.load(Ordering::Relaxed),
+        ..stats.report(started.elapsed())
+    }
+}
+
+async fn virtual_user(
+    client: &reqwest::Client,
+    token_cache: &TokenCache,
+    requests: &[HttpRequest],
+    schedule: &Schedule<'_>,
+    start_at: Instant,
+    sender: tokio::sync::mpsc::UnboundedSender<LoadSample>,
+) {
+    schedule.wait_until(start_at).await;
+    while schedule.claim_iteration() {
+        for request in requests {
+            schedule.throttle().await;
+            if schedule.should_stop() {
+                return;
+            }
+            let sent = Instant::now();
+            let result = send_with_token_cache(client, request.clone(), token_cache).await;
+            let sample = LoadSample {
+                started_ms: sent.duration_since(schedule.started).as_millis() as u64,
+                latency_ms: sent.elapsed().as_millis() as u64,
+                status: result.as_ref().ok().map(|r| r.status),
+                error: result.err(),
+            };
+            if sender.send(sample).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// One client for the whole test: `default`, unless a request needs a proxy or
+/// skips TLS verification, in which case the first such request's settings win.
+pub fn shared_client(
+    default: &reqwest::Client,
+    requests: &[HttpRequest],
+) -> Result<reqwest::Client, String> {
+    match requests
+        .iter()
+        .find(|r| r.config.proxy_url.is_some() || !r.config.verify_ssl)
+    {
+        Some(request) => crate::http_client::client::build_client(&request.config)
+            .map_err(|e| format!("Failed to build client: {}", e)),
+        None => Ok(default.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http_client::config::RequestConfig;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Answers `/fail` with 500 and anything else with 200.
+    async fn serve() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut stream, _)) = listener.accept().await else {
+                    break;
+                };
+                tokio::spawn(async move {
+                    let mut buffer = vec![0u8; 4096];
+                    let n = stream.read(&mut buffer).await.unwrap_or(0);
+                    let request = String::from_utf8_lossy(&buffer[..n]).to_lowercase();
+                    let status = if request.starts_with("get /fail") {
+                        "500 Internal Server Error"
+                    } else {
+                        "200 OK"
+                    };
+                    let response = format!(
+                        "HTTP/1.1 {}\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+                        status
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        format!("http://127.0.0.1:{}", port)
+    }
+
+    fn get(url: String) -> HttpRequest {
+        HttpRequest {
+            method: "GET".to_string(),
+            url,
+            headers: vec![],
+            body: None,
+            config: RequestConfig::default(),
+            multipart_fields: vec![],
+            auth: None,
+        }
+    }
+
+    fn options(virtual_users: u32, iterations: u32) -> LoadOptions {
+        LoadOptions {
+            virtual_users,
+            duration: None,
+            iterations: Some(iterations),
+            ramp_up: Duration::ZERO,
+            requests_per_second: None,
+        }
+    }
+
+    fn sample(started_ms: u64, latency_ms: u64, status: Option<u16>) -> LoadSample {
+        LoadSample {
+            started_ms,
+            latency_ms,
+            status,
+            error: status
+                .is_none()
+                .then(|| "error sending request: timed out".to_string()),
+        }
+    }
+
+    #[test]
+    fn report_breaks_down_outcomes_and_seconds() {
+        let samples = vec![
+            sample(10, 100, Some(200)),
+            sample(20, 300, Some(200)),
+            sample(1200, 200, Some(503)),
+            sample(1500, 5000, None),
+        ];
+        let mut stats = LiveStats::default();
+        for sample in &samples {
+            stats.add(sample);
+        }
+        let report = stats.report(Duration::from_secs(2));
+        assert_eq!(report.total, 4);
+        assert_eq!(report.errors, 2);
+        assert_eq!(report.throughput, 2.0);
+        assert_eq!((report.min_ms, report.max_ms), (100, 5000));
+        assert_eq!(
+            report.outcomes,
+            vec![
+                ("200".to_string(), 2),
+                ("503".to_string(), 1),
+                ("Timeout".to_string(), 1),
+            ]
+        );
+        assert_eq!(report.timeline.len(), 2);
+        assert_eq!(report.timeline[0].mean_ms, 200);
+        assert_eq!(report.timeline[0].p90_ms, 300);
+        assert_eq!(report.timeline[1].errors, 2);
+        assert_eq!((report.p50_ms, report.p99_ms), (200, 5000));
+    }
+
+    #[test]
+    fn options_need_a_limit() {
+        let mut opts = options(1, 1);
+        assert!(opts.validate().is_ok());
+        opts.iterations = None;
+        assert!(opts.validate().is_err());
+        opts.duration = Some(Duration::ZERO);
+        assert!(opts.validate().is_err());
+        opts.duration = Some(Duration::from_secs(1));
+        assert!(opts.validate().is_ok());
+        opts.virtual_users = 0;
+        assert!(opts.validate().is_err());
+    }
+
+    #[test]
+    fn histogram_keeps_two_significant_digits() {
+        let mut exact = LatencyHistogram::default();
+        for latency in 1..=99 {
+            exact.record(latency);
+        }
+        assert_eq!(exact.percentile(50.0), 50);
+        assert_eq!(exact.percentile(90.0), 90);
+
+        let mut histogram = LatencyHistogram::default();
+        for latency in [5, 1234, 1299, 98765] {
+            histogram.record(latency);
+        }
+        assert_eq!(histogram.percentile(50.0), 1200);
+        assert_eq!(histogram.percentile(99.0), 98000);
+        assert_eq!(LatencyHistogram::default().percentile(50.0), 0);
+    }
+
+    #[tokio::test]
+    async fn iterations_are_shared_between_users() {
+        let base = serve().await;
+        let requests = vec![get(format!("{}/ok", base)), get(format!("{}/fail", base))];
+        let mut batched = 0;
+        let report = run_load(
+            &reqwest::Client::new(),
+            &TokenCache::new(),
+            &requests,
+            &options(3, 4),
+            &AtomicBool::new(false),
+            |samples| batched += samples.len(),
+        )
+        .await;
+        assert_eq!(report.total, 8);
+        assert_eq!(batched, 8);
+        assert_eq!(report.errors, 4);
+        assert_eq!(
+            report.outcomes,
+            vec![("200".to_string(), 4), ("500".to_string(), 4)]
+        );
+        assert!(!report.stopped_early);
+    }
+
+    #[tokio::test]
+    async fn requests_per_second_paces_the_test() {
+        let base = serve().await;
+        let mut opts = options(2, 5);
+        opts.requests_per_second = Some(20);
+        let report = run_load(
+            &reqwest::Client::new(),
+            &TokenCache::new(),
+            &[get(format!("{}/ok", base))],
+            &opts,
+            &AtomicBool::new(false),
+            |_| {},
+        )
+        .await;
+        assert_eq!(report.total, 5);
+        // Five requests at 20/s need at least four 50 ms gaps.
+        assert!(report.duration_ms >= 200, "{}", report.duration_ms);
+    }
+
+    #[tokio::test]
+    async fn cancelled_test_sends_nothing() {
+        let report = run_load(
+            &reqwest::Client::new(),
+            &TokenCache::new(),
+            &[get("http://127.0.0.1:1".to_string())],
+            &options(2, 10),
+            &AtomicBool::new(true),
+            |_| {},
+        )
+        .await;
+        assert_eq!(report.total, 0);
+        assert!(report.stopped_early);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod collection;
+pub mod data;
+pub mod load;
+pub mod report;
@@ -0,0 +1,152 @@
+use crate::runner::collection::{group_by_iteration, RunReport, StepResult};
+
+pub fn to_json(report: &RunReport) -> Result<String, String> {
+    serde_json::to_string_pretty(report).map_err(|e| format!("Failed to serialize report: {}", e))
+}
+
+/// JUnit XML with one test suite per iteration and one test case per request.
+pub fn to_junit_xml(report: &RunReport) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" time=\"{}\">\n",
+        escape(&report.name),
+        report.steps.len(),
+        report.failed_count(),
+        seconds(report.duration_ms)
+    ));
+    for (iteration, steps) in group_by_iteration(&report.steps) {
+        let name = if report.iterations > 1 {
+            format!("{} (iteration {})", report.name, iteration + 1)
+        } else {
+            report.name.clone()
+        };
+        let failures = steps.iter().filter(|s| !s.passed()).count();
+        let time: u64 = steps.iter().map(|s| s.duration_ms).sum();
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" time=\"{}\" timestamp=\"{}\">\n",
+            escape(&name),
+            steps.len(),
+            failures,
+            seconds(time),
+            escape(&report.started_at)
+        ));
+        for step in steps {
+            push_test_case(&mut xml, &name, step);
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+fn push_test_case(xml: &mut String, suite: &str, step: &StepResult) {
+    xml.push_str(&format!(
+        "    <testcase classname=\"{}\" name=\"{} {}\" time=\"{}\"",
+        escape(suite),
+        escape(&step.method),
+        escape(&step.name),
+        seconds(step.duration_ms)
+    ));
+    let failures = step.failures();
+    if failures.is_empty() {
+        xml.push_str("/>\n");
+        return;
+    }
+    xml.push_str(">\n");
+    xml.push_str(&format!(
+        "      <failure message=\"{}\">{}</failure>\n",
+        escape(&failures[0]),
+        escape(&format!("{}\n{}", step.url, failures.join("\n")))
+    ));
+    xml.push_str("    </testcase>\n");
+}
+
+fn seconds(ms: u64) -> String {
+    format!("{:.3}", ms as f64 / 1000.0)
+}
+
+fn escape(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_control() || matches!(c, '\n' | '\t'))
+        .fold(String::with_capacity(text.len()), |mut out, c| {
+            match c {
+                '&' => out.push_str("&amp;"),
+                '<' => out.push_str("&lt;"),
+                '>' => out.push_str("&gt;"),
+                '"' => out.push_str("&quot;"),
+                '\'' => out.push_str("&apos;"),
+                _ => out.push(c),
+            }
+            out
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::assertions::AssertionResult;
+
+    fn step(iteration: usize, name: &str, passed: bool) -> StepResult {
+        StepResult {
+            iteration,
+            request_id: 1,
+            name: name.to_string(),
+            method: "GET".to_string(),
+            url: "http://localhost/a?x=1&y=2".to_string(),
+            status: Some(if passed { 200 } else { 500 }),
+            duration_ms: 1500,
+            assertions: vec![AssertionResult {
+                description: "Status equals 200".to_string(),
+                passed,
+                message: "status <500>".to_string(),
+            }],
+            extracted: vec![],
+            extraction_errors: vec![],
+            error: None,
+        }
+    }
+
+    fn report(steps: Vec<StepResult>, iterations: usize) -> RunReport {
+        RunReport {
+            name: "API & co".to_string(),
+            started_at: "2024-01-01T00:00:00Z".to_string(),
+            duration_ms: 3000,
+            steps,
+            stopped_early: false,
+            iterations,
+        }
+    }
+
+    #[test]
+    fn junit_counts_failures_and_escapes_text() {
+        let xml = to_junit_xml(&report(
+            vec![step(0, "Health", true), step(0, "Users", false)],
+            1,
+        ));
+        assert!(
+            xml.contains(r#"<testsuites name="API &amp; co" tests="2" failures="1" time="3.000">"#)
+        );
+        assert!(
+            xml.contains(r#"<testcase classname="API &amp; co" name="GET Health" time="1.500"/>"#)
+        );
+        assert!(xml.contains(r#"<failure message="Status equals 200: status &lt;500&gt;">"#));
+        assert!(xml.contains("x=1&amp;y=2"));
+    }
+
+    #[test]
+    fn junit_has_a_suite_per_iteration() {
+        let xml = to_junit_xml(&report(
+            vec![step(0, "Health", true), step(1, "Health", true)],
+            2,
+        ));
+        assert_eq!(xml.matches("<testsuite ").count(), 2);
+        assert!(xml.contains("API &amp; co (iteration 2)"));
+    }
+
+    #[test]
+    fn json_report_round_trips() {
+        let original = report(vec![step(0, "Health", true)], 1);
+        let parsed: RunReport = serde_json::from_str(&to_json(&original).unwrap()).unwrap();
+        assert_eq!(parsed, original);
+    }
+}
@@ -1,3 +1,6 @@
+use crate::data::assertions::Assertion;
+use crate::data::extraction::ExtractionRule;
+use crate::export::astranova::AstraNovaCollection;
 use crate::persistence::database::{self, Collection, CollectionFolder, CollectionRequest};
 use rusqlite::Connection;
 
@@ -113,6 +116,93 @@ pub fn save_request(
     .map_err(|e| e.to_string())
 }
 
+pub fn set_request_variables(
+    conn: &Connection,
+    id: i32,
+    variables: &[(String, String)],
+) -> Result<(), String> {
+    database::update_collection_request_variables(conn, id, variables).map_err(|e| e.to_string())
+}
+
+pub fn set_request_extractions(
+    conn: &Connection,
+    id: i32,
+    extractions: &[ExtractionRule],
+) -> Result<(), String> {
+    database::update_collection_request_extractions(conn, id, extractions)
+        .map_err(|e| e.to_string())
+}
+
+pub fn set_request_assertions(
+    conn: &Connection,
+    id: i32,
+    assertions: &[Assertion],
+) -> Result<(), String> {
+    database::update_collection_request_assertions(conn, id, assertions).map_err(|e| e.to_string())
+}
+
+/// Creates a collection from an AstraNova collection file, with its folders and requests.
+pub fn import_astranova(
+    conn: &Connection,
+    imported: &AstraNovaCollection,
+) -> Result<Collection, String> {
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| format!("Failed to start import: {}", e))?;
+    let mut collection = create(&tx, &imported.name)?;
+    collection.description = imported.description.clone();
+    collection.variables = imported.variables.clone();
+    update(&tx, &collection)?;
+    for req in &imported.requests {
+        save_imported_request(&tx, collection.id, None, req)?;
+    }
+    for folder in &imported.folders {
+        let created = create_folder(&tx, collection.id, &folder.name)?;
+        for req in &folder.requests {
+            save_imported_request(&tx, collection.id, Some(created.id), req)?;
+        }
+    }
+    tx.commit()
+        .map_err(|e| format!("Failed to save import: {}", e))?;
+    Ok(collection)
+}
+
+fn save_imported_request(
+    conn: &Connection,
+    collection_id: i32,
+    folder_id: Option<i32>,
+    req: &CollectionRequest,
+) -> Result<(), String> {
+    let saved = save_request(
+        conn,
+        collection_id,
+        folder_id,
+        &req.name,
+        &req.method,
+        &req.url,
+        &req.headers,
+        req.body.as_deref(),
+        &req.body_type,
+        &req.auth_type,
+        req.auth_data.as_deref(),
+        &req.params,
+        req.config_json.as_deref(),
+    )?;
+    set_request_variables(conn, saved.id, &req.variables)?;
+    set_request_extractions(conn, saved.id, &req.extractions)?;
+    set_request_assertions(conn, saved.id, &req.assertions)
+}
+
+pub fn set_variables(
+    conn: &Connection,
+    collection: &Collection,
+    variables: Vec<(String, String)>,
+) -> Result<(), String> {
+    let mut updated = collection.clone();
+    updated.variables = variables;
+    update(conn, &updated)
+}
+
 pub fn rename_request(conn: &Connection, id: i32, new_name: &str) -> Result<(), String> {
     database::rename_collection_request(conn, id, new_name).map_err(|e| e.to_string())
 }
@@ -141,47 +231,7 @@ mod tests {
     use super::*;
 
     fn setup_test_db() -> Connection {
-        let conn = Connection::open_in_memory().unwrap();
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS collections (
-                id INTEGER PRIMARY KEY,
-                name TEXT NOT NULL,
-                description TEXT
-            )",
-            [],
-        )
-        .unwrap();
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS collection_folders (
-                id INTEGER PRIMARY KEY,
-                collection_id INTEGER NOT NULL,
-                name TEXT NOT NULL,
-                parent_folder_id INTEGER
-            )",
-            [],
-        )
-        .unwrap();
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS collection_requests (
-                id INTEGER PRIMARY KEY,
-                collection_id INTEGER NOT NULL,
-                folder_id INTEGER,
-                name TEXT NOT NULL,
-                method TEXT NOT NULL,
-                url TEXT NOT NULL,
-                headers TEXT NOT NULL DEFAULT '[]',
-                body TEXT,
-                body_type TEXT NOT NULL DEFAULT 'text',
-                auth_type TEXT NOT NULL DEFAULT 'none',
-                auth_data TEXT,
-                params TEXT NOT NULL DEFAULT '[]',
-                config_json TEXT,
-                sort_order INTEGER NOT NULL DEFAULT 0
-            )",
-            [],
-        )
-        .unwrap();
-        conn
+        crate::persistence::database::open(std::path::Path::new(":memory:")).unwrap()
     }
 
     #[test]
@@ -231,6 +281,22 @@ mod tests {
         assert_eq!(reqs.len(), 1);
     }
 
+    #[test]
+    fn set_variables_persists_collection_scope() {
+        let conn = setup_test_db();
+        let col = create(&conn, "API").unwrap();
+        set_variables(
+            &conn,
+            &col,
+            vec![("BASE".to_string(), "https://api.test".to_string())],
+        )
+        .unwrap();
+
+        let cols = get_all(&conn);
+        assert_eq!(cols[0].name, "API");
+        assert_eq!(cols[0].variables[0].1, "https://api.test");
+    }
+
     #[test]
     fn rename_collection_test() {
         let conn = setup_test_db();
@@ -392,4 +458,50 @@ mod tests {
         let reqs = delete_request_and_refresh(&conn, col.id, None, req.id).unwrap();
         assert!(reqs.is_empty());
     }
+
+    #[test]
+    fn exported_astranova_collection_imports_with_assertions() {
+        use crate::data::assertions::{Assertion, AssertionKind};
+
+        let conn = setup_test_db();
+        let col = create(&conn, "API").unwrap();
+        let folder = create_folder(&conn, col.id, "Users").unwrap();
+        let req = save_request(
+            &conn,
+            col.id,
+            Some(folder.id),
+            "List",
+            "GET",
+            "{{HOST}}/users",
+            &[],
+            None,
+            "text",
+            "none",
+            None,
+            &[],
+            None,
+        )
+        .unwrap();
+        let assertion = Assertion {
+            kind: AssertionKind::StatusEquals,
+            target: String::new(),
+            expected: "200".to_string(),
+        };
+        set_request_assertions(&conn, req.id, std::slice::from_ref(&assertion)).unwrap();
+
+        let json = crate::export::astranova::export_collection(
+            &col,
+            &get_folders(&conn, col.id),
+            &crate::services::run_service::requests_for_run(&conn, col.id, None),
+        )
+        .unwrap();
+        let parsed = crate::export::astranova::parse_collection(&json).unwrap();
+        let imported = import_astranova(&conn, &parsed).unwrap();
+
+        let folders = get_folders(&conn, imported.id);
+        assert_eq!(folders[0].name, "Users");
+        let requests = get_requests(&conn, imported.id, Some(folders[0].id));
+        assert_eq!(requests[0].url, "{{HOST}}/users");
+        assert_eq!(requests[0].assertions, vec![assertion]);
+    }
 }
@@ -1,3 +1,4 @@
+use crate::data::vault::Vault;
 use crate::persistence::database::{self, Environment};
 use rusqlite::Connection;
 
@@ -32,27 +33,47 @@ pub fn delete_and_refresh(conn: &Connection, id: i32) -> Result<Vec<Environment>
     Ok(get_all(conn))
 }
 
+pub fn get_globals(conn: &Connection) -> Vec<(String, String)> {
+    database::get_global_variables(conn).unwrap_or_default()
+}
+
+pub fn save_globals(conn: &Connection, variables: &[(String, String)]) -> Result<(), String> {
+    database::save_global_variables(conn, variables).map_err(|e| e.to_string())
+}
+
+/// Opens the vault with `passphrase`, creating it on first use.
+pub fn unlock_vault(conn: &Connection, passphrase: &str) -> Result<Vault, String> {
+    match database::get_vault_record(conn).map_err(|e| e.to_string())? {
+        Some(record) => Vault::open(passphrase, &record),
+        None => {
+            let (vault, record) = Vault::create(passphrase)?;
+            database::save_vault_record(conn, &record).map_err(|e| e.to_string())?;
+            Ok(vault)
+        }
+    }
+}
+
+pub fn has_vault(conn: &Connection) -> bool {
+    matches!(database::get_vault_record(conn), Ok(Some(_)))
+}
+
+/// `KEY=value` lines of a `.env` file; blank lines and `#` comments are skipped.
+pub fn parse_env_file(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#') && !line.is_empty())
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     fn setup_test_db() -> Connection {
-        let conn = Connection::open_in_memory().unwrap();
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS environments (
-                id INTEGER PRIMARY KEY,
-                name TEXT NOT NULL UNIQUE,
-                variables TEXT NOT NULL
-            )",
-            [],
-        )
-        .unwrap();
-        conn.execute(
-            "ALTER TABLE environments ADD COLUMN default_endpoint TEXT",
-            [],
-        )
-        .ok();
-        conn
+        crate::persistence::database::open(std::path::Path::new(":memory:")).unwrap()
     }
 
     #[test]
@@ -115,4 +136,38 @@ mod tests {
         assert_eq!(envs.len(), 1);
         assert_eq!(envs[0].name, "prod");
     }
+
+    #[test]
+    fn save_globals_replaces_previous_set() {
+        let conn = setup_test_db();
+        assert!(get_globals(&conn).is_empty());
+        save_globals(&conn, &[("A".to_string(), "1".to_string())]).unwrap();
+        save_globals(&conn, &[("B".to_string(), "2".to_string())]).unwrap();
+        assert_eq!(get_globals(&conn), vec![("B".to_string(), "2".to_string())]);
+    }
+
+    #[test]
+    fn unlock_vault_creates_then_verifies_passphrase() {
+        let conn = setup_test_db();
+        assert!(!has_vault(&conn));
+        let vault = unlock_vault(&conn, "master").unwrap();
+        assert!(has_vault(&conn));
+        let sealed = vault.encrypt("secret").unwrap();
+
+        let reopened = unlock_vault(&conn, "master").unwrap();
+        assert_eq!(reopened.decrypt(&sealed).unwrap(), "secret");
+        assert!(unlock_vault(&conn, "wrong").is_err());
+    }
+
+    #[test]
+    fn parse_env_file_skips_comments() {
+        let vars = parse_env_file("# Environment: dev\n\nHOST = api.dev\nTOKEN=a=b\n");
+        assert_eq!(
+            vars,
+            vec![
+                ("HOST".to_string(), "api.dev".to_string()),
+                ("TOKEN".to_string(), "a=b".to_string()),
+            ]
+        );
+    }
 }
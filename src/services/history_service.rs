@@ -77,22 +77,7 @@ mod tests {
     use std::time::Duration;
 
     fn setup_test_db() -> Connection {
-        let conn = Connection::open_in_memory().unwrap();
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS request_history (
-                id INTEGER PRIMARY KEY,
-                method TEXT NOT NULL,
-                url TEXT NOT NULL,
-                status INTEGER,
-                duration_ms INTEGER,
-                timestamp TEXT NOT NULL,
-                request_data TEXT,
-                response_data TEXT
-            )",
-            [],
-        )
-        .unwrap();
-        conn
+        crate::persistence::database::open(std::path::Path::new(":memory:")).unwrap()
     }
 
     fn make_request(method: &str, url: &str) -> HttpRequest {
@@ -1,4 +1,6 @@
 pub mod collection_service;
 pub mod environment_service;
 pub mod history_service;
-pub mod request_restoration;
+pub mod openapi_service;
+pub mod openapi_sync;
+pub mod run_service;
@@ -0,0 +1,488 @@
+use crate::data::auth::Auth;
+use crate::openapi::collection_generator::{
+    generate_grouped_collection, FolderGrouping, GeneratedRequest, BASE_URL_VARIABLE,
+};
+use crate::openapi::models::{OpenApiSpec, ParsedEndpoint, ParsedSpec};
+use crate::openapi::parser::load_spec_model;
+use crate::openapi::validation::ResponseContract;
+use crate::openapi::{parse_spec, parse_spec_from_yaml};
+use crate::persistence::database::{self, Collection, Environment, OperationRef};
+use crate::services::openapi_sync;
+use rusqlite::Connection;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+pub struct ImportSummary {
+    #[allow(dead_code)]
+    pub collection: Collection,
+    pub requests: usize,
+    /// One unsaved environment per server in the spec, for the user to accept.
+    pub environments: Vec<ServerEnvironment>,
+}
+
+/// An environment proposed for one of the spec's servers.
+#[derive(Debug, Clone)]
+pub struct ServerEnvironment {
+    pub environment: Environment,
+    /// Allowed values of the server variables declared with an `enum`.
+    pub choices: Vec<(String, Vec<String>)>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ImportOptions {
+    /// `(method, path)` of the operations and webhooks to import; everything when
+    /// `None`. Webhooks use their name as the path.
+    pub operations: Option<HashSet<(String, String)>>,
+    pub grouping: FolderGrouping,
+    /// File path or URL the spec was read from, remembered for re-syncing.
+    pub source: Option<String>,
+}
+
+/// Parses a JSON or YAML spec.
+pub fn parse_content(content: &str) -> Result<ParsedSpec, String> {
+    if content.trim_start().starts_with('{') {
+        parse_spec(content)
+    } else {
+        parse_spec_from_yaml(content)
+    }
+}
+
+/// Creates a collection from a JSON or YAML spec and keeps the spec so responses
+/// can later be checked against it.
+#[allow(dead_code)]
+pub fn import_spec(conn: &Connection, content: &str) -> Result<ImportSummary, String> {
+    import_spec_with(conn, content, &ImportOptions::default())
+}
+
+/// Like [`import_spec`], writing only the chosen operations.
+pub fn import_spec_with(
+    conn: &Connection,
+    content: &str,
+    options: &ImportOptions,
+) -> Result<ImportSummary, String> {
+    let mut spec = parse_content(content)?;
+    let mut excluded = Vec::new();
+    if let Some(operations) = &options.operations {
+        excluded = spec
+            .endpoints
+            .iter()
+            .chain(&spec.webhooks)
+            .map(|e| (e.method.clone(), e.path.clone()))
+            .filter(|key| !operations.contains(key))
+            .collect();
+        let selected =
+            |e: &ParsedEndpoint| operations.contains(&(e.method.clone(), e.path.clone()));
+        spec.endpoints.retain(selected);
+        spec.webhooks.retain(selected);
+        if spec.endpoints.is_empty() && spec.webhooks.is_empty() {
+            return Err("No operations selected".to_string());
+        }
+    }
+    spec.check_refs(|method, path| {
+        spec.endpoints
+            .iter()
+            .chain(&spec.webhooks)
+            .any(|e| e.method == method && e.path == path)
+    })?;
+    let generated = generate_grouped_collection(&spec, 0, options.grouping);
+
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| format!("Failed to start import: {}", e))?;
+    let conn = &*tx;
+    let mut collection = database::create_collection(
+        conn,
+        &generated.collection.name,
+        generated.collection.description.as_deref(),
+    )
+    .map_err(|e| format!("Failed to create collection: {}", e))?;
+    collection.variables = generated.collection.variables.clone();
+    database::update_collection(conn, &collection)
+        .map_err(|e| format!("Failed to save collection variables: {}", e))?;
+    let spec_id = database::save_openapi_spec(
+        conn,
+        collection.id,
+        &spec.title,
+        content,
+        options.source.as_deref(),
+        &excluded,
+        options.grouping.stored_name(),
+    )
+    .map_err(|e| format!("Failed to save spec: {}", e))?;
+
+    let mut folder_ids = HashMap::new();
+    for folder in &generated.folders {
+        let created = database::create_folder(conn, collection.id, &folder.name, None)
+            .map_err(|e| format!("Failed to create folder: {}", e))?;
+        folder_ids.insert(folder.name.clone(), created.id);
+    }
+
+    for req in &generated.requests {
+        let folder_id = req
+            .folder_name
+            .as_ref()
+            .and_then(|name| folder_ids.get(name).copied());
+        save_generated_request(conn, collection.id, folder_id, spec_id, req)?;
+    }
+    tx.commit()
+        .map_err(|e| format!("Failed to save import: {}", e))?;
+
+    Ok(ImportSummary {
+        collection,
+        requests: generated.requests.len(),
+        environments: server_environments(&spec),
+    })
+}
+
+/// Saves a generated request, linked to its operation in spec `spec_id`.
+pub(crate) fn save_generated_request(
+    conn: &Connection,
+    collection_id: i32,
+    folder_id: Option<i32>,
+    spec_id: i32,
+    req: &GeneratedRequest,
+) -> Result<(), String> {
+    let auth_data = generated_auth_data(req)?;
+    let saved = database::save_collection_request(
+        conn,
+        collection_id,
+        folder_id,
+        &req.name,
+        &req.method,
+        &req.url,
+        &req.headers,
+        req.body.as_deref(),
+        "text",
+        req.auth.stored_type(),
+        auth_data.as_deref(),
+        &req.params,
+        None,
+    )
+    .map_err(|e| format!("Failed to save request '{}': {}", req.name, e))?;
+    let operation = OperationRef {
+        spec_id,
+        path: req.path.clone(),
+        method: req.method.clone(),
+        generated: Some(openapi_sync::generated_fingerprint(req)?),
+    };
+    database::update_collection_request_operation(conn, saved.id, Some(&operation))
+        .map_err(|e| format!("Failed to link request '{}': {}", req.name, e))
+}
+
+/// The `auth_data` column for a generated request.
+pub(crate) fn generated_auth_data(req: &GeneratedRequest) -> Result<Option<String>, String> {
+    match req.auth {
+        Auth::None => Ok(None),
+        ref auth => serde_json::to_string(auth)
+            .map(Some)
+            .map_err(|e| format!("Failed to serialize auth: {}", e)),
+    }
+}
+
+/// An environment per server, holding its URL as `baseUrl` with the server
+/// variables left as `{{placeholders}}`, and the variables' default values.
+pub fn server_environments(spec: &ParsedSpec) -> Vec<ServerEnvironment> {
+    spec.servers
+        .iter()
+        .map(|server| {
+            let url = server.templated_url().trim_end_matches('/').to_string();
+            let label = server
+                .description
+                .clone()
+                .unwrap_or_else(|| server.resolved_url().trim_end_matches('/').to_string());
+            let mut variables = vec![(BASE_URL_VARIABLE.to_string(), url.clone())];
+            variables.extend(server.variable_defaults());
+            let mut choices: Vec<(String, Vec<String>)> = server
+                .variables
+                .iter()
+                .filter(|(_, variable)| !variable.enum_values.is_empty())
+                .map(|(name, variable)| (name.clone(), variable.enum_values.clone()))
+                .collect();
+            choices.sort();
+            ServerEnvironment {
+                environment: Environment {
+                    id: 0,
+                    name: format!("{}: {}", spec.title, label),
+                    variables,
+                    default_endpoint: Some(url),
+                    secret_keys: vec![],
+                },
+                choices,
+            }
+        })
+        .collect()
+}
+
+/// Saves proposed environments, numbering names that are already taken.
+pub fn create_environments(
+    conn: &Connection,
+    environments: &[ServerEnvironment],
+) -> Result<Vec<Environment>, String> {
+    let mut taken: Vec<String> = database::get_environments(conn)
+        .map_err(|e| format!("Failed to load environments: {}", e))?
+        .into_iter()
+        .map(|env| env.name)
+        .collect();
+    let mut created = Vec::new();
+    for ServerEnvironment {
+        environment: proposed,
+        ..
+    } in environments
+    {
+        let mut name = proposed.name.clone();
+        let mut n = 2;
+        while taken.contains(&name) {
+            name = format!("{} ({})", proposed.name, n);
+            n += 1;
+        }
+        let mut env = database::create_environment(conn, &name)
+            .map_err(|e| format!("Failed to create environment '{}': {}", name, e))?;
+        env.variables = proposed.variables.clone();
+        env.default_endpoint = proposed.default_endpoint.clone();
+        database::update_environment(conn, &env)
+            .map_err(|e| format!("Failed to save environment '{}': {}", name, e))?;
+        taken.push(name);
+        created.push(env);
+    }
+    Ok(created)
+}
+
+/// Parsed specs by id, so opening linked requests doesn't parse the document each time.
+#[derive(Debug, Default)]
+pub struct SpecCache {
+    specs: HashMap<i32, (u64, OpenApiSpec)>,
+}
+
+impl SpecCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The spec stored as `spec_id`, parsed again only once its content changed.
+    fn get(&mut self, conn: &Connection, spec_id: i32) -> Option<&OpenApiSpec> {
+        let record = database::get_openapi_spec(conn, spec_id).ok()??;
+        let mut hasher = DefaultHasher::new();
+        record.content.hash(&mut hasher);
+        let hash = hasher.finish();
+        if self
+            .specs
+            .get(&spec_id)
+            .is_none_or(|(cached, _)| *cached != hash)
+        {
+            let spec = load_spec_model(&record.content).ok()?;
+            self.specs.insert(spec_id, (hash, spec));
+        }
+        self.specs.get(&spec_id).map(|(_, spec)| spec)
+    }
+}
+
+/// The documented responses of the operation a request was generated from.
+pub fn response_contract(
+    conn: &Connection,
+    cache: &mut SpecCache,
+    operation: &OperationRef,
+) -> Option<ResponseContract> {
+    let spec = cache.get(conn, operation.spec_id)?;
+    ResponseContract::for_operation(spec, &operation.path, &operation.method)
+}
+
+/// The id and document of the spec a collection was imported from.
+pub fn spec_for_collection(conn: &Connection, collection_id: i32) -> Option<(i32, OpenApiSpec)> {
+    let record = database::get_collection_openapi_spec(conn, collection_id).ok()??;
+    let spec = load_spec_model(&record.content).ok()?;
+    Some((record.id, spec))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::collection_service;
+    use std::path::Path;
+
+    const SPEC: &str = r#"
+openapi: 3.0.0
+info:
+  title: Shop
+  version: "1"
+servers:
+  - url: https://{region}.shop.example.com/
+    description: Production
+    variables:
+      region: { default: eu, enum: [eu, us] }
+  - url: http://localhost:3000
+security:
+  - apiKey: []
+components:
+  securitySchemes:
+    apiKey: { type: apiKey, in: header, name: X-API-Key }
+paths:
+  /orders:
+    get:
+      tags: [orders]
+      operationId: listOrders
+      responses:
+        "200":
+          description: ok
+          content:
+            application/json:
+              schema:
+                type: array
+                items: { type: object, required: [id] }
+  /users:
+    get:
+      tags: [users]
+      operationId: listUsers
+      security: []
+      responses:
+        "200": { description: ok }
+"#;
+
+    #[test]
+    fn imported_requests_are_filed_by_tag_and_linked_to_the_spec() {
+        let conn = database::open(Path::new(":memory:")).unwrap();
+        let summary = import_spec(&conn, SPEC).unwrap();
+        assert_eq!(summary.requests, 2);
+
+        let folders = collection_service::get_folders(&conn, summary.collection.id);
+        let orders = folders.iter().find(|f| f.name == "orders").unwrap();
+        let requests =
+            collection_service::get_requests(&conn, summary.collection.id, Some(orders.id));
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].name, "listOrders");
+        assert_eq!(
+            Auth::from_stored(&requests[0].auth_type, requests[0].auth_data.as_deref()),
+            Auth::ApiKey {
+                key: "X-API-Key".to_string(),
+                value: "{{apiKey}}".to_string(),
+                location: crate::data::auth::ApiKeyLocation::Header,
+            }
+        );
+        let users = folders.iter().find(|f| f.name == "users").unwrap();
+        let public = collection_service::get_requests(&conn, summary.collection.id, Some(users.id));
+        assert_eq!(public[0].auth_type, "none");
+        assert!(collection_service::get_requests(&conn, summary.collection.id, None).is_empty());
+
+        let operation = requests[0].operation.clone().unwrap();
+        assert_eq!(
+            (operation.path.as_str(), operation.method.as_str()),
+            ("/orders", "GET")
+        );
+        assert_eq!(requests[0].url, "{{baseUrl}}/orders");
+        let collection = &collection_service::get_all(&conn)[0];
+        assert_eq!(
+            collection.variables,
+            vec![(
+                "baseUrl".to_string(),
+                "https://eu.shop.example.com".to_string()
+            )]
+        );
+
+        let (spec_id, spec) = spec_for_collection(&conn, summary.collection.id).unwrap();
+        assert_eq!(
+            (spec_id, spec.info.title.as_str()),
+            (operation.spec_id, "Shop")
+        );
+
+        let mut cache = SpecCache::new();
+        let contract = response_contract(&conn, &mut cache, &operation).unwrap();
+        assert_eq!(contract.operation, "GET /orders");
+        let violations = contract.validate(200, Some("application/json"), r#"[{"id": 1}, {}]"#);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].location, "$[1]");
+
+        // A re-synced document replaces the cached one.
+        let next = r#"{"openapi": "3.0.0", "info": {"title": "Shop"}, "paths": {}}"#;
+        database::update_openapi_spec(&conn, operation.spec_id, "Shop", next).unwrap();
+        assert!(response_contract(&conn, &mut cache, &operation).is_none());
+    }
+
+    #[test]
+    fn only_selected_operations_are_written() {
+        let conn = database::open(Path::new(":memory:")).unwrap();
+        let options = ImportOptions {
+            operations: Some(HashSet::from([("GET".to_string(), "/users".to_string())])),
+            grouping: FolderGrouping::PathSegment,
+            ..Default::default()
+        };
+        let summary = import_spec_with(&conn, SPEC, &options).unwrap();
+        assert_eq!(summary.requests, 1);
+        let requests = collection_service::get_requests(&conn, summary.collection.id, None);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].name, "listUsers");
+
+        let none = ImportOptions {
+            operations: Some(HashSet::new()),
+            ..Default::default()
+        };
+        assert!(import_spec_with(&conn, SPEC, &none).is_err());
+        assert_eq!(collection_service::get_all(&conn).len(), 1);
+    }
+
+    #[test]
+    fn unresolved_refs_only_block_the_operations_that_need_them() {
+        let conn = database::open(Path::new(":memory:")).unwrap();
+        let spec = r##"{"openapi": "3.0.0", "info": {"title": "T"}, "paths": {
+            "/a": {"get": {"parameters": [{"$ref": "#/components/parameters/Gone"}],
+                "responses": {}}},
+            "/b": {"get": {"responses": {}}}}}"##;
+        let err = import_spec(&conn, spec).err().unwrap();
+        assert_eq!(
+            err,
+            "Unresolved $ref #/components/parameters/Gone in GET /a"
+        );
+
+        let options = ImportOptions {
+            operations: Some(HashSet::from([("GET".to_string(), "/b".to_string())])),
+            ..Default::default()
+        };
+        assert_eq!(import_spec_with(&conn, spec, &options).unwrap().requests, 1);
+    }
+
+    #[test]
+    fn servers_are_offered_as_environments() {
+        let conn = database::open(Path::new(":memory:")).unwrap();
+        let summary = import_spec(&conn, SPEC).unwrap();
+        let proposed = &summary.environments;
+        assert_eq!(proposed.len(), 2);
+        assert_eq!(proposed[0].environment.name, "Shop: Production");
+        assert_eq!(
+            proposed[0].environment.variables,
+            vec![
+                (
+                    "baseUrl".to_string(),
+                    "https://{{region}}.shop.example.com".to_string()
+                ),
+                ("region".to_string(), "eu".to_string()),
+            ]
+        );
+        assert_eq!(
+            proposed[0].choices,
+            vec![(
+                "region".to_string(),
+                vec!["eu".to_string(), "us".to_string()]
+            )]
+        );
+        let resolver = crate::data::variables::VariableResolver::from_environment(Some(
+            &proposed[0].environment,
+        ));
+        assert_eq!(
+            resolver.resolve("{{baseUrl}}/x"),
+            "https://eu.shop.example.com/x"
+        );
+        assert!(resolver.unresolved("{{baseUrl}}/x").is_empty());
+        assert_eq!(proposed[1].environment.name, "Shop: http://localhost:3000");
+        assert!(proposed[1].choices.is_empty());
+        assert!(database::get_environments(&conn).unwrap().is_empty());
+
+        create_environments(&conn, proposed).unwrap();
+        let again = create_environments(&conn, &proposed[..1]).unwrap();
+        assert_eq!(again[0].name, "Shop: Production (2)");
+        let saved = database::get_environments(&conn).unwrap();
+        assert_eq!(saved.len(), 3);
+        assert_eq!(
+            saved[0].default_endpoint.as_deref(),
+            Some("https://{{region}}.shop.example.com")
+        );
+    }
+}
@@ -0,0 +1,562 @@
+use crate::openapi::collection_generator::{
+    generate_grouped_collection, FolderGrouping, GeneratedRequest,
+};
+use crate::openapi::refs::bundle;
+use crate::persistence::database::{self, CollectionRequest, OperationRef, RequestFingerprint};
+use crate::services::openapi_service::{
+    generated_auth_data, parse_content, save_generated_request,
+};
+use crate::services::run_service;
+use rusqlite::Connection;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// A part of a re-sync the user can leave out.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ResyncItem {
+    /// A new operation, by method and path.
+    Add(String, String),
+    /// A changed or removed request, by id.
+    Request(i32),
+}
+
+#[derive(Debug, Clone)]
+pub struct ChangedOperation {
+    pub request: CollectionRequest,
+    pub generated: GeneratedRequest,
+    /// Fields taken from the updated spec.
+    pub updated: Vec<&'static str>,
+    /// Fields the spec changed but the user edited, so they're left alone.
+    pub kept: Vec<&'static str>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RemovedOperation {
+    pub request: CollectionRequest,
+    /// Edited requests are unlinked from the spec instead of deleted.
+    pub edited: bool,
+}
+
+/// The difference between a collection and the current version of its spec.
+#[derive(Debug, Clone)]
+pub struct ResyncPlan {
+    pub collection_id: i32,
+    pub spec_id: i32,
+    pub title: String,
+    /// The updated spec, stored in place of the old one.
+    pub content: String,
+    pub added: Vec<GeneratedRequest>,
+    pub removed: Vec<RemovedOperation>,
+    pub changed: Vec<ChangedOperation>,
+    /// Requests that already match the spec; only their hashes are refreshed.
+    pub unchanged: Vec<ChangedOperation>,
+    /// Collection variables the spec needs that the collection lacks.
+    pub variables: Vec<(String, String)>,
+}
+
+impl ResyncPlan {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    pub fn items(&self) -> HashSet<ResyncItem> {
+        let added = self
+            .added
+            .iter()
+            .map(|req| ResyncItem::Add(req.method.clone(), req.path.clone()));
+        let removed = self
+            .removed
+            .iter()
+            .map(|r| ResyncItem::Request(r.request.id));
+        let changed = self
+            .changed
+            .iter()
+            .map(|c| ResyncItem::Request(c.request.id));
+        added.chain(removed).chain(changed).collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResyncSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+}
+
+fn hash<T: Serialize + ?Sized>(value: &T) -> Result<String, String> {
+    let json =
+        serde_json::to_string(value).map_err(|e| format!("Failed to hash request: {}", e))?;
+    let digest = Sha256::digest(json.as_bytes());
+    Ok(digest[..8].iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Hashes of a request as the spec generates it.
+pub fn generated_fingerprint(req: &GeneratedRequest) -> Result<RequestFingerprint, String> {
+    Ok(RequestFingerprint {
+        name: hash(&req.name)?,
+        url: hash(&req.url)?,
+        headers: hash(&req.headers)?,
+        body: hash(&req.body)?,
+        params: hash(&req.params)?,
+        auth: hash(&(req.auth.stored_type(), generated_auth_data(req)?))?,
+    })
+}
+
+/// Hashes of a request as currently saved.
+pub fn saved_fingerprint(req: &CollectionRequest) -> Result<RequestFingerprint, String> {
+    Ok(RequestFingerprint {
+        name: hash(&req.name)?,
+        url: hash(&req.url)?,
+        headers: hash(&req.headers)?,
+        body: hash(&req.body)?,
+        params: hash(&req.params)?,
+        auth: hash(&(&req.auth_type, &req.auth_data))?,
+    })
+}
+
+fn fields(fingerprint: &RequestFingerprint) -> [(&'static str, &str); 6] {
+    [
+        ("name", &fingerprint.name),
+        ("url", &fingerprint.url),
+        ("headers", &fingerprint.headers),
+        ("body", &fingerprint.body),
+        ("params", &fingerprint.params),
+        ("auth", &fingerprint.auth),
+    ]
+}
+
+/// Reads the spec again from where it was imported.
+pub async fn fetch_source(source: String) -> Result<String, String> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        let response = reqwest::get(&source)
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| format!("Failed to download {}: {}", source, e))?;
+        return response
+            .text()
+            .await
+            .map_err(|e| format!("Failed to download {}: {}", source, e));
+    }
+    let content = tokio::fs::read_to_string(&source)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", source, e))?;
+    let path = Path::new(&source);
+    bundle(&content, path.parent().unwrap_or(Path::new(".")))
+}
+
+/// Compares the requests generated from `content` with the ones the
+/// collection's spec created before.
+pub fn plan_resync(
+    conn: &Connection,
+    collection_id: i32,
+    content: &str,
+) -> Result<ResyncPlan, String> {
+    let record = database::get_collection_openapi_spec(conn, collection_id)
+        .map_err(|e| format!("Failed to load spec: {}", e))?
+        .ok_or("This collection wasn't imported from an OpenAPI spec")?;
+    let spec = parse_content(content)?;
+    // Operations left out at import stay out.
+    let excluded = |method: &str, path: &str| {
+        record
+            .excluded_operations
+            .contains(&(method.to_string(), path.to_string()))
+    };
+    spec.check_refs(|method, path| !excluded(method, path))?;
+    let grouping = FolderGrouping::from_stored(&record.grouping);
+    let generated = generate_grouped_collection(&spec, collection_id, grouping);
+    let by_key: HashMap<(String, String), &GeneratedRequest> = generated
+        .requests
+        .iter()
+        .map(|req| ((req.method.clone(), req.path.clone()), req))
+        .collect();
+    let mut unmatched: HashSet<&(String, String)> = by_key
+        .keys()
+        .filter(|(method, path)| !excluded(method, path))
+        .collect();
+
+    let linked = run_service::requests_for_run(conn, collection_id, None)
+        .into_iter()
+        .filter_map(|req| {
+            let operation = req.operation.clone()?;
+            (operation.spec_id == record.id).then_some((req, operation))
+        });
+    let mut matched = Vec::new();
+    let mut removed = Vec::new();
+    for (request, operation) in linked {
+        let key = (operation.method.clone(), operation.path.clone());
+        let saved = saved_fingerprint(&request)?;
+        match by_key.get(&key) {
+            Some(req) => {
+                unmatched.remove(&key);
+                matched.push(compare(request, req, &saved, operation.generated.as_ref())?);
+            }
+            None => removed.push(RemovedOperation {
+                edited: operation.generated.as_ref() != Some(&saved),
+                request,
+            }),
+        }
+    }
+    let (changed, unchanged) = matched
+        .into_iter()
+        .partition(|c: &ChangedOperation| !c.updated.is_empty() || !c.kept.is_empty());
+
+    let mut added: Vec<GeneratedRequest> = unmatched
+        .into_iter()
+        .map(|key| by_key[key].clone())
+        .collect();
+    added.sort_by(|a, b| (&a.path, &a.method).cmp(&(&b.path, &b.method)));
+
+    let existing = database::get_collections(conn)
+        .map_err(|e| format!("Failed to load collections: {}", e))?
+        .into_iter()
+        .find(|c| c.id == collection_id)
+        .map(|c| c.variables)
+        .unwrap_or_default();
+    let variables = generated
+        .collection
+        .variables
+        .into_iter()
+        .filter(|(name, _)| !existing.iter().any(|(existing, _)| existing == name))
+        .collect();
+
+    Ok(ResyncPlan {
+        collection_id,
+        spec_id: record.id,
+        title: spec.title,
+        content: content.to_string(),
+        added,
+        removed,
+        changed,
+        unchanged,
+        variables,
+    })
+}
+
+/// A field is updated when the spec changed it and the user didn't; when both
+/// did, the user's version is kept.
+fn compare(
+    request: CollectionRequest,
+    generated: &GeneratedRequest,
+    saved: &RequestFingerprint,
+    previous: Option<&RequestFingerprint>,
+) -> Result<ChangedOperation, String> {
+    let next = generated_fingerprint(generated)?;
+    let mut updated = Vec::new();
+    let mut kept = Vec::new();
+    for (index, (field, next_hash)) in fields(&next).into_iter().enumerate() {
+        let saved_hash = fields(saved)[index].1;
+        let previous_hash = previous.map(|previous| fields(previous)[index].1);
+        if next_hash == saved_hash || previous_hash == Some(next_hash) {
+            continue;
+        }
+        if previous_hash == Some(saved_hash) {
+            updated.push(field);
+        } else {
+            kept.push(field);
+        }
+    }
+    Ok(ChangedOperation {
+        request,
+        generated: generated.clone(),
+        updated,
+        kept,
+    })
+}
+
+/// Applies the selected parts of `plan` and stores the updated spec.
+pub fn apply_resync(
+    conn: &Connection,
+    plan: &ResyncPlan,
+    selected: &HashSet<ResyncItem>,
+) -> Result<ResyncSummary, String> {
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| format!("Failed to start re-sync: {}", e))?;
+    let conn = &*tx;
+    let mut summary = ResyncSummary::default();
+    database::update_openapi_spec(conn, plan.spec_id, &plan.title, &plan.content)
+        .map_err(|e| format!("Failed to save spec: {}", e))?;
+
+    if !plan.variables.is_empty() {
+        if let Some(mut collection) = database::get_collections(conn)
+            .map_err(|e| format!("Failed to load collections: {}", e))?
+            .into_iter()
+            .find(|c| c.id == plan.collection_id)
+        {
+            collection.variables.extend(plan.variables.iter().cloned());
+            database::update_collection(conn, &collection)
+                .map_err(|e| format!("Failed to save collection variables: {}", e))?;
+        }
+    }
+
+    let mut folders = database::get_folders(conn, plan.collection_id)
+        .map_err(|e| format!("Failed to load folders: {}", e))?;
+    for req in &plan.added {
+        if !selected.contains(&ResyncItem::Add(req.method.clone(), req.path.clone())) {
+            continue;
+        }
+        let folder_id = match &req.folder_name {
+            Some(name) => match folders
+                .iter()
+                .find(|f| &f.name == name && f.parent_folder_id.is_none())
+            {
+                Some(folder) => Some(folder.id),
+                None => {
+                    let folder = database::create_folder(conn, plan.collection_id, name, None)
+                        .map_err(|e| format!("Failed to create folder: {}", e))?;
+                    let id = folder.id;
+                    folders.push(folder);
+                    Some(id)
+                }
+            },
+            None => None,
+        };
+        save_generated_request(conn, plan.collection_id, folder_id, plan.spec_id, req)?;
+        summary.added += 1;
+    }
+
+    for change in &plan.changed {
+        if !selected.contains(&ResyncItem::Request(change.request.id)) {
+            continue;
+        }
+        let mut request = change.request.clone();
+        let generated = &change.generated;
+        for field in &change.updated {
+            match *field {
+                "name" => request.name = generated.name.clone(),
+                "url" => request.url = generated.url.clone(),
+                "headers" => request.headers = generated.headers.clone(),
+                "body" => request.body = generated.body.clone(),
+                "params" => request.params = generated.params.clone(),
+                "auth" => {
+                    request.auth_type = generated.auth.stored_type().to_string();
+                    request.auth_data = generated_auth_data(generated)?;
+                }
+                _ => {}
+            }
+        }
+        database::update_collection_request(conn, &request)
+            .map_err(|e| format!("Failed to update request '{}': {}", request.name, e))?;
+        relink(conn, plan.spec_id, change)?;
+        summary.updated += 1;
+    }
+    for change in &plan.unchanged {
+        relink(conn, plan.spec_id, change)?;
+    }
+
+    for removal in &plan.removed {
+        let request = &removal.request;
+        if !selected.contains(&ResyncItem::Request(request.id)) {
+            continue;
+        }
+        let result = if removal.edited {
+            database::update_collection_request_operation(conn, request.id, None)
+        } else {
+            database::delete_collection_request(conn, request.id)
+        };
+        result.map_err(|e| format!("Failed to remove request '{}': {}", request.name, e))?;
+        summary.removed += 1;
+    }
+    tx.commit()
+        .map_err(|e| format!("Failed to save re-sync: {}", e))?;
+    Ok(summary)
+}
+
+/// Records what the spec now generates, so the next re-sync compares against it.
+fn relink(conn: &Connection, spec_id: i32, change: &ChangedOperation) -> Result<(), String> {
+    let operation = OperationRef {
+        spec_id,
+        path: change.generated.path.clone(),
+        method: change.generated.method.clone(),
+        generated: Some(generated_fingerprint(&change.generated)?),
+    };
+    database::update_collection_request_operation(conn, change.request.id, Some(&operation))
+        .map_err(|e| format!("Failed to link request '{}': {}", change.request.name, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::openapi_service::{import_spec, import_spec_with, ImportOptions};
+
+    const V1: &str = r#"
+openapi: 3.0.0
+info: { title: Shop, version: "1" }
+paths:
+  /orders:
+    get:
+      operationId: listOrders
+      parameters:
+        - { name: limit, in: query, example: "10" }
+      responses: { "200": { description: ok } }
+    post:
+      operationId: createOrder
+      requestBody:
+        content:
+          application/json: { example: { item: book } }
+      responses: { "201": { description: created } }
+  /legacy:
+    get:
+      operationId: legacy
+      responses: { "200": { description: ok } }
+  /legacy/edited:
+    get:
+      operationId: legacyEdited
+      responses: { "200": { description: ok } }
+"#;
+
+    const V2: &str = r#"
+openapi: 3.0.0
+info: { title: Shop, version: "2" }
+paths:
+  /orders:
+    get:
+      operationId: listOrders
+      parameters:
+        - { name: limit, in: query, example: "50" }
+      responses: { "200": { description: ok } }
+    post:
+      operationId: createOrder
+      requestBody:
+        content:
+          application/json: { example: { item: book, quantity: 1 } }
+      responses: { "201": { description: created } }
+  /refunds:
+    post:
+      operationId: createRefund
+      responses: { "201": { description: created } }
+"#;
+
+    fn request(conn: &Connection, collection_id: i32, name: &str) -> CollectionRequest {
+        run_service::requests_for_run(conn, collection_id, None)
+            .into_iter()
+            .find(|req| req.name == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn resync_applies_spec_changes_and_keeps_user_edits() {
+        let conn = database::open(Path::new(":memory:")).unwrap();
+        let collection_id = import_spec(&conn, V1).unwrap().collection.id;
+
+        let mut create = request(&conn, collection_id, "createOrder");
+        create.body = Some("{\"item\": \"my own body\"}".to_string());
+        database::update_collection_request(&conn, &create).unwrap();
+        let mut edited = request(&conn, collection_id, "legacyEdited");
+        edited
+            .headers
+            .push(("X-Trace".to_string(), "1".to_string()));
+        database::update_collection_request(&conn, &edited).unwrap();
+
+        let plan = plan_resync(&conn, collection_id, V2).unwrap();
+        let added: Vec<&str> = plan.added.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(added, vec!["/refunds"]);
+        let mut removed: Vec<(&str, bool)> = plan
+            .removed
+            .iter()
+            .map(|r| (r.request.name.as_str(), r.edited))
+            .collect();
+        removed.sort();
+        assert_eq!(removed, vec![("legacy", false), ("legacyEdited", true)]);
+        let mut changed: Vec<(&str, Vec<&str>, Vec<&str>)> = plan
+            .changed
+            .iter()
+            .map(|c| (c.request.name.as_str(), c.updated.clone(), c.kept.clone()))
+            .collect();
+        changed.sort();
+        assert_eq!(
+            changed,
+            vec![
+                ("createOrder", vec![], vec!["body"]),
+                ("listOrders", vec!["params"], vec![]),
+            ]
+        );
+
+        let summary = apply_resync(&conn, &plan, &plan.items()).unwrap();
+        assert_eq!(
+            summary,
+            ResyncSummary {
+                added: 1,
+                updated: 2,
+                removed: 2
+            }
+        );
+        let list = request(&conn, collection_id, "listOrders");
+        assert_eq!(list.params, vec![("limit".to_string(), "50".to_string())]);
+        let create = request(&conn, collection_id, "createOrder");
+        assert_eq!(create.body.as_deref(), Some("{\"item\": \"my own body\"}"));
+        assert_eq!(request(&conn, collection_id, "createRefund").method, "POST");
+        assert!(request(&conn, collection_id, "legacyEdited")
+            .operation
+            .is_none());
+        assert!(run_service::requests_for_run(&conn, collection_id, None)
+            .iter()
+            .all(|req| req.name != "legacy"));
+
+        let again = plan_resync(&conn, collection_id, V2).unwrap();
+        assert!(again.is_empty());
+        assert_eq!(again.unchanged.len(), 3);
+    }
+
+    #[test]
+    fn unselected_changes_are_left_for_the_next_resync() {
+        let conn = database::open(Path::new(":memory:")).unwrap();
+        let collection_id = import_spec(&conn, V1).unwrap().collection.id;
+        let plan = plan_resync(&conn, collection_id, V2).unwrap();
+        let summary = apply_resync(&conn, &plan, &HashSet::new()).unwrap();
+        assert_eq!(summary, ResyncSummary::default());
+
+        let again = plan_resync(&conn, collection_id, V2).unwrap();
+        assert_eq!(again.added.len(), 1);
+        assert_eq!(again.removed.len(), 2);
+        assert_eq!(again.changed.len(), 2);
+    }
+
+    #[test]
+    fn resync_keeps_the_import_selection_and_grouping() {
+        let conn = database::open(Path::new(":memory:")).unwrap();
+        let options = ImportOptions {
+            operations: Some(HashSet::from([("GET".to_string(), "/orders".to_string())])),
+            grouping: FolderGrouping::PathSegment,
+            source: None,
+        };
+        let collection_id = import_spec_with(&conn, V1, &options).unwrap().collection.id;
+        let plan = plan_resync(&conn, collection_id, V2).unwrap();
+        let added: Vec<_> = plan
+            .added
+            .iter()
+            .map(|req| (req.method.as_str(), req.path.as_str()))
+            .collect();
+        assert_eq!(added, vec![("POST", "/refunds")]);
+        assert_eq!(plan.added[0].folder_name.as_deref(), Some("refunds"));
+        assert!(plan.removed.is_empty());
+    }
+
+    #[test]
+    fn resync_fails_on_missing_refs_of_kept_operations() {
+        let conn = database::open(Path::new(":memory:")).unwrap();
+        let options = ImportOptions {
+            operations: Some(HashSet::from([("GET".to_string(), "/orders".to_string())])),
+            ..Default::default()
+        };
+        let collection_id = import_spec_with(&conn, V1, &options).unwrap().collection.id;
+
+        let broken_excluded = V2.replace(
+            "responses: { \"201\": { description: created } }\n  /refunds",
+            "responses: { \"201\": { $ref: \"#/components/responses/Gone\" } }\n  /refunds",
+        );
+        assert_ne!(broken_excluded, V2);
+        assert!(plan_resync(&conn, collection_id, &broken_excluded).is_ok());
+
+        let broken_kept = V2.replace(
+            "- { name: limit, in: query, example: \"50\" }",
+            "- $ref: \"#/components/parameters/Gone\"",
+        );
+        assert_eq!(
+            plan_resync(&conn, collection_id, &broken_kept).unwrap_err(),
+            "Unresolved $ref #/components/parameters/Gone in GET /orders"
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use crate::persistence::database::{self, CollectionFolder, CollectionRequest, CollectionRun};
+use crate::runner::collection::RunReport;
+use rusqlite::Connection;
+
+pub const RUN_HISTORY_LIMIT: usize = 50;
+
+/// Requests of a collection, or of one folder and its subfolders, in run order:
+/// a folder's own requests by `sort_order`, then each subfolder in turn.
+pub fn requests_for_run(
+    conn: &Connection,
+    collection_id: i32,
+    folder_id: Option<i32>,
+) -> Vec<CollectionRequest> {
+    let folders = database::get_folders(conn, collection_id).unwrap_or_default();
+    let mut requests = Vec::new();
+    collect_requests(conn, collection_id, folder_id, &folders, &mut requests);
+    requests
+}
+
+fn collect_requests(
+    conn: &Connection,
+    collection_id: i32,
+    folder_id: Option<i32>,
+    folders: &[CollectionFolder],
+    out: &mut Vec<CollectionRequest>,
+) {
+    out.extend(
+        database::get_collection_requests(conn, collection_id, folder_id).unwrap_or_default(),
+    );
+    for child in folders.iter().filter(|f| f.parent_folder_id == folder_id) {
+        collect_requests(conn, collection_id, Some(child.id), folders, out);
+    }
+}
+
+pub fn save_run(
+    conn: &Connection,
+    collection_id: i32,
+    folder_id: Option<i32>,
+    report: &RunReport,
+) -> Result<i32, String> {
+    let run = CollectionRun {
+        id: 0,
+        collection_id,
+        folder_id,
+        started_at: report.started_at.clone(),
+        duration_ms: report.duration_ms,
+        total: report.steps.len() as u32,
+        passed: report.passed_count() as u32,
+        failed: report.failed_count() as u32,
+        report: serde_json::to_string(report)
+            .map_err(|e| format!("Failed to serialize run report: {}", e))?,
+    };
+    database::save_collection_run(conn, &run).map_err(|e| e.to_string())
+}
+
+pub fn get_runs(conn: &Connection, collection_id: i32) -> Vec<CollectionRun> {
+    database::get_collection_runs(conn, collection_id, RUN_HISTORY_LIMIT).unwrap_or_default()
+}
+
+pub fn load_report(run: &CollectionRun) -> Result<RunReport, String> {
+    serde_json::from_str(&run.report).map_err(|e| format!("Failed to read run report: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::collection::StepResult;
+
+    fn setup_test_db() -> Connection {
+        crate::persistence::database::open(std::path::Path::new(":memory:")).unwrap()
+    }
+
+    fn add_request(conn: &Connection, collection_id: i32, folder_id: Option<i32>, name: &str) {
+        database::save_collection_request(
+            conn,
+            collection_id,
+            folder_id,
+            name,
+            "GET",
+            "/",
+            &[],
+            None,
+            "text",
+            "none",
+            None,
+            &[],
+            None,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn requests_for_run_walks_folders_depth_first() {
+        let conn = setup_test_db();
+        let col = database::create_collection(&conn, "API", None).unwrap();
+        let auth = database::create_folder(&conn, col.id, "Auth", None).unwrap();
+        let nested = database::create_folder(&conn, col.id, "Admin", Some(auth.id)).unwrap();
+        add_request(&conn, col.id, None, "root");
+        add_request(&conn, col.id, Some(nested.id), "nested");
+        add_request(&conn, col.id, Some(auth.id), "login");
+
+        let names = |requests: Vec<CollectionRequest>| -> Vec<String> {
+            requests.into_iter().map(|r| r.name).collect()
+        };
+        assert_eq!(
+            names(requests_for_run(&conn, col.id, None)),
+            vec!["root", "login", "nested"]
+        );
+        assert_eq!(
+            names(requests_for_run(&conn, col.id, Some(auth.id))),
+            vec!["login", "nested"]
+        );
+    }
+
+    #[test]
+    fn saved_run_reloads_its_report() {
+        let conn = setup_test_db();
+        let col = database::create_collection(&conn, "API", None).unwrap();
+        let report = RunReport {
+            name: "API".to_string(),
+            started_at: "2024-01-01T00:00:00Z".to_string(),
+            duration_ms: 40,
+            steps: vec![StepResult {
+                iteration: 0,
+                request_id: 1,
+                name: "Health".to_string(),
+                method: "GET".to_string(),
+                url: "http://localhost/health".to_string(),
+                status: Some(500),
+                duration_ms: 40,
+                assertions: vec![],
+                extracted: vec![],
+                extraction_errors: vec![],
+                error: Some("boom".to_string()),
+            }],
+            stopped_early: false,
+            iterations: 1,
+        };
+        save_run(&conn, col.id, None, &report).unwrap();
+
+        let runs = get_runs(&conn, col.id);
+        assert_eq!(runs.len(), 1);
+        assert_eq!((runs[0].total, runs[0].failed), (1, 1));
+        assert_eq!(load_report(&runs[0]).unwrap(), report);
+    }
+}
@@ -1,9 +1,16 @@
+use crate::data::variables::{VariableResolver, VariableScope};
 use crate::persistence::database::{self, Environment};
 use crate::protocols::websocket::{WsEvent, WsSender};
 use crate::ui::toast::ToastManager;
 use crate::ui::views::collection_view::{self, CollectionView};
 use crate::ui::views::environment_manager::{self, EnvironmentManagerView};
 use crate::ui::views::history_view::{self, HistoryView};
+use crate::ui::views::load_test_view::{self, LoadTestView};
+use crate::ui::views::mock_server_view::{self, MockServerView};
+use crate::ui::views::openapi_import_view::{self, OpenApiImportView};
+use crate::ui::views::openapi_resync_view::{self, OpenApiResyncView};
+use crate::ui::views::runner_view::{self, RunnerView};
+use crate::ui::views::schema_browser_view::{self, SchemaBrowserView};
 use crate::ui::views::websocket_view::{self, WebSocketView};
 use iced::{
     widget::{button, column, container, pick_list, row, rule, stack, text},
@@ -80,6 +87,12 @@ impl Protocol {
 pub enum View {
     Main,
     EnvironmentManager,
+    Runner,
+    LoadTest,
+    MockServer,
+    OpenApiImport,
+    OpenApiResync,
+    SchemaBrowser,
 }
 
 pub fn main() -> iced::Result {
@@ -94,14 +107,25 @@ pub(crate) struct AstraNovaApp {
     pub(crate) request_tabs: Vec<HttpRequestView>,
     pub(crate) active_request_tab_index: usize,
     pub(crate) http_client: reqwest::Client,
+    pub(crate) token_cache: crate::data::token_cache::TokenCache,
+    pub(crate) spec_cache: crate::services::openapi_service::SpecCache,
     pub(crate) db_conn: rusqlite::Connection,
     pub(crate) environments: Vec<Environment>,
     pub(crate) active_environment: Option<Environment>,
+    pub(crate) global_variables: Vec<(String, String)>,
+    pub(crate) runtime_variables: Vec<(String, String)>,
+    pub(crate) vault: Option<crate::data::vault::Vault>,
     pub(crate) env_manager_view: EnvironmentManagerView,
     pub(crate) history_view: HistoryView,
     pub(crate) collection_view: CollectionView,
     pub(crate) websocket_view: WebSocketView,
     pub(crate) graphql_view: GraphQLView,
+    pub(crate) runner_view: RunnerView,
+    pub(crate) load_test_view: LoadTestView,
+    pub(crate) mock_server_view: MockServerView,
+    pub(crate) openapi_import_view: OpenApiImportView,
+    pub(crate) openapi_resync_view: OpenApiResyncView,
+    pub(crate) schema_browser_view: SchemaBrowserView,
     pub(crate) active_protocol: Protocol,
     pub(crate) current_view: View,
     pub(crate) show_history: bool,
@@ -135,8 +159,18 @@ pub enum Message {
     CollectionMsg(collection_view::Message),
     ToggleCollections,
     ToggleEnvInfo,
+    ClearRuntimeVariables,
     WebSocketMsg(websocket_view::Message),
     GraphQLMsg(graphql_view::Message),
+    RunnerMsg(runner_view::Message),
+    RunnerEvent(crate::runner::collection::RunEvent),
+    LoadTestMsg(load_test_view::Message),
+    LoadTestEvent(crate::runner::load::LoadEvent),
+    MockServerMsg(mock_server_view::Message),
+    MockServerEvent(crate::mock::MockEvent),
+    OpenApiImportMsg(openapi_import_view::Message),
+    OpenApiResyncMsg(openapi_resync_view::Message),
+    SchemaBrowserMsg(schema_browser_view::Message),
     WsEvent(crate::protocols::websocket::WsEvent),
     WsConnected(
         WsSender,
@@ -147,7 +181,11 @@ pub enum Message {
     ),
     SelectProtocol(Protocol),
     OAuth2StartAuth(usize),
-    OAuth2AuthComplete(usize, Result<String, String>, Option<String>),
+    OAuth2AuthComplete(
+        usize,
+        Result<crate::data::oauth2::AuthorizationResponse, String>,
+        Option<String>,
+    ),
     OAuth2TokenReceived(
         usize,
         Result<crate::data::oauth2::OAuth2TokenResponse, String>,
@@ -162,6 +200,17 @@ pub enum Message {
         usize,
         Result<crate::data::oauth2::DeviceTokenResponse, String>,
     ),
+    OAuth2Discover(usize),
+    OAuth2DiscoveryReceived(usize, Result<crate::data::oidc::OidcDiscovery, String>),
+    OAuth2VerifyIdToken(usize),
+    OAuth2IdTokenVerified(usize, Result<String, String>),
+    OAuth2Introspect(usize),
+    OAuth2IntrospectionReceived(
+        usize,
+        Result<crate::data::oauth2::IntrospectionResponse, String>,
+    ),
+    OAuth2Revoke(usize),
+    OAuth2Revoked(usize, Result<(), String>),
 }
 
 impl Clone for Message {
@@ -185,8 +234,18 @@ impl Clone for Message {
             Self::CollectionMsg(m) => Self::CollectionMsg(m.clone()),
             Self::ToggleCollections => Self::ToggleCollections,
             Self::ToggleEnvInfo => Self::ToggleEnvInfo,
+            Self::ClearRuntimeVariables => Self::ClearRuntimeVariables,
             Self::WebSocketMsg(m) => Self::WebSocketMsg(m.clone()),
             Self::GraphQLMsg(m) => Self::GraphQLMsg(m.clone()),
+            Self::RunnerMsg(m) => Self::RunnerMsg(m.clone()),
+            Self::RunnerEvent(e) => Self::RunnerEvent(e.clone()),
+            Self::LoadTestMsg(m) => Self::LoadTestMsg(m.clone()),
+            Self::LoadTestEvent(e) => Self::LoadTestEvent(e.clone()),
+            Self::MockServerMsg(m) => Self::MockServerMsg(m.clone()),
+            Self::MockServerEvent(e) => Self::MockServerEvent(e.clone()),
+            Self::OpenApiImportMsg(m) => Self::OpenApiImportMsg(m.clone()),
+            Self::OpenApiResyncMsg(m) => Self::OpenApiResyncMsg(m.clone()),
+            Self::SchemaBrowserMsg(m) => Self::SchemaBrowserMsg(m.clone()),
             Self::WsEvent(e) => Self::WsEvent(e.clone()),
             Self::WsConnected(s, r, st, wh, rh) => Self::WsConnected(
                 s.clone(),
@@ -203,6 +262,16 @@ impl Clone for Message {
             Self::OAuth2StartDeviceAuth(i) => Self::OAuth2StartDeviceAuth(*i),
             Self::OAuth2DeviceAuthReceived(i, r) => Self::OAuth2DeviceAuthReceived(*i, r.clone()),
             Self::OAuth2DeviceTokenPoll(i, r) => Self::OAuth2DeviceTokenPoll(*i, r.clone()),
+            Self::OAuth2Discover(i) => Self::OAuth2Discover(*i),
+            Self::OAuth2DiscoveryReceived(i, r) => Self::OAuth2DiscoveryReceived(*i, r.clone()),
+            Self::OAuth2VerifyIdToken(i) => Self::OAuth2VerifyIdToken(*i),
+            Self::OAuth2IdTokenVerified(i, r) => Self::OAuth2IdTokenVerified(*i, r.clone()),
+            Self::OAuth2Introspect(i) => Self::OAuth2Introspect(*i),
+            Self::OAuth2IntrospectionReceived(i, r) => {
+                Self::OAuth2IntrospectionReceived(*i, r.clone())
+            }
+            Self::OAuth2Revoke(i) => Self::OAuth2Revoke(*i),
+            Self::OAuth2Revoked(i, r) => Self::OAuth2Revoked(*i, r.clone()),
         }
     }
 }
@@ -230,11 +299,19 @@ impl AstraNovaApp {
                     "ALTER TABLE environments ADD COLUMN default_endpoint TEXT",
                     [],
                 );
+                let _ = conn.execute(
+                    "ALTER TABLE environments ADD COLUMN secret_keys TEXT NOT NULL DEFAULT '[]'",
+                    [],
+                );
                 (conn, Vec::new())
             }
         };
 
         let history = crate::services::history_service::get_all(&db_conn, 50);
+        let global_variables = crate::services::environment_service::get_globals(&db_conn);
+        let mut env_manager_view = EnvironmentManagerView::new(environments.clone());
+        env_manager_view.set_globals(&global_variables);
+        env_manager_view.vault_exists = crate::services::environment_service::has_vault(&db_conn);
         let collections = crate::services::collection_service::get_all(&db_conn);
 
         let mut cv = CollectionView::new();
@@ -244,10 +321,15 @@ impl AstraNovaApp {
             request_tabs: vec![HttpRequestView::default()],
             active_request_tab_index: 0,
             http_client: reqwest::Client::new(),
+            token_cache: crate::data::token_cache::TokenCache::new(),
+            spec_cache: crate::services::openapi_service::SpecCache::new(),
             db_conn,
             environments: environments.clone(),
             active_environment: None,
-            env_manager_view: EnvironmentManagerView::new(environments),
+            global_variables,
+            runtime_variables: Vec::new(),
+            vault: None,
+            env_manager_view,
             history_view: {
                 let mut hv = HistoryView::new();
                 hv.entries = history;
@@ -256,6 +338,12 @@ impl AstraNovaApp {
             collection_view: cv,
             websocket_view: WebSocketView::new(),
             graphql_view: GraphQLView::default(),
+            runner_view: RunnerView::new(),
+            load_test_view: LoadTestView::new(),
+            mock_server_view: MockServerView::new(),
+            openapi_import_view: OpenApiImportView::default(),
+            openapi_resync_view: OpenApiResyncView::default(),
+            schema_browser_view: SchemaBrowserView::default(),
             active_protocol: Protocol::Http,
             current_view: View::Main,
             show_history: false,
@@ -358,6 +446,10 @@ impl AstraNovaApp {
                 }
                 Task::none()
             }
+            Message::ClearRuntimeVariables => {
+                self.runtime_variables.clear();
+                Task::none()
+            }
             Message::ToggleEnvInfo => {
                 self.show_env_info = !self.show_env_info;
                 Task::none()
@@ -371,6 +463,23 @@ impl AstraNovaApp {
             Message::WsEvent(event) => super::handlers::websocket::handle_ws_event(self, event),
             Message::WebSocketMsg(msg) => super::handlers::websocket::handle_message(self, msg),
             Message::GraphQLMsg(msg) => super::handlers::graphql::handle_message(self, msg),
+            Message::RunnerMsg(msg) => super::handlers::runner::handle_message(self, msg),
+            Message::RunnerEvent(event) => super::handlers::runner::handle_event(self, event),
+            Message::LoadTestMsg(msg) => super::handlers::load_test::handle_message(self, msg),
+            Message::LoadTestEvent(event) => super::handlers::load_test::handle_event(self, event),
+            Message::MockServerMsg(msg) => super::handlers::mock_server::handle_message(self, msg),
+            Message::MockServerEvent(event) => {
+                super::handlers::mock_server::handle_event(self, event)
+            }
+            Message::OpenApiImportMsg(msg) => {
+                super::handlers::openapi_import::handle_message(self, msg)
+            }
+            Message::OpenApiResyncMsg(msg) => {
+                super::handlers::openapi_resync::handle_message(self, msg)
+            }
+            Message::SchemaBrowserMsg(msg) => {
+                super::handlers::schema_browser::handle_message(self, msg)
+            }
             Message::WsConnected(sender, receiver_arc, shutdown_tx, write_handle, read_handle) => {
                 super::handlers::websocket::handle_ws_connected(
                     self,
@@ -402,9 +511,56 @@ impl AstraNovaApp {
             Message::OAuth2DeviceTokenPoll(index, result) => {
                 super::handlers::oauth2::handle_device_token_poll(self, index, result)
             }
+            Message::OAuth2Discover(index) => super::handlers::oauth2::handle_discover(self, index),
+            Message::OAuth2DiscoveryReceived(index, result) => {
+                super::handlers::oauth2::handle_discovery_received(self, index, result)
+            }
+            Message::OAuth2VerifyIdToken(index) => {
+                super::handlers::oauth2::handle_verify_id_token(self, index)
+            }
+            Message::OAuth2IdTokenVerified(index, result) => {
+                super::handlers::oauth2::handle_id_token_verified(self, index, result)
+            }
+            Message::OAuth2Introspect(index) => {
+                super::handlers::oauth2::handle_introspect(self, index)
+            }
+            Message::OAuth2IntrospectionReceived(index, result) => {
+                super::handlers::oauth2::handle_introspection_received(self, index, result)
+            }
+            Message::OAuth2Revoke(index) => super::handlers::oauth2::handle_revoke(self, index),
+            Message::OAuth2Revoked(index, result) => {
+                super::handlers::oauth2::handle_revoked(self, index, result)
+            }
+        }
+    }
+
+    /// Variables visible to the active tab of the current protocol.
+    fn active_variable_resolver(&self) -> VariableResolver {
+        match self.active_protocol {
+            Protocol::Http => self.tab_variable_resolver(self.active_request_tab_index),
+            _ => VariableResolver::scoped(
+                &self.global_variables,
+                &[],
+                self.active_environment.as_ref(),
+                &[],
+            )
+            .with_scope(VariableScope::Runtime, self.runtime_variables.clone()),
         }
     }
 
+    /// Variables visible to the HTTP request tab at `index`.
+    pub(crate) fn tab_variable_resolver(&self, index: usize) -> VariableResolver {
+        let view = self.request_tabs.get(index);
+        VariableResolver::scoped(
+            &self.global_variables,
+            self.collection_view
+                .collection_variables(view.and_then(|v| v.collection_id)),
+            self.active_environment.as_ref(),
+            &view.map(|v| v.local_variables()).unwrap_or_default(),
+        )
+        .with_scope(VariableScope::Runtime, self.runtime_variables.clone())
+    }
+
     fn handle_http_request_msg(
         &mut self,
         index: usize,
@@ -417,12 +573,29 @@ impl AstraNovaApp {
 
         match msg {
             http_request_view::Message::SendRequest => {
-                let mut temp_view = view.clone();
-                if let Some(env) = &self.active_environment {
-                    temp_view.apply_environment(env);
+                if crate::data::vault::has_locked_secrets(&view.auth) {
+                    self.toast_manager
+                        .error("Unlock the vault to use this request's encrypted credentials");
+                    return Task::none();
+                }
+                let resolver = VariableResolver::scoped(
+                    &self.global_variables,
+                    self.collection_view
+                        .collection_variables(view.collection_id),
+                    self.active_environment.as_ref(),
+                    &view.local_variables(),
+                )
+                .with_scope(VariableScope::Runtime, self.runtime_variables.clone());
+                let unresolved = view.unresolved_variables(&resolver);
+                if !unresolved.is_empty() {
+                    self.toast_manager
+                        .warning(format!("Unresolved variables: {}", unresolved.join(", ")));
                 }
-                let request = temp_view.build_request();
-                view.pending_request_data = serde_json::to_string(&request).ok();
+                let request = view.build_resolved_request(&resolver);
+                let redacted = crate::data::vault::redact_auth(&view.auth);
+                let mut history_request = view.build_request_with_auth(&redacted);
+                crate::data::vault::redact_headers(&mut history_request.headers, &view.auth);
+                view.pending_request_data = serde_json::to_string(&history_request).ok();
                 view.update(http_request_view::Message::SetLoading);
 
                 let http_client =
@@ -438,8 +611,16 @@ impl AstraNovaApp {
                         self.http_client.clone()
                     };
 
+                let token_cache = self.token_cache.clone();
                 Task::perform(
-                    async move { client::send_request(&http_client, request).await },
+                    async move {
+                        crate::data::token_cache::send_with_token_cache(
+                            &http_client,
+                            request,
+                            &token_cache,
+                        )
+                        .await
+                    },
                     move |result| {
                         Message::HttpRequestViewMsg(
                             index,
@@ -449,6 +630,7 @@ impl AstraNovaApp {
                 )
             }
             http_request_view::Message::ResponseReceived(ref result) => {
+                super::handlers::oauth2::sync_cached_token(self, index);
                 let view = self.request_tabs.get_mut(index).unwrap();
                 match result {
                     Ok(response) => {
@@ -487,6 +669,13 @@ impl AstraNovaApp {
                     }
                 }
                 view.update(msg);
+                let extracted = std::mem::take(&mut view.extraction_results);
+                if !extracted.is_empty() {
+                    super::handlers::environment::apply_extracted(self, &extracted);
+                    if let Some(view) = self.request_tabs.get_mut(index) {
+                        view.extraction_results = extracted;
+                    }
+                }
                 Task::none()
             }
             http_request_view::Message::MultipartBrowseFile(entry_id) => {
@@ -513,6 +702,18 @@ impl AstraNovaApp {
             http_request_view::Message::OAuth2StartDeviceAuth => {
                 Task::perform(async {}, move |_| Message::OAuth2StartDeviceAuth(index))
             }
+            http_request_view::Message::OAuth2Discover => {
+                Task::perform(async {}, move |_| Message::OAuth2Discover(index))
+            }
+            http_request_view::Message::OAuth2VerifyIdToken => {
+                Task::perform(async {}, move |_| Message::OAuth2VerifyIdToken(index))
+            }
+            http_request_view::Message::OAuth2Introspect => {
+                Task::perform(async {}, move |_| Message::OAuth2Introspect(index))
+            }
+            http_request_view::Message::OAuth2Revoke => {
+                Task::perform(async {}, move |_| Message::OAuth2Revoke(index))
+            }
             other => {
                 if let Some(view) = self.request_tabs.get_mut(index) {
                     view.update(other);
@@ -639,7 +840,7 @@ impl AstraNovaApp {
                 )
                 .placeholder("No Environment");
 
-                let mut env_controls = row![
+                let env_controls = row![
                     history_button,
                     collections_button,
                     protocol_selector,
@@ -651,43 +852,62 @@ impl AstraNovaApp {
                 ]
                 .spacing(10);
 
-                if self.active_environment.is_some() {
-                    let chevron = if self.show_env_info {
-                        lucide::chevron_down().size(12)
-                    } else {
-                        lucide::chevron_right().size(12)
-                    };
-                    env_controls = env_controls.push(
-                        button(row![chevron, text(" Help").size(12)].spacing(4))
-                            .on_press(Message::ToggleEnvInfo),
-                    );
-                }
+                let chevron = if self.show_env_info {
+                    lucide::chevron_down().size(12)
+                } else {
+                    lucide::chevron_right().size(12)
+                };
+                let env_controls = env_controls.push(
+                    button(row![chevron, text(" Variables").size(12)].spacing(4))
+                        .on_press(Message::ToggleEnvInfo),
+                );
 
-                let env_help_section: Element<Message> =
-                    if let Some(active_env) = &self.active_environment {
-                        if self.show_env_info {
-                            let variables_text = if active_env.variables.is_empty() {
-                                "This environment has no variables.".to_string()
-                            } else {
-                                let keys: Vec<_> = active_env
-                                    .variables
-                                    .iter()
-                                    .map(|(k, _)| k.as_str())
-                                    .collect();
-                                format!("Available: {}", keys.join(", "))
-                            };
-                            column![
-                                text("Use {{variable}} in URL, Headers, or Body.").size(12),
-                                text(variables_text).size(12)
-                            ]
-                            .spacing(5)
-                            .into()
+                let env_help_section: Element<Message> = if self.show_env_info {
+                    let resolver = self.active_variable_resolver();
+                    let variables = resolver.effective_variables();
+                    let mut section = column![
+                        text("Use {{variable}} in URL, Headers, or Body.").size(12),
+                        text("Precedence: Request > Runtime > Environment > Collection > Global")
+                            .size(12),
+                    ]
+                    .spacing(5);
+                    if variables.is_empty() {
+                        section = section.push(text("No variables defined.").size(12));
+                    }
+                    let secret_keys = self
+                        .active_environment
+                        .as_ref()
+                        .map(|env| env.secret_keys.as_slice())
+                        .unwrap_or_default();
+                    for (key, value, scope) in variables {
+                        let secret = crate::data::vault::is_encrypted(&value)
+                            || (scope == VariableScope::Environment && secret_keys.contains(&key));
+                        let value = if secret {
+                            crate::data::vault::MASK.to_string()
                         } else {
-                            column![].into()
-                        }
-                    } else {
-                        column![].into()
-                    };
+                            value
+                        };
+                        section = section.push(
+                            row![
+                                text(key).size(12),
+                                text(value).size(12),
+                                text(format!("({})", scope))
+                                    .size(11)
+                                    .color(iced::Color::from_rgb(0.5, 0.5, 0.5)),
+                            ]
+                            .spacing(8),
+                        );
+                    }
+                    if !self.runtime_variables.is_empty() {
+                        section = section.push(
+                            button(text("Clear runtime variables").size(12))
+                                .on_press(Message::ClearRuntimeVariables),
+                        );
+                    }
+                    section.into()
+                } else {
+                    column![].into()
+                };
 
                 let main_content = match self.active_protocol {
                     Protocol::Http => {
@@ -786,6 +1006,21 @@ impl AstraNovaApp {
                 stack![content, toast_overlay].into()
             }
             View::EnvironmentManager => self.env_manager_view.view().map(Message::EnvManagerMsg),
+            View::Runner => self.runner_view.view().map(Message::RunnerMsg),
+            View::LoadTest => self.load_test_view.view().map(Message::LoadTestMsg),
+            View::MockServer => self.mock_server_view.view().map(Message::MockServerMsg),
+            View::OpenApiImport => self
+                .openapi_import_view
+                .view()
+                .map(Message::OpenApiImportMsg),
+            View::OpenApiResync => self
+                .openapi_resync_view
+                .view()
+                .map(Message::OpenApiResyncMsg),
+            View::SchemaBrowser => self
+                .schema_browser_view
+                .view()
+                .map(Message::SchemaBrowserMsg),
         }
     }
 }
@@ -25,6 +25,8 @@ pub struct KeyValueEditor {
     pub entries: Vec<KeyValueEntry>,
     next_id: usize,
     button_text: String,
+    allow_secrets: bool,
+    secret_ids: Vec<usize>,
 }
 
 #[derive(Debug, Clone)]
@@ -33,6 +35,7 @@ pub enum Message {
     EntryValueChanged(usize, String),
     AddEntry,
     RemoveEntry(usize),
+    ToggleSecret(usize),
 }
 
 impl Default for KeyValueEditor {
@@ -47,8 +50,40 @@ impl KeyValueEditor {
             entries: vec![KeyValueEntry::new(0)],
             next_id: 1,
             button_text,
+            allow_secrets: false,
+            secret_ids: Vec::new(),
         }
     }
+
+    /// Shows a lock toggle per entry; secret values are masked.
+    pub fn with_secrets(mut self) -> Self {
+        self.allow_secrets = true;
+        self
+    }
+
+    pub fn is_secret(&self, id: usize) -> bool {
+        self.secret_ids.contains(&id)
+    }
+
+    pub fn set_secret(&mut self, id: usize, secret: bool) {
+        self.secret_ids.retain(|&s| s != id);
+        if secret {
+            self.secret_ids.push(id);
+        }
+    }
+
+    /// Keys of entries marked secret.
+    pub fn secret_keys(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter(|e| !e.key.is_empty() && self.is_secret(e.id))
+            .map(|e| e.key.clone())
+            .collect()
+    }
+
+    pub fn clear_secrets(&mut self) {
+        self.secret_ids.clear();
+    }
     pub fn update(&mut self, message: Message) {
         match message {
             Message::EntryKeyChanged(id, new_key) => {
@@ -67,6 +102,11 @@ impl KeyValueEditor {
             }
             Message::RemoveEntry(id) => {
                 self.entries.retain(|entry| entry.id != id);
+                self.secret_ids.retain(|&s| s != id);
+            }
+            Message::ToggleSecret(id) => {
+                let secret = self.is_secret(id);
+                self.set_secret(id, !secret);
             }
         }
     }
@@ -76,15 +116,23 @@ impl KeyValueEditor {
             .entries
             .iter()
             .fold(column![].spacing(8), |col, entry| {
+                let secret = self.is_secret(entry.id);
+                let mut entry_row = row![
+                    text_input("Key", &entry.key)
+                        .on_input(move |k| Message::EntryKeyChanged(entry.id, k)),
+                    text_input("Value", &entry.value)
+                        .secure(secret)
+                        .on_input(move |v| Message::EntryValueChanged(entry.id, v)),
+                ]
+                .spacing(10);
+                if self.allow_secrets {
+                    entry_row = entry_row.push(
+                        button(text(if secret { "Secret" } else { "Plain" }))
+                            .on_press(Message::ToggleSecret(entry.id)),
+                    );
+                }
                 col.push(
-                    row![
-                        text_input("Key", &entry.key)
-                            .on_input(move |k| Message::EntryKeyChanged(entry.id, k)),
-                        text_input("Value", &entry.value)
-                            .on_input(move |v| Message::EntryValueChanged(entry.id, v)),
-                        button(text("Remove")).on_press(Message::RemoveEntry(entry.id))
-                    ]
-                    .spacing(10),
+                    entry_row.push(button(text("Remove")).on_press(Message::RemoveEntry(entry.id))),
                 )
             });
 
@@ -182,4 +230,24 @@ mod tests {
         let editor = KeyValueEditor::new("Add Header".to_string());
         assert_eq!(editor.button_text, "Add Header");
     }
+
+    #[test]
+    fn toggle_secret_marks_entry_key() {
+        let mut editor = KeyValueEditor::default().with_secrets();
+        editor.update(Message::EntryKeyChanged(0, "TOKEN".to_string()));
+        editor.update(Message::ToggleSecret(0));
+        assert!(editor.is_secret(0));
+        assert_eq!(editor.secret_keys(), vec!["TOKEN"]);
+
+        editor.update(Message::ToggleSecret(0));
+        assert!(editor.secret_keys().is_empty());
+    }
+
+    #[test]
+    fn remove_entry_clears_secret_flag() {
+        let mut editor = KeyValueEditor::default().with_secrets();
+        editor.update(Message::ToggleSecret(0));
+        editor.update(Message::RemoveEntry(0));
+        assert!(!editor.is_secret(0));
+    }
 }
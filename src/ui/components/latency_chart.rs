@@ -0,0 +1,178 @@
+use crate::runner::load::TimelinePoint;
+use iced::mouse;
+use iced::widget::canvas::{self, Frame, Geometry, Path, Stroke, Text};
+use iced::{Color, Pixels, Point, Rectangle, Renderer, Size, Theme};
+
+const PADDING_LEFT: f32 = 56.0;
+const PADDING_BOTTOM: f32 = 20.0;
+const PADDING_TOP: f32 = 18.0;
+
+const MEAN_COLOR: Color = Color::from_rgb(0.2, 0.5, 0.8);
+const P90_COLOR: Color = Color::from_rgb(0.8, 0.5, 0.1);
+const ERROR_COLOR: Color = Color::from_rgb(0.8, 0.2, 0.2);
+const AXIS_COLOR: Color = Color::from_rgb(0.5, 0.5, 0.5);
+
+/// Mean and p90 latency per second of a load test, with seconds that had errors marked.
+pub struct LatencyChart<'a> {
+    pub timeline: &'a [TimelinePoint],
+}
+
+impl<Message> canvas::Program<Message> for LatencyChart<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let plot = Rectangle {
+            x: PADDING_LEFT,
+            y: PADDING_TOP,
+            width: (bounds.width - PADDING_LEFT - 8.0).max(1.0),
+            height: (bounds.height - PADDING_TOP - PADDING_BOTTOM).max(1.0),
+        };
+
+        let axes = Path::new(|b| {
+            b.move_to(Point::new(plot.x, plot.y));
+            b.line_to(Point::new(plot.x, plot.y + plot.height));
+            b.line_to(Point::new(plot.x + plot.width, plot.y + plot.height));
+        });
+        frame.stroke(&axes, Stroke::default().with_color(AXIS_COLOR));
+
+        let max_ms = self
+            .timeline
+            .iter()
+            .map(|p| p.p90_ms.max(p.mean_ms))
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        label(
+            &mut frame,
+            format!("{} ms", max_ms),
+            Point::new(4.0, plot.y - 6.0),
+        );
+        label(
+            &mut frame,
+            "0".to_string(),
+            Point::new(4.0, plot.y + plot.height - 6.0),
+        );
+        if let Some(last) = self.timeline.last() {
+            label(
+                &mut frame,
+                format!("{}s", last.second + 1),
+                Point::new(plot.x + plot.width - 24.0, plot.y + plot.height + 4.0),
+            );
+        }
+        legend(
+            &mut frame,
+            "mean",
+            MEAN_COLOR,
+            Point::new(plot.x + 8.0, 2.0),
+        );
+        legend(&mut frame, "p90", P90_COLOR, Point::new(plot.x + 70.0, 2.0));
+        legend(
+            &mut frame,
+            "errors",
+            ERROR_COLOR,
+            Point::new(plot.x + 124.0, 2.0),
+        );
+
+        let mean: Vec<u64> = self.timeline.iter().map(|p| p.mean_ms).collect();
+        let p90: Vec<u64> = self.timeline.iter().map(|p| p.p90_ms).collect();
+        for (values, color) in [(&p90, P90_COLOR), (&mean, MEAN_COLOR)] {
+            let points = plot_points(values, max_ms, plot);
+            if let Some((first, rest)) = points.split_first() {
+                let line = Path::new(|b| {
+                    b.move_to(*first);
+                    for point in rest {
+                        b.line_to(*point);
+                    }
+                });
+                frame.stroke(&line, Stroke::default().with_color(color).with_width(2.0));
+            }
+        }
+
+        let mean_points = plot_points(&mean, max_ms, plot);
+        for (point, entry) in mean_points.iter().zip(self.timeline) {
+            if entry.errors > 0 {
+                frame.fill(&Path::circle(*point, 3.5), ERROR_COLOR);
+            }
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+/// Spreads `values` across the plot width, scaled so `max` reaches the top.
+fn plot_points(values: &[u64], max: u64, plot: Rectangle) -> Vec<Point> {
+    let step = if values.len() > 1 {
+        plot.width / (values.len() - 1) as f32
+    } else {
+        0.0
+    };
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let ratio = (*value as f32 / max.max(1) as f32).min(1.0);
+            Point::new(
+                plot.x + step * i as f32,
+                plot.y + plot.height * (1.0 - ratio),
+            )
+        })
+        .collect()
+}
+
+fn label(frame: &mut Frame, content: String, position: Point) {
+    frame.fill_text(Text {
+        content,
+        position,
+        color: AXIS_COLOR,
+        size: Pixels(11.0),
+        ..Text::default()
+    });
+}
+
+fn legend(frame: &mut Frame, name: &str, color: Color, position: Point) {
+    frame.fill_rectangle(
+        Point::new(position.x, position.y + 4.0),
+        Size::new(10.0, 3.0),
+        color,
+    );
+    frame.fill_text(Text {
+        content: name.to_string(),
+        position: Point::new(position.x + 14.0, position.y - 2.0),
+        color: AXIS_COLOR,
+        size: Pixels(11.0),
+        ..Text::default()
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn points_span_the_plot_and_scale_to_max() {
+        let plot = Rectangle {
+            x: 10.0,
+            y: 0.0,
+            width: 100.0,
+            height: 50.0,
+        };
+        let points = plot_points(&[0, 50, 100], 100, plot);
+        assert_eq!(
+            points,
+            vec![
+                Point::new(10.0, 50.0),
+                Point::new(60.0, 25.0),
+                Point::new(110.0, 0.0)
+            ]
+        );
+        assert_eq!(plot_points(&[7], 7, plot), vec![Point::new(10.0, 0.0)]);
+    }
+}
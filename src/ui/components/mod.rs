@@ -1 +1,2 @@
 pub mod key_value_editor;
+pub mod latency_chart;
@@ -1,3 +1,4 @@
+use crate::persistence::database::CollectionRequest;
 use crate::ui::app::{AstraNovaApp, Message};
 use crate::ui::views::collection_view;
 use iced::Task;
@@ -29,6 +30,9 @@ pub fn handle_message(app: &mut AstraNovaApp, msg: collection_view::Message) ->
                 let reqs =
                     crate::services::collection_service::get_requests(&app.db_conn, col_id, None);
                 app.collection_view.sync_requests(&reqs);
+                app.collection_view.has_openapi_spec =
+                    crate::persistence::database::get_collection_openapi_spec(&app.db_conn, col_id)
+                        .is_ok_and(|spec| spec.is_some());
             }
         }
         collection_view::Message::SelectFolder(folder_id) => {
@@ -101,7 +105,7 @@ pub fn handle_message(app: &mut AstraNovaApp, msg: collection_view::Message) ->
             return Task::perform(
                 async move {
                     let file = rfd::AsyncFileDialog::new()
-                        .add_filter("Postman Collection", &["json"])
+                        .add_filter("AstraNova or Postman Collection", &["json"])
                         .pick_file()
                         .await;
                     if let Some(file_handle) = file {
@@ -117,6 +121,25 @@ pub fn handle_message(app: &mut AstraNovaApp, msg: collection_view::Message) ->
                 },
             );
         }
+        collection_view::Message::ImportCollectionData(Some(json))
+            if crate::export::astranova::is_astranova_collection(&json) =>
+        {
+            let imported = crate::export::astranova::parse_collection(&json).and_then(|c| {
+                crate::services::collection_service::import_astranova(&app.db_conn, &c)
+            });
+            match imported {
+                Ok(col) => {
+                    let cols = crate::services::collection_service::get_all(&app.db_conn);
+                    app.collection_view.sync_collections(&cols);
+                    app.toast_manager
+                        .success(format!("Imported collection {}", col.name));
+                }
+                Err(e) => {
+                    log::error!("Error importing collection: {}", e);
+                    app.toast_manager.error(format!("Import failed: {}", e));
+                }
+            }
+        }
         collection_view::Message::ImportCollectionData(Some(json)) => {
             match crate::import::postman::parse_postman_collection(&json) {
                 Ok(imported) => {
@@ -194,7 +217,7 @@ pub fn handle_message(app: &mut AstraNovaApp, msg: collection_view::Message) ->
                     if let Some(file_handle) = file {
                         let data = file_handle.read().await;
                         if let Ok(content) = std::str::from_utf8(&data) {
-                            return Some(content.to_string());
+                            return Some((file_handle.path().to_path_buf(), content.to_string()));
                         }
                     }
                     None
@@ -204,133 +227,31 @@ pub fn handle_message(app: &mut AstraNovaApp, msg: collection_view::Message) ->
                 },
             );
         }
-        collection_view::Message::ImportOpenApiData(Some(content)) => {
-            let parse_result = if content.trim_start().starts_with('{') {
-                crate::openapi::parse_spec(&content)
-            } else {
-                crate::openapi::parse_spec_from_yaml(&content)
-            };
-
-            match parse_result {
-                Ok(spec) => {
-                    let collection_id = app
-                        .db_conn
-                        .query_row(
-                            "SELECT COALESCE(MAX(id), 0) + 1 FROM collections",
-                            [],
-                            |row| row.get::<_, i32>(0),
-                        )
-                        .unwrap_or(1);
-
-                    let generated = crate::openapi::generate_collection(&spec, collection_id);
-
-                    match crate::services::collection_service::create_and_refresh(
-                        &app.db_conn,
-                        &generated.collection.name,
-                    ) {
-                        Ok(cols) => {
-                            if let Some(new_col) = cols.last() {
-                                for folder in &generated.folders {
-                                    match crate::services::collection_service::create_folder(
-                                        &app.db_conn,
-                                        new_col.id,
-                                        &folder.name,
-                                    ) {
-                                        Ok(created_folder) => {
-                                            for req in &generated.requests {
-                                                let _ = crate::services::collection_service::save_request(
-                                                    &app.db_conn,
-                                                    new_col.id,
-                                                    Some(created_folder.id),
-                                                    &req.name,
-                                                    &req.method,
-                                                    &req.url,
-                                                    &req.headers,
-                                                    req.body.as_deref(),
-                                                    "text",
-                                                    "none",
-                                                    None,
-                                                    &req.params,
-                                                    None,
-                                                );
-                                            }
-                                        }
-                                        Err(e) => log::error!("Error creating folder: {}", e),
-                                    }
-                                }
-                                for req in &generated.requests {
-                                    let _ = crate::services::collection_service::save_request(
-                                        &app.db_conn,
-                                        new_col.id,
-                                        None,
-                                        &req.name,
-                                        &req.method,
-                                        &req.url,
-                                        &req.headers,
-                                        req.body.as_deref(),
-                                        "text",
-                                        "none",
-                                        None,
-                                        &req.params,
-                                        None,
-                                    );
-                                }
-                                let cols =
-                                    crate::services::collection_service::get_all(&app.db_conn);
-                                app.collection_view.sync_collections(&cols);
-                                app.toast_manager.success(format!(
-                                    "Imported {} endpoints from OpenAPI spec",
-                                    generated.requests.len()
-                                ));
-                            }
-                        }
-                        Err(e) => {
-                            log::error!("Error creating collection: {}", e);
-                            app.toast_manager.error(format!("Import failed: {}", e));
-                        }
-                    }
-                }
-                Err(e) => {
-                    log::error!("Error parsing OpenAPI spec: {}", e);
+        collection_view::Message::ImportOpenApiData(Some((path, content))) => {
+            super::openapi_import::open_preview(app, &path, content);
+        }
+        collection_view::Message::ImportOpenApiData(None) => {}
+        collection_view::Message::CreateServerEnvironments => {
+            let proposed = std::mem::take(&mut app.collection_view.server_environments);
+            match crate::services::openapi_service::create_environments(&app.db_conn, &proposed) {
+                Ok(created) => {
+                    super::environment::set_environments(
+                        app,
+                        crate::services::environment_service::get_all(&app.db_conn),
+                    );
                     app.toast_manager
-                        .error(format!("Invalid OpenAPI spec: {}", e));
+                        .success(format!("Created {} environment(s)", created.len()));
                 }
+                Err(e) => app.toast_manager.error(e),
             }
         }
-        collection_view::Message::ImportOpenApiData(None) => {}
         collection_view::Message::ExportCollection(idx) => {
-            if let Some(col) = app.collection_view.collections.get(idx) {
-                let folders =
-                    crate::services::collection_service::get_folders(&app.db_conn, col.id);
-                let requests =
-                    crate::services::collection_service::get_requests(&app.db_conn, col.id, None);
-                match crate::export::postman::export_collection(col, &folders, &requests) {
-                    Ok(json) => {
-                        let col_name = col.name.clone();
-                        app.collection_view.update(msg);
-                        return Task::perform(
-                            async move {
-                                let file = rfd::AsyncFileDialog::new()
-                                    .add_filter("Postman Collection", &["json"])
-                                    .set_file_name(&format!("{}.json", col_name))
-                                    .save_file()
-                                    .await;
-                                if let Some(file_handle) = file {
-                                    let path = file_handle.path().to_path_buf();
-                                    let _ = tokio::fs::write(&path, json.as_bytes()).await;
-                                }
-                                None::<()>
-                            },
-                            |_: Option<_>| {
-                                Message::CollectionMsg(
-                                    collection_view::Message::ExportCollectionData(String::new()),
-                                )
-                            },
-                        );
-                    }
-                    Err(e) => log::error!("Error exporting collection: {}", e),
-                }
-            }
+            app.collection_view.update(msg);
+            return export_collection(app, idx, false);
+        }
+        collection_view::Message::ExportAstraNovaCollection(idx) => {
+            app.collection_view.update(msg);
+            return export_collection(app, idx, true);
         }
         collection_view::Message::ExportCollectionData(_) => {}
         collection_view::Message::ConfirmRenameCollection => {
@@ -348,6 +269,35 @@ pub fn handle_message(app: &mut AstraNovaApp, msg: collection_view::Message) ->
                 }
             }
         }
+        collection_view::Message::SaveVariables => {
+            if let collection_view::PanelState::CollectionDetail(idx) =
+                app.collection_view.panel_state
+            {
+                let variables: Vec<(String, String)> = app
+                    .collection_view
+                    .variables_editor
+                    .entries
+                    .iter()
+                    .filter(|e| !e.key.is_empty())
+                    .map(|e| (e.key.clone(), e.value.clone()))
+                    .collect();
+                if let Some(col) = app.collection_view.collections.get(idx) {
+                    match crate::services::collection_service::set_variables(
+                        &app.db_conn,
+                        col,
+                        variables,
+                    ) {
+                        Ok(()) => {
+                            let cols = crate::services::collection_service::get_all(&app.db_conn);
+                            app.collection_view.sync_collections(&cols);
+                            app.toast_manager
+                                .success("Collection variables saved".to_string());
+                        }
+                        Err(e) => log::error!("Error saving collection variables: {}", e),
+                    }
+                }
+            }
+        }
         collection_view::Message::ConfirmRenameFolder => {
             if let Some(folder_id) = app.collection_view.renaming_folder {
                 let new_name = app.collection_view.rename_folder_value.clone();
@@ -398,13 +348,71 @@ pub fn handle_message(app: &mut AstraNovaApp, msg: collection_view::Message) ->
         collection_view::Message::SaveCurrentRequest => {
             save_current_to_collection(app);
         }
+        collection_view::Message::RunCollection(col_id, folder_id) => {
+            super::runner::open_runner(app, col_id, folder_id);
+        }
+        collection_view::Message::LoadTest(target) => {
+            super::load_test::open_load_test(app, target);
+        }
+        collection_view::Message::MockServer(collection_id) => {
+            super::mock_server::open_mock_server(app, collection_id);
+        }
+        collection_view::Message::SchemaBrowser(collection_id) => {
+            super::schema_browser::open_schema_browser(app, collection_id);
+        }
+        collection_view::Message::ResyncOpenApi(collection_id) => {
+            return super::openapi_resync::start_resync(app, collection_id);
+        }
         _ => {}
     }
     app.collection_view.update(msg);
     Task::none()
 }
 
-fn refresh_requests_after_rename(app: &mut AstraNovaApp) {
+/// Saves a collection as a Postman file, or in the app's own format when `astranova` is set.
+fn export_collection(app: &mut AstraNovaApp, idx: usize, astranova: bool) -> Task<Message> {
+    let Some(col) = app.collection_view.collections.get(idx) else {
+        return Task::none();
+    };
+    let folders = crate::services::collection_service::get_folders(&app.db_conn, col.id);
+    let requests = crate::services::run_service::requests_for_run(&app.db_conn, col.id, None);
+    let (exported, filter, file_name) = if astranova {
+        (
+            crate::export::astranova::export_collection(col, &folders, &requests),
+            "AstraNova Collection",
+            format!("{}.astranova.json", col.name),
+        )
+    } else {
+        (
+            crate::export::postman::export_collection(col, &folders, &requests),
+            "Postman Collection",
+            format!("{}.json", col.name),
+        )
+    };
+    let json = match exported {
+        Ok(json) => json,
+        Err(e) => {
+            log::error!("Error exporting collection: {}", e);
+            return Task::none();
+        }
+    };
+    Task::perform(
+        async move {
+            let file = rfd::AsyncFileDialog::new()
+                .add_filter(filter, &["json"])
+                .set_file_name(&file_name)
+                .save_file()
+                .await;
+            if let Some(file_handle) = file {
+                let path = file_handle.path().to_path_buf();
+                let _ = tokio::fs::write(&path, json.as_bytes()).await;
+            }
+        },
+        |_| Message::CollectionMsg(collection_view::Message::ExportCollectionData(String::new())),
+    )
+}
+
+pub(super) fn refresh_requests_after_rename(app: &mut AstraNovaApp) {
     if let collection_view::PanelState::CollectionDetail(col_idx) = app.collection_view.panel_state
     {
         if let Some(col) = app.collection_view.collections.get(col_idx) {
@@ -473,18 +481,33 @@ fn load_collection_request(app: &mut AstraNovaApp, req_id: i32) {
         _ => return,
     };
 
-    let req = match all_reqs.iter().find(|r| r.id == req_id) {
-        Some(r) => r.clone(),
-        None => return,
+    let Some(req) = all_reqs.iter().find(|r| r.id == req_id) else {
+        return;
     };
 
-    let new_view = crate::services::request_restoration::build_view_from_collection_request(&req);
+    open_request_tab(app, req);
+}
+
+/// Opens a saved request in a new tab, linked to its OpenAPI operation if any.
+pub fn open_request_tab(app: &mut AstraNovaApp, req: &CollectionRequest) {
+    let mut new_view = crate::ui::request_restoration::build_view_from_collection_request(req);
+    new_view.response_contract = req.operation.as_ref().and_then(|operation| {
+        crate::services::openapi_service::response_contract(
+            &app.db_conn,
+            &mut app.spec_cache,
+            operation,
+        )
+    });
+    if let Some(vault) = &app.vault {
+        new_view.auth = crate::data::vault::open_auth(&new_view.auth, vault);
+    }
     app.request_tabs.push(new_view);
     app.active_request_tab_index = app.request_tabs.len() - 1;
+    super::oauth2::sync_cached_token(app, app.active_request_tab_index);
 }
 
 fn save_current_to_collection(app: &mut AstraNovaApp) {
-    if let Some(view) = app.request_tabs.get(app.active_request_tab_index) {
+    if let Some(view) = app.request_tabs.get_mut(app.active_request_tab_index) {
         let col_id = match app.collection_view.selected_collection_id {
             Some(id) => id,
             None => {
@@ -496,18 +519,18 @@ fn save_current_to_collection(app: &mut AstraNovaApp) {
             }
         };
 
-        let request = view.build_request();
-        let auth_type = match &view.auth {
-            crate::data::auth::Auth::BearerToken(_) => "bearer",
-            crate::data::auth::Auth::Basic { .. } => "basic",
-            crate::data::auth::Auth::ApiKey { .. } => "api_key",
-            crate::data::auth::Auth::Digest { .. } => "digest",
-            crate::data::auth::Auth::OAuth2(_) => "oauth2",
-            crate::data::auth::Auth::None => "none",
-        };
+        // Credentials live in auth_data only, never in the stored headers
+        let request = view.build_request_with_auth(&crate::data::auth::Auth::None);
+        let auth_type = view.auth.stored_type();
         let auth_data = match &view.auth {
             crate::data::auth::Auth::None => None,
-            auth => serde_json::to_string(auth).ok(),
+            auth => match crate::data::vault::seal_auth(auth, app.vault.as_ref()) {
+                Ok(sealed) => serde_json::to_string(&sealed).ok(),
+                Err(e) => {
+                    app.toast_manager.error(e);
+                    return;
+                }
+            },
         };
 
         let params: Vec<(String, String)> = view
@@ -523,19 +546,21 @@ fn save_current_to_collection(app: &mut AstraNovaApp) {
             _ => "text",
         };
 
-        let name = if request.url.len() > 40 {
-            format!("{} {}", request.method, &request.url[..40])
-        } else {
-            format!("{} {}", request.method, request.url)
-        };
+        // Params are stored separately, so keep the URL as typed (templates included)
+        let url = &view.url_input;
+        let name = format!(
+            "{} {}",
+            request.method,
+            url.chars().take(40).collect::<String>()
+        );
 
-        let _ = crate::services::collection_service::save_request(
+        let saved = crate::services::collection_service::save_request(
             &app.db_conn,
             col_id,
             None,
             &name,
             &request.method,
-            &request.url,
+            url,
             &request.headers,
             request.body.as_deref(),
             body_type,
@@ -545,6 +570,45 @@ fn save_current_to_collection(app: &mut AstraNovaApp) {
             None,
         );
 
+        let variables = view.local_variables();
+        if let Ok(saved) = &saved {
+            if !variables.is_empty() {
+                if let Err(e) = crate::services::collection_service::set_request_variables(
+                    &app.db_conn,
+                    saved.id,
+                    &variables,
+                ) {
+                    log::error!("Error saving request variables: {}", e);
+                }
+            }
+            if !view.extraction_rules.is_empty() {
+                if let Err(e) = crate::services::collection_service::set_request_extractions(
+                    &app.db_conn,
+                    saved.id,
+                    &view.extraction_rules,
+                ) {
+                    log::error!("Error saving extraction rules: {}", e);
+                }
+            }
+            if !view.assertions.is_empty() {
+                if let Err(e) = crate::services::collection_service::set_request_assertions(
+                    &app.db_conn,
+                    saved.id,
+                    &view.assertions,
+                ) {
+                    log::error!("Error saving assertions: {}", e);
+                }
+            }
+        }
+        match saved {
+            Ok(_) => view.collection_id = Some(col_id),
+            Err(e) => {
+                log::error!("Error saving request: {}", e);
+                app.toast_manager
+                    .error(format!("Failed to save request: {}", e));
+            }
+        }
+
         let reqs = crate::services::collection_service::get_requests(&app.db_conn, col_id, None);
         app.collection_view.sync_requests(&reqs);
     }
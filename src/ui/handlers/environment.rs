@@ -1,7 +1,25 @@
+use crate::data::extraction::{upsert_variable, Extracted, ExtractionTarget};
+use crate::data::vault;
+use crate::persistence::database::Environment;
 use crate::ui::app::{AstraNovaApp, Message};
 use crate::ui::views::environment_manager;
 use iced::Task;
 
+/// Replaces the loaded environments, decrypting secrets when the vault is unlocked.
+pub fn set_environments(app: &mut AstraNovaApp, environments: Vec<Environment>) {
+    app.environments = match &app.vault {
+        Some(v) => environments
+            .iter()
+            .map(|env| vault::open_environment(env, v))
+            .collect(),
+        None => environments,
+    };
+    app.env_manager_view.environments = app.environments.clone();
+    if let Some(active) = &app.active_environment {
+        app.active_environment = app.environments.iter().find(|e| e.id == active.id).cloned();
+    }
+}
+
 pub fn handle_message(app: &mut AstraNovaApp, msg: environment_manager::Message) -> Task<Message> {
     app.env_manager_view.update(msg.clone());
     match msg {
@@ -10,8 +28,7 @@ pub fn handle_message(app: &mut AstraNovaApp, msg: environment_manager::Message)
             match crate::services::environment_service::create_and_refresh(&app.db_conn, &name) {
                 Ok(environments) => {
                     let new_env = environments.last().cloned();
-                    app.environments = environments;
-                    app.env_manager_view.environments = app.environments.clone();
+                    set_environments(app, environments);
                     app.env_manager_view.new_environment_name = String::new();
                     if let Some(env) = new_env {
                         app.env_manager_view.selected_environment = Some(env);
@@ -22,10 +39,17 @@ pub fn handle_message(app: &mut AstraNovaApp, msg: environment_manager::Message)
         }
         environment_manager::Message::SaveEnvironment => {
             if let Some(env) = &app.env_manager_view.selected_environment {
-                match crate::services::environment_service::save_and_refresh(&app.db_conn, env) {
+                let sealed = match vault::seal_environment(env, app.vault.as_ref()) {
+                    Ok(sealed) => sealed,
+                    Err(e) => {
+                        app.toast_manager.error(e);
+                        return Task::none();
+                    }
+                };
+                match crate::services::environment_service::save_and_refresh(&app.db_conn, &sealed)
+                {
                     Ok(environments) => {
-                        app.environments = environments;
-                        app.env_manager_view.environments = app.environments.clone();
+                        set_environments(app, environments);
                         if let Some(selected_env) = &app.env_manager_view.selected_environment {
                             app.env_manager_view.selected_environment = app
                                 .environments
@@ -42,32 +66,59 @@ pub fn handle_message(app: &mut AstraNovaApp, msg: environment_manager::Message)
             if let Some(env) = &app.env_manager_view.selected_environment {
                 match crate::services::environment_service::delete_and_refresh(&app.db_conn, env.id)
                 {
-                    Ok(environments) => {
-                        app.environments = environments;
-                        app.env_manager_view.environments = app.environments.clone();
-                    }
+                    Ok(environments) => set_environments(app, environments),
                     Err(e) => log::error!("Error deleting environment: {}", e),
                 }
             }
         }
+        environment_manager::Message::SaveGlobals => {
+            let globals = app.env_manager_view.globals();
+            match crate::services::environment_service::save_globals(&app.db_conn, &globals) {
+                Ok(()) => app.global_variables = globals,
+                Err(e) => log::error!("Error saving global variables: {}", e),
+            }
+        }
+        environment_manager::Message::UnlockVault => {
+            let passphrase = std::mem::take(&mut app.env_manager_view.vault_passphrase);
+            match crate::services::environment_service::unlock_vault(&app.db_conn, &passphrase) {
+                Ok(unlocked) => {
+                    for tab in &mut app.request_tabs {
+                        tab.auth = vault::open_auth(&tab.auth, &unlocked);
+                    }
+                    app.vault = Some(unlocked);
+                    app.env_manager_view.vault_unlocked = true;
+                    app.env_manager_view.vault_exists = true;
+                    let environments = crate::services::environment_service::get_all(&app.db_conn);
+                    set_environments(app, environments);
+                    reselect_environment(app);
+                    app.toast_manager.success("Vault unlocked");
+                }
+                Err(e) => app.toast_manager.error(e),
+            }
+        }
+        environment_manager::Message::LockVault => {
+            // Open tabs hold decrypted credentials; seal them again until the next unlock.
+            if let Some(unlocked) = app.vault.take() {
+                for tab in &mut app.request_tabs {
+                    if let Ok(sealed) = vault::seal_auth(&tab.auth, Some(&unlocked)) {
+                        tab.auth = sealed;
+                    }
+                }
+            }
+            app.env_manager_view.vault_unlocked = false;
+            let environments = crate::services::environment_service::get_all(&app.db_conn);
+            set_environments(app, environments);
+            reselect_environment(app);
+        }
         environment_manager::Message::LoadEnvFile => {
             return Task::perform(
                 async {
                     let file = rfd::AsyncFileDialog::new().pick_file().await;
                     if let Some(file_handle) = file {
                         let data = file_handle.read().await;
-                        let mut vars = Vec::new();
-                        if let Ok(content) = std::str::from_utf8(&data) {
-                            for line in content.lines() {
-                                let trimmed_line = line.trim();
-                                if trimmed_line.starts_with('#') || trimmed_line.is_empty() {
-                                    continue;
-                                }
-                                if let Some((key, value)) = trimmed_line.split_once('=') {
-                                    vars.push((key.trim().to_string(), value.trim().to_string()));
-                                }
-                            }
-                        }
+                        let vars = std::str::from_utf8(&data)
+                            .map(crate::services::environment_service::parse_env_file)
+                            .unwrap_or_default();
                         Some(vars)
                     } else {
                         None
@@ -84,9 +135,17 @@ pub fn handle_message(app: &mut AstraNovaApp, msg: environment_manager::Message)
                     content.push_str(&format!("BASE_URL={}\n", endpoint));
                 }
                 content.push('\n');
+                let mut omitted = 0;
                 for (key, value) in &env.variables {
+                    if env.secret_keys.contains(key) || vault::is_encrypted(value) {
+                        omitted += 1;
+                        continue;
+                    }
                     content.push_str(&format!("{}={}\n", key, value));
                 }
+                if omitted > 0 {
+                    content.push_str(&format!("# {} secret variable(s) omitted\n", omitted));
+                }
                 let env_name = env.name.clone();
                 let content_clone = content.clone();
                 return Task::perform(
@@ -117,6 +176,95 @@ pub fn handle_message(app: &mut AstraNovaApp, msg: environment_manager::Message)
     Task::none()
 }
 
+/// Writes extracted response values into the active environment or runtime variables.
+pub fn apply_extracted(app: &mut AstraNovaApp, extracted: &[Extracted]) {
+    let mut updated_env = app.active_environment.clone();
+    let mut stored = Vec::new();
+    let mut failed = Vec::new();
+    let mut without_env = Vec::new();
+    for result in extracted {
+        let value = match &result.value {
+            Ok(value) => value,
+            Err(e) => {
+                failed.push(format!("{} ({})", result.variable, e));
+                continue;
+            }
+        };
+        match (result.target, updated_env.as_mut()) {
+            (ExtractionTarget::Environment, Some(env)) => {
+                upsert_variable(&mut env.variables, &result.variable, value);
+            }
+            (target, _) => {
+                if target == ExtractionTarget::Environment {
+                    without_env.push(result.variable.clone());
+                }
+                upsert_variable(&mut app.runtime_variables, &result.variable, value)
+            }
+        }
+        stored.push(result.variable.clone());
+    }
+
+    if let Some(env) = updated_env {
+        save_updated_environment(app, env);
+    }
+
+    if !stored.is_empty() {
+        app.toast_manager
+            .success(format!("Extracted {}", stored.join(", ")));
+    }
+    if !failed.is_empty() {
+        app.toast_manager
+            .warning(format!("Extraction failed: {}", failed.join(", ")));
+    }
+    if !without_env.is_empty() {
+        app.toast_manager.warning(format!(
+            "No environment selected; {} stored as runtime variables",
+            without_env.join(", ")
+        ));
+    }
+}
+
+/// Persists an active environment whose variables changed outside the manager,
+/// such as by extraction rules. Unchanged environments are left alone.
+pub fn save_updated_environment(app: &mut AstraNovaApp, env: Environment) {
+    if Some(&env) == app.active_environment.as_ref() {
+        return;
+    }
+    let saved = vault::seal_environment(&env, app.vault.as_ref()).and_then(|sealed| {
+        crate::services::environment_service::save_and_refresh(&app.db_conn, &sealed)
+    });
+    match saved {
+        Ok(environments) => {
+            set_environments(app, environments);
+            if app
+                .env_manager_view
+                .selected_environment
+                .as_ref()
+                .map(|e| e.id)
+                == Some(env.id)
+            {
+                reselect_environment(app);
+            }
+        }
+        Err(e) => app
+            .toast_manager
+            .error(format!("Failed to save extracted variables: {}", e)),
+    }
+}
+
+/// Reloads the editor for the selected environment after its values changed.
+fn reselect_environment(app: &mut AstraNovaApp) {
+    if let Some(id) = app
+        .env_manager_view
+        .selected_environment
+        .as_ref()
+        .map(|e| e.id)
+    {
+        app.env_manager_view
+            .update(environment_manager::Message::SelectEnvironment(id));
+    }
+}
+
 pub fn handle_file_loaded(
     app: &mut AstraNovaApp,
     vars: Option<Vec<(String, String)>>,
@@ -1,3 +1,4 @@
+use crate::data::variables::{VariableResolver, VariableScope};
 use crate::ui::app::{AstraNovaApp, Message};
 use crate::ui::views::graphql_view;
 use iced::Task;
@@ -5,14 +6,22 @@ use iced::Task;
 pub fn handle_message(app: &mut AstraNovaApp, msg: graphql_view::Message) -> Task<Message> {
     match msg {
         graphql_view::Message::SendRequest => {
-            let mut temp_view = app.graphql_view.clone();
-            if let Some(env) = &app.active_environment {
-                temp_view.apply_environment(env);
+            let resolver = VariableResolver::scoped(
+                &app.global_variables,
+                &[],
+                app.active_environment.as_ref(),
+                &[],
+            )
+            .with_scope(VariableScope::Runtime, app.runtime_variables.clone());
+            let unresolved = app.graphql_view.unresolved_variables(&resolver);
+            if !unresolved.is_empty() {
+                app.toast_manager
+                    .warning(format!("Unresolved variables: {}", unresolved.join(", ")));
             }
 
-            match temp_view.build_request() {
+            match app.graphql_view.build_resolved_request(&resolver) {
                 Ok(_graphql_request) => {
-                    let http_request = temp_view.build_http_request();
+                    let http_request = app.graphql_view.build_resolved_http_request(&resolver);
                     app.graphql_view.update(graphql_view::Message::SetLoading);
 
                     let http_client = if http_request.config.proxy_url.is_some()
@@ -29,11 +38,13 @@ pub fn handle_message(app: &mut AstraNovaApp, msg: graphql_view::Message) -> Tas
                         app.http_client.clone()
                     };
 
+                    let token_cache = app.token_cache.clone();
                     Task::perform(
                         async move {
-                            let response = crate::http_client::client::send_request(
+                            let response = crate::data::token_cache::send_with_token_cache(
                                 &http_client,
                                 http_request,
+                                &token_cache,
                             )
                             .await;
 
@@ -12,7 +12,7 @@ pub fn handle_message(app: &mut AstraNovaApp, msg: history_view::Message) -> Tas
             if let Some(entry) = crate::services::history_service::get_by_id(&app.db_conn, entry_id)
             {
                 if let Some(new_view) =
-                    crate::services::request_restoration::build_view_from_history(&entry)
+                    crate::ui::request_restoration::build_view_from_history(&entry)
                 {
                     app.request_tabs.push(new_view);
                     app.active_request_tab_index = app.request_tabs.len() - 1;
@@ -0,0 +1,136 @@
+use crate::persistence::database::CollectionRequest;
+use crate::runner::collection::prepare_request;
+use crate::runner::load::{run_load, shared_client, LoadEvent};
+use crate::services::run_service;
+use crate::ui::app::{AstraNovaApp, Message, View};
+use crate::ui::views::load_test_view::{self, LoadTarget};
+use futures::SinkExt;
+use iced::Task;
+
+pub fn open_load_test(app: &mut AstraNovaApp, target: LoadTarget) {
+    let requests = target_requests(app, target);
+    let name = match target {
+        LoadTarget::Folder {
+            collection_id,
+            folder_id,
+        } => super::runner::target_name(app, collection_id, folder_id),
+        LoadTarget::Request { collection_id, .. } => format!(
+            "{} / {}",
+            super::runner::target_name(app, collection_id, None),
+            requests
+                .first()
+                .map(|r| r.name.as_str())
+                .unwrap_or_default()
+        ),
+    };
+    app.load_test_view.open(target, name, requests.len());
+    app.current_view = View::LoadTest;
+}
+
+fn target_requests(app: &AstraNovaApp, target: LoadTarget) -> Vec<CollectionRequest> {
+    match target {
+        LoadTarget::Folder {
+            collection_id,
+            folder_id,
+        } => run_service::requests_for_run(&app.db_conn, collection_id, folder_id),
+        LoadTarget::Request {
+            collection_id,
+            request_id,
+        } => run_service::requests_for_run(&app.db_conn, collection_id, None)
+            .into_iter()
+            .filter(|r| r.id == request_id)
+            .collect(),
+    }
+}
+
+pub fn handle_message(app: &mut AstraNovaApp, msg: load_test_view::Message) -> Task<Message> {
+    app.load_test_view.update(msg.clone());
+    match msg {
+        load_test_view::Message::Start => start_load_test(app),
+        load_test_view::Message::Close => {
+            app.current_view = View::Main;
+            Task::none()
+        }
+        _ => Task::none(),
+    }
+}
+
+fn start_load_test(app: &mut AstraNovaApp) -> Task<Message> {
+    let Some(target) = app
+        .load_test_view
+        .target
+        .filter(|_| !app.load_test_view.running)
+    else {
+        return Task::none();
+    };
+    let options = match app.load_test_view.options() {
+        Ok(options) => options,
+        Err(e) => {
+            app.toast_manager.error(e);
+            return Task::none();
+        }
+    };
+    let saved = target_requests(app, target);
+    app.load_test_view.request_count = saved.len();
+    if saved.is_empty() {
+        return Task::none();
+    }
+
+    let collection_id = match target {
+        LoadTarget::Folder { collection_id, .. } | LoadTarget::Request { collection_id, .. } => {
+            collection_id
+        }
+    };
+    let context = super::runner::run_context(app, collection_id);
+    let prepared: Result<Vec<_>, String> = saved
+        .iter()
+        .map(|req| prepare_request(req, &context).map_err(|e| format!("{}: {}", req.name, e)))
+        .collect();
+    let (requests, client) =
+        match prepared.and_then(|r| Ok((shared_client(&app.http_client, &r)?, r))) {
+            Ok((client, requests)) => (requests, client),
+            Err(e) => {
+                app.toast_manager.error(e);
+                return Task::none();
+            }
+        };
+
+    let cancel = app.load_test_view.start();
+    let token_cache = app.token_cache.clone();
+    let stream = iced_futures::stream::channel(100, async move |mut sender| {
+        let mut progress = sender.clone();
+        let report = run_load(
+            &client,
+            &token_cache,
+            &requests,
+            &options,
+            &cancel,
+            |samples| {
+                // Dropped batches only delay the live numbers; the final report is complete.
+                let _ = progress.try_send(LoadEvent::Samples(samples));
+            },
+        )
+        .await;
+        let _ = sender.send(LoadEvent::Finished(report)).await;
+    });
+    Task::run(stream, Message::LoadTestEvent)
+}
+
+pub fn handle_event(app: &mut AstraNovaApp, event: LoadEvent) -> Task<Message> {
+    match event {
+        LoadEvent::Samples(samples) => app.load_test_view.add_samples(samples),
+        LoadEvent::Finished(report) => {
+            let summary = format!(
+                "Load test finished: {} requests, {} errors, p90 {} ms",
+                report.total, report.errors, report.p90_ms
+            );
+            if report.errors == 0 {
+                app.toast_manager.success(summary);
+            } else {
+                app.toast_manager.warning(summary);
+            }
+            app.load_test_view.finish(report);
+        }
+    }
+    Task::none()
+}
@@ -0,0 +1,119 @@
+use crate::mock::{routes_from_collection, routes_from_spec, server, MockEvent};
+use crate::persistence::database::DEFAULT_HISTORY_LIMIT;
+use crate::services::{history_service, run_service};
+use crate::ui::app::{AstraNovaApp, Message, View};
+use crate::ui::views::mock_server_view;
+use futures::SinkExt;
+use iced::Task;
+
+/// Opens the mock server panel, with routes for `collection_id` when given.
+pub fn open_mock_server(app: &mut AstraNovaApp, collection_id: Option<i32>) {
+    if let Some(collection_id) = collection_id {
+        let requests = run_service::requests_for_run(&app.db_conn, collection_id, None);
+        let history = history_service::get_all(&app.db_conn, DEFAULT_HISTORY_LIMIT);
+        app.mock_server_view.open(
+            super::runner::target_name(app, collection_id, None),
+            routes_from_collection(&requests, &history),
+        );
+    }
+    app.current_view = View::MockServer;
+}
+
+pub fn handle_message(app: &mut AstraNovaApp, msg: mock_server_view::Message) -> Task<Message> {
+    app.mock_server_view.update(msg.clone());
+    match msg {
+        mock_server_view::Message::OpenSpec => Task::perform(
+            async move {
+                let file = rfd::AsyncFileDialog::new()
+                    .add_filter("OpenAPI / Swagger", &["json", "yaml", "yml"])
+                    .pick_file()
+                    .await?;
+                let data = file.read().await;
+                let content = String::from_utf8(data).ok()?;
+                Some((file.file_name(), content))
+            },
+            |result| Message::MockServerMsg(mock_server_view::Message::SpecLoaded(result)),
+        ),
+        mock_server_view::Message::SpecLoaded(Some((name, content))) => {
+            let parse_result = if content.trim_start().starts_with('{') {
+                crate::openapi::parse_spec(&content)
+            } else {
+                crate::openapi::parse_spec_from_yaml(&content)
+            };
+            match parse_result {
+                Ok(spec) => {
+                    let routes = routes_from_spec(&spec);
+                    app.toast_manager.success(format!(
+                        "Loaded {} routes from {}",
+                        routes.len(),
+                        name
+                    ));
+                    app.mock_server_view.open(spec.title.clone(), routes);
+                }
+                Err(e) => app
+                    .toast_manager
+                    .error(format!("Failed to parse OpenAPI spec: {}", e)),
+            }
+            Task::none()
+        }
+        mock_server_view::Message::Start => start_mock_server(app),
+        mock_server_view::Message::Close => {
+            app.current_view = View::Main;
+            Task::none()
+        }
+        _ => Task::none(),
+    }
+}
+
+fn start_mock_server(app: &mut AstraNovaApp) -> Task<Message> {
+    if app.mock_server_view.running || app.mock_server_view.routes.is_empty() {
+        return Task::none();
+    }
+    let port = match app.mock_server_view.port() {
+        Ok(port) => port,
+        Err(e) => {
+            app.toast_manager.error(e);
+            return Task::none();
+        }
+    };
+
+    let cancel = app.mock_server_view.start();
+    let state = app.mock_server_view.state.clone();
+    let stream = iced_futures::stream::channel(100, async move |mut sender| {
+        let listener = match server::bind(port).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                let _ = sender.send(MockEvent::Stopped(Some(e))).await;
+                return;
+            }
+        };
+        let port = listener.local_addr().map(|a| a.port()).unwrap_or(port);
+        let _ = sender.send(MockEvent::Listening(port)).await;
+        let mut log = sender.clone();
+        let result = server::serve(listener, state, &cancel, |entry| {
+            // A burst larger than the channel only loses log lines, not responses.
+            let _ = log.try_send(MockEvent::Request(entry));
+        })
+        .await;
+        let _ = sender.send(MockEvent::Stopped(result.err())).await;
+    });
+    Task::run(stream, Message::MockServerEvent)
+}
+
+pub fn handle_event(app: &mut AstraNovaApp, event: MockEvent) -> Task<Message> {
+    match event {
+        MockEvent::Listening(port) => {
+            app.mock_server_view.listening = Some(port);
+            app.toast_manager
+                .success(format!("Mock server listening on port {}", port));
+        }
+        MockEvent::Request(entry) => app.mock_server_view.add_log(entry),
+        MockEvent::Stopped(error) => {
+            app.mock_server_view.stopped();
+            if let Some(e) = error {
+                app.toast_manager.error(e);
+            }
+        }
+    }
+    Task::none()
+}
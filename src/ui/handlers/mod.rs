@@ -2,5 +2,11 @@ pub mod collection;
 pub mod environment;
 pub mod graphql;
 pub mod history;
+pub mod load_test;
+pub mod mock_server;
 pub mod oauth2;
+pub mod openapi_import;
+pub mod openapi_resync;
+pub mod runner;
+pub mod schema_browser;
 pub mod websocket;
@@ -1,34 +1,87 @@
-use crate::data::auth::Auth;
+use crate::data::auth::{Auth, OAuth2GrantType, OAuth2Status};
+use crate::data::token_cache::TokenKey;
 use crate::ui::app::{AstraNovaApp, Message};
 use iced::Task;
 
+const REDIRECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// Cache key of the tab's OAuth2 config, with its variables resolved like a send would.
+fn token_key(app: &AstraNovaApp, index: usize) -> Option<TokenKey> {
+    match &app.request_tabs.get(index)?.auth {
+        Auth::OAuth2(config) => Some(TokenKey::resolved(
+            config,
+            &app.tab_variable_resolver(index),
+        )),
+        _ => None,
+    }
+}
+
+/// Copies the shared cached token into the tab's OAuth2 config.
+pub fn sync_cached_token(app: &mut AstraNovaApp, index: usize) {
+    let Some(key) = token_key(app, index) else {
+        return;
+    };
+    if let Some(view) = app.request_tabs.get_mut(index) {
+        if let Auth::OAuth2(config) = &mut view.auth {
+            app.token_cache.sync_config(&key, config);
+        }
+    }
+}
+
 pub fn handle_start_auth(app: &mut AstraNovaApp, index: usize) -> Task<Message> {
     if let Some(view) = app.request_tabs.get_mut(index) {
         if let Auth::OAuth2(config) = &mut view.auth {
-            let pkce = if config.pkce_enabled {
+            if let Err(e) = crate::data::oauth2::loopback_address(&config.redirect_uri) {
+                config.status = OAuth2Status::Error(e.clone());
+                app.toast_manager.error(e);
+                return Task::none();
+            }
+
+            let implicit = config.grant_type == OAuth2GrantType::Implicit;
+            let pkce = if config.pkce_enabled && !implicit {
                 Some(crate::data::oauth2::PKCEChallenge::generate())
             } else {
                 None
             };
 
             let state = crate::data::oauth2::generate_state();
-            let auth_url = crate::data::oauth2::build_authorization_url(
-                &config.auth_url,
-                &config.client_id,
-                &config.redirect_uri,
-                &config.scopes,
-                pkce.as_ref(),
-                &state,
-            );
+            let auth_url = if implicit {
+                crate::data::oauth2::build_implicit_authorization_url(
+                    &config.auth_url,
+                    &config.client_id,
+                    &config.redirect_uri,
+                    &config.scopes,
+                    &state,
+                )
+            } else {
+                crate::data::oauth2::build_authorization_url(
+                    &config.auth_url,
+                    &config.client_id,
+                    &config.redirect_uri,
+                    &config.scopes,
+                    pkce.as_ref(),
+                    &state,
+                )
+            };
 
             let verifier = pkce.map(|p| p.verifier);
             config.pkce_verifier = verifier.clone();
+            config.status = OAuth2Status::AwaitingAuthorization;
+            let redirect_uri = config.redirect_uri.clone();
 
             return Task::perform(
                 async move {
-                    let _ = open::that(&auth_url);
+                    let response = crate::data::oauth2::wait_for_redirect(
+                        &redirect_uri,
+                        REDIRECT_TIMEOUT,
+                        || {
+                            let _ = open::that(&auth_url);
+                        },
+                    )
+                    .await?;
+                    response.verify(&state)
                 },
-                move |_| Message::OAuth2AuthComplete(index, Ok(String::new()), verifier),
+                move |result| Message::OAuth2AuthComplete(index, result, verifier),
             );
         }
     }
@@ -38,18 +91,54 @@ pub fn handle_start_auth(app: &mut AstraNovaApp, index: usize) -> Task<Message>
 pub fn handle_auth_complete(
     app: &mut AstraNovaApp,
     index: usize,
-    _result: Result<String, String>,
+    result: Result<crate::data::oauth2::AuthorizationResponse, String>,
     pkce_verifier: Option<String>,
 ) -> Task<Message> {
     if let Some(view) = app.request_tabs.get_mut(index) {
         if let Auth::OAuth2(config) = &mut view.auth {
-            if let Some(ref v) = pkce_verifier {
-                config.pkce_verifier = Some(v.clone());
+            let response = match result {
+                Ok(response) => response,
+                Err(e) => {
+                    log::error!("OAuth2 authorization failed: {}", e);
+                    config.status = OAuth2Status::Error(e.clone());
+                    app.toast_manager.error(e);
+                    return Task::none();
+                }
+            };
+
+            if let Some(access_token) = response.access_token {
+                let token_response = crate::data::oauth2::OAuth2TokenResponse {
+                    access_token,
+                    token_type: response.token_type.unwrap_or_else(|| "Bearer".to_string()),
+                    expires_in: response.expires_in,
+                    refresh_token: None,
+                    scope: response.scope,
+                    id_token: response.id_token,
+                };
+                return handle_token_received(app, index, Ok(token_response));
             }
 
-            log::warn!(
-                "OAuth2 Authorization Code flow: authorization code not yet captured from redirect. \
-                 PKCE verifier stored. Implement redirect capture to complete the flow."
+            let Some(code) = response.code else {
+                return Task::none();
+            };
+
+            config.status = OAuth2Status::Loading;
+            let token_url = config.token_url.clone();
+            let client_auth = crate::data::oauth2::ClientAuth::from_config(config);
+            let redirect_uri = config.redirect_uri.clone();
+
+            return Task::perform(
+                async move {
+                    crate::data::oauth2::exchange_code(
+                        &token_url,
+                        &code,
+                        &client_auth,
+                        &redirect_uri,
+                        pkce_verifier.as_deref(),
+                    )
+                    .await
+                },
+                move |result| Message::OAuth2TokenReceived(index, result),
             );
         }
     }
@@ -61,18 +150,31 @@ pub fn handle_token_received(
     index: usize,
     result: Result<crate::data::oauth2::OAuth2TokenResponse, String>,
 ) -> Task<Message> {
-    if let Some(view) = app.request_tabs.get_mut(index) {
+    let key = token_key(app, index);
+    if let (Some(view), Some(key)) = (app.request_tabs.get_mut(index), key) {
         if let Auth::OAuth2(config) = &mut view.auth {
             match result {
                 Ok(token_response) => {
+                    app.token_cache.store_response(key, config, &token_response);
                     config.access_token = token_response.access_token;
                     if let Some(refresh) = token_response.refresh_token {
                         config.refresh_token = refresh;
                     }
+                    config.token_expiry =
+                        crate::data::oauth2::token_expiry(token_response.expires_in);
+                    if let Some(id_token) = token_response.id_token {
+                        config.id_token = id_token;
+                    }
+                    config.pkce_verifier = None;
+                    config.status = OAuth2Status::Success("Token received".to_string());
+                    app.toast_manager
+                        .success("OAuth2 token received".to_string());
                     log::info!("OAuth2 token received successfully");
                 }
                 Err(e) => {
                     log::error!("OAuth2 token exchange failed: {}", e);
+                    config.status = OAuth2Status::Error(e.clone());
+                    app.toast_manager.error(e);
                 }
             }
         }
@@ -83,11 +185,10 @@ pub fn handle_token_received(
 pub fn handle_refresh_token(app: &mut AstraNovaApp, index: usize) -> Task<Message> {
     if let Some(view) = app.request_tabs.get(index) {
         if let Auth::OAuth2(config) = &view.auth {
+            let client_auth = crate::data::oauth2::ClientAuth::from_config(config);
             if !config.device_code.is_empty() {
                 let token_url = config.token_url.clone();
                 let device_code = config.device_code.clone();
-                let client_id = config.client_id.clone();
-                let client_secret = config.client_secret.clone();
                 let tab_index = index;
 
                 return Task::perform(
@@ -95,32 +196,41 @@ pub fn handle_refresh_token(app: &mut AstraNovaApp, index: usize) -> Task<Messag
                         crate::data::oauth2::poll_device_token(
                             &token_url,
                             &device_code,
-                            &client_id,
-                            &client_secret,
+                            &client_auth,
                         )
                         .await
                     },
                     move |result| Message::OAuth2DeviceTokenPoll(tab_index, result),
                 );
+            } else if matches!(
+                config.grant_type,
+                OAuth2GrantType::ClientCredentials
+                    | OAuth2GrantType::Password
+                    | OAuth2GrantType::JwtBearer
+            ) {
+                let config = config.as_ref().clone();
+                let tab_index = index;
+
+                return Task::perform(
+                    async move {
+                        crate::data::oauth2::request_direct_token(&config)
+                            .await?
+                            .ok_or_else(|| "Grant type requires user authorization".to_string())
+                    },
+                    move |result| Message::OAuth2TokenReceived(tab_index, result),
+                );
             } else if config.refresh_token.is_empty() {
                 app.toast_manager
                     .warning("No refresh token available. Get a new token first.".to_string());
             } else {
                 let token_url = config.token_url.clone();
                 let refresh_token = config.refresh_token.clone();
-                let client_id = config.client_id.clone();
-                let client_secret = config.client_secret.clone();
                 let tab_index = index;
 
                 return Task::perform(
                     async move {
-                        crate::data::oauth2::refresh_token(
-                            &token_url,
-                            &refresh_token,
-                            &client_id,
-                            &client_secret,
-                        )
-                        .await
+                        crate::data::oauth2::refresh_token(&token_url, &refresh_token, &client_auth)
+                            .await
                     },
                     move |result| Message::OAuth2TokenReceived(tab_index, result),
                 );
@@ -194,15 +304,36 @@ pub fn handle_device_token_poll(
     index: usize,
     result: Result<crate::data::oauth2::DeviceTokenResponse, String>,
 ) -> Task<Message> {
-    if let Some(view) = app.request_tabs.get_mut(index) {
+    let key = token_key(app, index);
+    if let (Some(view), Some(key)) = (app.request_tabs.get_mut(index), key) {
         if let Auth::OAuth2(config) = &mut view.auth {
             match result {
                 Ok(device_token) => {
                     if let Some(access_token) = device_token.access_token {
+                        app.token_cache.store_response(
+                            key,
+                            config,
+                            &crate::data::oauth2::OAuth2TokenResponse {
+                                access_token: access_token.clone(),
+                                token_type: device_token
+                                    .token_type
+                                    .clone()
+                                    .unwrap_or_else(|| "Bearer".to_string()),
+                                expires_in: device_token.expires_in,
+                                refresh_token: device_token.refresh_token.clone(),
+                                scope: device_token.scope.clone(),
+                                id_token: device_token.id_token.clone(),
+                            },
+                        );
                         config.access_token = access_token;
                         if let Some(refresh) = device_token.refresh_token {
                             config.refresh_token = refresh;
                         }
+                        config.token_expiry =
+                            crate::data::oauth2::token_expiry(device_token.expires_in);
+                        if let Some(id_token) = device_token.id_token {
+                            config.id_token = id_token;
+                        }
                         config.device_code.clear();
                         config.user_code.clear();
                         config.verification_uri.clear();
@@ -228,3 +359,236 @@ pub fn handle_device_token_poll(
     }
     Task::none()
 }
+
+pub fn handle_discover(app: &mut AstraNovaApp, index: usize) -> Task<Message> {
+    if let Some(view) = app.request_tabs.get_mut(index) {
+        if let Auth::OAuth2(config) = &mut view.auth {
+            config.status = OAuth2Status::Loading;
+            let issuer_url = config.issuer_url.clone();
+            return Task::perform(
+                async move { crate::data::oidc::discover(&issuer_url).await },
+                move |result| Message::OAuth2DiscoveryReceived(index, result),
+            );
+        }
+    }
+    Task::none()
+}
+
+pub fn handle_discovery_received(
+    app: &mut AstraNovaApp,
+    index: usize,
+    result: Result<crate::data::oidc::OidcDiscovery, String>,
+) -> Task<Message> {
+    if let Some(view) = app.request_tabs.get_mut(index) {
+        if let Auth::OAuth2(config) = &mut view.auth {
+            match result {
+                Ok(discovery) => {
+                    if let Some(url) = discovery.authorization_endpoint {
+                        config.auth_url = url;
+                    }
+                    if let Some(url) = discovery.token_endpoint {
+                        config.token_url = url;
+                    }
+                    if let Some(url) = discovery.device_authorization_endpoint {
+                        config.device_auth_url = url;
+                    }
+                    if let Some(url) = discovery.introspection_endpoint {
+                        config.introspection_url = url;
+                    }
+                    if let Some(url) = discovery.revocation_endpoint {
+                        config.revocation_url = url;
+                    }
+                    config.jwks_uri = discovery.jwks_uri.unwrap_or_default();
+                    config.scopes_supported = discovery.scopes_supported;
+                    config.code_challenge_methods_supported =
+                        discovery.code_challenge_methods_supported;
+                    if config
+                        .code_challenge_methods_supported
+                        .iter()
+                        .any(|m| m == "S256")
+                    {
+                        config.pkce_enabled = true;
+                    }
+                    config.status =
+                        OAuth2Status::Success(format!("Discovered {}", discovery.issuer));
+                    app.toast_manager
+                        .success("OpenID configuration loaded".to_string());
+                }
+                Err(e) => {
+                    log::error!("OpenID discovery failed: {}", e);
+                    config.status = OAuth2Status::Error(e.clone());
+                    app.toast_manager.error(e);
+                }
+            }
+        }
+    }
+    Task::none()
+}
+
+pub fn handle_verify_id_token(app: &mut AstraNovaApp, index: usize) -> Task<Message> {
+    if let Some(view) = app.request_tabs.get_mut(index) {
+        if let Auth::OAuth2(config) = &mut view.auth {
+            config.status = OAuth2Status::Loading;
+            let id_token = config.id_token.clone();
+            let jwks_uri = config.jwks_uri.clone();
+            return Task::perform(
+                async move { crate::data::oidc::verify_id_token(&id_token, &jwks_uri).await },
+                move |result| Message::OAuth2IdTokenVerified(index, result),
+            );
+        }
+    }
+    Task::none()
+}
+
+pub fn handle_id_token_verified(
+    app: &mut AstraNovaApp,
+    index: usize,
+    result: Result<String, String>,
+) -> Task<Message> {
+    if let Some(view) = app.request_tabs.get_mut(index) {
+        if let Auth::OAuth2(config) = &mut view.auth {
+            match result {
+                Ok(msg) => {
+                    config.status = OAuth2Status::Success(msg.clone());
+                    app.toast_manager.success(msg);
+                }
+                Err(e) => {
+                    config.status = OAuth2Status::Error(e.clone());
+                    app.toast_manager.error(e);
+                }
+            }
+        }
+    }
+    Task::none()
+}
+
+pub fn handle_introspect(app: &mut AstraNovaApp, index: usize) -> Task<Message> {
+    if let Some(view) = app.request_tabs.get_mut(index) {
+        if let Auth::OAuth2(config) = &mut view.auth {
+            if config.access_token.is_empty() {
+                app.toast_manager
+                    .warning("No access token to introspect".to_string());
+                return Task::none();
+            }
+            config.status = OAuth2Status::Loading;
+            let introspection_url = config.introspection_url.clone();
+            let token = config.access_token.clone();
+            let client_auth = crate::data::oauth2::ClientAuth::from_config(config);
+            return Task::perform(
+                async move {
+                    crate::data::oauth2::introspect_token(
+                        &introspection_url,
+                        &token,
+                        "access_token",
+                        &client_auth,
+                    )
+                    .await
+                },
+                move |result| Message::OAuth2IntrospectionReceived(index, result),
+            );
+        }
+    }
+    Task::none()
+}
+
+pub fn handle_introspection_received(
+    app: &mut AstraNovaApp,
+    index: usize,
+    result: Result<crate::data::oauth2::IntrospectionResponse, String>,
+) -> Task<Message> {
+    if let Some(view) = app.request_tabs.get_mut(index) {
+        if let Auth::OAuth2(config) = &mut view.auth {
+            match result {
+                Ok(introspection) => {
+                    config.status = if introspection.active {
+                        OAuth2Status::Success("Token is active".to_string())
+                    } else {
+                        OAuth2Status::Error("Token is not active".to_string())
+                    };
+                    config.introspection = Some(introspection);
+                }
+                Err(e) => {
+                    log::error!("Token introspection failed: {}", e);
+                    config.introspection = None;
+                    config.status = OAuth2Status::Error(e.clone());
+                    app.toast_manager.error(e);
+                }
+            }
+        }
+    }
+    Task::none()
+}
+
+pub fn handle_revoke(app: &mut AstraNovaApp, index: usize) -> Task<Message> {
+    if let Some(view) = app.request_tabs.get_mut(index) {
+        if let Auth::OAuth2(config) = &mut view.auth {
+            if config.access_token.is_empty() && config.refresh_token.is_empty() {
+                app.toast_manager.warning("No token to revoke".to_string());
+                return Task::none();
+            }
+            config.status = OAuth2Status::Loading;
+            let revocation_url = config.revocation_url.clone();
+            let access_token = config.access_token.clone();
+            let refresh_token = config.refresh_token.clone();
+            let client_auth = crate::data::oauth2::ClientAuth::from_config(config);
+            return Task::perform(
+                async move {
+                    // Revoking the refresh token first also invalidates the access tokens
+                    // issued from it on most servers.
+                    if !refresh_token.is_empty() {
+                        crate::data::oauth2::revoke_token(
+                            &revocation_url,
+                            &refresh_token,
+                            "refresh_token",
+                            &client_auth,
+                        )
+                        .await?;
+                    }
+                    if !access_token.is_empty() {
+                        crate::data::oauth2::revoke_token(
+                            &revocation_url,
+                            &access_token,
+                            "access_token",
+                            &client_auth,
+                        )
+                        .await?;
+                    }
+                    Ok(())
+                },
+                move |result| Message::OAuth2Revoked(index, result),
+            );
+        }
+    }
+    Task::none()
+}
+
+pub fn handle_revoked(
+    app: &mut AstraNovaApp,
+    index: usize,
+    result: Result<(), String>,
+) -> Task<Message> {
+    let key = token_key(app, index);
+    if let (Some(view), Some(key)) = (app.request_tabs.get_mut(index), key) {
+        if let Auth::OAuth2(config) = &mut view.auth {
+            match result {
+                Ok(()) => {
+                    app.token_cache.remove(&key);
+                    config.access_token.clear();
+                    config.refresh_token.clear();
+                    config.token_expiry = None;
+                    config.id_token.clear();
+                    config.introspection = None;
+                    config.status = OAuth2Status::Success("Tokens revoked".to_string());
+                    app.toast_manager
+                        .success("OAuth2 tokens revoked".to_string());
+                }
+                Err(e) => {
+                    log::error!("Token revocation failed: {}", e);
+                    config.status = OAuth2Status::Error(e.clone());
+                    app.toast_manager.error(e);
+                }
+            }
+        }
+    }
+    Task::none()
+}
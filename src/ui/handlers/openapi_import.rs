@@ -0,0 +1,66 @@
+use crate::openapi::parser::detect_format;
+use crate::openapi::refs::bundle;
+use crate::services::openapi_service::{self, ImportOptions};
+use crate::ui::app::{AstraNovaApp, Message, View};
+use crate::ui::views::openapi_import_view;
+use iced::Task;
+use std::path::Path;
+
+/// Parses a picked spec and shows the endpoint preview before anything is saved.
+/// Files the spec refers to are read from next to `path`.
+pub fn open_preview(app: &mut AstraNovaApp, path: &Path, content: String) {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let format = detect_format(&content);
+    let parsed = bundle(&content, dir)
+        .and_then(|content| openapi_service::parse_content(&content).map(|spec| (spec, content)));
+    match parsed {
+        Ok((spec, content)) => {
+            let source = Some(path.display().to_string());
+            app.openapi_import_view.open(spec, format, content, source);
+            app.current_view = View::OpenApiImport;
+        }
+        Err(e) => {
+            log::error!("Error parsing OpenAPI spec: {}", e);
+            app.toast_manager.error(format!("Import failed: {}", e));
+        }
+    }
+}
+
+pub fn handle_message(app: &mut AstraNovaApp, msg: openapi_import_view::Message) -> Task<Message> {
+    app.openapi_import_view.update(msg.clone());
+    match msg {
+        openapi_import_view::Message::Import => import_selected(app),
+        openapi_import_view::Message::Cancel => {
+            app.openapi_import_view.close();
+            app.current_view = View::Main;
+        }
+        _ => {}
+    }
+    Task::none()
+}
+
+fn import_selected(app: &mut AstraNovaApp) {
+    let view = &app.openapi_import_view;
+    let options = ImportOptions {
+        operations: Some(view.selected.clone()),
+        grouping: view.grouping,
+        source: view.source.clone(),
+    };
+    match openapi_service::import_spec_with(&app.db_conn, &view.content, &options) {
+        Ok(summary) => {
+            let cols = crate::services::collection_service::get_all(&app.db_conn);
+            app.collection_view.sync_collections(&cols);
+            app.toast_manager.success(format!(
+                "Imported {} endpoints from OpenAPI spec",
+                summary.requests
+            ));
+            app.collection_view.server_environments = summary.environments;
+            app.openapi_import_view.close();
+            app.current_view = View::Main;
+        }
+        Err(e) => {
+            log::error!("Error importing OpenAPI spec: {}", e);
+            app.toast_manager.error(format!("Import failed: {}", e));
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use crate::persistence::database;
+use crate::services::openapi_sync::{self, ResyncSummary};
+use crate::ui::app::{AstraNovaApp, Message, View};
+use crate::ui::views::openapi_resync_view;
+use iced::Task;
+
+/// Reads the spec of `collection_id` again from the file or URL it came from.
+pub fn start_resync(app: &mut AstraNovaApp, collection_id: i32) -> Task<Message> {
+    let source = match database::get_collection_openapi_spec(&app.db_conn, collection_id) {
+        Ok(Some(record)) => record.source,
+        _ => {
+            app.toast_manager
+                .error("This collection wasn't imported from an OpenAPI spec");
+            return Task::none();
+        }
+    };
+    let Some(source) = source else {
+        app.toast_manager
+            .error("The spec's location wasn't recorded; import it again to enable re-sync");
+        return Task::none();
+    };
+    Task::perform(openapi_sync::fetch_source(source), move |result| {
+        Message::OpenApiResyncMsg(openapi_resync_view::Message::Loaded(collection_id, result))
+    })
+}
+
+pub fn handle_message(app: &mut AstraNovaApp, msg: openapi_resync_view::Message) -> Task<Message> {
+    app.openapi_resync_view.update(msg.clone());
+    match msg {
+        openapi_resync_view::Message::Loaded(collection_id, Ok(content)) => {
+            match openapi_sync::plan_resync(&app.db_conn, collection_id, &content) {
+                // Still store the spec, so validation and schemas follow it.
+                Ok(plan) if plan.is_empty() => {
+                    match openapi_sync::apply_resync(&app.db_conn, &plan, &plan.items()) {
+                        Ok(_) => app.toast_manager.success("Collection is up to date"),
+                        Err(e) => app.toast_manager.error(format!("Re-sync failed: {}", e)),
+                    }
+                }
+                Ok(plan) => {
+                    app.openapi_resync_view.open(plan);
+                    app.current_view = View::OpenApiResync;
+                }
+                Err(e) => app.toast_manager.error(format!("Re-sync failed: {}", e)),
+            }
+        }
+        openapi_resync_view::Message::Loaded(_, Err(e)) => {
+            log::error!("Error reading OpenAPI spec: {}", e);
+            app.toast_manager.error(format!("Re-sync failed: {}", e));
+        }
+        openapi_resync_view::Message::Apply => apply(app),
+        openapi_resync_view::Message::Cancel => {
+            app.openapi_resync_view.close();
+            app.current_view = View::Main;
+        }
+        _ => {}
+    }
+    Task::none()
+}
+
+fn apply(app: &mut AstraNovaApp) {
+    let view = &app.openapi_resync_view;
+    let Some(plan) = &view.plan else {
+        return;
+    };
+    match openapi_sync::apply_resync(&app.db_conn, plan, &view.selected) {
+        Ok(ResyncSummary {
+            added,
+            updated,
+            removed,
+        }) => {
+            let cols = crate::services::collection_service::get_all(&app.db_conn);
+            app.collection_view.sync_collections(&cols);
+            let folders =
+                crate::services::collection_service::get_folders(&app.db_conn, plan.collection_id);
+            app.collection_view.sync_folders(&folders);
+            super::collection::refresh_requests_after_rename(app);
+            app.toast_manager.success(format!(
+                "Re-synced: {} added, {} updated, {} removed",
+                added, updated, removed
+            ));
+            app.openapi_resync_view.close();
+            app.current_view = View::Main;
+        }
+        Err(e) => {
+            log::error!("Error re-syncing OpenAPI spec: {}", e);
+            app.toast_manager.error(format!("Re-sync failed: {}", e));
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use crate::runner::collection::{run_requests, RunContext, RunEvent};
+use crate::services::run_service;
+use crate::ui::app::{AstraNovaApp, Message, View};
+use crate::ui::views::runner_view;
+use futures::SinkExt;
+use iced::Task;
+
+/// Switches to the runner for a whole collection or one of its folders.
+pub fn open_runner(app: &mut AstraNovaApp, collection_id: i32, folder_id: Option<i32>) {
+    let target_name = target_name(app, collection_id, folder_id);
+    let request_count = run_service::requests_for_run(&app.db_conn, collection_id, folder_id).len();
+    app.runner_view.open(
+        collection_id,
+        folder_id,
+        target_name,
+        request_count,
+        run_service::get_runs(&app.db_conn, collection_id),
+    );
+    app.current_view = View::Runner;
+}
+
+/// "Collection" or "Collection / Folder".
+pub fn target_name(app: &AstraNovaApp, collection_id: i32, folder_id: Option<i32>) -> String {
+    let collection_name = app
+        .collection_view
+        .collections
+        .iter()
+        .find(|c| c.id == collection_id)
+        .map(|c| c.name.clone())
+        .unwrap_or_default();
+    match folder_id {
+        Some(id) => {
+            let folder_name =
+                crate::services::collection_service::get_folders(&app.db_conn, collection_id)
+                    .into_iter()
+                    .find(|f| f.id == id)
+                    .map(|f| f.name)
+                    .unwrap_or_default();
+            format!("{} / {}", collection_name, folder_name)
+        }
+        None => collection_name,
+    }
+}
+
+/// The app's current variables and credentials, as a run of `collection_id` starts with.
+pub fn run_context(app: &AstraNovaApp, collection_id: i32) -> RunContext {
+    RunContext {
+        globals: app.global_variables.clone(),
+        collection_variables: app
+            .collection_view
+            .collection_variables(Some(collection_id))
+            .to_vec(),
+        environment: app.active_environment.clone(),
+        runtime_variables: app.runtime_variables.clone(),
+        data: Vec::new(),
+        vault: app.vault.clone(),
+    }
+}
+
+pub fn handle_message(app: &mut AstraNovaApp, msg: runner_view::Message) -> Task<Message> {
+    app.runner_view.update(msg.clone());
+    match msg {
+        runner_view::Message::Start => start_run(app),
+        runner_view::Message::PickDataFile => Task::perform(
+            async {
+                let file = rfd::AsyncFileDialog::new()
+                    .add_filter("Data", &["csv", "json"])
+                    .pick_file()
+                    .await?;
+                let rows = crate::runner::data::load_data_file(file.path());
+                Some((file.file_name(), rows))
+            },
+            |loaded| Message::RunnerMsg(runner_view::Message::DataFileLoaded(loaded)),
+        ),
+        runner_view::Message::DataFileLoaded(Some((name, Err(e)))) => {
+            app.toast_manager
+                .error(format!("Failed to load {}: {}", name, e));
+            Task::none()
+        }
+        runner_view::Message::Close => {
+            app.current_view = View::Main;
+            Task::none()
+        }
+        _ => Task::none(),
+    }
+}
+
+fn start_run(app: &mut AstraNovaApp) -> Task<Message> {
+    let Some(collection_id) = app
+        .runner_view
+        .collection_id
+        .filter(|_| !app.runner_view.running)
+    else {
+        return Task::none();
+    };
+    let requests =
+        run_service::requests_for_run(&app.db_conn, collection_id, app.runner_view.folder_id);
+    app.runner_view.request_count = requests.len();
+    if requests.is_empty() {
+        return Task::none();
+    }
+
+    let context = run_context(app, collection_id);
+    let options = app.runner_view.options();
+    let name = app.runner_view.target_name.clone();
+    let cancel = app.runner_view.start();
+    let client = app.http_client.clone();
+    let token_cache = app.token_cache.clone();
+
+    let stream = iced_futures::stream::channel(100, async move |mut sender| {
+        let mut progress = sender.clone();
+        let (report, context) = run_requests(
+            &client,
+            &token_cache,
+            &name,
+            &requests,
+            context,
+            &options,
+            &cancel,
+            |step| {
+                // Dropped steps still arrive with the final report.
+                let _ = progress.try_send(RunEvent::Step(step.clone()));
+            },
+        )
+        .await;
+        let _ = sender
+            .send(RunEvent::Finished(Box::new((report, context))))
+            .await;
+    });
+    Task::run(stream, Message::RunnerEvent)
+}
+
+pub fn handle_event(app: &mut AstraNovaApp, event: RunEvent) -> Task<Message> {
+    match event {
+        RunEvent::Step(step) => {
+            if app.runner_view.running {
+                app.runner_view.steps.push(step);
+            }
+        }
+        RunEvent::Finished(finished) => {
+            let (report, context) = *finished;
+            app.runtime_variables = context.runtime_variables;
+            if let Some(env) = context
+                .environment
+                .filter(|env| app.active_environment.as_ref().map(|e| e.id) == Some(env.id))
+            {
+                super::environment::save_updated_environment(app, env);
+            }
+
+            let Some(collection_id) = app.runner_view.collection_id else {
+                return Task::none();
+            };
+            if let Err(e) = run_service::save_run(
+                &app.db_conn,
+                collection_id,
+                app.runner_view.folder_id,
+                &report,
+            ) {
+                app.toast_manager
+                    .error(format!("Failed to save run: {}", e));
+            }
+            let summary = format!(
+                "Run finished: {} passed, {} failed",
+                report.passed_count(),
+                report.failed_count()
+            );
+            if report.passed() {
+                app.toast_manager.success(summary);
+            } else {
+                app.toast_manager.warning(summary);
+            }
+            let runs = run_service::get_runs(&app.db_conn, collection_id);
+            app.runner_view.finish(report, runs);
+        }
+    }
+    Task::none()
+}
@@ -0,0 +1,57 @@
+use crate::openapi::schema_tree::SchemaBrowser;
+use crate::services::{openapi_service, run_service};
+use crate::ui::app::{AstraNovaApp, Message, View};
+use crate::ui::views::schema_browser_view;
+use iced::Task;
+
+/// Opens the schemas of the spec `collection_id` was imported from.
+pub fn open_schema_browser(app: &mut AstraNovaApp, collection_id: i32) {
+    match openapi_service::spec_for_collection(&app.db_conn, collection_id) {
+        Some((spec_id, spec)) => {
+            let browser = SchemaBrowser::new(&spec);
+            app.schema_browser_view
+                .open(spec.info.title.clone(), collection_id, spec_id, browser);
+            app.current_view = View::SchemaBrowser;
+        }
+        None => app
+            .toast_manager
+            .error("This collection wasn't imported from an OpenAPI spec"),
+    }
+}
+
+pub fn handle_message(app: &mut AstraNovaApp, msg: schema_browser_view::Message) -> Task<Message> {
+    app.schema_browser_view.update(msg.clone());
+    match msg {
+        schema_browser_view::Message::OpenOperation(method, path) => {
+            open_operation(app, &method, &path)
+        }
+        schema_browser_view::Message::Close => app.current_view = View::Main,
+        _ => {}
+    }
+    Task::none()
+}
+
+/// Opens the collection request generated from an operation.
+fn open_operation(app: &mut AstraNovaApp, method: &str, path: &str) {
+    let view = &app.schema_browser_view;
+    let (Some(collection_id), Some(spec_id)) = (view.collection_id, view.spec_id) else {
+        return;
+    };
+    let request = run_service::requests_for_run(&app.db_conn, collection_id, None)
+        .into_iter()
+        .find(|req| {
+            req.operation
+                .as_ref()
+                .is_some_and(|op| op.spec_id == spec_id && op.path == path && op.method == method)
+        });
+    match request {
+        Some(req) => {
+            super::collection::open_request_tab(app, &req);
+            app.current_view = View::Main;
+        }
+        None => app.toast_manager.error(format!(
+            "No request in this collection for {} {}",
+            method, path
+        )),
+    }
+}
@@ -1,6 +1,7 @@
 pub mod app;
 pub mod components;
 pub mod handlers;
+pub mod request_restoration;
 pub mod theme;
 pub mod toast;
 pub mod views;
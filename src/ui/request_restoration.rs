@@ -56,66 +56,23 @@ pub fn build_view_from_collection_request(req: &CollectionRequest) -> HttpReques
         })
         .collect();
 
-    if let Some(data) = &req.auth_data {
-        if data.starts_with('{') {
-            if let Ok(auth) = serde_json::from_str::<crate::data::auth::Auth>(data) {
-                view.auth = auth;
-            }
-        } else {
-            match req.auth_type.as_str() {
-                "bearer" => {
-                    view.auth = crate::data::auth::Auth::BearerToken(data.clone());
-                }
-                "basic" => {
-                    let parts: Vec<&str> = data.splitn(2, ':').collect();
-                    if parts.len() == 2 {
-                        view.auth = crate::data::auth::Auth::Basic {
-                            user: parts[0].to_string(),
-                            pass: parts[1].to_string(),
-                        };
-                    }
-                }
-                "api_key" => {
-                    let parts: Vec<&str> = data.splitn(3, ':').collect();
-                    if parts.len() == 3 {
-                        let location = match parts[2] {
-                            "query" => crate::data::auth::ApiKeyLocation::Query,
-                            _ => crate::data::auth::ApiKeyLocation::Header,
-                        };
-                        view.auth = crate::data::auth::Auth::ApiKey {
-                            key: parts[0].to_string(),
-                            value: parts[1].to_string(),
-                            location,
-                        };
-                    } else if parts.len() == 2 {
-                        view.auth = crate::data::auth::Auth::ApiKey {
-                            key: parts[0].to_string(),
-                            value: parts[1].to_string(),
-                            location: crate::data::auth::ApiKeyLocation::Header,
-                        };
-                    }
-                }
-                "digest" => {
-                    let parts: Vec<&str> = data.splitn(2, ':').collect();
-                    if parts.len() == 2 {
-                        view.auth = crate::data::auth::Auth::Digest {
-                            user: parts[0].to_string(),
-                            pass: parts[1].to_string(),
-                        };
-                    }
-                }
-                "oauth2" => {
-                    view.auth = crate::data::auth::Auth::OAuth2(Box::new(
-                        crate::data::auth::OAuth2Config {
-                            access_token: data.clone(),
-                            ..Default::default()
-                        },
-                    ));
-                }
-                _ => {}
-            }
-        }
+    if !req.variables.is_empty() {
+        view.variables_editor.entries = req
+            .variables
+            .iter()
+            .enumerate()
+            .map(|(i, (k, v))| KeyValueEntry {
+                id: i,
+                key: k.clone(),
+                value: v.clone(),
+            })
+            .collect();
     }
+    view.extraction_rules = req.extractions.clone();
+    view.assertions = req.assertions.clone();
+    view.collection_id = Some(req.collection_id);
+
+    view.auth = crate::data::auth::Auth::from_stored(&req.auth_type, req.auth_data.as_deref());
 
     view
 }
@@ -230,10 +187,27 @@ mod tests {
         let req = CollectionRequest {
             id: 1,
             collection_id: 1,
-            folder_id: None,
             name: "Get Users".to_string(),
             method: "GET".to_string(),
             url: "https://api.example.com/users".to_string(),
+            body_type: "text".to_string(),
+            auth_type: "none".to_string(),
+            ..Default::default()
+        };
+        let view = build_view_from_collection_request(&req);
+        assert_eq!(view.url_input, "https://api.example.com/users");
+        assert_eq!(view.method, "GET");
+    }
+
+    #[test]
+    fn build_view_from_collection_request_restores_variables() {
+        let req = CollectionRequest {
+            id: 1,
+            collection_id: 7,
+            folder_id: None,
+            name: "Get Item".to_string(),
+            method: "GET".to_string(),
+            url: "{{HOST}}/items/{{ID}}".to_string(),
             headers: vec![],
             body: None,
             body_type: "text".to_string(),
@@ -242,10 +216,25 @@ mod tests {
             params: vec![],
             config_json: None,
             sort_order: 0,
+            variables: vec![("ID".to_string(), "42".to_string())],
+            extractions: vec![crate::data::extraction::ExtractionRule {
+                variable: "etag".to_string(),
+                source: crate::data::extraction::ExtractionSource::Header,
+                expression: "ETag".to_string(),
+                ..Default::default()
+            }],
+            assertions: vec![crate::data::assertions::Assertion {
+                expected: "200".to_string(),
+                ..Default::default()
+            }],
+            operation: None,
         };
         let view = build_view_from_collection_request(&req);
-        assert_eq!(view.url_input, "https://api.example.com/users");
-        assert_eq!(view.method, "GET");
+        assert_eq!(view.url_input, "{{HOST}}/items/{{ID}}");
+        assert_eq!(view.collection_id, Some(7));
+        assert_eq!(view.local_variables(), req.variables);
+        assert_eq!(view.extraction_rules, req.extractions);
+        assert_eq!(view.assertions, req.assertions);
     }
 
     #[test]
@@ -253,18 +242,13 @@ mod tests {
         let req = CollectionRequest {
             id: 1,
             collection_id: 1,
-            folder_id: None,
             name: "Protected".to_string(),
             method: "GET".to_string(),
             url: "https://api.example.com/protected".to_string(),
-            headers: vec![],
-            body: None,
             body_type: "text".to_string(),
             auth_type: "bearer".to_string(),
             auth_data: Some("my-token".to_string()),
-            params: vec![],
-            config_json: None,
-            sort_order: 0,
+            ..Default::default()
         };
         let view = build_view_from_collection_request(&req);
         match &view.auth {
@@ -278,18 +262,13 @@ mod tests {
         let req = CollectionRequest {
             id: 1,
             collection_id: 1,
-            folder_id: None,
             name: "Basic Auth".to_string(),
             method: "GET".to_string(),
             url: "https://api.example.com".to_string(),
-            headers: vec![],
-            body: None,
             body_type: "text".to_string(),
             auth_type: "basic".to_string(),
             auth_data: Some("admin:secret".to_string()),
-            params: vec![],
-            config_json: None,
-            sort_order: 0,
+            ..Default::default()
         };
         let view = build_view_from_collection_request(&req);
         match &view.auth {
@@ -306,18 +285,13 @@ mod tests {
         let req = CollectionRequest {
             id: 1,
             collection_id: 1,
-            folder_id: None,
             name: "API Key".to_string(),
             method: "GET".to_string(),
             url: "https://api.example.com".to_string(),
-            headers: vec![],
-            body: None,
             body_type: "text".to_string(),
             auth_type: "api_key".to_string(),
             auth_data: Some("X-API-Key:abc123".to_string()),
-            params: vec![],
-            config_json: None,
-            sort_order: 0,
+            ..Default::default()
         };
         let view = build_view_from_collection_request(&req);
         match &view.auth {
@@ -334,18 +308,13 @@ mod tests {
         let req = CollectionRequest {
             id: 1,
             collection_id: 1,
-            folder_id: None,
             name: "Digest".to_string(),
             method: "GET".to_string(),
             url: "https://api.example.com".to_string(),
-            headers: vec![],
-            body: None,
             body_type: "text".to_string(),
             auth_type: "digest".to_string(),
             auth_data: Some("admin:secret".to_string()),
-            params: vec![],
-            config_json: None,
-            sort_order: 0,
+            ..Default::default()
         };
         let view = build_view_from_collection_request(&req);
         match &view.auth {
@@ -375,18 +344,13 @@ mod tests {
         let req = CollectionRequest {
             id: 1,
             collection_id: 1,
-            folder_id: None,
             name: "OAuth2".to_string(),
             method: "GET".to_string(),
             url: "https://api.example.com".to_string(),
-            headers: vec![],
-            body: None,
             body_type: "text".to_string(),
             auth_type: "oauth2".to_string(),
             auth_data: Some(auth_json),
-            params: vec![],
-            config_json: None,
-            sort_order: 0,
+            ..Default::default()
         };
         let view = build_view_from_collection_request(&req);
         match &view.auth {
@@ -409,18 +373,13 @@ mod tests {
         let req = CollectionRequest {
             id: 1,
             collection_id: 1,
-            folder_id: None,
             name: "OAuth2".to_string(),
             method: "GET".to_string(),
             url: "https://api.example.com".to_string(),
-            headers: vec![],
-            body: None,
             body_type: "text".to_string(),
             auth_type: "oauth2".to_string(),
             auth_data: Some("my-access-token".to_string()),
-            params: vec![],
-            config_json: None,
-            sort_order: 0,
+            ..Default::default()
         };
         let view = build_view_from_collection_request(&req);
         match &view.auth {
@@ -439,18 +398,12 @@ mod tests {
         let req = CollectionRequest {
             id: 1,
             collection_id: 1,
-            folder_id: None,
             name: "With Config".to_string(),
             method: "GET".to_string(),
             url: "https://api.example.com".to_string(),
-            headers: vec![],
-            body: None,
             body_type: "text".to_string(),
             auth_type: "none".to_string(),
-            auth_data: None,
-            params: vec![],
-            config_json: None,
-            sort_order: 0,
+            ..Default::default()
         };
         let view = build_view_from_collection_request(&req);
         assert_eq!(view.request_config, RequestConfig::default());
@@ -461,18 +414,12 @@ mod tests {
         let req = CollectionRequest {
             id: 1,
             collection_id: 1,
-            folder_id: None,
             name: "Upload".to_string(),
             method: "POST".to_string(),
             url: "https://api.example.com/upload".to_string(),
-            headers: vec![],
-            body: None,
             body_type: "multipart".to_string(),
             auth_type: "none".to_string(),
-            auth_data: None,
-            params: vec![],
-            config_json: None,
-            sort_order: 0,
+            ..Default::default()
         };
         let view = build_view_from_collection_request(&req);
         assert_eq!(view.body_type, BodyType::Multipart);
@@ -483,18 +430,14 @@ mod tests {
         let req = CollectionRequest {
             id: 1,
             collection_id: 1,
-            folder_id: None,
             name: "Full".to_string(),
             method: "GET".to_string(),
             url: "https://api.example.com".to_string(),
             headers: vec![("X-Custom".to_string(), "value".to_string())],
-            body: None,
             body_type: "text".to_string(),
             auth_type: "none".to_string(),
-            auth_data: None,
             params: vec![("key".to_string(), "val".to_string())],
-            config_json: None,
-            sort_order: 0,
+            ..Default::default()
         };
         let view = build_view_from_collection_request(&req);
         assert_eq!(view.headers_editor.entries.len(), 1);
@@ -524,24 +467,32 @@ mod tests {
                 device_code_expires_in: Some(300),
                 device_code_interval: Some(5),
                 status: crate::data::auth::OAuth2Status::default(),
+                issuer_url: "https://issuer.example.com".to_string(),
+                jwks_uri: "https://issuer.example.com/jwks".to_string(),
+                scopes_supported: vec!["openid".to_string(), "email".to_string()],
+                code_challenge_methods_supported: vec!["S256".to_string()],
+                id_token: "eyJhbGciOiJSUzI1NiJ9.e30.sig".to_string(),
+                username: "alice".to_string(),
+                password: "hunter2".to_string(),
+                assertion: "assertion.jwt.sig".to_string(),
+                client_auth_method: crate::data::auth::ClientAuthMethod::PrivateKeyJwt,
+                private_key: "/keys/client.pem".to_string(),
+                introspection_url: "https://issuer.example.com/introspect".to_string(),
+                revocation_url: "https://issuer.example.com/revoke".to_string(),
+                introspection: None,
             }));
 
         let json = serde_json::to_string(&original_auth).unwrap();
         let req = CollectionRequest {
             id: 1,
             collection_id: 1,
-            folder_id: None,
             name: "Full OAuth2".to_string(),
             method: "POST".to_string(),
             url: "https://api.example.com/resource".to_string(),
-            headers: vec![],
-            body: None,
             body_type: "text".to_string(),
             auth_type: "oauth2".to_string(),
             auth_data: Some(json),
-            params: vec![],
-            config_json: None,
-            sort_order: 0,
+            ..Default::default()
         };
         let view = build_view_from_collection_request(&req);
         match &view.auth {
@@ -562,6 +513,24 @@ mod tests {
                 assert_eq!(config.verification_uri, "https://device.example.com/verify");
                 assert_eq!(config.device_code_expires_in, Some(300));
                 assert_eq!(config.device_code_interval, Some(5));
+                assert_eq!(config.issuer_url, "https://issuer.example.com");
+                assert_eq!(config.jwks_uri, "https://issuer.example.com/jwks");
+                assert_eq!(config.scopes_supported, vec!["openid", "email"]);
+                assert_eq!(config.code_challenge_methods_supported, vec!["S256"]);
+                assert_eq!(config.id_token, "eyJhbGciOiJSUzI1NiJ9.e30.sig");
+                assert_eq!(config.username, "alice");
+                assert_eq!(config.password, "hunter2");
+                assert_eq!(config.assertion, "assertion.jwt.sig");
+                assert_eq!(
+                    config.client_auth_method,
+                    crate::data::auth::ClientAuthMethod::PrivateKeyJwt
+                );
+                assert_eq!(config.private_key, "/keys/client.pem");
+                assert_eq!(
+                    config.introspection_url,
+                    "https://issuer.example.com/introspect"
+                );
+                assert_eq!(config.revocation_url, "https://issuer.example.com/revoke");
                 assert!(matches!(
                     config.grant_type,
                     crate::data::auth::OAuth2GrantType::DeviceCode
@@ -583,18 +552,13 @@ mod tests {
         let req = CollectionRequest {
             id: 1,
             collection_id: 1,
-            folder_id: None,
             name: "API Key".to_string(),
             method: "GET".to_string(),
             url: "https://api.example.com".to_string(),
-            headers: vec![],
-            body: None,
             body_type: "text".to_string(),
             auth_type: "api_key".to_string(),
             auth_data: Some(json),
-            params: vec![],
-            config_json: None,
-            sort_order: 0,
+            ..Default::default()
         };
         let view = build_view_from_collection_request(&req);
         match &view.auth {
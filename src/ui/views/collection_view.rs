@@ -1,10 +1,14 @@
 use crate::persistence::database::{Collection, CollectionFolder, CollectionRequest};
+use crate::services::openapi_service::ServerEnvironment;
+use crate::ui::components::key_value_editor::{self, KeyValueEditor};
 use crate::ui::theme;
+use crate::ui::views::load_test_view::LoadTarget;
 use iced::{
     widget::{button, column, container, row, scrollable, text, text_input},
     Alignment, Color, Element, Length, Renderer, Theme,
 };
 use iced_fonts::lucide;
+use std::path::PathBuf;
 
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -26,8 +30,14 @@ pub enum Message {
     ImportCollection,
     ImportCollectionData(Option<String>),
     ImportOpenApi,
-    ImportOpenApiData(Option<String>),
+    /// Path and content of the picked spec.
+    ImportOpenApiData(Option<(PathBuf, String)>),
+    /// Saves the environments offered for the servers of an imported spec.
+    CreateServerEnvironments,
+    DismissServerEnvironments,
     ExportCollection(usize),
+    /// Exports in the app's own format, which keeps auth, extractions and assertions.
+    ExportAstraNovaCollection(usize),
     ExportCollectionData(String),
     NewFolderNameChanged(i32, String),
     CreateFolder(i32),
@@ -48,6 +58,17 @@ pub enum Message {
     RequestDeleteRequest(i32),
     ConfirmDeleteRequest(i32),
     CancelDeleteRequest,
+    ToggleVariables,
+    VariablesEditor(key_value_editor::Message),
+    SaveVariables,
+    RunCollection(i32, Option<i32>),
+    LoadTest(LoadTarget),
+    /// Opens the mock server, with routes from a collection when given.
+    MockServer(Option<i32>),
+    /// Opens the schemas of the spec a collection was imported from.
+    SchemaBrowser(i32),
+    /// Re-reads the spec a collection was imported from and offers its changes.
+    ResyncOpenApi(i32),
     Close,
 }
 
@@ -81,6 +102,12 @@ pub struct CollectionView {
     pub pending_delete_collection: Option<usize>,
     pub pending_delete_folder: Option<i32>,
     pub pending_delete_request: Option<i32>,
+    pub show_variables: bool,
+    pub variables_editor: KeyValueEditor,
+    /// Environments offered after an OpenAPI import, one per server.
+    pub server_environments: Vec<ServerEnvironment>,
+    /// Whether the open collection was imported from an OpenAPI spec.
+    pub has_openapi_spec: bool,
 }
 
 impl Clone for CollectionView {
@@ -106,6 +133,10 @@ impl Clone for CollectionView {
             pending_delete_collection: self.pending_delete_collection,
             pending_delete_folder: self.pending_delete_folder,
             pending_delete_request: self.pending_delete_request,
+            show_variables: self.show_variables,
+            variables_editor: self.variables_editor.clone(),
+            server_environments: self.server_environments.clone(),
+            has_openapi_spec: self.has_openapi_spec,
         }
     }
 }
@@ -126,8 +157,32 @@ impl CollectionView {
             Message::SelectCollection(idx) => {
                 self.panel_state = PanelState::CollectionDetail(idx);
                 self.selected_collection_id = self.collections.get(idx).map(|c| c.id);
+                self.variables_editor = KeyValueEditor::new("Add Variable".to_string());
+                if let Some(col) = self.collections.get(idx) {
+                    if !col.variables.is_empty() {
+                        self.variables_editor.entries = col
+                            .variables
+                            .iter()
+                            .enumerate()
+                            .map(|(i, (k, v))| key_value_editor::KeyValueEntry {
+                                id: i,
+                                key: k.clone(),
+                                value: v.clone(),
+                            })
+                            .collect();
+                    }
+                }
+                None
+            }
+            Message::ToggleVariables => {
+                self.show_variables = !self.show_variables;
+                None
+            }
+            Message::VariablesEditor(msg) => {
+                self.variables_editor.update(msg);
                 None
             }
+            Message::SaveVariables => None,
             Message::SelectFolder(folder_id) => {
                 if let PanelState::CollectionDetail(col_idx) = self.panel_state {
                     self.panel_state = PanelState::FolderDetail(col_idx, folder_id);
@@ -192,10 +247,21 @@ impl CollectionView {
             Message::ImportCollectionData(_) => None,
             Message::ImportOpenApi => None,
             Message::ImportOpenApiData(_) => None,
+            Message::CreateServerEnvironments => None,
+            Message::DismissServerEnvironments => {
+                self.server_environments.clear();
+                None
+            }
             Message::ExportCollection(_) => None,
+            Message::ExportAstraNovaCollection(_) => None,
             Message::ExportCollectionData(_) => None,
             Message::LoadRequest(req_id) => Some(req_id),
             Message::SaveCurrentRequest => None,
+            Message::RunCollection(_, _) => None,
+            Message::LoadTest(_) => None,
+            Message::MockServer(_) => None,
+            Message::SchemaBrowser(_) => None,
+            Message::ResyncOpenApi(_) => None,
             Message::StartRenameCollection(idx) => {
                 if let Some(col) = self.collections.get(idx) {
                     self.renaming_collection = Some(idx);
@@ -289,6 +355,13 @@ impl CollectionView {
         }
     }
 
+    pub fn collection_variables(&self, collection_id: Option<i32>) -> &[(String, String)] {
+        collection_id
+            .and_then(|id| self.collections.iter().find(|c| c.id == id))
+            .map(|c| c.variables.as_slice())
+            .unwrap_or(&[])
+    }
+
     pub fn sync_folders(&mut self, folders: &[CollectionFolder]) {
         self.folders = folders.to_vec();
         self.expanded_folders.resize(folders.len(), false);
@@ -316,6 +389,8 @@ impl CollectionView {
                 .on_press(Message::ImportCollection),
             button(row![lucide::file_code().size(14), text(" OpenAPI")].spacing(4))
                 .on_press(Message::ImportOpenApi),
+            button(row![lucide::server().size(14), text(" Mock")].spacing(4))
+                .on_press(Message::MockServer(None)),
         ]
         .spacing(10)
         .align_y(Alignment::Center);
@@ -371,6 +446,8 @@ impl CollectionView {
                             .on_press(Message::StartRenameCollection(index)),
                         button(lucide::download().size(12))
                             .on_press(Message::ExportCollection(index)),
+                        button(lucide::file_json().size(12))
+                            .on_press(Message::ExportAstraNovaCollection(index)),
                         button(
                             text("Delete?")
                                 .size(11)
@@ -389,6 +466,8 @@ impl CollectionView {
                             .on_press(Message::StartRenameCollection(index)),
                         button(lucide::download().size(12))
                             .on_press(Message::ExportCollection(index)),
+                        button(lucide::file_json().size(12))
+                            .on_press(Message::ExportAstraNovaCollection(index)),
                         button(
                             lucide::trash()
                                 .size(12)
@@ -423,21 +502,71 @@ impl CollectionView {
             );
         }
 
+        let mut content = column![header].spacing(8).padding(10);
+        if let Some(offer) = self.server_environments_offer() {
+            content = content.push(offer);
+        }
+
         container(
-            column![
-                header,
-                new_collection_input,
-                save_button,
-                scrollable(list).height(Length::Fill),
-            ]
-            .spacing(8)
-            .padding(10),
+            content
+                .push(new_collection_input)
+                .push(save_button)
+                .push(scrollable(list).height(Length::Fill)),
         )
         .width(Length::Fill)
         .height(Length::Fill)
         .into()
     }
 
+    fn server_environments_offer(&self) -> Option<Element<'_, Message, Theme, Renderer>> {
+        if self.server_environments.is_empty() {
+            return None;
+        }
+        let names = self
+            .server_environments
+            .iter()
+            .fold(column![].spacing(2), |col, proposed| {
+                let env = &proposed.environment;
+                let col = col.push(
+                    text(format!(
+                        "{}  {}",
+                        env.name,
+                        env.default_endpoint.as_deref().unwrap_or("")
+                    ))
+                    .size(11)
+                    .color(Color::from_rgb(0.5, 0.5, 0.5)),
+                );
+                proposed.choices.iter().fold(col, |col, (name, values)| {
+                    col.push(
+                        text(format!("    {{{{{}}}}}: {}", name, values.join(" | ")))
+                            .size(11)
+                            .color(Color::from_rgb(0.5, 0.5, 0.5)),
+                    )
+                })
+            });
+        Some(
+            container(
+                column![
+                    text(format!(
+                        "Create {} environment(s) from the spec's servers?",
+                        self.server_environments.len()
+                    ))
+                    .size(13),
+                    names,
+                    row![
+                        button(text("Create").size(12)).on_press(Message::CreateServerEnvironments),
+                        button(text("Skip").size(12)).on_press(Message::DismissServerEnvironments),
+                    ]
+                    .spacing(6),
+                ]
+                .spacing(6),
+            )
+            .padding(8)
+            .style(container::bordered_box)
+            .into(),
+        )
+    }
+
     fn collection_detail_view(&self, col_idx: usize) -> Element<'_, Message, Theme, Renderer> {
         let col = match self.collections.get(col_idx) {
             Some(c) => c,
@@ -447,9 +576,63 @@ impl CollectionView {
         let back_button = button(row![lucide::arrow_left().size(14), text(" Back")].spacing(4))
             .on_press(Message::Close);
 
-        let header = row![back_button, text(&col.name).size(16),]
-            .spacing(10)
-            .align_y(Alignment::Center);
+        let variables_chevron = if self.show_variables {
+            lucide::chevron_down().size(12)
+        } else {
+            lucide::chevron_right().size(12)
+        };
+        let variables_button = button(
+            row![
+                variables_chevron,
+                text(format!(" Variables ({})", col.variables.len())).size(12)
+            ]
+            .spacing(4),
+        )
+        .on_press(Message::ToggleVariables);
+
+        let run_button = button(row![lucide::play().size(14), text(" Run")].spacing(4))
+            .on_press(Message::RunCollection(col.id, None));
+        let load_button = button(row![lucide::gauge().size(14), text(" Load test")].spacing(4))
+            .on_press(Message::LoadTest(LoadTarget::Folder {
+                collection_id: col.id,
+                folder_id: None,
+            }));
+        let mock_button = button(row![lucide::server().size(14), text(" Mock")].spacing(4))
+            .on_press(Message::MockServer(Some(col.id)));
+
+        let mut header = row![
+            back_button,
+            text(&col.name).size(16),
+            variables_button,
+            run_button,
+            load_button,
+            mock_button,
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+        if self.has_openapi_spec {
+            header = header
+                .push(
+                    button(row![lucide::braces().size(14), text(" Schemas")].spacing(4))
+                        .on_press(Message::SchemaBrowser(col.id)),
+                )
+                .push(
+                    button(row![lucide::refresh_cw().size(14), text(" Re-sync")].spacing(4))
+                        .on_press(Message::ResyncOpenApi(col.id)),
+                );
+        }
+
+        let variables_section: Element<'_, Message, Theme, Renderer> = if self.show_variables {
+            column![
+                self.variables_editor.view().map(Message::VariablesEditor),
+                button(row![lucide::save().size(14), text(" Save Variables")].spacing(4))
+                    .on_press(Message::SaveVariables),
+            ]
+            .spacing(8)
+            .into()
+        } else {
+            column![].into()
+        };
 
         let new_folder_input = text_input("New folder name...", &self.new_folder_name)
             .on_input(|s| Message::NewFolderNameChanged(col.id, s))
@@ -591,6 +774,12 @@ impl CollectionView {
                                             .color(Color::from_rgb(0.8, 0.2, 0.2))
                                     )
                                     .on_press(Message::RequestDeleteRequest(req.id)),
+                                    button(lucide::gauge().size(10)).on_press(Message::LoadTest(
+                                        LoadTarget::Request {
+                                            collection_id: req.collection_id,
+                                            request_id: req.id,
+                                        }
+                                    )),
                                 ]
                                 .spacing(4)
                                 .align_y(Alignment::Center)
@@ -669,6 +858,12 @@ impl CollectionView {
                                 .color(Color::from_rgb(0.8, 0.2, 0.2))
                         )
                         .on_press(Message::RequestDeleteRequest(req.id)),
+                        button(lucide::gauge().size(10)).on_press(Message::LoadTest(
+                            LoadTarget::Request {
+                                collection_id: req.collection_id,
+                                request_id: req.id,
+                            }
+                        )),
                     ]
                     .spacing(6)
                     .align_y(Alignment::Center)
@@ -682,6 +877,7 @@ impl CollectionView {
         container(
             column![
                 header,
+                variables_section,
                 folder_controls,
                 scrollable(list).height(Length::Fill),
             ]
@@ -698,19 +894,31 @@ impl CollectionView {
         _col_idx: usize,
         folder_id: i32,
     ) -> Element<'_, Message, Theme, Renderer> {
-        let folder_name = self
-            .folders
-            .iter()
-            .find(|f| f.id == folder_id)
-            .map(|f| f.name.as_str())
-            .unwrap_or("");
+        let folder = self.folders.iter().find(|f| f.id == folder_id);
+        let folder_name = folder.map(|f| f.name.as_str()).unwrap_or("");
 
         let back_button = button(row![lucide::arrow_left().size(14), text(" Back")].spacing(4))
             .on_press(Message::Close);
 
-        let header = row![back_button, text(folder_name).size(16),]
-            .spacing(10)
-            .align_y(Alignment::Center);
+        let run_button = button(row![lucide::play().size(14), text(" Run")].spacing(4))
+            .on_press_maybe(folder.map(|f| Message::RunCollection(f.collection_id, Some(f.id))));
+
+        let load_button = button(row![lucide::gauge().size(14), text(" Load test")].spacing(4))
+            .on_press_maybe(folder.map(|f| {
+                Message::LoadTest(LoadTarget::Folder {
+                    collection_id: f.collection_id,
+                    folder_id: Some(f.id),
+                })
+            }));
+
+        let header = row![
+            back_button,
+            text(folder_name).size(16),
+            run_button,
+            load_button
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
 
         let mut list = column![].spacing(4);
 
@@ -767,6 +975,12 @@ impl CollectionView {
                                     .color(Color::from_rgb(0.8, 0.2, 0.2))
                             )
                             .on_press(Message::RequestDeleteRequest(req.id)),
+                            button(lucide::gauge().size(10)).on_press(Message::LoadTest(
+                                LoadTarget::Request {
+                                    collection_id: req.collection_id,
+                                    request_id: req.id,
+                                }
+                            )),
                         ]
                         .spacing(6)
                         .align_y(Alignment::Center)
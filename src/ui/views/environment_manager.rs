@@ -14,6 +14,8 @@ pub enum Message {
     DefaultEndpointChanged(String),
     NewEnvironmentNameChanged(String),
     VariablesEditor(key_value_editor::Message),
+    GlobalsEditor(key_value_editor::Message),
+    SaveGlobals,
     UpdateVariables(Vec<(String, String)>),
     CreateEnvironment,
     SaveEnvironment,
@@ -22,6 +24,9 @@ pub enum Message {
     CancelDeleteEnvironment,
     LoadEnvFile,
     ExportEnvFile,
+    VaultPassphraseChanged(String),
+    UnlockVault,
+    LockVault,
     Close,
 }
 
@@ -31,7 +36,11 @@ pub struct EnvironmentManagerView {
     pub selected_environment: Option<Environment>,
     pub new_environment_name: String,
     pub variables_editor: KeyValueEditor,
+    pub globals_editor: KeyValueEditor,
     pub pending_delete_environment: Option<i32>,
+    pub vault_passphrase: String,
+    pub vault_unlocked: bool,
+    pub vault_exists: bool,
 }
 
 impl EnvironmentManagerView {
@@ -40,11 +49,39 @@ impl EnvironmentManagerView {
             environments,
             selected_environment: None,
             new_environment_name: String::new(),
-            variables_editor: KeyValueEditor::new("Add Variable".to_string()),
+            variables_editor: KeyValueEditor::new("Add Variable".to_string()).with_secrets(),
+            globals_editor: KeyValueEditor::new("Add Global".to_string()),
             pending_delete_environment: None,
+            vault_passphrase: String::new(),
+            vault_unlocked: false,
+            vault_exists: false,
         }
     }
 
+    pub fn set_globals(&mut self, variables: &[(String, String)]) {
+        if variables.is_empty() {
+            return;
+        }
+        self.globals_editor.entries = variables
+            .iter()
+            .enumerate()
+            .map(|(i, (k, v))| key_value_editor::KeyValueEntry {
+                id: i,
+                key: k.clone(),
+                value: v.clone(),
+            })
+            .collect();
+    }
+
+    pub fn globals(&self) -> Vec<(String, String)> {
+        self.globals_editor
+            .entries
+            .iter()
+            .filter(|e| !e.key.is_empty())
+            .map(|e| (e.key.clone(), e.value.clone()))
+            .collect()
+    }
+
     pub fn update(&mut self, message: Message) {
         match message {
             Message::SelectEnvironment(id) => {
@@ -60,6 +97,11 @@ impl EnvironmentManagerView {
                             value: v.clone(),
                         })
                         .collect();
+                    self.variables_editor.clear_secrets();
+                    for (i, (k, _)) in env.variables.iter().enumerate() {
+                        self.variables_editor
+                            .set_secret(i, env.secret_keys.contains(k));
+                    }
                 }
             }
             Message::EnvironmentNameChanged(name) => {
@@ -96,6 +138,10 @@ impl EnvironmentManagerView {
                 }
             }
             Message::VariablesEditor(msg) => self.variables_editor.update(msg),
+            Message::GlobalsEditor(msg) => self.globals_editor.update(msg),
+            Message::SaveGlobals => {
+                // This message is handled in app.rs
+            }
             Message::CreateEnvironment => {
                 // This message is handled in app.rs
             }
@@ -107,6 +153,7 @@ impl EnvironmentManagerView {
                         .iter()
                         .map(|e| (e.key.clone(), e.value.clone()))
                         .collect();
+                    env.secret_keys = self.variables_editor.secret_keys();
                 }
             }
             Message::RequestDeleteEnvironment(env_id) => {
@@ -124,6 +171,15 @@ impl EnvironmentManagerView {
             Message::ExportEnvFile => {
                 // This message is handled in app.rs
             }
+            Message::VaultPassphraseChanged(passphrase) => {
+                self.vault_passphrase = passphrase;
+            }
+            Message::UnlockVault => {
+                // This message is handled in app.rs
+            }
+            Message::LockVault => {
+                // This message is handled in app.rs
+            }
             Message::Close => {}
         }
     }
@@ -194,10 +250,52 @@ impl EnvironmentManagerView {
         ]
         .spacing(10);
 
+        let globals_section = column![
+            text("Global Variables"),
+            text("Available in every environment; environment and collection values take precedence.")
+                .size(12),
+            self.globals_editor.view().map(Message::GlobalsEditor),
+            button(row![lucide::save().size(14), text(" Save Globals")].spacing(4))
+                .on_press(Message::SaveGlobals),
+        ]
+        .spacing(10);
+
+        let vault_section = if self.vault_unlocked {
+            column![
+                row![lucide::lock_open().size(14), text(" Vault unlocked")].spacing(4),
+                button(row![lucide::lock().size(14), text(" Lock")].spacing(4))
+                    .on_press(Message::LockVault),
+            ]
+        } else {
+            let (label, action) = if self.vault_exists {
+                ("Vault locked; secret values stay encrypted", " Unlock")
+            } else {
+                (
+                    "Set a master passphrase to encrypt secret values",
+                    " Create Vault",
+                )
+            };
+            column![
+                row![lucide::lock().size(14), text(format!(" {}", label))].spacing(4),
+                row![
+                    text_input("Master passphrase", &self.vault_passphrase)
+                        .on_input(Message::VaultPassphraseChanged)
+                        .on_submit(Message::UnlockVault)
+                        .secure(true),
+                    button(row![lucide::key_round().size(14), text(action)].spacing(4))
+                        .on_press(Message::UnlockVault),
+                ]
+                .spacing(10),
+            ]
+        }
+        .spacing(10);
+
         let content = column![
             row![text("Environments"), environments_list].spacing(10),
+            vault_section,
             environment_details,
             create_new_env_section,
+            globals_section,
             button(row![lucide::x().size(14), text(" Close")].spacing(4)).on_press(Message::Close),
         ]
         .spacing(20)
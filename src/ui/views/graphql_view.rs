@@ -1,5 +1,6 @@
 use crate::data::auth::Auth;
 use crate::data::auth::AuthType;
+use crate::data::variables::VariableResolver;
 use crate::http_client::config::RequestConfig;
 use crate::protocols::graphql::{GraphQLRequest, GraphQLResponse};
 use crate::ui::components::key_value_editor::{self, KeyValueEditor};
@@ -207,73 +208,64 @@ impl Default for GraphQLView {
 }
 
 impl GraphQLView {
-    pub fn apply_environment(&mut self, env: &crate::persistence::database::Environment) {
-        for (key, value) in &env.variables {
-            let placeholder = format!("{{{{{}}}}}", key);
-            self.url_input = self.url_input.replace(&placeholder, value);
-
-            let new_query = self.query_input.text().replace(&placeholder, value);
-            self.query_input = text_editor::Content::with_text(&new_query);
-
-            let new_vars = self.variables_input.text().replace(&placeholder, value);
-            self.variables_input = text_editor::Content::with_text(&new_vars);
-
-            self.operation_name = self.operation_name.replace(&placeholder, value);
+    /// Names of `{{variables}}` used by this request that `resolver` cannot fill.
+    pub fn unresolved_variables(&self, resolver: &VariableResolver) -> Vec<String> {
+        let mut texts = vec![
+            self.url_input.clone(),
+            self.query_input.text(),
+            self.variables_input.text(),
+            self.operation_name.clone(),
+        ];
+        for entry in &self.headers_editor.entries {
+            texts.push(entry.key.clone());
+            texts.push(entry.value.clone());
+        }
 
-            for entry in &mut self.headers_editor.entries {
-                entry.value = entry.value.replace(&placeholder, value);
+        let mut missing: Vec<String> = Vec::new();
+        let found = texts
+            .iter()
+            .flat_map(|text| resolver.unresolved(text))
+            .chain(resolver.unresolved_in_auth(&self.auth));
+        for name in found {
+            if !missing.contains(&name) {
+                missing.push(name);
             }
+        }
+        missing
+    }
 
-            match &mut self.auth {
-                Auth::BearerToken(token) => {
-                    *token = token.replace(&placeholder, value);
-                }
-                Auth::Basic { user, pass } => {
-                    *user = user.replace(&placeholder, value);
-                    *pass = pass.replace(&placeholder, value);
-                }
-                Auth::ApiKey {
-                    key, value: val, ..
-                } => {
-                    *key = key.replace(&placeholder, value);
-                    *val = val.replace(&placeholder, value);
-                }
-                Auth::Digest { user, pass } => {
-                    *user = user.replace(&placeholder, value);
-                    *pass = pass.replace(&placeholder, value);
-                }
-                Auth::OAuth2(config) => {
-                    config.auth_url = config.auth_url.replace(&placeholder, value);
-                    config.token_url = config.token_url.replace(&placeholder, value);
-                    config.device_auth_url = config.device_auth_url.replace(&placeholder, value);
-                    config.client_id = config.client_id.replace(&placeholder, value);
-                    config.client_secret = config.client_secret.replace(&placeholder, value);
-                    config.scopes = config.scopes.replace(&placeholder, value);
-                    config.redirect_uri = config.redirect_uri.replace(&placeholder, value);
-                    config.access_token = config.access_token.replace(&placeholder, value);
-                    config.refresh_token = config.refresh_token.replace(&placeholder, value);
-                }
-                Auth::None => {}
-            }
+    fn resolve(resolver: Option<&VariableResolver>, text: &str) -> String {
+        match resolver {
+            Some(resolver) => resolver.resolve(text),
+            None => text.to_string(),
         }
     }
 
-    pub fn build_request(&self) -> Result<GraphQLRequest, String> {
-        let query = self.query_input.text();
+    pub fn build_resolved_request(
+        &self,
+        resolver: &VariableResolver,
+    ) -> Result<GraphQLRequest, String> {
+        self.build_request_with(Some(resolver))
+    }
+
+    fn build_request_with(
+        &self,
+        resolver: Option<&VariableResolver>,
+    ) -> Result<GraphQLRequest, String> {
+        let query = Self::resolve(resolver, &self.query_input.text());
         crate::protocols::graphql::validate_query(&query)?;
 
-        let variables = if self.variables_input.text().trim().is_empty() {
+        let variables_text = Self::resolve(resolver, &self.variables_input.text());
+        let variables = if variables_text.trim().is_empty() {
             None
         } else {
-            Some(crate::protocols::graphql::parse_variables(
-                &self.variables_input.text(),
-            )?)
+            Some(crate::protocols::graphql::parse_variables(&variables_text)?)
         };
 
         let operation_name = if self.operation_name.trim().is_empty() {
             None
         } else {
-            Some(self.operation_name.clone())
+            Some(Self::resolve(resolver, &self.operation_name))
         };
 
         Ok(GraphQLRequest {
@@ -283,24 +275,45 @@ impl GraphQLView {
         })
     }
 
-    pub fn build_http_request(&self) -> crate::http_client::request::HttpRequest {
-        let graphql_request = self.build_request().unwrap_or_else(|_| GraphQLRequest {
-            query: String::new(),
-            variables: None,
-            operation_name: None,
-        });
+    pub fn build_resolved_http_request(
+        &self,
+        resolver: &VariableResolver,
+    ) -> crate::http_client::request::HttpRequest {
+        self.build_http_request_with(Some(resolver))
+    }
+
+    fn build_http_request_with(
+        &self,
+        resolver: Option<&VariableResolver>,
+    ) -> crate::http_client::request::HttpRequest {
+        let graphql_request =
+            self.build_request_with(resolver)
+                .unwrap_or_else(|_| GraphQLRequest {
+                    query: String::new(),
+                    variables: None,
+                    operation_name: None,
+                });
+        let auth = match resolver {
+            Some(resolver) => resolver.resolve_auth(&self.auth),
+            None => self.auth.clone(),
+        };
 
         let mut headers: Vec<(String, String)> = self
             .headers_editor
             .entries
             .iter()
             .filter(|h| !h.key.is_empty())
-            .map(|h| (h.key.clone(), h.value.clone()))
+            .map(|h| {
+                (
+                    Self::resolve(resolver, &h.key),
+                    Self::resolve(resolver, &h.value),
+                )
+            })
             .collect();
 
         headers.push(("Content-Type".to_string(), "application/json".to_string()));
 
-        match &self.auth {
+        match &auth {
             Auth::BearerToken(token) if !token.is_empty() => {
                 headers.push(("Authorization".to_string(), format!("Bearer {}", token)));
             }
@@ -319,7 +332,16 @@ impl GraphQLView {
                 crate::data::auth::ApiKeyLocation::Query => {
                     // API Key in query not typical for GraphQL, but supported
                 }
+                crate::data::auth::ApiKeyLocation::Cookie => {
+                    headers.push(("Cookie".to_string(), format!("{}={}", key, value)));
+                }
             },
+            Auth::OAuth2(config) if !config.access_token.is_empty() => {
+                headers.push((
+                    "Authorization".to_string(),
+                    format!("Bearer {}", config.access_token),
+                ));
+            }
             _ => {}
         }
 
@@ -327,12 +349,12 @@ impl GraphQLView {
 
         crate::http_client::request::HttpRequest {
             method: "POST".to_string(),
-            url: self.url_input.clone(),
+            url: Self::resolve(resolver, &self.url_input),
             headers,
             body: Some(body),
             config: self.request_config.clone(),
             multipart_fields: vec![],
-            auth: Some(self.auth.clone()),
+            auth: Some(auth),
         }
     }
 
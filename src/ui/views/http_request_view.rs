@@ -1,10 +1,15 @@
+use crate::data::assertions::{run_assertions, Assertion, AssertionKind, AssertionResult};
 use crate::data::auth::{Auth, AuthType};
+use crate::data::extraction::{
+    run_extractions, Extracted, ExtractionRule, ExtractionSource, ExtractionTarget,
+};
+use crate::data::variables::VariableResolver;
 use crate::http_client::config::RequestConfig;
 use crate::http_client::response::HttpResponse;
 use crate::http_client::snippets::{self, SnippetFormat};
-use crate::persistence::database::Environment;
+use crate::http_client::template::RequestTemplate;
+use crate::openapi::validation::{ResponseContract, Violation};
 use crate::ui::components::key_value_editor::{self, KeyValueEditor};
-use base64::{engine::general_purpose, Engine as _};
 use bytes::Bytes;
 use iced::highlighter;
 use iced::widget::image::{Handle, Image};
@@ -111,6 +116,18 @@ pub enum Message {
     AuthInputChanged(AuthInput),
     HeadersEditor(key_value_editor::Message),
     ParamsEditor(key_value_editor::Message),
+    VariablesEditor(key_value_editor::Message),
+    AddExtractionRule,
+    RemoveExtractionRule(usize),
+    ExtractionVariableChanged(usize, String),
+    ExtractionSourceSelected(usize, ExtractionSource),
+    ExtractionExpressionChanged(usize, String),
+    ExtractionTargetSelected(usize, ExtractionTarget),
+    AddAssertion,
+    RemoveAssertion(usize),
+    AssertionKindSelected(usize, AssertionKind),
+    AssertionTargetChanged(usize, String),
+    AssertionExpectedChanged(usize, String),
     BodyInputChanged(text_editor::Action),
     RequestContentTypeSelected(ContentType),
     SendRequest,
@@ -149,6 +166,10 @@ pub enum Message {
     OAuth2CopyUserCode(String),
     OAuth2CopyAccessToken(String),
     OAuth2CopyRefreshToken(String),
+    OAuth2Discover,
+    OAuth2VerifyIdToken,
+    OAuth2Introspect,
+    OAuth2Revoke,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -157,6 +178,9 @@ pub enum TabId {
     Body,
     Headers,
     Params,
+    Variables,
+    Extract,
+    Tests,
     Authorization,
     Settings,
 }
@@ -167,6 +191,8 @@ pub enum ResponseTab {
     Body,
     Headers,
     Timeline,
+    Tests,
+    Schema,
 }
 
 #[derive(Debug, Clone)]
@@ -190,6 +216,14 @@ pub enum AuthInput {
     OAuth2PkceEnabled(bool),
     OAuth2AccessToken(String),
     OAuth2RefreshToken(String),
+    OAuth2IssuerUrl(String),
+    OAuth2Username(String),
+    OAuth2Password(String),
+    OAuth2Assertion(String),
+    OAuth2ClientAuthMethod(crate::data::auth::ClientAuthMethod),
+    OAuth2PrivateKey(String),
+    OAuth2IntrospectionUrl(String),
+    OAuth2RevocationUrl(String),
 }
 
 #[derive(Debug, Default)]
@@ -232,6 +266,15 @@ pub struct HttpRequestView {
     pub auth: Auth,
     pub headers_editor: KeyValueEditor,
     pub params_editor: KeyValueEditor,
+    pub variables_editor: KeyValueEditor,
+    pub extraction_rules: Vec<ExtractionRule>,
+    pub extraction_results: Vec<Extracted>,
+    pub assertions: Vec<Assertion>,
+    pub assertion_results: Vec<AssertionResult>,
+    /// Responses documented by the OpenAPI operation this request was generated from.
+    pub response_contract: Option<ResponseContract>,
+    pub schema_violations: Vec<Violation>,
+    pub collection_id: Option<i32>,
     active_tab: TabId,
     active_response_tab: ResponseTab,
     request_status: RequestStatus,
@@ -264,6 +307,14 @@ impl Clone for HttpRequestView {
             auth: self.auth.clone(),
             headers_editor: self.headers_editor.clone(),
             params_editor: self.params_editor.clone(),
+            variables_editor: self.variables_editor.clone(),
+            extraction_rules: self.extraction_rules.clone(),
+            extraction_results: self.extraction_results.clone(),
+            assertions: self.assertions.clone(),
+            assertion_results: self.assertion_results.clone(),
+            response_contract: self.response_contract.clone(),
+            schema_violations: self.schema_violations.clone(),
+            collection_id: self.collection_id,
             active_tab: self.active_tab.clone(),
             active_response_tab: self.active_response_tab.clone(),
             request_status: self.request_status.clone(),
@@ -300,6 +351,14 @@ impl Default for HttpRequestView {
             auth: Auth::default(),
             headers_editor: KeyValueEditor::new("Add Header".to_string()),
             params_editor: KeyValueEditor::new("Add Param".to_string()),
+            variables_editor: KeyValueEditor::new("Add Variable".to_string()),
+            extraction_rules: Vec::new(),
+            extraction_results: Vec::new(),
+            assertions: Vec::new(),
+            assertion_results: Vec::new(),
+            response_contract: None,
+            schema_violations: Vec::new(),
+            collection_id: None,
             active_tab: TabId::Body,
             active_response_tab: ResponseTab::Body,
             request_status: RequestStatus::Idle,
@@ -353,176 +412,97 @@ impl HttpRequestView {
         self.multipart_next_id = fields.len();
     }
 
-    pub fn apply_environment(&mut self, env: &Environment) {
-        for (key, value) in &env.variables {
-            let placeholder = format!("{{{{{}}}}}", key);
-            self.url_input = self.url_input.replace(&placeholder, value);
-
-            let new_body = self.body_input.text().replace(&placeholder, value);
-            self.body_input = text_editor::Content::with_text(&new_body);
-
-            for entry in &mut self.headers_editor.entries {
-                entry.value = entry.value.replace(&placeholder, value);
-            }
-            for entry in &mut self.params_editor.entries {
-                entry.value = entry.value.replace(&placeholder, value);
-            }
+    /// Request-local variable overrides with a non-empty name.
+    pub fn local_variables(&self) -> Vec<(String, String)> {
+        self.variables_editor
+            .entries
+            .iter()
+            .filter(|e| !e.key.is_empty())
+            .map(|e| (e.key.clone(), e.value.clone()))
+            .collect()
+    }
 
-            match &mut self.auth {
-                Auth::BearerToken(token) => {
-                    *token = token.replace(&placeholder, value);
-                }
-                Auth::Basic { user, pass } => {
-                    *user = user.replace(&placeholder, value);
-                    *pass = pass.replace(&placeholder, value);
-                }
-                Auth::ApiKey {
-                    key, value: val, ..
-                } => {
-                    *key = key.replace(&placeholder, value);
-                    *val = val.replace(&placeholder, value);
-                }
-                Auth::Digest { user, pass } => {
-                    *user = user.replace(&placeholder, value);
-                    *pass = pass.replace(&placeholder, value);
-                }
-                Auth::OAuth2(config) => {
-                    config.auth_url = config.auth_url.replace(&placeholder, value);
-                    config.token_url = config.token_url.replace(&placeholder, value);
-                    config.device_auth_url = config.device_auth_url.replace(&placeholder, value);
-                    config.client_id = config.client_id.replace(&placeholder, value);
-                    config.client_secret = config.client_secret.replace(&placeholder, value);
-                    config.scopes = config.scopes.replace(&placeholder, value);
-                    config.redirect_uri = config.redirect_uri.replace(&placeholder, value);
-                    config.access_token = config.access_token.replace(&placeholder, value);
-                    config.refresh_token = config.refresh_token.replace(&placeholder, value);
-                }
-                Auth::None => {}
-            }
-        }
+    /// Names of `{{variables}}` used by this request that `resolver` cannot fill.
+    pub fn unresolved_variables(&self, resolver: &VariableResolver) -> Vec<String> {
+        self.template(&self.auth).unresolved_variables(resolver)
     }
 
+    /// Builds the request with `{{variables}}` left as typed.
     pub fn build_request(&self) -> crate::http_client::request::HttpRequest {
-        let params: Vec<(String, String)> = self
-            .params_editor
-            .entries
-            .iter()
-            .filter(|p| !p.key.is_empty())
-            .map(|p| (p.key.clone(), p.value.clone()))
-            .collect();
+        self.build(None, &self.auth)
+    }
 
-        let query_string = params
-            .iter()
-            .map(|(k, v)| format!("{}={}", urlencoding::encode(k), urlencoding::encode(v)))
-            .collect::<Vec<String>>()
-            .join("&");
-
-        let mut final_url = if query_string.is_empty() {
-            self.url_input.clone()
-        } else if self.url_input.contains('?') {
-            format!("{}&{}", self.url_input, query_string)
-        } else {
-            format!("{}?{}", self.url_input, query_string)
-        };
+    /// Builds the unresolved request with `auth` in place of the tab's own,
+    /// e.g. a redacted copy for history.
+    pub fn build_request_with_auth(&self, auth: &Auth) -> crate::http_client::request::HttpRequest {
+        self.build(None, auth)
+    }
 
-        let mut headers: Vec<(String, String)> = self
-            .headers_editor
-            .entries
-            .iter()
-            .filter(|h| !h.key.is_empty())
-            .map(|h| (h.key.clone(), h.value.clone()))
-            .collect();
+    /// Builds the request with variables resolved, leaving the view untouched.
+    pub fn build_resolved_request(
+        &self,
+        resolver: &VariableResolver,
+    ) -> crate::http_client::request::HttpRequest {
+        self.build(Some(resolver), &self.auth)
+    }
 
-        match &self.auth {
-            Auth::BearerToken(token) if !token.is_empty() => {
-                headers.push(("Authorization".to_string(), format!("Bearer {}", token)));
-            }
-            Auth::Basic { user, pass } if !user.is_empty() || !pass.is_empty() => {
-                let encoded = general_purpose::STANDARD.encode(format!("{}:{}", user, pass));
-                headers.push(("Authorization".to_string(), format!("Basic {}", encoded)));
-            }
-            Auth::ApiKey {
-                key,
-                value,
-                location,
-            } if !key.is_empty() => match location {
-                crate::data::auth::ApiKeyLocation::Header => {
-                    headers.push((key.clone(), value.clone()));
-                }
-                crate::data::auth::ApiKeyLocation::Query => {
-                    let separator = if final_url.contains('?') { "&" } else { "?" };
-                    final_url = format!(
-                        "{}{}{}={}",
-                        final_url,
-                        separator,
-                        urlencoding::encode(key),
-                        urlencoding::encode(value)
-                    );
-                }
-            },
-            Auth::OAuth2(config) if !config.access_token.is_empty() => {
-                headers.push((
-                    "Authorization".to_string(),
-                    format!("Bearer {}", config.access_token),
-                ));
-            }
-            _ => {}
+    fn build(
+        &self,
+        resolver: Option<&VariableResolver>,
+        auth: &Auth,
+    ) -> crate::http_client::request::HttpRequest {
+        let template = self.template(auth);
+        match resolver {
+            Some(resolver) => template.build_resolved(resolver),
+            None => template.build(),
         }
+    }
 
-        let body = if self.body_input.text().is_empty() {
-            None
-        } else {
-            Some(self.body_input.text())
+    /// The editor's fields, with `auth` in place of the tab's own.
+    fn template(&self, auth: &Auth) -> RequestTemplate {
+        let entries = |editor: &KeyValueEditor| {
+            editor
+                .entries
+                .iter()
+                .map(|e| (e.key.clone(), e.value.clone()))
+                .collect()
         };
-
-        // Only set Content-Type for text body (multipart sets it automatically)
-        if body.is_some() && self.body_type == BodyType::Text {
-            let content_type_str = match self.request_content_type {
-                ContentType::Json => "application/json",
-                ContentType::Text => "text/plain",
-                ContentType::Html => "text/html",
-                ContentType::Xml => "application/xml",
-            };
-            headers.push(("Content-Type".to_string(), content_type_str.to_string()));
-        }
-
-        // Convert multipart entries to MultipartField
-        let multipart_fields: Vec<crate::http_client::request::MultipartField> =
-            if self.body_type == BodyType::Multipart {
+        let body = self.body_input.text();
+        let is_text = self.body_type == BodyType::Text;
+        let content_type = match self.request_content_type {
+            ContentType::Json => "application/json",
+            ContentType::Text => "text/plain",
+            ContentType::Html => "text/html",
+            ContentType::Xml => "application/xml",
+        };
+        RequestTemplate {
+            method: self.method.to_string(),
+            url: self.url_input.clone(),
+            params: entries(&self.params_editor),
+            headers: entries(&self.headers_editor),
+            body: (!body.is_empty()).then_some(body),
+            // Multipart requests get their Content-Type from the form boundary
+            content_type: is_text.then(|| content_type.to_string()),
+            multipart_fields: if is_text {
+                Vec::new()
+            } else {
                 self.multipart_entries
                     .iter()
-                    .filter(|e| !e.name.is_empty())
-                    .map(|e| {
-                        if e.is_file {
-                            crate::http_client::request::MultipartField {
-                                name: e.name.clone(),
-                                value: crate::http_client::request::MultipartValue::File {
-                                    path: e.value.clone(),
-                                    filename: None,
-                                },
+                    .map(|e| crate::http_client::request::MultipartField {
+                        name: e.name.clone(),
+                        value: if e.is_file {
+                            crate::http_client::request::MultipartValue::File {
+                                path: e.value.clone(),
+                                filename: None,
                             }
                         } else {
-                            crate::http_client::request::MultipartField {
-                                name: e.name.clone(),
-                                value: crate::http_client::request::MultipartValue::Text(
-                                    e.value.clone(),
-                                ),
-                            }
-                        }
+                            crate::http_client::request::MultipartValue::Text(e.value.clone())
+                        },
                     })
                     .collect()
-            } else {
-                vec![]
-            };
-
-        crate::http_client::request::HttpRequest {
-            method: self.method.to_string(),
-            url: final_url,
-            headers,
-            body,
+            },
+            auth: auth.clone(),
             config: self.request_config.clone(),
-            multipart_fields,
-            auth: Some(self.auth.clone()),
         }
     }
 
@@ -610,10 +590,82 @@ impl HttpRequestView {
                 (Auth::OAuth2(config), AuthInput::OAuth2DeviceAuthUrl(url)) => {
                     config.device_auth_url = url;
                 }
+                (Auth::OAuth2(config), AuthInput::OAuth2IssuerUrl(url)) => {
+                    config.issuer_url = url;
+                }
+                (Auth::OAuth2(config), AuthInput::OAuth2Username(username)) => {
+                    config.username = username;
+                }
+                (Auth::OAuth2(config), AuthInput::OAuth2Password(password)) => {
+                    config.password = password;
+                }
+                (Auth::OAuth2(config), AuthInput::OAuth2Assertion(assertion)) => {
+                    config.assertion = assertion;
+                }
+                (Auth::OAuth2(config), AuthInput::OAuth2ClientAuthMethod(method)) => {
+                    config.client_auth_method = method;
+                }
+                (Auth::OAuth2(config), AuthInput::OAuth2PrivateKey(key)) => {
+                    config.private_key = key;
+                }
+                (Auth::OAuth2(config), AuthInput::OAuth2IntrospectionUrl(url)) => {
+                    config.introspection_url = url;
+                }
+                (Auth::OAuth2(config), AuthInput::OAuth2RevocationUrl(url)) => {
+                    config.revocation_url = url;
+                }
                 _ => {}
             },
             Message::HeadersEditor(msg) => self.headers_editor.update(msg),
             Message::ParamsEditor(msg) => self.params_editor.update(msg),
+            Message::VariablesEditor(msg) => self.variables_editor.update(msg),
+            Message::AddExtractionRule => self.extraction_rules.push(ExtractionRule::default()),
+            Message::RemoveExtractionRule(index) => {
+                if index < self.extraction_rules.len() {
+                    self.extraction_rules.remove(index);
+                }
+            }
+            Message::ExtractionVariableChanged(index, variable) => {
+                if let Some(rule) = self.extraction_rules.get_mut(index) {
+                    rule.variable = variable;
+                }
+            }
+            Message::ExtractionSourceSelected(index, source) => {
+                if let Some(rule) = self.extraction_rules.get_mut(index) {
+                    rule.source = source;
+                }
+            }
+            Message::ExtractionExpressionChanged(index, expression) => {
+                if let Some(rule) = self.extraction_rules.get_mut(index) {
+                    rule.expression = expression;
+                }
+            }
+            Message::ExtractionTargetSelected(index, target) => {
+                if let Some(rule) = self.extraction_rules.get_mut(index) {
+                    rule.target = target;
+                }
+            }
+            Message::AddAssertion => self.assertions.push(Assertion::default()),
+            Message::RemoveAssertion(index) => {
+                if index < self.assertions.len() {
+                    self.assertions.remove(index);
+                }
+            }
+            Message::AssertionKindSelected(index, kind) => {
+                if let Some(assertion) = self.assertions.get_mut(index) {
+                    assertion.kind = kind;
+                }
+            }
+            Message::AssertionTargetChanged(index, target) => {
+                if let Some(assertion) = self.assertions.get_mut(index) {
+                    assertion.target = target;
+                }
+            }
+            Message::AssertionExpectedChanged(index, expected) => {
+                if let Some(assertion) = self.assertions.get_mut(index) {
+                    assertion.expected = expected;
+                }
+            }
             Message::BodyInputChanged(action) => self.body_input.perform(action),
             Message::RequestContentTypeSelected(content_type) => {
                 self.request_content_type = content_type
@@ -652,11 +704,28 @@ impl HttpRequestView {
                     };
 
                     self.response_body_editor = text_editor::Content::with_text(&formatted_body);
+                    self.extraction_results = run_extractions(&self.extraction_rules, &response);
+                    self.assertion_results = run_assertions(&self.assertions, &response);
+                    self.schema_violations = self
+                        .response_contract
+                        .as_ref()
+                        .map(|contract| {
+                            let content_type = response
+                                .headers
+                                .iter()
+                                .find(|(k, _)| k.eq_ignore_ascii_case("content-type"))
+                                .map(|(_, v)| v.as_str());
+                            contract.validate(response.status, content_type, &response.body)
+                        })
+                        .unwrap_or_default();
                     self.last_response = Some(response);
                     self.request_status = RequestStatus::Success;
                 }
                 Err(e) => {
                     self.request_status = RequestStatus::Error(format!("Error: {}", e));
+                    self.extraction_results.clear();
+                    self.assertion_results.clear();
+                    self.schema_violations.clear();
                     self.last_response = None;
                     self.response_body_editor = text_editor::Content::new();
                     self.status_code = None;
@@ -841,7 +910,145 @@ impl HttpRequestView {
                     let _ = clipboard.set_text(token);
                 }
             }
+            Message::OAuth2Discover => {
+                // Handled in app.rs
+            }
+            Message::OAuth2VerifyIdToken => {
+                // Handled in app.rs
+            }
+            Message::OAuth2Introspect => {
+                // Handled in app.rs
+            }
+            Message::OAuth2Revoke => {
+                // Handled in app.rs
+            }
+        }
+    }
+
+    fn oidc_discovery_summary(
+        config: &crate::data::auth::OAuth2Config,
+    ) -> Element<'_, Message, Theme, Renderer> {
+        let mut summary = column![].spacing(4);
+        if !config.scopes_supported.is_empty() {
+            summary = summary.push(
+                text(format!(
+                    "Supported scopes: {}",
+                    config.scopes_supported.join(" ")
+                ))
+                .size(12),
+            );
+        }
+        if !config.code_challenge_methods_supported.is_empty() {
+            summary = summary.push(
+                text(format!(
+                    "PKCE methods: {}",
+                    config.code_challenge_methods_supported.join(", ")
+                ))
+                .size(12),
+            );
+        }
+        summary.into()
+    }
+
+    fn token_management_section(
+        config: &crate::data::auth::OAuth2Config,
+    ) -> Element<'_, Message, Theme, Renderer> {
+        let has_token = !config.access_token.is_empty() || !config.refresh_token.is_empty();
+
+        let mut section = column![
+            row![
+                text_input("Introspection URL", &config.introspection_url)
+                    .on_input(|u| Message::AuthInputChanged(AuthInput::OAuth2IntrospectionUrl(u)))
+                    .padding(10),
+                button(row![lucide::search().size(14), text(" Introspect")].spacing(4))
+                    .on_press_maybe(
+                        (has_token && !config.introspection_url.trim().is_empty())
+                            .then_some(Message::OAuth2Introspect)
+                    ),
+            ]
+            .spacing(4)
+            .align_y(Alignment::Center),
+            row![
+                text_input("Revocation URL", &config.revocation_url)
+                    .on_input(|u| Message::AuthInputChanged(AuthInput::OAuth2RevocationUrl(u)))
+                    .padding(10),
+                button(row![lucide::ban().size(14), text(" Revoke")].spacing(4)).on_press_maybe(
+                    (has_token && !config.revocation_url.trim().is_empty())
+                        .then_some(Message::OAuth2Revoke)
+                ),
+            ]
+            .spacing(4)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(10);
+
+        if let Some(introspection) = &config.introspection {
+            let (label, color) = if introspection.active {
+                ("active", Color::from_rgb(0.2, 0.7, 0.3))
+            } else {
+                ("inactive", Color::from_rgb(0.8, 0.2, 0.2))
+            };
+            let mut details =
+                column![text(format!("Token {}", label)).size(12).color(color)].spacing(4);
+            if let Some(exp) = introspection.expires_at() {
+                details = details.push(text(format!("Expires: {}", exp)).size(12));
+            }
+            if let Some(scope) = &introspection.scope {
+                details = details.push(text(format!("Scope: {}", scope)).size(12));
+            }
+            if let Some(sub) = &introspection.sub {
+                details = details.push(text(format!("Subject: {}", sub)).size(12));
+            }
+            section = section.push(
+                container(details)
+                    .padding(10)
+                    .width(Length::Fill)
+                    .style(iced::widget::container::rounded_box),
+            );
         }
+
+        section.into()
+    }
+
+    fn id_token_section(
+        config: &crate::data::auth::OAuth2Config,
+    ) -> Element<'_, Message, Theme, Renderer> {
+        if config.id_token.is_empty() {
+            return column![].into();
+        }
+
+        let decoded = match crate::data::oidc::decode_id_token(&config.id_token) {
+            Ok(decoded) => column![
+                text("Header").size(12),
+                text(serde_json::to_string_pretty(&decoded.header).unwrap_or_default())
+                    .size(12)
+                    .font(iced::Font::MONOSPACE),
+                text("Claims").size(12),
+                text(serde_json::to_string_pretty(&decoded.claims).unwrap_or_default())
+                    .size(12)
+                    .font(iced::Font::MONOSPACE),
+            ]
+            .spacing(4),
+            Err(e) => column![text(e).size(12).color(Color::from_rgb(0.8, 0.2, 0.2))],
+        };
+
+        column![
+            row![
+                text("ID Token").size(14),
+                button(row![lucide::shield_check().size(12), text(" Verify Signature")].spacing(4))
+                    .on_press_maybe(
+                        (!config.jwks_uri.is_empty()).then_some(Message::OAuth2VerifyIdToken)
+                    ),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+            container(decoded)
+                .padding(10)
+                .width(Length::Fill)
+                .style(iced::widget::container::rounded_box),
+        ]
+        .spacing(6)
+        .into()
     }
 
     pub fn view(&self) -> Element<'_, Message, Theme, Renderer> {
@@ -871,6 +1078,39 @@ impl HttpRequestView {
                     .width(Length::Fill)
                     .height(Length::Fill),
             )
+            .push(
+                TabId::Variables,
+                TabLabel::Text("Variables".to_string()),
+                container(
+                    column![
+                        text(
+                            "Request variables override environment, collection and global values."
+                        )
+                        .size(12),
+                        self.variables_editor.view().map(Message::VariablesEditor),
+                    ]
+                    .spacing(10),
+                )
+                .padding(10)
+                .width(Length::Fill)
+                .height(Length::Fill),
+            )
+            .push(
+                TabId::Extract,
+                TabLabel::Text("Extract".to_string()),
+                container(self.create_extract_tab_content())
+                    .padding(10)
+                    .width(Length::Fill)
+                    .height(Length::Fill),
+            )
+            .push(
+                TabId::Tests,
+                TabLabel::Text("Tests".to_string()),
+                container(scrollable(self.create_tests_tab_content()))
+                    .padding(10)
+                    .width(Length::Fill)
+                    .height(Length::Fill),
+            )
             .push(
                 TabId::Authorization,
                 TabLabel::Text("Authorization".to_string()),
@@ -904,7 +1144,7 @@ impl HttpRequestView {
                 .align_y(Alignment::Center)
                 .into(),
             RequestStatus::Success => {
-                let response_tabs = Tabs::new(Message::ResponseTabSelected)
+                let mut response_tabs = Tabs::new(Message::ResponseTabSelected)
                     .push(ResponseTab::Body, TabLabel::Text("Body".to_string()), {
                         let syntax = self
                             .content_type
@@ -954,6 +1194,19 @@ impl HttpRequestView {
                         TabLabel::Text("Timeline".to_string()),
                         self.create_response_timeline_view(),
                     )
+                    .push(
+                        ResponseTab::Tests,
+                        TabLabel::Text(self.tests_tab_label()),
+                        self.create_response_tests_view(),
+                    );
+                if let Some(contract) = &self.response_contract {
+                    response_tabs = response_tabs.push(
+                        ResponseTab::Schema,
+                        TabLabel::Text(self.schema_tab_label()),
+                        self.create_response_schema_view(contract),
+                    );
+                }
+                let response_tabs = response_tabs
                     .set_active_tab(&self.active_response_tab)
                     .width(Length::Fill)
                     .height(Length::Fill);
@@ -1121,6 +1374,113 @@ impl HttpRequestView {
         }
     }
 
+    fn tests_tab_label(&self) -> String {
+        if self.assertion_results.is_empty() {
+            "Tests".to_string()
+        } else {
+            let passed = self.assertion_results.iter().filter(|r| r.passed).count();
+            format!("Tests ({}/{})", passed, self.assertion_results.len())
+        }
+    }
+
+    fn create_response_tests_view(&self) -> Element<'_, Message, Theme, Renderer> {
+        if self.assertion_results.is_empty() {
+            return container(text("No assertions. Add them in the request's Tests tab."))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .align_x(Alignment::Center)
+                .align_y(Alignment::Center)
+                .into();
+        }
+        let results = self
+            .assertion_results
+            .iter()
+            .fold(column![].spacing(6), |col, result| {
+                let (icon, color) = if result.passed {
+                    (
+                        lucide::circle_check().size(14),
+                        Color::from_rgb(0.2, 0.7, 0.3),
+                    )
+                } else {
+                    (lucide::circle_x().size(14), Color::from_rgb(0.8, 0.2, 0.2))
+                };
+                col.push(
+                    row![
+                        icon.color(color),
+                        text(&result.description).size(14),
+                        text(&result.message)
+                            .size(12)
+                            .color(Color::from_rgb(0.5, 0.5, 0.5)),
+                    ]
+                    .spacing(8),
+                )
+            });
+        container(scrollable(results))
+            .padding(10)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+
+    fn schema_tab_label(&self) -> String {
+        if self.schema_violations.is_empty() {
+            "Schema".to_string()
+        } else {
+            format!("Schema ({})", self.schema_violations.len())
+        }
+    }
+
+    fn create_response_schema_view<'a>(
+        &'a self,
+        contract: &'a ResponseContract,
+    ) -> Element<'a, Message, Theme, Renderer> {
+        if self.schema_violations.is_empty() {
+            return container(
+                row![
+                    lucide::circle_check()
+                        .size(14)
+                        .color(Color::from_rgb(0.2, 0.7, 0.3)),
+                    text(format!(
+                        "Response matches the schema of {}",
+                        contract.operation
+                    ))
+                    .size(14),
+                ]
+                .spacing(8),
+            )
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .align_x(Alignment::Center)
+            .align_y(Alignment::Center)
+            .into();
+        }
+        let violations = self.schema_violations.iter().fold(
+            column![text(format!("Checked against {}", contract.operation))
+                .size(12)
+                .color(Color::from_rgb(0.5, 0.5, 0.5))]
+            .spacing(6),
+            |col, violation| {
+                col.push(
+                    row![
+                        lucide::circle_x()
+                            .size(14)
+                            .color(Color::from_rgb(0.8, 0.2, 0.2)),
+                        text(&violation.location).size(14),
+                        text(&violation.message)
+                            .size(12)
+                            .color(Color::from_rgb(0.5, 0.5, 0.5)),
+                    ]
+                    .spacing(8),
+                )
+            },
+        );
+        container(scrollable(violations))
+            .padding(10)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+
     fn create_response_timeline_view(&self) -> Element<'_, Message, Theme, Renderer> {
         if let Some(response) = &self.last_response {
             let mut items = column![].spacing(8);
@@ -1210,6 +1570,86 @@ impl HttpRequestView {
         }
     }
 
+    fn create_tests_tab_content(&self) -> Element<'_, Message, Theme, Renderer> {
+        let assertions = self.assertions.iter().enumerate().fold(
+            column![text("Checks evaluated against every response.").size(12)].spacing(8),
+            |col, (i, assertion)| {
+                let mut assertion_row = row![pick_list(
+                    &AssertionKind::ALL[..],
+                    Some(assertion.kind),
+                    move |kind| Message::AssertionKindSelected(i, kind)
+                )]
+                .spacing(10);
+                if assertion.kind.has_target() {
+                    assertion_row = assertion_row.push(
+                        text_input(assertion.kind.target_placeholder(), &assertion.target)
+                            .on_input(move |t| Message::AssertionTargetChanged(i, t)),
+                    );
+                }
+                col.push(
+                    assertion_row
+                        .push(
+                            text_input(assertion.kind.expected_placeholder(), &assertion.expected)
+                                .on_input(move |e| Message::AssertionExpectedChanged(i, e)),
+                        )
+                        .push(button(text("Remove")).on_press(Message::RemoveAssertion(i))),
+                )
+            },
+        );
+
+        column![
+            assertions,
+            button(text("Add Assertion")).on_press(Message::AddAssertion),
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    fn create_extract_tab_content(&self) -> Element<'_, Message, Theme, Renderer> {
+        let rules = self.extraction_rules.iter().enumerate().fold(
+            column![text("Copy values from the response into variables after each send.").size(12)]
+                .spacing(8),
+            |col, (i, rule)| {
+                col.push(
+                    row![
+                        text_input("Variable", &rule.variable)
+                            .on_input(move |v| Message::ExtractionVariableChanged(i, v)),
+                        pick_list(&ExtractionSource::ALL[..], Some(rule.source), move |s| {
+                            Message::ExtractionSourceSelected(i, s)
+                        }),
+                        text_input(rule.source.placeholder(), &rule.expression)
+                            .on_input(move |e| Message::ExtractionExpressionChanged(i, e)),
+                        pick_list(&ExtractionTarget::ALL[..], Some(rule.target), move |t| {
+                            Message::ExtractionTargetSelected(i, t)
+                        }),
+                        button(text("Remove")).on_press(Message::RemoveExtractionRule(i)),
+                    ]
+                    .spacing(10),
+                )
+            },
+        );
+
+        let results = self
+            .extraction_results
+            .iter()
+            .fold(column![].spacing(4), |col, result| {
+                col.push(match &result.value {
+                    Ok(value) => text(format!("{} = {}", result.variable, value)).size(12),
+                    Err(e) => text(format!("{}: {}", result.variable, e))
+                        .size(12)
+                        .color(iced::Color::from_rgb(0.8, 0.2, 0.2)),
+                })
+            });
+
+        column![
+            rules,
+            button(text("Add Rule")).on_press(Message::AddExtractionRule),
+            results,
+        ]
+        .spacing(10)
+        .into()
+    }
+
     fn create_auth_tab_content(&self) -> Element<'_, Message, Theme, Renderer> {
         let current_auth_type = self.auth.auth_type();
 
@@ -1246,7 +1686,8 @@ impl HttpRequestView {
                     .padding(10),
                 text_input("Value", value)
                     .on_input(|v| Message::AuthInputChanged(AuthInput::ApiKeyValue(v)))
-                    .padding(10),
+                    .padding(10)
+                    .secure(true),
                 pick_list(
                     &crate::data::auth::ApiKeyLocation::ALL[..],
                     Some(*location),
@@ -1372,8 +1813,78 @@ impl HttpRequestView {
                             .on_press(Message::OAuth2StartAuth),
                     ]
                     .spacing(10),
+                    crate::data::auth::OAuth2GrantType::Password => column![
+                        text_input("Token URL", &config.token_url)
+                            .on_input(|u| Message::AuthInputChanged(AuthInput::OAuth2TokenUrl(u)))
+                            .padding(10),
+                        text_input("Username", &config.username)
+                            .on_input(|u| Message::AuthInputChanged(AuthInput::OAuth2Username(u)))
+                            .padding(10),
+                        text_input("Password", &config.password)
+                            .on_input(|p| Message::AuthInputChanged(AuthInput::OAuth2Password(p)))
+                            .padding(10)
+                            .secure(true),
+                        text_input("Scopes (space-separated)", &config.scopes)
+                            .on_input(|s| Message::AuthInputChanged(AuthInput::OAuth2Scopes(s)))
+                            .padding(10),
+                        button(row![lucide::key().size(14), text(" Get Token")].spacing(4))
+                            .on_press(Message::OAuth2RefreshToken),
+                    ]
+                    .spacing(10),
+                    crate::data::auth::OAuth2GrantType::JwtBearer => column![
+                        text_input("Token URL", &config.token_url)
+                            .on_input(|u| Message::AuthInputChanged(AuthInput::OAuth2TokenUrl(u)))
+                            .padding(10),
+                        text_input(
+                            "Assertion JWT (leave empty to sign with the private key)",
+                            &config.assertion
+                        )
+                        .on_input(|a| Message::AuthInputChanged(AuthInput::OAuth2Assertion(a)))
+                        .padding(10)
+                        .secure(true),
+                        text_input("Private key (PEM or file path)", &config.private_key)
+                            .on_input(|k| Message::AuthInputChanged(AuthInput::OAuth2PrivateKey(k)))
+                            .padding(10)
+                            .secure(true),
+                        text_input("Scopes (space-separated)", &config.scopes)
+                            .on_input(|s| Message::AuthInputChanged(AuthInput::OAuth2Scopes(s)))
+                            .padding(10),
+                        button(row![lucide::key().size(14), text(" Get Token")].spacing(4))
+                            .on_press(Message::OAuth2RefreshToken),
+                    ]
+                    .spacing(10),
                 };
 
+                let client_auth_fields = column![
+                    row![
+                        text("Client Authentication").size(14),
+                        pick_list(
+                            &crate::data::auth::ClientAuthMethod::ALL[..],
+                            Some(config.client_auth_method),
+                            |m| Message::AuthInputChanged(AuthInput::OAuth2ClientAuthMethod(m)),
+                        )
+                        .padding(8),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                    if config.client_auth_method
+                        == crate::data::auth::ClientAuthMethod::PrivateKeyJwt
+                        && config.grant_type != crate::data::auth::OAuth2GrantType::JwtBearer
+                    {
+                        Element::from(
+                            text_input("Private key (PEM or file path)", &config.private_key)
+                                .on_input(|k| {
+                                    Message::AuthInputChanged(AuthInput::OAuth2PrivateKey(k))
+                                })
+                                .padding(10)
+                                .secure(true),
+                        )
+                    } else {
+                        Element::from(column![])
+                    },
+                ]
+                .spacing(10);
+
                 column![
                     row![
                         text("OAuth 2.0").size(16),
@@ -1393,6 +1904,20 @@ impl HttpRequestView {
                         .on_input(|s| Message::AuthInputChanged(AuthInput::OAuth2ClientSecret(s)))
                         .padding(10)
                         .secure(true),
+                    row![
+                        text_input("Issuer URL (OpenID Connect)", &config.issuer_url)
+                            .on_input(|u| Message::AuthInputChanged(AuthInput::OAuth2IssuerUrl(u)))
+                            .padding(10),
+                        button(row![lucide::search().size(14), text(" Discover")].spacing(4))
+                            .on_press_maybe(
+                                (!config.issuer_url.trim().is_empty())
+                                    .then_some(Message::OAuth2Discover)
+                            ),
+                    ]
+                    .spacing(4)
+                    .align_y(Alignment::Center),
+                    Self::oidc_discovery_summary(config),
+                    client_auth_fields,
                     grant_type_fields,
                     rule::horizontal(10),
                     text("Tokens").size(14),
@@ -1424,6 +1949,8 @@ impl HttpRequestView {
                     ]
                     .spacing(4)
                     .align_y(Alignment::Center),
+                    Self::id_token_section(config),
+                    Self::token_management_section(config),
                     if !config.status.to_string().is_empty() {
                         Element::from(text(config.status.to_string()).size(12).color(
                             match &config.status {
@@ -1738,8 +2265,17 @@ fn response_content_type_to_syntax(ct: &str) -> &str {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::data::variables::VariableScope;
     use crate::persistence::database::Environment;
     use crate::ui::components::key_value_editor::KeyValueEntry;
+    use base64::Engine as _;
+
+    fn resolved(
+        view: &HttpRequestView,
+        env: &Environment,
+    ) -> crate::http_client::request::HttpRequest {
+        view.build_resolved_request(&VariableResolver::from_environment(Some(env)))
+    }
 
     fn make_view(url: &str, method: &str) -> HttpRequestView {
         HttpRequestView {
@@ -1951,8 +2487,8 @@ mod tests {
     }
 
     #[test]
-    fn apply_environment_replaces_url_variable() {
-        let mut view = make_view("{{BASE_URL}}/api/users", "GET");
+    fn build_resolved_request_replaces_url_variable() {
+        let view = make_view("{{BASE_URL}}/api/users", "GET");
         let env = Environment {
             id: 1,
             name: "test".to_string(),
@@ -1961,13 +2497,15 @@ mod tests {
                 "https://api.example.com".to_string(),
             )],
             default_endpoint: None,
+            secret_keys: vec![],
         };
-        view.apply_environment(&env);
-        assert_eq!(view.url_input, "https://api.example.com/api/users");
+        let request = resolved(&view, &env);
+        assert_eq!(request.url, "https://api.example.com/api/users");
+        assert_eq!(view.url_input, "{{BASE_URL}}/api/users");
     }
 
     #[test]
-    fn apply_environment_replaces_body_variable() {
+    fn build_resolved_request_replaces_body_variable() {
         let mut view = make_view("https://example.com", "POST");
         view.body_input = text_editor::Content::with_text(r#"{"token": "{{API_TOKEN}}"}"#);
         let env = Environment {
@@ -1975,14 +2513,16 @@ mod tests {
             name: "test".to_string(),
             variables: vec![("API_TOKEN".to_string(), "abc123".to_string())],
             default_endpoint: None,
+            secret_keys: vec![],
         };
-        view.apply_environment(&env);
+        let request = resolved(&view, &env);
         // text_editor::Content::with_text may append a trailing newline
-        assert!(view.body_input.text().contains(r#"{"token": "abc123"}"#));
+        assert!(request.body.unwrap().contains(r#"{"token": "abc123"}"#));
+        assert!(view.body_input.text().contains("{{API_TOKEN}}"));
     }
 
     #[test]
-    fn apply_environment_replaces_header_variable() {
+    fn build_resolved_request_replaces_header_variable() {
         let mut view = make_view("https://example.com", "GET");
         view.headers_editor.entries = vec![KeyValueEntry {
             id: 0,
@@ -1994,13 +2534,18 @@ mod tests {
             name: "test".to_string(),
             variables: vec![("TOKEN".to_string(), "my-jwt-token".to_string())],
             default_endpoint: None,
+            secret_keys: vec![],
         };
-        view.apply_environment(&env);
-        assert_eq!(view.headers_editor.entries[0].value, "Bearer my-jwt-token");
+        let request = resolved(&view, &env);
+        assert!(request.headers.contains(&(
+            "Authorization".to_string(),
+            "Bearer my-jwt-token".to_string()
+        )));
+        assert_eq!(view.headers_editor.entries[0].value, "Bearer {{TOKEN}}");
     }
 
     #[test]
-    fn apply_environment_replaces_param_variable() {
+    fn build_resolved_request_replaces_param_variable() {
         let mut view = make_view("https://example.com", "GET");
         view.params_editor.entries = vec![KeyValueEntry {
             id: 0,
@@ -2012,13 +2557,15 @@ mod tests {
             name: "test".to_string(),
             variables: vec![("API_KEY".to_string(), "secret-key-123".to_string())],
             default_endpoint: None,
+            secret_keys: vec![],
         };
-        view.apply_environment(&env);
-        assert_eq!(view.params_editor.entries[0].value, "secret-key-123");
+        let request = resolved(&view, &env);
+        assert_eq!(request.url, "https://example.com?key=secret-key-123");
+        assert_eq!(view.params_editor.entries[0].value, "{{API_KEY}}");
     }
 
     #[test]
-    fn apply_environment_replaces_bearer_token_variable() {
+    fn build_resolved_request_replaces_bearer_token_variable() {
         let mut view = make_view("https://example.com", "GET");
         view.auth = Auth::BearerToken("{{JWT}}".to_string());
         let env = Environment {
@@ -2026,16 +2573,18 @@ mod tests {
             name: "test".to_string(),
             variables: vec![("JWT".to_string(), "eyJhbGciOiJIUzI1NiJ9".to_string())],
             default_endpoint: None,
+            secret_keys: vec![],
         };
-        view.apply_environment(&env);
+        let request = resolved(&view, &env);
         assert_eq!(
-            view.auth,
-            Auth::BearerToken("eyJhbGciOiJIUzI1NiJ9".to_string())
+            request.auth,
+            Some(Auth::BearerToken("eyJhbGciOiJIUzI1NiJ9".to_string()))
         );
+        assert_eq!(view.auth, Auth::BearerToken("{{JWT}}".to_string()));
     }
 
     #[test]
-    fn apply_environment_replaces_basic_auth_variable() {
+    fn build_resolved_request_replaces_basic_auth_variable() {
         let mut view = make_view("https://example.com", "GET");
         view.auth = Auth::Basic {
             user: "{{USER}}".to_string(),
@@ -2049,20 +2598,188 @@ mod tests {
                 ("PASS".to_string(), "secret".to_string()),
             ],
             default_endpoint: None,
+            secret_keys: vec![],
         };
-        view.apply_environment(&env);
+        let request = resolved(&view, &env);
         assert_eq!(
-            view.auth,
-            Auth::Basic {
+            request.auth,
+            Some(Auth::Basic {
                 user: "admin".to_string(),
                 pass: "secret".to_string()
-            }
+            })
+        );
+    }
+
+    #[test]
+    fn build_request_keeps_templates_unresolved() {
+        let mut view = make_view("{{BASE_URL}}/users", "GET");
+        view.params_editor.entries = vec![KeyValueEntry {
+            id: 0,
+            key: "id".to_string(),
+            value: "{{ID}}".to_string(),
+        }];
+        let request = view.build_request();
+        assert_eq!(request.url, "{{BASE_URL}}/users?id=%7B%7BID%7D%7D");
+    }
+
+    #[test]
+    fn build_request_with_redacted_auth_omits_credentials() {
+        let mut view = make_view("http://example.com", "GET");
+        view.auth = Auth::BearerToken("s3cret".to_string());
+        let request = view.build_request_with_auth(&crate::data::vault::redact_auth(&view.auth));
+        let json = serde_json::to_string(&request).unwrap();
+        assert!(!json.contains("s3cret"));
+        assert_eq!(view.auth, Auth::BearerToken("s3cret".to_string()));
+    }
+
+    #[test]
+    fn response_received_runs_extraction_rules() {
+        let mut view = make_view("http://example.com/login", "POST");
+        view.update(Message::AddExtractionRule);
+        view.update(Message::ExtractionVariableChanged(0, "token".to_string()));
+        view.update(Message::ExtractionExpressionChanged(
+            0,
+            "$.token".to_string(),
+        ));
+        view.update(Message::ResponseReceived(Ok(HttpResponse {
+            url: "http://example.com/login".to_string(),
+            method: "POST".to_string(),
+            status: 200,
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: r#"{"token":"abc"}"#.to_string(),
+            duration: Duration::from_millis(5),
+            size: 15,
+            redirect_chain: vec![],
+        })));
+        assert_eq!(view.extraction_results.len(), 1);
+        assert_eq!(view.extraction_results[0].value, Ok("abc".to_string()));
+
+        view.update(Message::ResponseReceived(Err("timeout".to_string())));
+        assert!(view.extraction_results.is_empty());
+    }
+
+    #[test]
+    fn response_received_evaluates_assertions() {
+        let mut view = make_view("http://example.com/health", "GET");
+        view.update(Message::AddAssertion);
+        view.update(Message::AssertionExpectedChanged(0, "200".to_string()));
+        view.update(Message::AddAssertion);
+        view.update(Message::AssertionKindSelected(
+            1,
+            AssertionKind::ResponseTimeBelow,
+        ));
+        view.update(Message::AssertionExpectedChanged(1, "1".to_string()));
+        view.update(Message::ResponseReceived(Ok(HttpResponse {
+            url: "http://example.com/health".to_string(),
+            method: "GET".to_string(),
+            status: 200,
+            headers: vec![],
+            body: "ok".to_string(),
+            duration: Duration::from_millis(30),
+            size: 2,
+            redirect_chain: vec![],
+        })));
+        assert_eq!(view.assertion_results.len(), 2);
+        assert!(view.assertion_results[0].passed);
+        assert!(!view.assertion_results[1].passed);
+        assert_eq!(view.tests_tab_label(), "Tests (1/2)");
+    }
+
+    #[test]
+    fn response_received_checks_the_openapi_contract() {
+        let spec = crate::openapi::parser::load_spec_model(
+            r#"{
+                "openapi": "3.0.0",
+                "info": { "title": "Health", "version": "1" },
+                "paths": {
+                    "/health": {
+                        "get": {
+                            "responses": {
+                                "200": {
+                                    "description": "ok",
+                                    "content": {
+                                        "application/json": {
+                                            "schema": { "type": "object", "required": ["status"] }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        let mut view = make_view("http://example.com/health", "GET");
+        view.response_contract = ResponseContract::for_operation(&spec, "/health", "GET");
+        view.update(Message::ResponseReceived(Ok(HttpResponse {
+            url: "http://example.com/health".to_string(),
+            method: "GET".to_string(),
+            status: 200,
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: "{}".to_string(),
+            duration: Duration::from_millis(5),
+            size: 2,
+            redirect_chain: vec![],
+        })));
+        assert_eq!(view.schema_violations.len(), 1);
+        assert_eq!(view.schema_tab_label(), "Schema (1)");
+
+        view.update(Message::ResponseReceived(Err("timeout".to_string())));
+        assert!(view.schema_violations.is_empty());
+    }
+
+    #[test]
+    fn local_variables_override_environment() {
+        let mut view = make_view("{{HOST}}/users", "GET");
+        view.variables_editor.entries = vec![
+            KeyValueEntry {
+                id: 0,
+                key: "HOST".to_string(),
+                value: "http://localhost:3000".to_string(),
+            },
+            KeyValueEntry {
+                id: 1,
+                key: String::new(),
+                value: "ignored".to_string(),
+            },
+        ];
+        let env = Environment {
+            id: 1,
+            name: "test".to_string(),
+            variables: vec![("HOST".to_string(), "https://api.example.com".to_string())],
+            default_endpoint: None,
+            secret_keys: vec![],
+        };
+        let resolver = VariableResolver::scoped(&[], &[], Some(&env), &view.local_variables());
+        assert_eq!(
+            view.build_resolved_request(&resolver).url,
+            "http://localhost:3000/users"
         );
     }
 
     #[test]
-    fn apply_environment_multiple_variables() {
-        let mut view = make_view("{{PROTO}}://{{HOST}}:{{PORT}}/api", "GET");
+    fn unresolved_variables_reports_missing_names() {
+        let mut view = make_view("{{BASE_URL}}/users/{{ID}}", "GET");
+        view.headers_editor.entries = vec![KeyValueEntry {
+            id: 0,
+            key: "X-Trace".to_string(),
+            value: "{{$uuid}}".to_string(),
+        }];
+        view.auth = Auth::BearerToken("{{TOKEN}}".to_string());
+        let resolver = VariableResolver::default().with_scope(
+            VariableScope::Global,
+            vec![(
+                "BASE_URL".to_string(),
+                "https://api.example.com".to_string(),
+            )],
+        );
+        assert_eq!(view.unresolved_variables(&resolver), vec!["ID", "TOKEN"]);
+    }
+
+    #[test]
+    fn build_resolved_request_multiple_variables() {
+        let view = make_view("{{PROTO}}://{{HOST}}:{{PORT}}/api", "GET");
         let env = Environment {
             id: 1,
             name: "test".to_string(),
@@ -2072,22 +2789,22 @@ mod tests {
                 ("PORT".to_string(), "8080".to_string()),
             ],
             default_endpoint: None,
+            secret_keys: vec![],
         };
-        view.apply_environment(&env);
-        assert_eq!(view.url_input, "https://localhost:8080/api");
+        assert_eq!(resolved(&view, &env).url, "https://localhost:8080/api");
     }
 
     #[test]
-    fn apply_environment_no_variables_no_change() {
-        let mut view = make_view("https://example.com/api", "GET");
+    fn build_resolved_request_no_variables_no_change() {
+        let view = make_view("https://example.com/api", "GET");
         let env = Environment {
             id: 1,
             name: "test".to_string(),
             variables: vec![],
             default_endpoint: None,
+            secret_keys: vec![],
         };
-        view.apply_environment(&env);
-        assert_eq!(view.url_input, "https://example.com/api");
+        assert_eq!(resolved(&view, &env).url, "https://example.com/api");
     }
 
     #[test]
@@ -2254,7 +2971,7 @@ mod tests {
     }
 
     #[test]
-    fn apply_environment_replaces_api_key_variable() {
+    fn build_resolved_request_replaces_api_key_variable() {
         let mut view = make_view("https://example.com", "GET");
         view.auth = Auth::ApiKey {
             key: "X-API-Key".to_string(),
@@ -2266,16 +2983,16 @@ mod tests {
             name: "test".to_string(),
             variables: vec![("API_KEY".to_string(), "my-secret".to_string())],
             default_endpoint: None,
+            secret_keys: vec![],
         };
-        view.apply_environment(&env);
-        match &view.auth {
-            Auth::ApiKey { value, .. } => assert_eq!(value, "my-secret"),
+        match resolved(&view, &env).auth {
+            Some(Auth::ApiKey { value, .. }) => assert_eq!(value, "my-secret"),
             _ => panic!("Expected ApiKey"),
         }
     }
 
     #[test]
-    fn apply_environment_replaces_oauth2_device_auth_url() {
+    fn build_resolved_request_replaces_oauth2_device_auth_url() {
         let mut view = make_view("https://example.com", "GET");
         let config = crate::data::auth::OAuth2Config {
             device_auth_url: "{{DEVICE_AUTH}}".to_string(),
@@ -2290,10 +3007,10 @@ mod tests {
                 "https://device.example.com".to_string(),
             )],
             default_endpoint: None,
+            secret_keys: vec![],
         };
-        view.apply_environment(&env);
-        match &view.auth {
-            Auth::OAuth2(config) => {
+        match resolved(&view, &env).auth {
+            Some(Auth::OAuth2(config)) => {
                 assert_eq!(config.device_auth_url, "https://device.example.com");
             }
             _ => panic!("Expected OAuth2"),
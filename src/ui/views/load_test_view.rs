@@ -0,0 +1,374 @@
+use crate::runner::load::{LiveStats, LoadOptions, LoadReport, LoadSample};
+use crate::ui::components::latency_chart::LatencyChart;
+use crate::ui::theme::status_color;
+use iced::widget::container as iced_container;
+use iced::{
+    widget::{button, canvas, column, container, pick_list, row, scrollable, text, text_input},
+    Alignment, Color, Element, Length,
+};
+use iced_fonts::lucide;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// What a load test sends on each iteration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadTarget {
+    /// A collection, or one folder and its subfolders.
+    Folder {
+        collection_id: i32,
+        folder_id: Option<i32>,
+    },
+    Request {
+        collection_id: i32,
+        request_id: i32,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoadLimit {
+    #[default]
+    Duration,
+    Iterations,
+}
+
+impl LoadLimit {
+    pub const ALL: [LoadLimit; 2] = [LoadLimit::Duration, LoadLimit::Iterations];
+}
+
+impl std::fmt::Display for LoadLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadLimit::Duration => write!(f, "Duration (s)"),
+            LoadLimit::Iterations => write!(f, "Iterations"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    VirtualUsersChanged(String),
+    LimitSelected(LoadLimit),
+    LimitValueChanged(String),
+    RampUpChanged(String),
+    RequestsPerSecondChanged(String),
+    Start,
+    Stop,
+    Close,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LoadTestView {
+    pub target: Option<LoadTarget>,
+    pub target_name: String,
+    pub request_count: usize,
+    pub users_input: String,
+    pub limit: LoadLimit,
+    pub limit_input: String,
+    pub ramp_up_input: String,
+    /// Empty means no cap.
+    pub rps_input: String,
+    pub running: bool,
+    started: Option<Instant>,
+    /// Running totals of the test in progress.
+    live: LiveStats,
+    pub report: Option<LoadReport>,
+    pub cancel: Arc<AtomicBool>,
+}
+
+impl LoadTestView {
+    pub fn new() -> Self {
+        Self {
+            users_input: "10".to_string(),
+            limit_input: "30".to_string(),
+            ramp_up_input: "0".to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Points the panel at a new target, keeping the current settings.
+    pub fn open(&mut self, target: LoadTarget, target_name: String, request_count: usize) {
+        if self.running {
+            return;
+        }
+        self.target = Some(target);
+        self.target_name = target_name;
+        self.request_count = request_count;
+        self.live = LiveStats::default();
+        self.report = None;
+    }
+
+    pub fn options(&self) -> Result<LoadOptions, String> {
+        let number = |input: &str, name: &str| {
+            input
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| format!("{} must be a whole number", name))
+        };
+        let limit = number(&self.limit_input, &self.limit.to_string())?;
+        let options = LoadOptions {
+            virtual_users: number(&self.users_input, "Virtual users")?,
+            duration: (self.limit == LoadLimit::Duration)
+                .then(|| Duration::from_secs(limit.into())),
+            iterations: (self.limit == LoadLimit::Iterations).then_some(limit),
+            ramp_up: Duration::from_secs(number(&self.ramp_up_input, "Ramp-up")?.into()),
+            requests_per_second: match self.rps_input.trim() {
+                "" => None,
+                value => Some(number(value, "Requests per second")?),
+            },
+        };
+        options.validate()?;
+        Ok(options)
+    }
+
+    /// Resets results and returns a fresh cancellation flag for the new test.
+    pub fn start(&mut self) -> Arc<AtomicBool> {
+        self.running = true;
+        self.started = Some(Instant::now());
+        self.live = LiveStats::default();
+        self.report = None;
+        self.cancel = Arc::new(AtomicBool::new(false));
+        self.cancel.clone()
+    }
+
+    pub fn add_samples(&mut self, samples: Vec<LoadSample>) {
+        if !self.running {
+            return;
+        }
+        for sample in &samples {
+            self.live.add(sample);
+        }
+        let elapsed = self.started.map(|s| s.elapsed()).unwrap_or_default();
+        self.report = Some(self.live.report(elapsed));
+    }
+
+    pub fn finish(&mut self, report: LoadReport) {
+        self.running = false;
+        self.started = None;
+        self.live = LiveStats::default();
+        self.report = Some(report);
+    }
+
+    pub fn update(&mut self, message: Message) {
+        let digits = |value: &str| value.chars().all(|c| c.is_ascii_digit());
+        match message {
+            Message::VirtualUsersChanged(value) if digits(&value) => self.users_input = value,
+            Message::LimitSelected(limit) => self.limit = limit,
+            Message::LimitValueChanged(value) if digits(&value) => self.limit_input = value,
+            Message::RampUpChanged(value) if digits(&value) => self.ramp_up_input = value,
+            Message::RequestsPerSecondChanged(value) if digits(&value) => self.rps_input = value,
+            Message::Stop => self.cancel.store(true, Ordering::Relaxed),
+            Message::Start | Message::Close => {
+                // This message is handled in app.rs
+            }
+            _ => {}
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let back_button = button(row![lucide::arrow_left().size(14), text(" Back")].spacing(4))
+            .on_press(Message::Close);
+        let header = row![
+            back_button,
+            text(format!("Load test: {}", self.target_name)).size(18),
+            text(format!("{} request(s)", self.request_count))
+                .size(13)
+                .color(Color::from_rgb(0.5, 0.5, 0.5)),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let start_button = if self.running {
+            button(row![lucide::square().size(14), text(" Stop")].spacing(4))
+                .on_press(Message::Stop)
+                .style(button::danger)
+        } else {
+            button(row![lucide::play().size(14), text(" Start")].spacing(4))
+                .on_press_maybe((self.request_count > 0).then_some(Message::Start))
+        };
+        let number_input = |placeholder, value, on_input: fn(String) -> Message| {
+            text_input(placeholder, value)
+                .on_input(on_input)
+                .width(Length::Fixed(70.0))
+                .size(13)
+        };
+        let settings = row![
+            text("Virtual users").size(13),
+            number_input("10", &self.users_input, Message::VirtualUsersChanged),
+            pick_list(LoadLimit::ALL, Some(self.limit), Message::LimitSelected).text_size(13),
+            number_input("30", &self.limit_input, Message::LimitValueChanged),
+            text("Ramp-up (s)").size(13),
+            number_input("0", &self.ramp_up_input, Message::RampUpChanged),
+            text("Max req/s").size(13),
+            number_input(
+                "no limit",
+                &self.rps_input,
+                Message::RequestsPerSecondChanged
+            ),
+            start_button,
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let results: Element<'_, Message> = match &self.report {
+            Some(report) => column![
+                self.stats_view(report),
+                container(
+                    canvas(LatencyChart {
+                        timeline: &report.timeline
+                    })
+                    .width(Length::Fill)
+                    .height(Length::Fixed(240.0))
+                )
+                .padding(6)
+                .style(iced_container::bordered_box),
+                Self::outcomes_view(report),
+            ]
+            .spacing(12)
+            .into(),
+            None => text(if self.running {
+                "Starting virtual users..."
+            } else {
+                "Press Start to send the requests from several virtual users at once."
+            })
+            .size(14)
+            .color(Color::from_rgb(0.5, 0.5, 0.5))
+            .into(),
+        };
+
+        iced_container(
+            column![header, settings, scrollable(results)]
+                .spacing(14)
+                .padding(20),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+
+    fn stats_view(&self, report: &LoadReport) -> Element<'_, Message> {
+        let stat = |label: &str, value: String| {
+            column![
+                text(label.to_string())
+                    .size(12)
+                    .color(Color::from_rgb(0.5, 0.5, 0.5)),
+                text(value).size(16),
+            ]
+            .spacing(2)
+        };
+        let error_rate = if report.total > 0 {
+            report.errors as f64 * 100.0 / report.total as f64
+        } else {
+            0.0
+        };
+        let mut stats = row![
+            stat("Requests", report.total.to_string()),
+            stat("Errors", format!("{} ({:.1}%)", report.errors, error_rate)),
+            stat("Throughput", format!("{:.1} req/s", report.throughput)),
+            stat("p50", format!("{} ms", report.p50_ms)),
+            stat("p90", format!("{} ms", report.p90_ms)),
+            stat("p99", format!("{} ms", report.p99_ms)),
+            stat(
+                "Min / mean / max",
+                format!(
+                    "{} / {} / {} ms",
+                    report.min_ms, report.mean_ms, report.max_ms
+                )
+            ),
+        ]
+        .spacing(24)
+        .align_y(Alignment::End);
+        if self.running {
+            stats = stats.push(text("Running...").size(13));
+        } else if report.stopped_early {
+            stats = stats.push(
+                text("Stopped early")
+                    .size(12)
+                    .color(Color::from_rgb(0.8, 0.5, 0.1)),
+            );
+        }
+        stats.into()
+    }
+
+    fn outcomes_view(report: &LoadReport) -> Element<'_, Message> {
+        report
+            .outcomes
+            .iter()
+            .fold(
+                column![text("Responses").size(14)].spacing(4),
+                |col, (outcome, count)| {
+                    let color = match outcome.parse::<u16>() {
+                        Ok(status) => status_color(status),
+                        Err(_) => Color::from_rgb(0.8, 0.2, 0.2),
+                    };
+                    col.push(
+                        row![
+                            text(outcome)
+                                .size(13)
+                                .color(color)
+                                .width(Length::Fixed(140.0)),
+                            text(count.to_string()).size(13),
+                        ]
+                        .spacing(8),
+                    )
+                },
+            )
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn options_follow_the_selected_limit() {
+        let mut view = LoadTestView::new();
+        view.update(Message::VirtualUsersChanged("5".to_string()));
+        view.update(Message::LimitSelected(LoadLimit::Iterations));
+        view.update(Message::LimitValueChanged("100".to_string()));
+        view.update(Message::RequestsPerSecondChanged("2x".to_string()));
+        assert_eq!(
+            view.options().unwrap(),
+            LoadOptions {
+                virtual_users: 5,
+                duration: None,
+                iterations: Some(100),
+                ramp_up: Duration::ZERO,
+                requests_per_second: None,
+            }
+        );
+
+        view.update(Message::VirtualUsersChanged("0".to_string()));
+        assert!(view.options().is_err());
+
+        view.update(Message::VirtualUsersChanged("5".to_string()));
+        view.update(Message::LimitSelected(LoadLimit::Duration));
+        view.update(Message::LimitValueChanged("0".to_string()));
+        assert!(view.options().is_err());
+    }
+
+    #[test]
+    fn samples_update_the_live_report_until_finished() {
+        let mut view = LoadTestView::new();
+        let cancel = view.start();
+        view.add_samples(vec![LoadSample {
+            started_ms: 0,
+            latency_ms: 40,
+            status: Some(200),
+            error: None,
+        }]);
+        assert_eq!(view.report.as_ref().map(|r| r.p50_ms), Some(40));
+
+        view.update(Message::Stop);
+        assert!(cancel.load(Ordering::Relaxed));
+        view.finish(LoadReport::default());
+        view.add_samples(vec![LoadSample {
+            started_ms: 0,
+            latency_ms: 90,
+            status: Some(200),
+            error: None,
+        }]);
+        assert_eq!(view.report, Some(LoadReport::default()));
+    }
+}
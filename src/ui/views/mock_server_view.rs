@@ -0,0 +1,348 @@
+use crate::mock::{MockLogEntry, MockRoute, MockState};
+use crate::ui::theme::{method_color, status_color};
+use iced::widget::container as iced_container;
+use iced::{
+    widget::{button, column, container, pick_list, row, scrollable, text, text_input},
+    Alignment, Color, Element, Length,
+};
+use iced_fonts::lucide;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+const MAX_LOG_ENTRIES: usize = 500;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    PortChanged(String),
+    LatencyChanged(String),
+    StatusSelected(usize, u16),
+    OpenSpec,
+    /// File name and contents of the picked spec.
+    SpecLoaded(Option<(String, String)>),
+    Start,
+    Stop,
+    ClearLog,
+    Close,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MockServerView {
+    pub source_name: String,
+    pub routes: Vec<MockRoute>,
+    pub port_input: String,
+    pub latency_input: String,
+    pub running: bool,
+    /// Port the server is listening on.
+    pub listening: Option<u16>,
+    /// Newest first.
+    pub log: Vec<MockLogEntry>,
+    /// What the running server reads on each request.
+    pub state: Arc<RwLock<MockState>>,
+    pub cancel: Arc<AtomicBool>,
+}
+
+impl MockServerView {
+    pub fn new() -> Self {
+        Self {
+            port_input: "4010".to_string(),
+            latency_input: "0".to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Replaces the routes; a running server picks them up on the next request.
+    pub fn open(&mut self, source_name: String, routes: Vec<MockRoute>) {
+        self.source_name = source_name;
+        self.routes = routes;
+        self.publish();
+    }
+
+    pub fn port(&self) -> Result<u16, String> {
+        self.port_input
+            .trim()
+            .parse()
+            .map_err(|_| "Port must be a number between 0 and 65535".to_string())
+    }
+
+    fn latency(&self) -> Duration {
+        Duration::from_millis(self.latency_input.trim().parse().unwrap_or(0))
+    }
+
+    fn publish(&self) {
+        if let Ok(mut state) = self.state.write() {
+            state.routes = self.routes.clone();
+            state.latency = self.latency();
+        }
+    }
+
+    /// Marks the server as starting and returns a fresh cancellation flag.
+    pub fn start(&mut self) -> Arc<AtomicBool> {
+        self.running = true;
+        self.listening = None;
+        self.cancel = Arc::new(AtomicBool::new(false));
+        self.publish();
+        self.cancel.clone()
+    }
+
+    pub fn stopped(&mut self) {
+        self.running = false;
+        self.listening = None;
+    }
+
+    pub fn add_log(&mut self, entry: MockLogEntry) {
+        self.log.insert(0, entry);
+        self.log.truncate(MAX_LOG_ENTRIES);
+    }
+
+    pub fn update(&mut self, message: Message) {
+        let digits = |value: &str| value.chars().all(|c| c.is_ascii_digit());
+        match message {
+            Message::PortChanged(value) if digits(&value) && !self.running => {
+                self.port_input = value
+            }
+            Message::LatencyChanged(value) if digits(&value) => {
+                self.latency_input = value;
+                self.publish();
+            }
+            Message::StatusSelected(index, status) => {
+                if let Some(route) = self.routes.get_mut(index) {
+                    if let Some(position) = route.responses.iter().position(|r| r.status == status)
+                    {
+                        route.selected = position;
+                    }
+                }
+                self.publish();
+            }
+            Message::Stop => self.cancel.store(true, Ordering::Relaxed),
+            Message::ClearLog => self.log.clear(),
+            Message::OpenSpec | Message::SpecLoaded(_) | Message::Start | Message::Close => {
+                // This message is handled in app.rs
+            }
+            _ => {}
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let back_button = button(row![lucide::arrow_left().size(14), text(" Back")].spacing(4))
+            .on_press(Message::Close);
+        let title = if self.source_name.is_empty() {
+            "Mock server".to_string()
+        } else {
+            format!("Mock server: {}", self.source_name)
+        };
+        let header = row![
+            back_button,
+            text(title).size(18),
+            text(format!("{} route(s)", self.routes.len()))
+                .size(13)
+                .color(Color::from_rgb(0.5, 0.5, 0.5)),
+            button(row![lucide::file_code().size(14), text(" From OpenAPI")].spacing(4))
+                .on_press(Message::OpenSpec),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let start_button = if self.running {
+            button(row![lucide::square().size(14), text(" Stop")].spacing(4))
+                .on_press(Message::Stop)
+                .style(button::danger)
+        } else {
+            button(row![lucide::play().size(14), text(" Start")].spacing(4))
+                .on_press_maybe((!self.routes.is_empty()).then_some(Message::Start))
+        };
+        let status: Element<'_, Message> = match (self.running, self.listening) {
+            (true, Some(port)) => text(format!("Listening on http://127.0.0.1:{}", port))
+                .size(13)
+                .color(Color::from_rgb(0.2, 0.6, 0.3))
+                .into(),
+            (true, None) => text("Starting...").size(13).into(),
+            _ => text("Stopped")
+                .size(13)
+                .color(Color::from_rgb(0.5, 0.5, 0.5))
+                .into(),
+        };
+        let settings = row![
+            text("Port").size(13),
+            text_input("4010", &self.port_input)
+                .on_input(Message::PortChanged)
+                .width(Length::Fixed(70.0))
+                .size(13),
+            text("Latency (ms)").size(13),
+            text_input("0", &self.latency_input)
+                .on_input(Message::LatencyChanged)
+                .width(Length::Fixed(70.0))
+                .size(13),
+            start_button,
+            status,
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let hint = text("Send a \"Prefer: code=404\" header to pick another documented status.")
+            .size(12)
+            .color(Color::from_rgb(0.5, 0.5, 0.5));
+
+        let body = row![
+            column![text("Routes").size(14), scrollable(self.routes_view())]
+                .spacing(6)
+                .width(Length::FillPortion(1)),
+            column![
+                row![
+                    text("Request log").size(14),
+                    button(text("Clear").size(12)).on_press(Message::ClearLog),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+                scrollable(self.log_view()),
+            ]
+            .spacing(6)
+            .width(Length::FillPortion(1)),
+        ]
+        .spacing(20)
+        .height(Length::Fill);
+
+        iced_container(
+            column![header, settings, hint, body]
+                .spacing(14)
+                .padding(20),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+
+    fn routes_view(&self) -> Element<'_, Message> {
+        if self.routes.is_empty() {
+            return text("Open a collection or an OpenAPI file to generate routes.")
+                .size(13)
+                .color(Color::from_rgb(0.5, 0.5, 0.5))
+                .into();
+        }
+        self.routes
+            .iter()
+            .enumerate()
+            .fold(column![].spacing(4), |col, (index, route)| {
+                let selected = route.responses.get(route.selected).map(|r| r.status);
+                col.push(
+                    container(
+                        row![
+                            text(&route.method)
+                                .size(12)
+                                .color(method_color(&route.method))
+                                .width(Length::Fixed(60.0)),
+                            column![
+                                text(&route.path).size(13),
+                                text(&route.name)
+                                    .size(11)
+                                    .color(Color::from_rgb(0.5, 0.5, 0.5)),
+                            ]
+                            .width(Length::Fill),
+                            pick_list(route.statuses(), selected, move |status| {
+                                Message::StatusSelected(index, status)
+                            })
+                            .text_size(12),
+                        ]
+                        .spacing(8)
+                        .align_y(Alignment::Center),
+                    )
+                    .padding(6)
+                    .style(iced_container::bordered_box),
+                )
+            })
+            .into()
+    }
+
+    fn log_view(&self) -> Element<'_, Message> {
+        if self.log.is_empty() {
+            return text("Requests to the mock server appear here.")
+                .size(13)
+                .color(Color::from_rgb(0.5, 0.5, 0.5))
+                .into();
+        }
+        self.log
+            .iter()
+            .fold(column![].spacing(2), |col, entry| {
+                col.push(
+                    row![
+                        text(&entry.time)
+                            .size(12)
+                            .color(Color::from_rgb(0.5, 0.5, 0.5))
+                            .width(Length::Fixed(64.0)),
+                        text(&entry.method)
+                            .size(12)
+                            .color(method_color(&entry.method))
+                            .width(Length::Fixed(60.0)),
+                        text(entry.status.to_string())
+                            .size(12)
+                            .color(status_color(entry.status))
+                            .width(Length::Fixed(36.0)),
+                        text(&entry.path).size(12).width(Length::Fill),
+                        text(entry.route.as_deref().unwrap_or("no route"))
+                            .size(11)
+                            .color(Color::from_rgb(0.5, 0.5, 0.5)),
+                    ]
+                    .spacing(8),
+                )
+            })
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::route::MockResponse;
+
+    fn route() -> MockRoute {
+        MockRoute {
+            name: "List users".to_string(),
+            method: "GET".to_string(),
+            path: "/users".to_string(),
+            responses: vec![
+                MockResponse {
+                    status: 200,
+                    content_type: None,
+                    body: "[]".to_string(),
+                },
+                MockResponse {
+                    status: 500,
+                    content_type: None,
+                    body: String::new(),
+                },
+            ],
+            selected: 0,
+        }
+    }
+
+    #[test]
+    fn settings_are_shared_with_the_running_server() {
+        let mut view = MockServerView::new();
+        view.open("Users".to_string(), vec![route()]);
+        view.update(Message::StatusSelected(0, 500));
+        view.update(Message::LatencyChanged("250".to_string()));
+        view.update(Message::LatencyChanged("fast".to_string()));
+
+        let state = view.state.read().unwrap();
+        assert_eq!(state.routes[0].selected, 1);
+        assert_eq!(state.latency, Duration::from_millis(250));
+    }
+
+    #[test]
+    fn port_is_locked_while_running() {
+        let mut view = MockServerView::new();
+        view.update(Message::PortChanged("8080".to_string()));
+        assert_eq!(view.port(), Ok(8080));
+
+        let cancel = view.start();
+        view.update(Message::PortChanged("9090".to_string()));
+        assert_eq!(view.port(), Ok(8080));
+        view.update(Message::Stop);
+        assert!(cancel.load(Ordering::Relaxed));
+
+        view.update(Message::PortChanged("70000".to_string()));
+        view.stopped();
+        view.update(Message::PortChanged("70000".to_string()));
+        assert!(view.port().is_err());
+    }
+}
@@ -3,4 +3,10 @@ pub mod environment_manager;
 pub mod graphql_view;
 pub mod history_view;
 pub mod http_request_view;
+pub mod load_test_view;
+pub mod mock_server_view;
+pub mod openapi_import_view;
+pub mod openapi_resync_view;
+pub mod runner_view;
+pub mod schema_browser_view;
 pub mod websocket_view;
@@ -0,0 +1,367 @@
+use crate::openapi::collection_generator::{FolderGrouping, WEBHOOKS_FOLDER};
+use crate::openapi::models::{ParsedEndpoint, ParsedSpec};
+use crate::openapi::parser::SpecFormat;
+use crate::ui::theme::method_color;
+use iced::widget::container as iced_container;
+use iced::{
+    widget::{button, checkbox, column, container, pick_list, row, scrollable, text, text_input},
+    Alignment, Color, Element, Length,
+};
+use iced_fonts::lucide;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    SearchChanged(String),
+    ShowDeprecatedToggled(bool),
+    GroupingSelected(FolderGrouping),
+    TagToggled(String, bool),
+    /// Method and path of the endpoint.
+    EndpointToggled(String, String, bool),
+    SelectAll,
+    SelectNone,
+    Import,
+    Cancel,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OpenApiImportView {
+    pub spec: Option<ParsedSpec>,
+    /// The format of the file as picked, before any bundling.
+    pub format: SpecFormat,
+    /// The spec as read from disk, saved with the collection on import.
+    pub content: String,
+    /// Where the spec was read from, kept so the collection can be re-synced.
+    pub source: Option<String>,
+    pub search: String,
+    pub show_deprecated: bool,
+    pub grouping: FolderGrouping,
+    /// `(method, path)` of the endpoints to import.
+    pub selected: HashSet<(String, String)>,
+}
+
+fn key(endpoint: &ParsedEndpoint) -> (String, String) {
+    (endpoint.method.clone(), endpoint.path.clone())
+}
+
+impl OpenApiImportView {
+    /// Shows `spec` with every operation that isn't deprecated selected.
+    pub fn open(
+        &mut self,
+        spec: ParsedSpec,
+        format: SpecFormat,
+        content: String,
+        source: Option<String>,
+    ) {
+        self.selected = spec
+            .endpoints
+            .iter()
+            .chain(&spec.webhooks)
+            .filter(|e| !e.deprecated)
+            .map(key)
+            .collect();
+        self.spec = Some(spec);
+        self.format = format;
+        self.content = content;
+        self.source = source;
+        self.search.clear();
+        self.show_deprecated = false;
+        self.grouping = FolderGrouping::Tag;
+    }
+
+    pub fn close(&mut self) {
+        self.spec = None;
+        self.content.clear();
+        self.source = None;
+        self.selected.clear();
+    }
+
+    fn is_visible(&self, endpoint: &ParsedEndpoint) -> bool {
+        self.show_deprecated || !endpoint.deprecated
+    }
+
+    /// Endpoints matching the search and deprecation filters.
+    fn visible_endpoints(&self) -> Vec<&ParsedEndpoint> {
+        let Some(spec) = &self.spec else {
+            return vec![];
+        };
+        let query = self.search.trim();
+        spec.endpoints
+            .iter()
+            .chain(&spec.webhooks)
+            .filter(|e| query.is_empty() || e.matches(query))
+            .filter(|e| self.is_visible(e))
+            .collect()
+    }
+
+    /// Visible endpoints by tag, in tag order and then path order.
+    fn visible_by_tag(&self) -> Vec<(String, Vec<&ParsedEndpoint>)> {
+        let Some(spec) = &self.spec else {
+            return vec![];
+        };
+        let visible: HashSet<(String, String)> =
+            self.visible_endpoints().into_iter().map(key).collect();
+        let mut groups: Vec<(String, Vec<&ParsedEndpoint>)> = spec
+            .endpoints_by_tag()
+            .into_iter()
+            .map(|(tag, endpoints)| {
+                let mut endpoints: Vec<&ParsedEndpoint> = endpoints
+                    .into_iter()
+                    .filter(|e| visible.contains(&key(e)))
+                    .collect();
+                endpoints.sort_by(|a, b| (&a.path, &a.method).cmp(&(&b.path, &b.method)));
+                (tag, endpoints)
+            })
+            .filter(|(_, endpoints)| !endpoints.is_empty())
+            .collect();
+        groups.sort_by(|a, b| a.0.cmp(&b.0));
+        let webhooks: Vec<&ParsedEndpoint> = spec
+            .webhooks
+            .iter()
+            .filter(|e| visible.contains(&key(e)))
+            .collect();
+        if !webhooks.is_empty() {
+            groups.push((WEBHOOKS_FOLDER.to_string(), webhooks));
+        }
+        groups
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::SearchChanged(value) => self.search = value,
+            Message::ShowDeprecatedToggled(show) => {
+                self.show_deprecated = show;
+                if !show {
+                    // Hidden operations are never imported.
+                    if let Some(spec) = &self.spec {
+                        let all = spec.endpoints.iter().chain(&spec.webhooks);
+                        for endpoint in all.filter(|e| e.deprecated) {
+                            self.selected.remove(&key(endpoint));
+                        }
+                    }
+                }
+            }
+            Message::GroupingSelected(grouping) => self.grouping = grouping,
+            Message::TagToggled(tag, checked) => {
+                let keys: Vec<(String, String)> = self
+                    .visible_by_tag()
+                    .into_iter()
+                    .filter(|(name, _)| *name == tag)
+                    .flat_map(|(_, endpoints)| endpoints.into_iter().map(key))
+                    .collect();
+                for k in keys {
+                    if checked {
+                        self.selected.insert(k);
+                    } else {
+                        self.selected.remove(&k);
+                    }
+                }
+            }
+            Message::EndpointToggled(method, path, checked) => {
+                if checked {
+                    self.selected.insert((method, path));
+                } else {
+                    self.selected.remove(&(method, path));
+                }
+            }
+            Message::SelectAll => {
+                let keys: Vec<(String, String)> =
+                    self.visible_endpoints().into_iter().map(key).collect();
+                self.selected.extend(keys);
+            }
+            Message::SelectNone => self.selected.clear(),
+            Message::Import | Message::Cancel => {
+                // This message is handled in app.rs
+            }
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let Some(spec) = &self.spec else {
+            return text("No spec loaded").into();
+        };
+
+        let header = row![
+            button(row![lucide::arrow_left().size(14), text(" Cancel")].spacing(4))
+                .on_press(Message::Cancel),
+            text(format!("Import {}", spec.title)).size(18),
+            text(format!(
+                "{} · {} of {} operation(s) selected",
+                self.format.label(),
+                self.selected.len(),
+                spec.endpoints.len() + spec.webhooks.len()
+            ))
+            .size(13)
+            .color(Color::from_rgb(0.5, 0.5, 0.5)),
+            button(row![lucide::download().size(14), text(" Import")].spacing(4))
+                .on_press_maybe((!self.selected.is_empty()).then_some(Message::Import)),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let filters = row![
+            text_input(
+                "Search by path, summary, operation ID or method...",
+                &self.search
+            )
+            .on_input(Message::SearchChanged)
+            .size(13)
+            .width(Length::Fill),
+            checkbox(self.show_deprecated)
+                .label("Show deprecated")
+                .on_toggle(Message::ShowDeprecatedToggled)
+                .size(14),
+            text("Folders").size(13),
+            pick_list(
+                &FolderGrouping::ALL[..],
+                Some(self.grouping),
+                Message::GroupingSelected
+            )
+            .text_size(13),
+            button(text("All").size(12)).on_press(Message::SelectAll),
+            button(text("None").size(12)).on_press(Message::SelectNone),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        iced_container(
+            column![
+                header,
+                filters,
+                scrollable(self.endpoints_view()).height(Length::Fill)
+            ]
+            .spacing(14)
+            .padding(20),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+
+    fn endpoints_view(&self) -> Element<'_, Message> {
+        let groups = self.visible_by_tag();
+        if groups.is_empty() {
+            return text("No operations match the filters.")
+                .size(13)
+                .color(Color::from_rgb(0.5, 0.5, 0.5))
+                .into();
+        }
+        groups
+            .into_iter()
+            .fold(column![].spacing(8), |col, (tag, endpoints)| {
+                let all_selected = endpoints.iter().all(|e| self.selected.contains(&key(e)));
+                let toggle_tag = tag.clone();
+                let tag_row = checkbox(all_selected)
+                    .label(format!("{} ({})", tag, endpoints.len()))
+                    .on_toggle(move |checked| Message::TagToggled(toggle_tag.clone(), checked))
+                    .size(14);
+                let rows = endpoints.into_iter().fold(column![].spacing(2), |rows, e| {
+                    rows.push(self.endpoint_row(e))
+                });
+                col.push(
+                    container(column![tag_row, rows].spacing(6))
+                        .padding(8)
+                        .width(Length::Fill)
+                        .style(iced_container::bordered_box),
+                )
+            })
+            .into()
+    }
+
+    fn endpoint_row<'a>(&self, endpoint: &'a ParsedEndpoint) -> Element<'a, Message> {
+        let (method, path) = key(endpoint);
+        let label = endpoint
+            .summary
+            .as_deref()
+            .or(endpoint.operation_id.as_deref())
+            .unwrap_or("");
+        let mut line = row![
+            checkbox(self.selected.contains(&key(endpoint)))
+                .on_toggle(move |checked| {
+                    Message::EndpointToggled(method.clone(), path.clone(), checked)
+                })
+                .size(14),
+            text(&endpoint.method)
+                .size(12)
+                .color(method_color(&endpoint.method))
+                .width(Length::Fixed(60.0)),
+            text(&endpoint.path).size(13),
+            text(label).size(12).color(Color::from_rgb(0.5, 0.5, 0.5)),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center);
+        if endpoint.deprecated {
+            line = line.push(
+                text("deprecated")
+                    .size(11)
+                    .color(Color::from_rgb(0.8, 0.5, 0.2)),
+            );
+        }
+        container(line).padding([0, 24]).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = r#"{
+        "openapi": "3.0.0",
+        "info": { "title": "Gateway", "version": "1" },
+        "paths": {
+            "/users": {
+                "get": { "tags": ["users"], "operationId": "listUsers", "responses": {} },
+                "post": { "tags": ["users"], "deprecated": true, "responses": {} }
+            },
+            "/orders": {
+                "get": { "tags": ["orders"], "operationId": "listOrders", "responses": {} }
+            }
+        }
+    }"#;
+
+    fn view() -> OpenApiImportView {
+        let mut view = OpenApiImportView::default();
+        view.open(
+            crate::openapi::parse_spec(SPEC).unwrap(),
+            crate::openapi::parser::detect_format(SPEC),
+            SPEC.to_string(),
+            None,
+        );
+        view
+    }
+
+    fn selected(view: &OpenApiImportView) -> Vec<String> {
+        let mut keys: Vec<String> = view
+            .selected
+            .iter()
+            .map(|(method, path)| format!("{} {}", method, path))
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn deprecated_operations_are_hidden_and_unselected() {
+        let mut view = view();
+        assert_eq!(view.format.label(), "OpenAPI 3.0.0 (JSON)");
+        assert_eq!(selected(&view), vec!["GET /orders", "GET /users"]);
+        assert_eq!(view.visible_endpoints().len(), 2);
+
+        view.update(Message::ShowDeprecatedToggled(true));
+        view.update(Message::TagToggled("users".to_string(), true));
+        assert_eq!(selected(&view).len(), 3);
+
+        view.update(Message::ShowDeprecatedToggled(false));
+        assert_eq!(selected(&view), vec!["GET /orders", "GET /users"]);
+    }
+
+    #[test]
+    fn tag_toggle_only_touches_endpoints_matching_the_search() {
+        let mut view = view();
+        view.update(Message::SelectNone);
+        view.update(Message::SearchChanged("listUsers".to_string()));
+        view.update(Message::TagToggled("users".to_string(), true));
+        view.update(Message::TagToggled("orders".to_string(), true));
+        assert_eq!(selected(&view), vec!["GET /users"]);
+    }
+}
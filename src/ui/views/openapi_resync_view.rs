@@ -0,0 +1,192 @@
+use crate::services::openapi_sync::{ResyncItem, ResyncPlan};
+use crate::ui::theme::method_color;
+use iced::widget::container as iced_container;
+use iced::{
+    widget::{button, checkbox, column, container, row, scrollable, text},
+    Alignment, Color, Element, Length,
+};
+use iced_fonts::lucide;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// The spec read again from its source, for the collection with this id.
+    Loaded(i32, Result<String, String>),
+    Toggled(ResyncItem, bool),
+    Apply,
+    Cancel,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OpenApiResyncView {
+    pub plan: Option<ResyncPlan>,
+    pub selected: HashSet<ResyncItem>,
+}
+
+impl OpenApiResyncView {
+    /// Shows `plan` with every change selected.
+    pub fn open(&mut self, plan: ResyncPlan) {
+        self.selected = plan.items();
+        self.plan = Some(plan);
+    }
+
+    pub fn close(&mut self) {
+        self.plan = None;
+        self.selected.clear();
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::Toggled(item, checked) => {
+                if checked {
+                    self.selected.insert(item);
+                } else {
+                    self.selected.remove(&item);
+                }
+            }
+            Message::Loaded(_, _) | Message::Apply | Message::Cancel => {
+                // This message is handled in app.rs
+            }
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let Some(plan) = &self.plan else {
+            return text("Nothing to re-sync").into();
+        };
+
+        let header = row![
+            button(row![lucide::arrow_left().size(14), text(" Cancel")].spacing(4))
+                .on_press(Message::Cancel),
+            text(format!("Re-sync {}", plan.title)).size(18),
+            text(format!(
+                "{} added, {} changed, {} removed",
+                plan.added.len(),
+                plan.changed.len(),
+                plan.removed.len()
+            ))
+            .size(13)
+            .color(Color::from_rgb(0.5, 0.5, 0.5)),
+            button(row![lucide::refresh_cw().size(14), text(" Apply")].spacing(4))
+                .on_press(Message::Apply),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let added = plan.added.iter().map(|req| {
+            let item = ResyncItem::Add(req.method.clone(), req.path.clone());
+            self.change_row(item, &req.method, &req.path, req.name.clone())
+        });
+        let changed = plan.changed.iter().map(|change| {
+            let op = &change.generated;
+            let mut note = Vec::new();
+            if !change.updated.is_empty() {
+                note.push(format!("updates {}", change.updated.join(", ")));
+            }
+            if !change.kept.is_empty() {
+                note.push(format!("keeps your {}", change.kept.join(", ")));
+            }
+            let item = ResyncItem::Request(change.request.id);
+            self.change_row(item, &op.method, &op.path, note.join("; "))
+        });
+        let removed = plan.removed.iter().map(|removal| {
+            let request = &removal.request;
+            let operation = request.operation.as_ref();
+            let method = operation.map(|op| op.method.as_str()).unwrap_or_default();
+            let path = operation.map(|op| op.path.as_str()).unwrap_or_default();
+            let note = if removal.edited {
+                format!("{} (edited, kept but unlinked)", request.name)
+            } else {
+                request.name.clone()
+            };
+            self.change_row(ResyncItem::Request(request.id), method, path, note)
+        });
+
+        let sections = column![
+            section("Added", added.collect()),
+            section("Changed", changed.collect()),
+            section("Removed", removed.collect()),
+        ]
+        .spacing(8);
+
+        iced_container(
+            column![header, scrollable(sections).height(Length::Fill)]
+                .spacing(14)
+                .padding(20),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+
+    fn change_row<'a>(
+        &self,
+        item: ResyncItem,
+        method: &'a str,
+        path: &'a str,
+        note: String,
+    ) -> Element<'a, Message> {
+        let checked = self.selected.contains(&item);
+        row![
+            checkbox(checked)
+                .on_toggle(move |checked| Message::Toggled(item.clone(), checked))
+                .size(14),
+            text(method)
+                .size(12)
+                .color(method_color(method))
+                .width(Length::Fixed(60.0)),
+            text(path).size(13),
+            text(note).size(12).color(Color::from_rgb(0.5, 0.5, 0.5)),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .into()
+    }
+}
+
+fn section<'a>(title: &str, rows: Vec<Element<'a, Message>>) -> Element<'a, Message> {
+    if rows.is_empty() {
+        return column![].into();
+    }
+    let heading = text(format!("{} ({})", title, rows.len())).size(14);
+    container(column![heading, column(rows).spacing(2)].spacing(6))
+        .padding(8)
+        .width(Length::Fill)
+        .style(iced_container::bordered_box)
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::database;
+    use crate::services::{openapi_service, openapi_sync};
+    use std::path::Path;
+
+    #[test]
+    fn open_selects_every_change_and_toggles_them() {
+        let conn = database::open(Path::new(":memory:")).unwrap();
+        let spec = r#"{"openapi": "3.0.0", "info": {"title": "T"}, "paths": {
+            "/a": {"get": {"responses": {}}}}}"#;
+        let collection_id = openapi_service::import_spec(&conn, spec)
+            .unwrap()
+            .collection
+            .id;
+        let next = r#"{"openapi": "3.0.0", "info": {"title": "T"}, "paths": {
+            "/a": {"get": {"responses": {}}}, "/b": {"get": {"responses": {}}}}}"#;
+        let plan = openapi_sync::plan_resync(&conn, collection_id, next).unwrap();
+
+        let mut view = OpenApiResyncView::default();
+        view.open(plan);
+        let item = ResyncItem::Add("GET".to_string(), "/b".to_string());
+        assert_eq!(view.selected, HashSet::from([item.clone()]));
+
+        view.update(Message::Toggled(item.clone(), false));
+        assert!(view.selected.is_empty());
+        view.update(Message::Toggled(item, true));
+        assert_eq!(view.selected.len(), 1);
+
+        view.close();
+        assert!(view.plan.is_none() && view.selected.is_empty());
+    }
+}
@@ -0,0 +1,433 @@
+use crate::persistence::database::CollectionRun;
+use crate::runner::collection::{group_by_iteration, RunOptions, RunReport, StepResult};
+use crate::runner::data::IterationData;
+use crate::ui::theme::{method_color, status_color};
+use iced::widget::container as iced_container;
+use iced::{
+    widget::{button, checkbox, column, container, row, scrollable, text, text_input},
+    Alignment, Color, Element, Length,
+};
+use iced_fonts::lucide;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    DelayChanged(String),
+    StopOnFailureToggled(bool),
+    PickDataFile,
+    DataFileLoaded(Option<(String, Result<Vec<IterationData>, String>)>),
+    ClearDataFile,
+    Start,
+    Stop,
+    SelectRun(i32),
+    Close,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RunnerView {
+    pub collection_id: Option<i32>,
+    pub folder_id: Option<i32>,
+    pub target_name: String,
+    pub request_count: usize,
+    pub delay_input: String,
+    pub stop_on_failure: bool,
+    /// File name of the loaded iteration data.
+    pub data_file: Option<String>,
+    pub data: Vec<IterationData>,
+    pub running: bool,
+    /// Steps of the run in progress, or of the run being shown.
+    pub steps: Vec<StepResult>,
+    pub report: Option<RunReport>,
+    pub runs: Vec<CollectionRun>,
+    pub selected_run: Option<i32>,
+    pub cancel: Arc<AtomicBool>,
+}
+
+impl RunnerView {
+    pub fn new() -> Self {
+        Self {
+            delay_input: "0".to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Points the runner at a collection or folder, keeping the current options.
+    pub fn open(
+        &mut self,
+        collection_id: i32,
+        folder_id: Option<i32>,
+        target_name: String,
+        request_count: usize,
+        runs: Vec<CollectionRun>,
+    ) {
+        if self.running {
+            return;
+        }
+        self.collection_id = Some(collection_id);
+        self.folder_id = folder_id;
+        self.target_name = target_name;
+        self.request_count = request_count;
+        self.steps.clear();
+        self.report = None;
+        self.runs = runs;
+        self.selected_run = None;
+    }
+
+    pub fn options(&self) -> RunOptions {
+        RunOptions {
+            delay_ms: self.delay_input.trim().parse().unwrap_or(0),
+            stop_on_failure: self.stop_on_failure,
+            data: self.data.clone(),
+        }
+    }
+
+    fn iteration_count(&self) -> usize {
+        match &self.report {
+            Some(report) => report.iterations,
+            None => self.data.len().max(1),
+        }
+    }
+
+    /// Resets progress and returns a fresh cancellation flag for the new run.
+    pub fn start(&mut self) -> Arc<AtomicBool> {
+        self.running = true;
+        self.steps.clear();
+        self.report = None;
+        self.selected_run = None;
+        self.cancel = Arc::new(AtomicBool::new(false));
+        self.cancel.clone()
+    }
+
+    pub fn finish(&mut self, report: RunReport, runs: Vec<CollectionRun>) {
+        self.running = false;
+        self.steps = report.steps.clone();
+        self.report = Some(report);
+        self.runs = runs;
+        self.selected_run = self.runs.first().map(|r| r.id);
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::DelayChanged(value) => {
+                if value.chars().all(|c| c.is_ascii_digit()) {
+                    self.delay_input = value;
+                }
+            }
+            Message::StopOnFailureToggled(value) => self.stop_on_failure = value,
+            Message::DataFileLoaded(Some((name, Ok(rows)))) => {
+                self.data_file = Some(name);
+                self.data = rows;
+            }
+            Message::DataFileLoaded(_) | Message::ClearDataFile => {
+                self.data_file = None;
+                self.data.clear();
+            }
+            Message::Stop => self.cancel.store(true, Ordering::Relaxed),
+            Message::SelectRun(id) => {
+                if self.running {
+                    return;
+                }
+                let report = self
+                    .runs
+                    .iter()
+                    .find(|r| r.id == id)
+                    .and_then(|run| crate::services::run_service::load_report(run).ok());
+                if let Some(report) = report {
+                    self.selected_run = Some(id);
+                    self.steps = report.steps.clone();
+                    self.report = Some(report);
+                }
+            }
+            Message::PickDataFile | Message::Start => {
+                // This message is handled in app.rs
+            }
+            Message::Close => {
+                // This message is handled in app.rs
+            }
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let back_button = button(row![lucide::arrow_left().size(14), text(" Back")].spacing(4))
+            .on_press(Message::Close);
+        let header = row![
+            back_button,
+            text(format!("Run: {}", self.target_name)).size(18),
+            text(format!("{} request(s)", self.request_count))
+                .size(13)
+                .color(Color::from_rgb(0.5, 0.5, 0.5)),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let run_button = if self.running {
+            button(row![lucide::square().size(14), text(" Stop")].spacing(4))
+                .on_press(Message::Stop)
+                .style(button::danger)
+        } else {
+            button(row![lucide::play().size(14), text(" Run")].spacing(4))
+                .on_press_maybe((self.request_count > 0).then_some(Message::Start))
+        };
+        let options = row![
+            text("Delay (ms)").size(13),
+            text_input("0", &self.delay_input)
+                .on_input(Message::DelayChanged)
+                .width(Length::Fixed(80.0))
+                .size(13),
+            checkbox(self.stop_on_failure)
+                .label("Stop on failure")
+                .on_toggle(Message::StopOnFailureToggled)
+                .size(14),
+            self.data_file_view(),
+            run_button,
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let results = column![self.summary_view(), scrollable(self.steps_view())]
+            .spacing(10)
+            .width(Length::FillPortion(3));
+
+        let body = row![results, self.runs_view()].spacing(12);
+
+        iced_container(column![header, options, body].spacing(14).padding(20))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+
+    fn summary_view(&self) -> Element<'_, Message> {
+        if self.running {
+            return text(format!(
+                "Running... {}/{}",
+                self.steps.len(),
+                self.request_count * self.iteration_count()
+            ))
+            .size(14)
+            .into();
+        }
+        let Some(report) = &self.report else {
+            return text("Press Run to send every request in order.")
+                .size(14)
+                .color(Color::from_rgb(0.5, 0.5, 0.5))
+                .into();
+        };
+        let mut summary = row![
+            text(format!("{} passed", report.passed_count()))
+                .size(14)
+                .color(Color::from_rgb(0.2, 0.7, 0.3)),
+            text(format!("{} failed", report.failed_count()))
+                .size(14)
+                .color(Color::from_rgb(0.8, 0.2, 0.2)),
+            text(format!("{} ms", report.duration_ms)).size(14),
+            text(&report.started_at)
+                .size(12)
+                .color(Color::from_rgb(0.5, 0.5, 0.5)),
+        ]
+        .spacing(12)
+        .align_y(Alignment::Center);
+        if report.stopped_early {
+            summary = summary.push(
+                text("Stopped early")
+                    .size(12)
+                    .color(Color::from_rgb(0.8, 0.5, 0.1)),
+            );
+        }
+        summary.into()
+    }
+
+    fn data_file_view(&self) -> Element<'_, Message> {
+        let picker = button(row![lucide::file_spreadsheet().size(14), text(" Data")].spacing(4))
+            .on_press_maybe((!self.running).then_some(Message::PickDataFile));
+        match &self.data_file {
+            Some(name) => row![
+                picker,
+                text(format!("{} · {} iteration(s)", name, self.data.len())).size(13),
+                button(lucide::x().size(12))
+                    .on_press_maybe((!self.running).then_some(Message::ClearDataFile)),
+            ]
+            .spacing(6)
+            .align_y(Alignment::Center)
+            .into(),
+            None => picker.into(),
+        }
+    }
+
+    fn steps_view(&self) -> Element<'_, Message> {
+        if self.iteration_count() <= 1 {
+            return Self::step_list(&self.steps);
+        }
+        group_by_iteration(&self.steps)
+            .into_iter()
+            .fold(column![].spacing(12), |col, (iteration, steps)| {
+                let passed = steps.iter().filter(|s| s.passed()).count();
+                col.push(
+                    column![
+                        text(format!(
+                            "Iteration {} · {}/{} passed",
+                            iteration + 1,
+                            passed,
+                            steps.len()
+                        ))
+                        .size(14),
+                        Self::step_list(steps),
+                    ]
+                    .spacing(6),
+                )
+            })
+            .into()
+    }
+
+    fn step_list(steps: &[StepResult]) -> Element<'_, Message> {
+        steps
+            .iter()
+            .fold(column![].spacing(8), |col, step| {
+                let (icon, color) = if step.passed() {
+                    (
+                        lucide::circle_check().size(14),
+                        Color::from_rgb(0.2, 0.7, 0.3),
+                    )
+                } else {
+                    (lucide::circle_x().size(14), Color::from_rgb(0.8, 0.2, 0.2))
+                };
+                let status = match step.status {
+                    Some(status) => text(status.to_string())
+                        .size(13)
+                        .color(status_color(status)),
+                    None => text("—").size(13),
+                };
+                let mut entry = column![row![
+                    icon.color(color),
+                    text(&step.method)
+                        .size(13)
+                        .color(method_color(&step.method)),
+                    text(&step.name).size(14),
+                    status,
+                    text(format!("{} ms", step.duration_ms))
+                        .size(12)
+                        .color(Color::from_rgb(0.5, 0.5, 0.5)),
+                ]
+                .spacing(8)
+                .align_y(Alignment::Center)]
+                .spacing(2);
+                for failure in step.failures().into_iter().chain(
+                    step.extraction_errors
+                        .iter()
+                        .map(|e| format!("Extraction failed: {}", e)),
+                ) {
+                    entry =
+                        entry.push(text(failure).size(12).color(Color::from_rgb(0.8, 0.2, 0.2)));
+                }
+                col.push(entry)
+            })
+            .into()
+    }
+
+    fn runs_view(&self) -> Element<'_, Message> {
+        let list = self.runs.iter().fold(
+            column![text("Past runs").size(14)].spacing(4),
+            |col, run| {
+                let color = if run.failed == 0 {
+                    Color::from_rgb(0.2, 0.7, 0.3)
+                } else {
+                    Color::from_rgb(0.8, 0.2, 0.2)
+                };
+                let label = column![
+                    text(&run.started_at).size(11),
+                    text(format!(
+                        "{}/{} passed · {} ms",
+                        run.passed, run.total, run.duration_ms
+                    ))
+                    .size(12)
+                    .color(color),
+                ];
+                let style = if self.selected_run == Some(run.id) {
+                    button::primary
+                } else {
+                    button::secondary
+                };
+                col.push(
+                    button(label)
+                        .on_press(Message::SelectRun(run.id))
+                        .style(style)
+                        .width(Length::Fill),
+                )
+            },
+        );
+        container(scrollable(list))
+            .width(Length::FillPortion(1))
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved_run(id: i32, report: &RunReport) -> CollectionRun {
+        CollectionRun {
+            id,
+            collection_id: 1,
+            folder_id: None,
+            started_at: report.started_at.clone(),
+            duration_ms: report.duration_ms,
+            total: 0,
+            passed: 0,
+            failed: 0,
+            report: serde_json::to_string(report).unwrap(),
+        }
+    }
+
+    fn report(name: &str) -> RunReport {
+        RunReport {
+            name: name.to_string(),
+            started_at: "2024-01-01T00:00:00Z".to_string(),
+            duration_ms: 10,
+            steps: vec![],
+            stopped_early: false,
+            iterations: 1,
+        }
+    }
+
+    #[test]
+    fn delay_accepts_digits_only() {
+        let mut view = RunnerView::new();
+        view.update(Message::DelayChanged("250".to_string()));
+        view.update(Message::DelayChanged("25x".to_string()));
+        view.update(Message::StopOnFailureToggled(true));
+        assert_eq!(
+            view.options(),
+            RunOptions {
+                delay_ms: 250,
+                stop_on_failure: true,
+                data: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn stop_sets_cancel_flag_of_current_run() {
+        let mut view = RunnerView::new();
+        let cancel = view.start();
+        view.update(Message::Stop);
+        assert!(cancel.load(Ordering::Relaxed));
+        assert!(!view.start().load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn select_run_shows_its_report() {
+        let mut view = RunnerView::new();
+        let older = report("older");
+        view.open(
+            1,
+            None,
+            "API".to_string(),
+            2,
+            vec![saved_run(2, &report("newer")), saved_run(1, &older)],
+        );
+        view.update(Message::SelectRun(1));
+        assert_eq!(view.selected_run, Some(1));
+        assert_eq!(view.report, Some(older));
+    }
+}
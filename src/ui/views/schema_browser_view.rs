@@ -0,0 +1,297 @@
+use crate::openapi::schema_tree::{SchemaBrowser, SchemaNode};
+use crate::ui::theme::method_color;
+use iced::widget::container as iced_container;
+use iced::{
+    widget::{button, column, container, row, scrollable, text, text_input},
+    Alignment, Color, Element, Length,
+};
+use iced_fonts::lucide;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    SearchChanged(String),
+    SelectSchema(String),
+    /// Path of the node, such as `Pet/category`.
+    ToggleNode(String),
+    /// Method and path of an operation using the selected schema.
+    OpenOperation(String, String),
+    Close,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SchemaBrowserView {
+    pub title: String,
+    pub collection_id: Option<i32>,
+    /// The stored spec the collection's requests are linked to.
+    pub spec_id: Option<i32>,
+    pub browser: SchemaBrowser,
+    pub search: String,
+    pub selected: Option<String>,
+    /// Tree of the selected schema.
+    pub tree: Option<SchemaNode>,
+    pub expanded: HashSet<String>,
+}
+
+impl SchemaBrowserView {
+    pub fn open(
+        &mut self,
+        title: String,
+        collection_id: i32,
+        spec_id: i32,
+        browser: SchemaBrowser,
+    ) {
+        let first = browser.names().first().map(|name| name.to_string());
+        self.title = title;
+        self.collection_id = Some(collection_id);
+        self.spec_id = Some(spec_id);
+        self.browser = browser;
+        self.search.clear();
+        self.select(first);
+    }
+
+    fn select(&mut self, name: Option<String>) {
+        self.tree = name.as_deref().and_then(|name| self.browser.tree(name));
+        self.selected = name;
+        self.expanded.clear();
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::SearchChanged(value) => self.search = value,
+            Message::SelectSchema(name) => self.select(Some(name)),
+            Message::ToggleNode(path) => {
+                if !self.expanded.remove(&path) {
+                    self.expanded.insert(path);
+                }
+            }
+            Message::OpenOperation(_, _) | Message::Close => {
+                // This message is handled in app.rs
+            }
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let names = self.browser.names();
+        let header = row![
+            button(row![lucide::arrow_left().size(14), text(" Back")].spacing(4))
+                .on_press(Message::Close),
+            text(format!("Schemas: {}", self.title)).size(18),
+            text(format!("{} schema(s)", names.len()))
+                .size(13)
+                .color(Color::from_rgb(0.5, 0.5, 0.5)),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        let query = self.search.trim().to_lowercase();
+        let list = names
+            .into_iter()
+            .filter(|name| name.to_lowercase().contains(&query))
+            .fold(column![].spacing(2), |col, name| {
+                let style = if self.selected.as_deref() == Some(name) {
+                    button::primary
+                } else {
+                    button::text
+                };
+                col.push(
+                    button(text(name).size(13))
+                        .on_press(Message::SelectSchema(name.to_string()))
+                        .style(style)
+                        .width(Length::Fill),
+                )
+            });
+        let sidebar = column![
+            text_input("Filter schemas...", &self.search)
+                .on_input(Message::SearchChanged)
+                .size(13),
+            scrollable(list).height(Length::Fill),
+        ]
+        .spacing(8)
+        .width(Length::Fixed(240.0));
+
+        let detail: Element<'_, Message> = match (self.selected.as_deref(), &self.tree) {
+            (Some(name), Some(tree)) => column![
+                scrollable(self.node_view(tree, name.to_string(), 0))
+                    .height(Length::FillPortion(3)),
+                text("Used by").size(14),
+                scrollable(self.usages_view(name)).height(Length::FillPortion(1)),
+            ]
+            .spacing(8)
+            .width(Length::Fill)
+            .into(),
+            _ => text("This spec has no component schemas.")
+                .size(13)
+                .color(Color::from_rgb(0.5, 0.5, 0.5))
+                .into(),
+        };
+
+        iced_container(
+            column![
+                header,
+                row![sidebar, detail].spacing(20).height(Length::Fill)
+            ]
+            .spacing(14)
+            .padding(20),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+
+    fn node_view<'a>(
+        &'a self,
+        node: &'a SchemaNode,
+        path: String,
+        depth: u16,
+    ) -> Element<'a, Message> {
+        let expanded = depth == 0 || self.expanded.contains(&path);
+        let toggle: Element<'a, Message> = if node.children.is_empty() || depth == 0 {
+            text("").width(Length::Fixed(20.0)).into()
+        } else {
+            let icon = if expanded {
+                lucide::chevron_down().size(12)
+            } else {
+                lucide::chevron_right().size(12)
+            };
+            button(icon)
+                .on_press(Message::ToggleNode(path.clone()))
+                .style(button::text)
+                .width(Length::Fixed(20.0))
+                .into()
+        };
+
+        let mut type_label = node.type_label.clone();
+        if node.nullable {
+            type_label.push_str(" | null");
+        }
+        if node.recursive {
+            type_label.push_str(" (recursive)");
+        }
+        let mut line = row![
+            toggle,
+            text(&node.name).size(13),
+            text(type_label)
+                .size(12)
+                .color(Color::from_rgb(0.3, 0.5, 0.8)),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center);
+        if node.required {
+            line = line.push(
+                text("required")
+                    .size(11)
+                    .color(Color::from_rgb(0.8, 0.3, 0.3)),
+            );
+        }
+        if let Some(choice) = node.choice {
+            line = line.push(
+                text(format!("{} ({})", choice_wording(choice), choice))
+                    .size(11)
+                    .color(Color::from_rgb(0.5, 0.5, 0.5)),
+            );
+        }
+
+        let mut details = column![line].spacing(2);
+        if !node.enum_values.is_empty() {
+            details = details.push(
+                text(format!("enum: {}", node.enum_values.join(", ")))
+                    .size(11)
+                    .color(Color::from_rgb(0.5, 0.5, 0.5)),
+            );
+        }
+        if let Some(description) = &node.description {
+            details = details.push(
+                text(description)
+                    .size(11)
+                    .color(Color::from_rgb(0.5, 0.5, 0.5)),
+            );
+        }
+        if expanded {
+            for child in &node.children {
+                let child_path = format!("{}/{}", path, child.name);
+                details = details.push(self.node_view(child, child_path, depth + 1));
+            }
+        }
+        container(details)
+            .padding(iced::Padding::ZERO.left(if depth == 0 { 0.0 } else { 16.0 }))
+            .into()
+    }
+
+    fn usages_view(&self, name: &str) -> Element<'_, Message> {
+        let usages = self.browser.usages(name);
+        if usages.is_empty() {
+            return text("No operation refers to this schema.")
+                .size(13)
+                .color(Color::from_rgb(0.5, 0.5, 0.5))
+                .into();
+        }
+        usages
+            .iter()
+            .fold(column![].spacing(2), |col, usage| {
+                col.push(
+                    button(
+                        row![
+                            text(&usage.method)
+                                .size(12)
+                                .color(method_color(&usage.method))
+                                .width(Length::Fixed(60.0)),
+                            text(&usage.path).size(13),
+                            text(&usage.location)
+                                .size(11)
+                                .color(Color::from_rgb(0.5, 0.5, 0.5)),
+                        ]
+                        .spacing(8)
+                        .align_y(Alignment::Center),
+                    )
+                    .on_press(Message::OpenOperation(
+                        usage.method.clone(),
+                        usage.path.clone(),
+                    ))
+                    .style(button::text),
+                )
+            })
+            .into()
+    }
+}
+
+/// How many of a composition's options a value has to match.
+fn choice_wording(choice: &str) -> &'static str {
+    match choice {
+        "oneOf" => "exactly one of the options below",
+        "anyOf" => "at least one of the options below",
+        _ => "all of the options below",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selecting_a_schema_collapses_the_tree() {
+        let spec = crate::openapi::parser::load_spec_model(
+            r#"{"openapi": "3.0.0", "info": {"title": "T"}, "paths": {},
+                "components": {"schemas": {"B": {"type": "object"}, "A": {"type": "string"}}}}"#,
+        )
+        .unwrap();
+        let mut view = SchemaBrowserView::default();
+        view.open("T".to_string(), 1, 2, SchemaBrowser::new(&spec));
+        assert_eq!(view.selected.as_deref(), Some("A"));
+
+        view.update(Message::ToggleNode("A/x".to_string()));
+        assert!(view.expanded.contains("A/x"));
+        view.update(Message::ToggleNode("A/x".to_string()));
+        assert!(view.expanded.is_empty());
+
+        view.update(Message::ToggleNode("A/x".to_string()));
+        view.update(Message::SelectSchema("B".to_string()));
+        assert!(view.expanded.is_empty());
+    }
+
+    #[test]
+    fn compositions_are_worded_by_keyword() {
+        assert_eq!(choice_wording("oneOf"), "exactly one of the options below");
+        assert_eq!(choice_wording("anyOf"), "at least one of the options below");
+    }
+}